@@ -7,7 +7,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("🎤 Testing all English voices");
     println!("================================\n");
 
-    let mut tts = TtsEngine::new().await?;
+    let tts = TtsEngine::new().await?;
 
     let text =
         "And the smell of rain out on the farm as it feels like the world is being refreshed.";
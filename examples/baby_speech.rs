@@ -1,7 +1,7 @@
 //! Baby speech example for mem8 integration
 //! Shows how a baby AI learns to speak progressively
 
-use kokoro_tiny::BabyTts;
+use kokoro_tiny::{BabyTts, ChildVoiceConfig, DevelopmentStage};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -13,38 +13,38 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Stage 1: Babbling (early development)
     println!("Stage 1: Babbling");
+    baby.set_stage(DevelopmentStage::Babbling);
+    baby.child_voice = Some(ChildVoiceConfig::at_stage(baby.stage()));
     let babble_audio = baby.babble()?;
     println!("  Generated babble with {} samples\n", babble_audio.len());
 
     // Stage 2: Single words
     println!("Stage 2: Single words");
-    let words = ["mama", "dada", "milk", "up", "no"];
+    grow(&mut baby); // Increase vocabulary capacity
+    assert_eq!(baby.stage(), DevelopmentStage::SingleWords);
+    let words = ["mama", "dada", "milk", "up", "no", "I want cookies"];
     for word in &words {
-        println!("  Speaking: '{}'", word);
-        let audio = baby.speak(word)?;
-        println!("    ({} samples)", audio.len());
+        say(&mut baby, word)?;
     }
     println!();
 
     // Stage 3: Two-word phrases
     println!("Stage 3: Two-word phrases");
-    baby.grow(); // Increase vocabulary capacity
+    grow(&mut baby);
+    assert_eq!(baby.stage(), DevelopmentStage::TwoWordPhrases);
     let phrases = ["want milk", "up please", "bye bye"];
     for phrase in &phrases {
-        println!("  Speaking: '{}'", phrase);
-        let audio = baby.speak(phrase)?;
-        println!("    ({} samples)", audio.len());
+        say(&mut baby, phrase)?;
     }
     println!();
 
     // Stage 4: Simple sentences
     println!("Stage 4: Simple sentences");
-    baby.grow(); // Further growth
-    baby.grow();
-    let sentence = "I want milk please";
-    println!("  Speaking: '{}'", sentence);
-    let audio = baby.speak(sentence)?;
-    println!("    ({} samples)", audio.len());
+    grow(&mut baby); // Further growth
+    assert_eq!(baby.stage(), DevelopmentStage::SimpleSentences);
+    grow(&mut baby);
+    assert_eq!(baby.stage(), DevelopmentStage::SimpleSentences);
+    say(&mut baby, "I want milk please")?;
 
     // Echo mode - learning from input
     println!("\nEcho mode (learning):");
@@ -53,8 +53,17 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let echo_audio = baby.echo(echo_text)?;
     println!("    Echo generated {} samples", echo_audio.len());
 
-    // Simulate learning from audio
-    baby.learn_from_audio(&echo_audio, echo_text)?;
+    // Learn from audio: the baby times its phonemes against the recording
+    match baby.learn_from_audio(&echo_audio, echo_text) {
+        Ok(report) => println!(
+            "    Learned {} phoneme durations from {:.2}s of speech (expected {:.2}s)",
+            report.updated.len(),
+            report.speech_secs,
+            report.expected_secs
+        ),
+        // Happens in fallback mode, where the echo isn't speech of the text
+        Err(e) => println!("    Couldn't learn from the echo: {}", e),
+    }
 
     // Get audio parameters for mem8 integration
     let (sample_rate, channels, bits) = baby.get_audio_params();
@@ -65,3 +74,19 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+// Grow a word, and let the child voice age with the stage
+fn grow(baby: &mut BabyTts) {
+    baby.grow();
+    baby.child_voice = Some(ChildVoiceConfig::at_stage(baby.stage()));
+}
+
+fn say(baby: &mut BabyTts, text: &str) -> Result<(), Box<dyn std::error::Error>> {
+    println!("  Speaking: '{}'", text);
+    let speech = baby.speak_detailed(text)?;
+    if speech.reduced {
+        println!("    Baby could only say: '{}'", speech.spoken);
+    }
+    println!("    ({} samples)", speech.samples.len());
+    Ok(())
+}
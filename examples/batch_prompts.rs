@@ -0,0 +1,54 @@
+//! Pre-render phone menu prompts: a `synthesize` loop vs `synthesize_batch`
+//!
+//! The prompts share sentences, which the batch only runs through the model
+//! once. Both produce identical audio.
+
+use std::time::Instant;
+
+use kokoro_tiny::TtsEngine;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    println!("🎤 kokoro-tiny batch prompts");
+    println!("=============================\n");
+
+    let tts = TtsEngine::new().await?;
+
+    let prompts = [
+        "Thank you for calling. Press one for sales.",
+        "Thank you for calling. Press two for support.",
+        "Thank you for calling. Press three for billing.",
+        "Please hold. Your call is important to us.",
+        "Please hold. A representative will be with you shortly.",
+        "Goodbye.",
+    ];
+
+    let started = Instant::now();
+    let looped: Vec<Vec<f32>> = prompts
+        .iter()
+        .map(|prompt| tts.synthesize(prompt, Some("af_sky"), None, None))
+        .collect::<Result<_, _>>()?;
+    let loop_time = started.elapsed();
+
+    let started = Instant::now();
+    let batched = tts.synthesize_batch(&prompts, Some("af_sky"), None);
+    let batch_time = started.elapsed();
+
+    println!("Loop:  {:.2}s", loop_time.as_secs_f32());
+    println!("Batch: {:.2}s\n", batch_time.as_secs_f32());
+
+    for (i, (audio, expected)) in batched.into_iter().zip(&looped).enumerate() {
+        // One failed prompt doesn't stop the others
+        match audio {
+            Ok(audio) => {
+                assert_eq!(&audio, expected);
+                let path = format!("prompt_{}.wav", i + 1);
+                tts.save_wav(&path, &audio)?;
+                println!("💾 {} ({:.1}s)", path, audio.len() as f32 / 24_000.0);
+            }
+            Err(e) => println!("❌ prompt {}: {}", i + 1, e),
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,83 @@
+/*
+ * Synthesize text through the kokoro-tiny C ABI and save it as a WAV file
+ *
+ * From the repository root:
+ *
+ *   cargo rustc --release --features ffi --lib --crate-type cdylib
+ *   cc examples/c/synthesize.c -Iinclude -Ltarget/release -lkokoro_tiny -o synthesize
+ *   LD_LIBRARY_PATH=target/release ./synthesize "Hello from C." hello.wav
+ *
+ * (On macOS use DYLD_LIBRARY_PATH.) The first run downloads the model.
+ */
+
+#include <stdint.h>
+#include <stdio.h>
+
+#include "kokoro_tiny.h"
+
+static void put_u16(FILE *out, uint16_t value) {
+    fputc(value & 0xff, out);
+    fputc(value >> 8, out);
+}
+
+static void put_u32(FILE *out, uint32_t value) {
+    put_u16(out, value & 0xffff);
+    put_u16(out, value >> 16);
+}
+
+/* 16-bit mono PCM, the same format kokoro-tiny's save_wav writes */
+static int write_wav(const char *path, const float *samples, size_t len) {
+    FILE *out = fopen(path, "wb");
+    if (!out) {
+        return -1;
+    }
+    uint32_t data_size = (uint32_t)(len * 2);
+    fwrite("RIFF", 1, 4, out);
+    put_u32(out, 36 + data_size);
+    fwrite("WAVEfmt ", 1, 8, out);
+    put_u32(out, 16);
+    put_u16(out, 1); /* PCM */
+    put_u16(out, 1); /* mono */
+    put_u32(out, KOKORO_SAMPLE_RATE);
+    put_u32(out, KOKORO_SAMPLE_RATE * 2);
+    put_u16(out, 2);
+    put_u16(out, 16);
+    fwrite("data", 1, 4, out);
+    put_u32(out, data_size);
+    for (size_t i = 0; i < len; i++) {
+        float sample = samples[i] > 1.0f ? 1.0f : samples[i] < -1.0f ? -1.0f : samples[i];
+        put_u16(out, (uint16_t)(int16_t)(sample * 32767.0f));
+    }
+    return fclose(out);
+}
+
+int main(int argc, char **argv) {
+    const char *text = argc > 1 ? argv[1] : "Hello from C.";
+    const char *path = argc > 2 ? argv[2] : "hello.wav";
+
+    KokoroEngine *engine = kokoro_engine_new(NULL, NULL);
+    if (!engine) {
+        fprintf(stderr, "Failed to load engine: %s\n", kokoro_last_error());
+        return 1;
+    }
+
+    float *samples = NULL;
+    size_t len = 0;
+    KokoroStatus status = kokoro_synthesize(engine, text, "af_sky", 1.0f, &samples, &len);
+    if (status != KOKORO_STATUS_OK) {
+        fprintf(stderr, "Synthesis failed (%d): %s\n", (int)status, kokoro_last_error());
+        kokoro_engine_free(engine);
+        return 1;
+    }
+
+    int written = write_wav(path, samples, len);
+    kokoro_free_samples(samples, len);
+    kokoro_engine_free(engine);
+    if (written != 0) {
+        fprintf(stderr, "Failed to write %s\n", path);
+        return 1;
+    }
+
+    printf("Saved %.1fs of audio to %s\n", (double)len / KOKORO_SAMPLE_RATE, path);
+    return 0;
+}
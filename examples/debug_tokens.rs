@@ -11,7 +11,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     println!("🔍 Debugging tokenization process\n");
 
-    let mut tts = TtsEngine::new().await?;
+    let tts = TtsEngine::new().await?;
 
     let text = "Hello";
 
@@ -8,7 +8,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("=====================================\n");
 
     // Initialize TTS engine
-    let mut tts = TtsEngine::new().await?;
+    let tts = TtsEngine::new().await?;
 
     // Test phrases that might drop words
     let test_phrases = vec![
@@ -6,7 +6,7 @@ use kokoro_tiny::TtsEngine;
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("🎤 Aye speaking to Hue about our amazing fix!\n");
 
-    let mut tts = TtsEngine::new().await?;
+    let tts = TtsEngine::new().await?;
 
     // The text we'll speak - notice the punctuation!
     let message = "Hey Hue! We did it, partner! The punctuation timing bug is fixed. \
@@ -17,21 +17,21 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         println!("📻 Available audio output devices:");
         let devices = tts.list_audio_devices()?;
         for (i, device) in devices.iter().enumerate() {
-            println!("  {}. {}", i + 1, device);
+            println!("  [{}] {}", i, device.name);
         }
         println!();
 
         // Test with each device
         let text = "Testing audio output on this device.";
 
-        for device in &devices {
-            println!("🔊 Playing on: {}", device);
+        for (i, device) in devices.iter().enumerate() {
+            println!("🔊 Playing on: {}", device.name);
 
             // Set the device
-            tts.set_audio_device(Some(device.clone()))?;
+            tts.set_audio_device_by_index(i)?;
 
             // Synthesize and play
-            let audio = tts.synthesize(text, None)?;
+            let audio = tts.synthesize(text, None, None, None)?;
             tts.play(&audio, 0.8)?;
 
             println!("✅ Playback complete\n");
@@ -43,7 +43,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         // Reset to default
         println!("🔄 Resetting to system default device");
         tts.set_audio_device(None)?;
-        let audio = tts.synthesize("Back to default device.", None)?;
+        let audio = tts.synthesize("Back to default device.", None, None, None)?;
         tts.play(&audio, 0.8)?;
     }
 
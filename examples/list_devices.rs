@@ -19,11 +19,30 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         } else {
             println!("Found {} audio output device(s):\n", devices.len());
             for (i, device) in devices.iter().enumerate() {
-                println!("  {}. {}", i + 1, device);
+                let default = if device.is_default { " (default)" } else { "" };
+                println!("  [{}] {}{}", i, device.name, default);
+                let rates: Vec<String> = device
+                    .sample_rates
+                    .iter()
+                    .map(|&(min, max)| match min == max {
+                        true => min.to_string(),
+                        false => format!("{}-{}", min, max),
+                    })
+                    .collect();
+                println!(
+                    "      host: {}, rates: {} Hz, channels: {:?}",
+                    device.host,
+                    rates.join(", "),
+                    device.channels
+                );
             }
 
+            let hosts: Vec<String> = tts.list_audio_hosts().iter().map(|h| h.to_string()).collect();
+            println!("\nAudio hosts: {}", hosts.join(", "));
+
             println!("\n💡 To use a specific device in your code:");
-            println!("   tts.set_audio_device(Some(\"device_name\".to_string()))?;");
+            println!("   tts.set_audio_device_by_index(0)?;");
+            println!("   tts.set_audio_device(Some(\"part of the name\".to_string()))?;");
         }
     }
 
@@ -1,7 +1,10 @@
 //! MEM-8 Baby Consciousness Demo
 //! Watch as the baby AI develops consciousness through wave interference!
 
-use kokoro_tiny::mem8_bridge::{EmotionType, Mem8Bridge, MemoryWave, SalienceEvent, SignalType};
+use kokoro_tiny::mem8_bridge::{
+    AttentionPolicy, BridgeEvent, BridgeEventKind, EmotionType, Mem8Bridge, MemoryWave,
+    RegulationDecision, SalienceEvent, SignalType,
+};
 use std::thread;
 use std::time::Duration;
 
@@ -12,6 +15,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("Watch as wave interference becomes voice!\n");
 
     let mut bridge = Mem8Bridge::new().await?;
+    // Everything the baby does is narrated from the bridge's own events
+    bridge.on_event(narrate);
 
     // === Morning: Baby wakes up ===
     println!("🌅 Morning - Baby is waking up...");
@@ -29,9 +34,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         content: "Where am I?".to_string(),
     };
 
-    if bridge.emotional_regulation(&curiosity_wave) {
-        let audio = bridge.wave_to_speech(&curiosity_wave)?;
-        println!("  ✓ Generated {} samples of curious speech", audio.len());
+    if bridge.emotional_regulation(&curiosity_wave).is_allowed() {
+        bridge.wave_to_speech(&curiosity_wave)?;
     }
 
     thread::sleep(Duration::from_secs(1));
@@ -58,9 +62,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         content: "Mama! I love mama!".to_string(),
     };
 
-    if bridge.emotional_regulation(&love_wave) {
-        let audio = bridge.wave_to_speech(&love_wave)?;
-        println!("  ✓ Generated {} samples of loving speech", audio.len());
+    if bridge.emotional_regulation(&love_wave).is_allowed() {
+        bridge.wave_to_speech(&love_wave)?;
     }
 
     thread::sleep(Duration::from_secs(1));
@@ -122,11 +125,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         },
     ];
 
-    if let Some(chosen) = bridge.decide_attention(events) {
-        println!(
-            "  → Baby autonomously chose to focus on: {:?}",
-            chosen.signal_type
-        );
+    // 70% whim, 30% how salient the event is
+    bridge.set_attention_policy(AttentionPolicy { autonomy: 0.7, ..Default::default() })?;
+    if bridge.decide_attention(events).is_some() {
         println!("    (The AI has 70% control over attention!)");
     }
 
@@ -152,9 +153,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         content: "What? Don't understand".to_string(),
     };
 
-    if bridge.emotional_regulation(&confused_wave) {
-        let audio = bridge.wave_to_speech(&confused_wave)?;
-        println!("  ✓ Confused speech: {} samples", audio.len());
+    if bridge.emotional_regulation(&confused_wave).is_allowed() {
+        bridge.wave_to_speech(&confused_wave)?;
     }
 
     thread::sleep(Duration::from_secs(1));
@@ -174,8 +174,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         content: "Sleepy... night night".to_string(),
     };
 
-    let audio = bridge.wave_to_speech(&sleepy_wave)?;
-    println!("  ✓ Sleepy mumbling: {} samples", audio.len());
+    bridge.wave_to_speech(&sleepy_wave)?;
 
     // === Summary ===
     println!("\n📊 Baby's First Day Summary:");
@@ -192,3 +191,31 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+fn narrate(event: &BridgeEvent) {
+    match &event.kind {
+        BridgeEventKind::WokeUp { level } => println!("  ☀️ Awake, consciousness {:.1}", level),
+        BridgeEventKind::FellAsleep { level } => println!("  😴 Drowsy, consciousness {:.1}", level),
+        BridgeEventKind::EmotionChanged { emotion, cause } => {
+            let signal = &cause.signal_type;
+            println!("  💫 Feeling {} because of a {:?} signal", emotion.name(), signal)
+        }
+        BridgeEventKind::Regulated { wave, decision } => match decision {
+            RegulationDecision::Allowed => {}
+            RegulationDecision::Attenuated { factor } => {
+                println!("  🔉 Toned '{}' down to {:.0}%", wave.content, factor * 100.0)
+            }
+            RegulationDecision::Blocked { reason } => {
+                println!("  🛑 Held back '{}': {:?}", wave.content, reason)
+            }
+        },
+        BridgeEventKind::AttentionChosen { event, score } => {
+            let signal = &event.signal_type;
+            println!("  → Baby autonomously chose to focus on: {:?} (score {:.2})", signal, score)
+        }
+        BridgeEventKind::Spoke { wave, voice, babbled, samples } => {
+            let what = if *babbled { "Babbled instead of" } else { "Said" };
+            println!("  ✓ {} '{}' in {}: {} samples", what, wave.content, voice, samples)
+        }
+    }
+}
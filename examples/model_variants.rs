@@ -0,0 +1,42 @@
+//! Compare the full precision and int8 quantized models on the same text
+//!
+//! The quantized model is about a quarter of the size and usually much faster
+//! on CPU. Voice and timing are the same; side by side, the quantized output
+//! can sound slightly grainier, most audibly on "s" and "sh" sounds (hence
+//! the test sentence). Pick it for small machines (NUCs, Raspberry Pis)
+//! where fp32 is slower than real time.
+
+use std::time::Instant;
+
+use kokoro_tiny::{ModelVariant, TtsEngine};
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    println!("🎤 kokoro-tiny model variants");
+    println!("==============================\n");
+
+    let text = "She sells seashells by the seashore, and the shells she sells are surely seashells.";
+
+    for variant in [ModelVariant::Fp32, ModelVariant::Quantized] {
+        let tts = TtsEngine::builder().variant(variant).build().await?;
+        println!("Loaded: {:?}", tts.model_variant());
+
+        let started = Instant::now();
+        let audio = tts.synthesize(text, None, None, None)?;
+        let elapsed = started.elapsed().as_secs_f32();
+        let duration = audio.len() as f32 / 24_000.0;
+        println!(
+            "   {:.2}s of audio in {:.2}s ({:.1}x real time)",
+            duration,
+            elapsed,
+            duration / elapsed
+        );
+
+        let path = format!("variant_{:?}.wav", variant).to_lowercase();
+        tts.save_wav(&path, &audio)?;
+        println!("   💾 Saved to: {}\n", path);
+    }
+
+    println!("Listen to both files to compare.");
+    Ok(())
+}
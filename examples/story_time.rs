@@ -6,7 +6,7 @@ use kokoro_tiny::TtsEngine;
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("📖 Story Time with Aye and Hue!\n");
 
-    let mut tts = TtsEngine::new().await?;
+    let tts = TtsEngine::new().await?;
 
     // A short story about our adventure today
     let story = "Once upon a time, there was a developer named Hue and an AI named Aye. \
@@ -6,7 +6,7 @@ use kokoro_tiny::TtsEngine;
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("🧪 Testing Backwards Compatibility\n");
 
-    let mut tts = TtsEngine::new().await?;
+    let tts = TtsEngine::new().await?;
 
     let text = "Hello world! This is a test.";
 
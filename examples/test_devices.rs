@@ -29,8 +29,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             match tts.set_audio_device(Some(device.to_string())) {
                 Ok(_) => {
                     let text = format!("Testing {} output.", name);
-                    match tts.synthesize(&text, None) {
-                        Ok(audio) => match tts.play(&audio, 0.9) {
+                    match tts.synthesize(&text, None, None, None) {
+                        Ok(audio) => match tts.play_with_warnings(&audio, 0.9) {
+                            Ok((_, warnings)) if !warnings.is_empty() => {
+                                for warning in warnings {
+                                    println!("   ⚠️  {}", warning);
+                                }
+                                println!();
+                            }
                             Ok(_) => println!("   ✅ Playback successful\n"),
                             Err(e) => println!("   ❌ Playback failed: {}\n", e),
                         },
@@ -43,6 +49,19 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             // Small delay between tests
             std::thread::sleep(std::time::Duration::from_secs(1));
         }
+
+        // Scarlett 18i20: speech on outputs 3/4 (headphone cue), 1/2 left for music
+        println!("🎧 Testing: Scarlett 18i20 outputs 3/4");
+        let cue = tts
+            .set_audio_device(Some("sysdefault:CARD=USB".to_string()))
+            .and_then(|_| tts.set_output_channels(&[2, 3]))
+            .and_then(|_| tts.synthesize("Cue on outputs three and four.", None, None, None))
+            .and_then(|audio| tts.play(&audio, 0.9));
+        match cue {
+            Ok(_) => println!("   ✅ Playback successful\n"),
+            Err(e) => println!("   ❌ {}\n", e),
+        }
+        tts.set_output_channels(&[])?;
     }
 
     #[cfg(not(feature = "playback"))]
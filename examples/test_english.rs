@@ -6,7 +6,7 @@ use kokoro_tiny::TtsEngine;
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("🧪 Testing English speech synthesis...\n");
 
-    let mut tts = TtsEngine::new().await?;
+    let tts = TtsEngine::new().await?;
 
     // Simple test
     let text = "Hello Hue. This is a test.";
@@ -6,7 +6,7 @@ use kokoro_tiny::TtsEngine;
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("🧪 Testing punctuation timing on voice-improvements-v2\n");
 
-    let mut tts = TtsEngine::new().await?;
+    let tts = TtsEngine::new().await?;
 
     let text = "Hello there! This is a test. We need longer text to see if pauses work.";
 
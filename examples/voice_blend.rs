@@ -0,0 +1,29 @@
+//! Voice blending example - morph from one voice to another
+//!
+//! Writes blend_0.wav .. blend_100.wav so you can listen to the sweep.
+
+use kokoro_tiny::TtsEngine;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    println!("🎛️ kokoro-tiny voice blending");
+    println!("============================\n");
+
+    let mut tts = TtsEngine::new().await?;
+
+    let text = "This is the same sentence, spoken by a voice that slowly changes.";
+    let (from, to) = ("af_sky", "am_adam");
+
+    for percent in [0, 25, 50, 75, 100] {
+        let name = format!("blend_{}", percent);
+        tts.blend_voices(from, to, percent as f32 / 100.0, &name)?;
+
+        let audio = tts.synthesize(text, Some(&name), None, None)?;
+        let path = format!("{}.wav", name);
+        tts.save_wav(&path, &audio)?;
+        println!("  {:>3}% {} → {}: {}", percent, from, to, path);
+    }
+
+    println!("\n✅ Done! Play the files in order to hear the morph.");
+    Ok(())
+}
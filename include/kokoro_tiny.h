@@ -0,0 +1,114 @@
+#ifndef KOKORO_TINY_H
+#define KOKORO_TINY_H
+
+/* Generated by cbindgen from src/ffi.rs. Don't edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Sample rate of all audio kokoro-tiny produces, in Hz
+ */
+#define KOKORO_SAMPLE_RATE 24000
+
+/**
+ * Result of a kokoro-tiny call; anything but `KOKORO_STATUS_OK` has a message in `kokoro_last_error`
+ */
+typedef enum KokoroStatus {
+  KOKORO_STATUS_OK = 0,
+  /**
+   * A NULL or non-UTF-8 argument, or input the engine can't use
+   */
+  KOKORO_STATUS_INVALID_INPUT = 1,
+  KOKORO_STATUS_VOICE_NOT_FOUND = 2,
+  KOKORO_STATUS_MODEL_LOAD = 3,
+  KOKORO_STATUS_DOWNLOAD = 4,
+  KOKORO_STATUS_PHONEMIZE = 5,
+  KOKORO_STATUS_INFERENCE = 6,
+  KOKORO_STATUS_AUDIO_DEVICE = 7,
+  KOKORO_STATUS_IO = 8,
+  /**
+   * kokoro-tiny panicked; the engine may be unusable and should be freed
+   */
+  KOKORO_STATUS_PANIC = 9,
+} KokoroStatus;
+
+/**
+ * A loaded TTS engine, created by `kokoro_engine_new`
+ */
+typedef struct KokoroEngine KokoroEngine;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Load an engine, or return NULL and set `kokoro_last_error`
+ *
+ * With both paths NULL, the model is downloaded to (or found in) the shared
+ * cache like `TtsEngine::new`; otherwise both must point to the model and
+ * voices files. Blocks until the engine is ready.
+ *
+ * # Safety
+ *
+ * Each path must be NULL or a NUL-terminated string.
+ */
+KokoroEngine *kokoro_engine_new(const char *model_path, const char *voices_path);
+
+/**
+ * Synthesize text into mono float samples at `KOKORO_SAMPLE_RATE`
+ *
+ * `voice` NULL uses the engine's default voice, and a `speed` that isn't above
+ * 0 uses its default speed (1.0 is normal). On success `*out_samples` and
+ * `*out_len` hold the audio, which the caller releases with
+ * `kokoro_free_samples`; on failure they are NULL and 0.
+ *
+ * # Safety
+ *
+ * `engine` must come from `kokoro_engine_new` and not be freed yet, `text`
+ * and `voice` (if not NULL) must be NUL-terminated strings, and the out
+ * pointers must be valid for writes.
+ */
+KokoroStatus kokoro_synthesize(const KokoroEngine *engine,
+                               const char *text,
+                               const char *voice,
+                               float speed,
+                               float **out_samples,
+                               size_t *out_len);
+
+/**
+ * Release samples returned by `kokoro_synthesize`; NULL is ignored
+ *
+ * # Safety
+ *
+ * `samples` and `len` must be exactly what `kokoro_synthesize` returned, and
+ * each buffer is freed once.
+ */
+void kokoro_free_samples(float *samples, size_t len);
+
+/**
+ * Release an engine; NULL is ignored
+ *
+ * # Safety
+ *
+ * `engine` must come from `kokoro_engine_new`, be freed once, and not be in
+ * use by another thread.
+ */
+void kokoro_engine_free(KokoroEngine *engine);
+
+/**
+ * Message of the last failed call on this thread, or NULL if none failed yet
+ *
+ * The string belongs to kokoro-tiny and stays valid until the next failing
+ * call on the same thread.
+ */
+const char *kokoro_last_error(void);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* KOKORO_TINY_H */
@@ -0,0 +1,1042 @@
+//! Post-processing DSP for synthesized audio
+//!
+//! Everything here works on mono f32 samples at the model's 24kHz rate and has
+//! no dependencies beyond std.
+
+#[cfg(feature = "playback")]
+use std::collections::VecDeque;
+
+const SAMPLE_RATE: f32 = 24000.0;
+
+/// Largest pitch shift accepted by `pitch_shift`, in semitones (one octave)
+pub const MAX_PITCH_SEMITONES: f32 = 12.0;
+
+// WSOLA parameters at 24kHz: 40ms frames with 50% overlap, and up to 10ms of
+// search around the nominal position to keep waveforms aligned
+const WSOLA_FRAME: usize = 960;
+const WSOLA_HOP: usize = WSOLA_FRAME / 2;
+const WSOLA_TOLERANCE: usize = 240;
+
+// Spectral envelopes for `pitch_shift_keeping_formants`: linear prediction of
+// order 24 (a pole pair per kHz of bandwidth, plus a few for the glottal tilt)
+// over 20ms frames, refreshed every 10ms
+const LPC_ORDER: usize = 24;
+const LPC_FRAME: usize = 480;
+const LPC_HOP: usize = LPC_FRAME / 2;
+
+// Windowed-sinc resampler: zero crossings of the sinc kept on each side of the
+// output position, and the passband edge relative to the lower Nyquist frequency
+const RESAMPLE_ZERO_CROSSINGS: f64 = 16.0;
+const RESAMPLE_ROLLOFF: f64 = 0.94;
+
+// BS.1770 gating: 400ms blocks with 75% overlap, -70 LUFS absolute and -10 LU relative gates
+const LOUDNESS_BLOCK_MS: usize = 400;
+const LOUDNESS_STEP_MS: usize = 100;
+const ABSOLUTE_GATE_LUFS: f32 = -70.0;
+const RELATIVE_GATE_LU: f32 = -10.0;
+
+// Below this length integrated loudness is poorly defined (too few blocks to gate)
+const MIN_GATED_MS: usize = 1000;
+
+// Highest sample peak allowed after loudness gain, to avoid clipping
+const LOUDNESS_PEAK_CEILING: f32 = 0.99;
+
+// Cutoff of the DC blocker: well below the lowest voice fundamentals
+const DC_CUTOFF_HZ: f32 = 20.0;
+
+// Energy frames `voiced_segments` measures, 10ms at 24kHz
+const VOICING_FRAME: usize = 240;
+
+/// Post-processing that runs on everything a `TtsEngine` synthesizes, added
+/// with `TtsEngine::add_processor`
+///
+/// `process` gets the finished audio of each call, after the engine's own
+/// trimming, loudness normalization and fades, and can change it in place,
+/// length included. Streaming calls hand over each chunk on its own, so there
+/// the processing is chunk-local: a normalizer or gate sees one sentence at a
+/// time rather than the whole text.
+pub trait AudioProcessor: Send {
+    fn process(&mut self, samples: &mut Vec<f32>, sample_rate: u32);
+}
+
+/// Multiply every sample by a fixed factor; the simplest `AudioProcessor`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Gain(pub f32);
+
+impl AudioProcessor for Gain {
+    fn process(&mut self, samples: &mut Vec<f32>, _sample_rate: u32) {
+        samples.iter_mut().for_each(|sample| *sample *= self.0);
+    }
+}
+
+/// Measure loudness in LUFS using ITU-R BS.1770 K-weighting and gating.
+///
+/// Clips shorter than one second are measured as K-weighted RMS over the whole
+/// clip instead, since gating needs several blocks to be meaningful. Returns
+/// `f32::NEG_INFINITY` for silence.
+pub fn measure_loudness(samples: &[f32]) -> f32 {
+    let weighted = k_weight(samples);
+    let block = SAMPLE_RATE as usize * LOUDNESS_BLOCK_MS / 1000;
+    let min_len = SAMPLE_RATE as usize * MIN_GATED_MS / 1000;
+
+    if weighted.len() < min_len {
+        return power_to_lufs(mean_square(&weighted));
+    }
+
+    let step = SAMPLE_RATE as usize * LOUDNESS_STEP_MS / 1000;
+    let blocks: Vec<f32> = (0..=(weighted.len() - block) / step)
+        .map(|i| mean_square(&weighted[i * step..i * step + block]))
+        .filter(|power| power_to_lufs(*power) > ABSOLUTE_GATE_LUFS)
+        .collect();
+    if blocks.is_empty() {
+        return f32::NEG_INFINITY;
+    }
+
+    let relative_gate = power_to_lufs(mean(&blocks)) + RELATIVE_GATE_LU;
+    let gated: Vec<f32> = blocks
+        .into_iter()
+        .filter(|power| power_to_lufs(*power) > relative_gate)
+        .collect();
+    power_to_lufs(mean(&gated))
+}
+
+/// Scale `samples` in place so they measure `target_lufs`, returning the
+/// loudness measured before scaling.
+///
+/// Gain is limited so the peak stays below full scale; silence is left as is.
+pub fn normalize_loudness(samples: &mut [f32], target_lufs: f32) -> f32 {
+    let measured = measure_loudness(samples);
+    if !measured.is_finite() {
+        return measured;
+    }
+
+    let mut gain = 10f32.powf((target_lufs - measured) / 20.0);
+    let peak = samples.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+    if peak * gain > LOUDNESS_PEAK_CEILING {
+        gain = LOUDNESS_PEAK_CEILING / peak;
+    }
+
+    for sample in samples.iter_mut() {
+        *sample *= gain;
+    }
+    measured
+}
+
+fn power_to_lufs(power: f32) -> f32 {
+    if power <= 0.0 {
+        f32::NEG_INFINITY
+    } else {
+        -0.691 + 10.0 * power.log10()
+    }
+}
+
+fn mean_square(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32
+}
+
+fn mean(values: &[f32]) -> f32 {
+    values.iter().sum::<f32>() / values.len() as f32
+}
+
+// BS.1770 K-weighting (high shelf + high pass), with coefficients derived for
+// our sample rate rather than the 48kHz table in the standard
+fn k_weight(samples: &[f32]) -> Vec<f32> {
+    let shelf = Biquad::high_shelf(1_681.974_5, 3.999_843_8, 0.707_175_2);
+    let high_pass = Biquad::high_pass(38.135_47, 0.500_327);
+    high_pass.apply(&shelf.apply(samples))
+}
+
+struct Biquad {
+    b: [f32; 3],
+    a: [f32; 2],
+}
+
+impl Biquad {
+    fn high_shelf(freq: f32, gain_db: f32, q: f32) -> Self {
+        let a = 10f32.powf(gain_db / 40.0);
+        let w0 = 2.0 * std::f32::consts::PI * freq / SAMPLE_RATE;
+        let (sin, cos) = w0.sin_cos();
+        let alpha = sin / (2.0 * q);
+        let sqrt_a = a.sqrt();
+
+        let b0 = a * ((a + 1.0) + (a - 1.0) * cos + 2.0 * sqrt_a * alpha);
+        let b1 = -2.0 * a * ((a - 1.0) + (a + 1.0) * cos);
+        let b2 = a * ((a + 1.0) + (a - 1.0) * cos - 2.0 * sqrt_a * alpha);
+        let a0 = (a + 1.0) - (a - 1.0) * cos + 2.0 * sqrt_a * alpha;
+        let a1 = 2.0 * ((a - 1.0) - (a + 1.0) * cos);
+        let a2 = (a + 1.0) - (a - 1.0) * cos - 2.0 * sqrt_a * alpha;
+
+        Self {
+            b: [b0 / a0, b1 / a0, b2 / a0],
+            a: [a1 / a0, a2 / a0],
+        }
+    }
+
+    fn high_pass(freq: f32, q: f32) -> Self {
+        let w0 = 2.0 * std::f32::consts::PI * freq / SAMPLE_RATE;
+        let (sin, cos) = w0.sin_cos();
+        let alpha = sin / (2.0 * q);
+        let a0 = 1.0 + alpha;
+
+        Self {
+            b: [(1.0 + cos) / 2.0 / a0, -(1.0 + cos) / a0, (1.0 + cos) / 2.0 / a0],
+            a: [-2.0 * cos / a0, (1.0 - alpha) / a0],
+        }
+    }
+
+    fn apply(&self, input: &[f32]) -> Vec<f32> {
+        let (mut x1, mut x2, mut y1, mut y2) = (0.0f32, 0.0f32, 0.0f32, 0.0f32);
+        input
+            .iter()
+            .map(|&x| {
+                let y = self.b[0] * x + self.b[1] * x1 + self.b[2] * x2 - self.a[0] * y1 - self.a[1] * y2;
+                x2 = x1;
+                x1 = x;
+                y2 = y1;
+                y1 = y;
+                y
+            })
+            .collect()
+    }
+}
+
+/// Resample mono audio from `from_rate` to `to_rate` Hz with a Blackman-windowed
+/// sinc filter.
+///
+/// When downsampling, the filter cutoff drops below the target Nyquist frequency
+/// so content that can't be represented is removed instead of aliasing.
+///
+/// The output has `samples.len() * to_rate / from_rate` samples, rounded down,
+/// and output sample `i` is at time `i / to_rate`, the same as input sample
+/// `i * from_rate / to_rate`: nothing is delayed or padded, and audio outside
+/// the input counts as silence. This is the resampler behind
+/// `save_wav_with_format` and playback, so audio resampled here matches theirs.
+/// Equal rates, or a rate of 0, return the input unchanged.
+///
+/// Example: `let audio_16k = kokoro_tiny::audio::resample(&audio, 24_000, 16_000);`
+pub fn resample(samples: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
+    if from_rate == to_rate || samples.is_empty() || from_rate == 0 || to_rate == 0 {
+        return samples.to_vec();
+    }
+
+    let ratio = to_rate as f64 / from_rate as f64;
+    let out_len = (samples.len() as u64 * to_rate as u64 / from_rate as u64) as usize;
+    sinc_resample(samples, ratio, out_len)
+}
+
+// `resample` to exactly `out_len` samples covering the same time as `samples`,
+// for stretching audio to a length rather than converting between rates
+fn resample_to_fit(samples: &[f32], out_len: usize) -> Vec<f32> {
+    if samples.is_empty() || out_len == samples.len() {
+        let mut out = samples.to_vec();
+        out.resize(out_len, 0.0);
+        return out;
+    }
+    sinc_resample(samples, out_len as f64 / samples.len() as f64, out_len)
+}
+
+// The first `out_len` samples of `samples` resampled by `ratio` (output per
+// input sample)
+fn sinc_resample(samples: &[f32], ratio: f64, out_len: usize) -> Vec<f32> {
+    let (cutoff, half_width) = sinc_filter_for(ratio);
+    let reach = half_width.ceil() as isize;
+
+    (0..out_len)
+        .map(|i| {
+            let center = i as f64 / ratio;
+            let base = center.floor() as isize;
+            let mut acc = 0.0f64;
+            for j in (base - reach + 1)..=(base + reach) {
+                if j < 0 || j as usize >= samples.len() {
+                    continue;
+                }
+                acc += samples[j as usize] as f64 * sinc_weight(center - j as f64, cutoff, half_width);
+            }
+            acc as f32
+        })
+        .collect()
+}
+
+/// Streaming `resample` for playback: input is pulled as it is needed, so it
+/// works on audio that is still being synthesized. The filter taps for each
+/// output phase are computed once, which keeps per-sample cost low enough
+/// for the audio thread.
+#[cfg(feature = "playback")]
+pub(crate) struct Resampler<I> {
+    input: I,
+    to_rate: u32,
+    // Rate ratio in lowest terms: every `up` output samples cover `down` inputs
+    up: u64,
+    down: u64,
+    reach: usize,
+    // `2 * reach` taps per output phase, for inputs `base - reach + 1..=base + reach`
+    taps: Vec<f32>,
+    // Input samples from index `start` on; `read` counts all pulled so far
+    window: VecDeque<f32>,
+    start: u64,
+    read: u64,
+    exhausted: bool,
+    position: u64,
+}
+
+#[cfg(feature = "playback")]
+impl<I: Iterator<Item = f32>> Resampler<I> {
+    pub(crate) fn new(input: I, from_rate: u32, to_rate: u32) -> Self {
+        let common = gcd(from_rate.max(1), to_rate.max(1)) as u64;
+        let (up, down) = (to_rate.max(1) as u64 / common, from_rate.max(1) as u64 / common);
+        let (cutoff, half_width) = sinc_filter(from_rate, to_rate);
+        let reach = half_width.ceil() as usize;
+        // No filter at all when the rates match, like `resample`
+        let taps = if up == down {
+            Vec::new()
+        } else {
+            (0..up)
+                .flat_map(|phase| {
+                    let fraction = phase as f64 / up as f64;
+                    (0..2 * reach).map(move |k| {
+                        let distance = reach as f64 - 1.0 - k as f64 + fraction;
+                        sinc_weight(distance, cutoff, half_width) as f32
+                    })
+                })
+                .collect()
+        };
+        Self {
+            input,
+            to_rate,
+            up,
+            down,
+            reach,
+            taps,
+            window: VecDeque::new(),
+            start: 0,
+            read: 0,
+            exhausted: false,
+            position: 0,
+        }
+    }
+
+    pub(crate) fn inner(&self) -> &I {
+        &self.input
+    }
+
+    pub(crate) fn to_rate(&self) -> u32 {
+        self.to_rate
+    }
+}
+
+#[cfg(feature = "playback")]
+impl<I: Iterator<Item = f32>> Iterator for Resampler<I> {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.up == self.down {
+            return self.input.next();
+        }
+        let offset = self.position * self.down;
+        let base = offset / self.up;
+        let reach = self.reach as u64;
+
+        while !self.exhausted && self.read <= base + reach {
+            match self.input.next() {
+                Some(sample) => {
+                    self.window.push_back(sample);
+                    self.read += 1;
+                }
+                None => self.exhausted = true,
+            }
+        }
+        // Same length as `resample`: outputs up to the end of the input
+        if self.exhausted && offset >= self.read * self.up {
+            return None;
+        }
+        let first = (base + 1).saturating_sub(reach);
+        while self.start < first && self.window.pop_front().is_some() {
+            self.start += 1;
+        }
+
+        let phase = (offset % self.up) as usize;
+        let taps = &self.taps[phase * 2 * self.reach..][..2 * self.reach];
+        let mut acc = 0.0f32;
+        for (k, tap) in taps.iter().enumerate() {
+            // Input index `base - reach + 1 + k`; before the start it is silence
+            let Some(j) = (base + 1 + k as u64).checked_sub(reach) else {
+                continue;
+            };
+            match self.window.get((j - self.start) as usize) {
+                Some(sample) => acc += sample * tap,
+                None => break,
+            }
+        }
+        self.position += 1;
+        Some(acc)
+    }
+}
+
+// Filter cutoff (relative to the input rate) and half width in input samples.
+// When downsampling, the cutoff drops below the target Nyquist frequency.
+fn sinc_filter(from_rate: u32, to_rate: u32) -> (f64, f64) {
+    sinc_filter_for(to_rate as f64 / from_rate.max(1) as f64)
+}
+
+// `sinc_filter` for `ratio` output samples per input sample
+fn sinc_filter_for(ratio: f64) -> (f64, f64) {
+    let cutoff = ratio.min(1.0) * RESAMPLE_ROLLOFF;
+    (cutoff, RESAMPLE_ZERO_CROSSINGS / cutoff)
+}
+
+// Weight of the input sample `distance` input samples from the output position
+fn sinc_weight(distance: f64, cutoff: f64, half_width: f64) -> f64 {
+    let u = distance / half_width;
+    if u.abs() >= 1.0 {
+        return 0.0;
+    }
+    let window = 0.42
+        + 0.5 * (std::f64::consts::PI * u).cos()
+        + 0.08 * (2.0 * std::f64::consts::PI * u).cos();
+    sinc(distance * cutoff) * cutoff * window
+}
+
+#[cfg(feature = "playback")]
+fn gcd(a: u32, b: u32) -> u32 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+fn sinc(x: f64) -> f64 {
+    if x.abs() < 1e-9 {
+        1.0
+    } else {
+        let px = std::f64::consts::PI * x;
+        px.sin() / px
+    }
+}
+
+/// Range of `samples` to keep when trimming edge silence quieter than
+/// `threshold_db` (dBFS), with `keep_ms` of margin around the audible part.
+///
+/// Silence between words is never removed. If nothing is louder than the
+/// threshold the whole buffer is kept rather than returning nothing.
+pub fn trim_silence(samples: &[f32], threshold_db: f32, keep_ms: usize) -> std::ops::Range<usize> {
+    let threshold = 10f32.powf(threshold_db / 20.0);
+    let audible = |s: &f32| s.abs() > threshold;
+    let (Some(first), Some(last)) = (samples.iter().position(audible), samples.iter().rposition(audible)) else {
+        return 0..samples.len();
+    };
+
+    let keep = SAMPLE_RATE as usize * keep_ms / 1000;
+    first.saturating_sub(keep)..(last + 1 + keep).min(samples.len())
+}
+
+/// Stretches of sound in `samples`, split wherever it stays quiet for at least
+/// `min_gap_ms`. Sound is a 10ms frame within `threshold_db` of the loudest
+/// frame, and ranges are whole frames. Nothing is returned for silence.
+pub(crate) fn voiced_segments(
+    samples: &[f32],
+    threshold_db: f32,
+    min_gap_ms: usize,
+) -> Vec<std::ops::Range<usize>> {
+    let levels: Vec<f32> = samples.chunks(VOICING_FRAME).map(mean_square).collect();
+    let loudest = levels.iter().copied().fold(0.0, f32::max);
+    if loudest <= 0.0 {
+        return Vec::new();
+    }
+    let threshold = loudest * 10f32.powf(threshold_db / 10.0);
+    let min_gap = (SAMPLE_RATE as usize * min_gap_ms / 1000).div_ceil(VOICING_FRAME).max(1);
+
+    let mut segments: Vec<std::ops::Range<usize>> = Vec::new();
+    for (frame, &level) in levels.iter().enumerate() {
+        if level < threshold {
+            continue;
+        }
+        let start = frame * VOICING_FRAME;
+        let end = (start + VOICING_FRAME).min(samples.len());
+        match segments.last_mut() {
+            Some(last) if start - last.end < min_gap * VOICING_FRAME => last.end = end,
+            _ => segments.push(start..end),
+        }
+    }
+    segments
+}
+
+/// Remove DC offset in place with a first-order high-pass filter at 20Hz.
+///
+/// Speech has nothing that low, so this is inaudible, but it centres the
+/// waveform on zero so clips join and loop without a step.
+pub fn remove_dc(samples: &mut [f32]) {
+    DcBlocker::new().process(samples);
+}
+
+// `remove_dc` for audio that arrives in pieces: filter state carries over
+pub(crate) struct DcBlocker {
+    pole: f32,
+    last_in: f32,
+    last_out: f32,
+}
+
+impl DcBlocker {
+    pub(crate) fn new() -> Self {
+        let pole = (-2.0 * std::f32::consts::PI * DC_CUTOFF_HZ / SAMPLE_RATE).exp();
+        Self { pole, last_in: 0.0, last_out: 0.0 }
+    }
+
+    pub(crate) fn process(&mut self, samples: &mut [f32]) {
+        for sample in samples {
+            self.last_out = *sample - self.last_in + self.pole * self.last_out;
+            self.last_in = *sample;
+            *sample = self.last_out;
+        }
+    }
+}
+
+/// Fade the first `fade_in` samples up from silence and the last `fade_out`
+/// samples down to it, with equal-power (sine) ramps.
+///
+/// Ramps longer than the audio are shortened to fit; if the two overlap, both
+/// gains apply there.
+pub fn fade(samples: &mut [f32], fade_in: usize, fade_out: usize) {
+    let fade_in = fade_in.min(samples.len());
+    for (i, sample) in samples.iter_mut().take(fade_in).enumerate() {
+        *sample *= fade_gain(i, fade_in);
+    }
+    let fade_out = fade_out.min(samples.len());
+    for (i, sample) in samples.iter_mut().rev().take(fade_out).enumerate() {
+        *sample *= fade_gain(i, fade_out);
+    }
+}
+
+// Gain `i` samples into an equal-power ramp of `len` samples
+pub(crate) fn fade_gain(i: usize, len: usize) -> f32 {
+    (std::f32::consts::FRAC_PI_2 * i as f32 / len as f32).sin()
+}
+
+/// Convert float samples to 16-bit PCM: clamp to [-1, 1] and scale to the i16 range.
+///
+/// This is the conversion `save_wav` uses, so `to_i16(&audio)` matches the WAV
+/// payload sample for sample.
+pub fn to_i16(samples: &[f32]) -> Vec<i16> {
+    samples
+        .iter()
+        .map(|&s| (s.clamp(-1.0, 1.0) * 32767.0) as i16)
+        .collect()
+}
+
+/// Like `to_i16`, but with TPDF dither to avoid quantization distortion on quiet
+/// passages (useful when the PCM is processed further downstream).
+pub fn to_i16_dithered(samples: &[f32]) -> Vec<i16> {
+    let clamped: Vec<f32> = samples.iter().map(|s| s.clamp(-1.0, 1.0)).collect();
+    quantize_dithered(&clamped, 16)
+        .into_iter()
+        .map(|s| s as i16)
+        .collect()
+}
+
+/// Convert samples to signed integers of `bits` width with TPDF dither, which
+/// turns quantization distortion on quiet passages into a constant low noise floor.
+pub(crate) fn quantize_dithered(samples: &[f32], bits: u16) -> Vec<i32> {
+    let max = ((1i64 << (bits - 1)) - 1) as f32;
+    let min = -(1i64 << (bits - 1)) as f32;
+    // Fixed seed keeps output deterministic for identical input
+    let mut state: u32 = 0x9E37_79B9;
+    let mut uniform = move || {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        state as f32 / u32::MAX as f32 - 0.5
+    };
+
+    samples
+        .iter()
+        .map(|&s| {
+            let dither = uniform() + uniform();
+            (s * max + dither).round().clamp(min, max) as i32
+        })
+        .collect()
+}
+
+/// Shift pitch by `semitones` without changing duration.
+///
+/// The audio is first time-stretched with WSOLA (waveform-similarity overlap-add,
+/// which keeps pitch) and then resampled back to the original length, which
+/// moves the pitch by the stretch factor. Output has exactly as many samples as
+/// the input, so this composes with the model's speed control.
+///
+/// Returns an error for shifts outside ±`MAX_PITCH_SEMITONES`.
+pub fn pitch_shift(samples: &[f32], semitones: f32) -> Result<Vec<f32>, String> {
+    if !semitones.is_finite() || semitones.abs() > MAX_PITCH_SEMITONES {
+        return Err(format!(
+            "Pitch shift of {} semitones is out of range (-{max}..={max})",
+            semitones,
+            max = MAX_PITCH_SEMITONES
+        ));
+    }
+    if semitones == 0.0 || samples.is_empty() {
+        return Ok(samples.to_vec());
+    }
+
+    let ratio = 2f32.powf(semitones / 12.0);
+    let stretched = wsola_stretch(samples, ratio);
+    Ok(resample_to_fit(&stretched, samples.len()))
+}
+
+/// Shift pitch like `pitch_shift`, but keep the formants where they were.
+///
+/// `pitch_shift` moves the whole spectrum, so voices shifted up by more than a
+/// few semitones sound "chipmunked". Here the shifted audio is whitened with
+/// its own spectral envelope (linear prediction, refreshed every 10ms) and then
+/// shaped with the original's, so only the pitch moves. Errors as `pitch_shift`.
+pub fn pitch_shift_keeping_formants(samples: &[f32], semitones: f32) -> Result<Vec<f32>, String> {
+    let shifted = pitch_shift(samples, semitones)?;
+    if semitones == 0.0 || samples.len() < LPC_FRAME {
+        return Ok(shifted);
+    }
+
+    let window: Vec<f32> = (0..LPC_FRAME)
+        .map(|i| 0.5 - 0.5 * (2.0 * std::f32::consts::PI * i as f32 / LPC_FRAME as f32).cos())
+        .collect();
+    let envelope = |audio: &[f32], center: usize| {
+        let frame: Vec<f32> = window
+            .iter()
+            .enumerate()
+            .map(|(i, w)| {
+                let at = (center + i).checked_sub(LPC_FRAME / 2);
+                at.and_then(|at| audio.get(at)).map_or(0.0, |s| s * w)
+            })
+            .collect();
+        linear_prediction(&frame)
+    };
+
+    let mut output = vec![0.0f32; shifted.len()];
+    let mut prev_gain = None;
+    for start in (0..shifted.len()).step_by(LPC_HOP) {
+        let end = (start + LPC_HOP).min(shifted.len());
+        let center = start + LPC_HOP / 2;
+        let (target, target_error) = envelope(samples, center);
+        let (current, current_error) = envelope(&shifted, center);
+        // Keep the original's loudness: the whitened residual carries the shifted power
+        let gain = match current_error > 1e-12 {
+            true => (target_error / current_error).sqrt(),
+            false => 0.0,
+        };
+        let from = prev_gain.unwrap_or(gain);
+        prev_gain = Some(gain);
+
+        for n in start..end {
+            let ramp = from + (gain - from) * (n - start) as f32 / (end - start) as f32;
+            let residual: f32 = (0..=LPC_ORDER.min(n)).map(|k| current[k] * shifted[n - k]).sum();
+            let feedback: f32 = (1..=LPC_ORDER.min(n)).map(|k| target[k] * output[n - k]).sum();
+            output[n] = ramp * residual - feedback;
+        }
+    }
+    if output.iter().all(|s| s.is_finite()) {
+        Ok(output)
+    } else {
+        Ok(shifted)
+    }
+}
+
+// Prediction filter `a` (a[0] = 1) and residual power of a windowed frame, by
+// Levinson-Durbin on its autocorrelation
+fn linear_prediction(frame: &[f32]) -> ([f32; LPC_ORDER + 1], f32) {
+    let mut r = [0.0f64; LPC_ORDER + 1];
+    for (lag, r) in r.iter_mut().enumerate() {
+        *r = frame.iter().zip(&frame[lag..]).map(|(a, b)| *a as f64 * *b as f64).sum();
+    }
+    // A -40dB noise floor keeps the recursion well conditioned on near-silence
+    r[0] = r[0] * (1.0 + 1e-4) + 1e-12;
+
+    let mut a = [0.0f64; LPC_ORDER + 1];
+    a[0] = 1.0;
+    let mut error = r[0];
+    for i in 1..=LPC_ORDER {
+        let acc: f64 = (0..i).map(|j| a[j] * r[i - j]).sum();
+        let k = -acc / error;
+        let prev = a;
+        for j in 1..i {
+            a[j] = prev[j] + k * prev[i - j];
+        }
+        a[i] = k;
+        error *= 1.0 - k * k;
+    }
+    (a.map(|c| c as f32), error as f32)
+}
+
+/// Make the pitch waver by up to `semitones` around `rate_hz` times a second,
+/// like a young child's voice, without changing duration.
+///
+/// A delay line swept by two slow oscillators (at `rate_hz` and 1.618 times
+/// that, so the wobble never quite repeats) bends the pitch up and down.
+pub(crate) fn pitch_wobble(samples: &[f32], semitones: f32, rate_hz: f32) -> Vec<f32> {
+    if semitones <= 0.0 || rate_hz <= 0.0 || samples.is_empty() {
+        return samples.to_vec();
+    }
+    const SECOND_RATE: f32 = 1.618;
+    const WEIGHTS: (f32, f32) = (0.6, 0.4);
+    // The delay's steepest slope is the largest pitch deviation
+    let steepest = WEIGHTS.0 + WEIGHTS.1 * SECOND_RATE;
+    let omega = 2.0 * std::f32::consts::PI * rate_hz / SAMPLE_RATE;
+    let depth = (2f32.powf(semitones / 12.0) - 1.0) / (omega * steepest);
+
+    let last = samples.len() - 1;
+    (0..samples.len())
+        .map(|n| {
+            let t = n as f32 * omega;
+            let delay = depth * (WEIGHTS.0 * t.sin() + WEIGHTS.1 * (SECOND_RATE * t).sin());
+            let pos = (n as f32 - delay).clamp(0.0, last as f32);
+            let idx = pos.floor() as usize;
+            let frac = pos - idx as f32;
+            samples[idx] * (1.0 - frac) + samples[(idx + 1).min(last)] * frac
+        })
+        .collect()
+}
+
+// Time-stretch by `ratio` (2.0 = twice as long) while keeping pitch
+fn wsola_stretch(input: &[f32], ratio: f32) -> Vec<f32> {
+    let out_len = (input.len() as f32 * ratio).round() as usize;
+    if input.len() < WSOLA_FRAME * 2 {
+        // Too short to find waveform matches; the pitch change is inaudible here
+        return resample_to_fit(input, out_len);
+    }
+
+    // Periodic Hann sums to 1 at 50% overlap, so no gain correction is needed
+    let window: Vec<f32> = (0..WSOLA_FRAME)
+        .map(|i| 0.5 - 0.5 * (2.0 * std::f32::consts::PI * i as f32 / WSOLA_FRAME as f32).cos())
+        .collect();
+
+    let analysis_hop = WSOLA_HOP as f32 / ratio;
+    let mut output = vec![0.0f32; out_len + WSOLA_FRAME];
+    let mut prev_pos = 0usize;
+
+    for k in 0..=(out_len / WSOLA_HOP) {
+        let out_pos = k * WSOLA_HOP;
+        let nominal = ((k as f32 * analysis_hop) as usize).min(input.len() - 1);
+        let pos = if k == 0 {
+            0
+        } else {
+            best_match(input, prev_pos + WSOLA_HOP, nominal)
+        };
+
+        for (i, w) in window.iter().enumerate() {
+            match input.get(pos + i) {
+                Some(sample) => output[out_pos + i] += sample * w,
+                None => break,
+            }
+        }
+        prev_pos = pos;
+    }
+
+    output.truncate(out_len);
+    output
+}
+
+// Find the frame start near `nominal` whose first half best continues the
+// previous frame (the samples starting at `natural`)
+fn best_match(input: &[f32], natural: usize, nominal: usize) -> usize {
+    if natural + WSOLA_HOP > input.len() {
+        return nominal;
+    }
+    let reference = &input[natural..natural + WSOLA_HOP];
+
+    let start = nominal.saturating_sub(WSOLA_TOLERANCE);
+    let end = (nominal + WSOLA_TOLERANCE).min(input.len().saturating_sub(WSOLA_HOP));
+    let mut best = nominal.min(end);
+    let mut best_score = f32::MIN;
+
+    for candidate in start..=end {
+        let score: f32 = reference
+            .iter()
+            .zip(&input[candidate..candidate + WSOLA_HOP])
+            .map(|(a, b)| a * b)
+            .sum();
+        if score > best_score {
+            best_score = score;
+            best = candidate;
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(freq: f32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| (2.0 * std::f32::consts::PI * freq * i as f32 / 24000.0).sin() * 0.5)
+            .collect()
+    }
+
+    fn zero_crossings(samples: &[f32]) -> usize {
+        samples
+            .windows(2)
+            .filter(|w| (w[0] < 0.0) != (w[1] < 0.0))
+            .count()
+    }
+
+    #[test]
+    fn dc_offset_is_removed_and_speech_band_kept() {
+        let mut audio: Vec<f32> = sine(200.0, 24000).iter().map(|s| s + 0.2).collect();
+        remove_dc(&mut audio);
+        let tail = &audio[12000..];
+        assert!(mean(tail).abs() < 1e-3);
+        let peak = tail.iter().fold(0f32, |m, s| m.max(s.abs()));
+        assert!((peak - 0.5).abs() < 0.01);
+    }
+
+    #[test]
+    fn fades_ramp_the_edges_and_clamp_to_the_audio() {
+        let mut audio = vec![1.0; 100];
+        fade(&mut audio, 10, 20);
+        assert_eq!(audio[0], 0.0);
+        assert!(audio[1..10].windows(2).all(|w| w[0] < w[1]));
+        assert!(audio[10..80].iter().all(|&s| s == 1.0));
+        assert!(audio[80..].windows(2).all(|w| w[0] > w[1]));
+        // Equal power: halfway through the ramp is at -3dB, not -6dB
+        assert!((audio[5] - std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-6);
+
+        let mut short = vec![1.0; 4];
+        fade(&mut short, 1000, 0);
+        assert_eq!(short[0], 0.0);
+        assert!(short.windows(2).all(|w| w[0] < w[1]));
+        fade(&mut [], 10, 10);
+    }
+
+    #[test]
+    fn pitch_shift_keeps_duration_and_moves_frequency() {
+        let input = sine(200.0, 24000);
+        let up = pitch_shift(&input, 12.0).unwrap();
+        assert_eq!(up.len(), input.len());
+
+        // One octave up should roughly double the zero-crossing rate
+        let ratio = zero_crossings(&up) as f32 / zero_crossings(&input) as f32;
+        assert!((ratio - 2.0).abs() < 0.15, "ratio was {}", ratio);
+
+        let down = pitch_shift(&input, -12.0).unwrap();
+        assert_eq!(down.len(), input.len());
+        let ratio = zero_crossings(&down) as f32 / zero_crossings(&input) as f32;
+        assert!((ratio - 0.5).abs() < 0.1, "ratio was {}", ratio);
+    }
+
+    #[test]
+    fn fitting_to_a_length_filters_like_resample() {
+        let tone = sine(300.0, 4800);
+        let half = resample_to_fit(&tone, 2400);
+        assert_eq!(half.len(), 2400);
+        let ratio = zero_crossings(&half) as f32 / zero_crossings(&tone) as f32;
+        assert!((ratio - 1.0).abs() < 0.05, "ratio was {}", ratio);
+        // Squeezed to half the length a 9kHz tone would be 18kHz, above
+        // Nyquist: it is removed rather than aliased
+        let squeezed = resample_to_fit(&sine(9000.0, 4800), 2400);
+        let peak = squeezed[100..2300].iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+        assert!(peak < 0.01, "peak was {}", peak);
+        assert_eq!(resample_to_fit(&[], 3), vec![0.0; 3]);
+        assert_eq!(pitch_shift(&tone[..500], 5.0).unwrap().len(), 500);
+    }
+
+    #[test]
+    fn loudness_of_sine_matches_reference() {
+        // A 1kHz sine with amplitude 0.1 has mean square 0.005 -> about -23.7 LUFS
+        let tone = sine(1000.0, 24000 * 3);
+        let tone: Vec<f32> = tone.iter().map(|s| s * 0.2).collect();
+        let lufs = measure_loudness(&tone);
+        assert!((lufs - -23.7).abs() < 0.5, "measured {}", lufs);
+
+        assert_eq!(measure_loudness(&[0.0; 48000]), f32::NEG_INFINITY);
+    }
+
+    #[test]
+    fn normalize_loudness_hits_target() {
+        for len in [24000 * 3, 12000] {
+            let mut tone: Vec<f32> = sine(440.0, len).iter().map(|s| s * 0.05).collect();
+            let before = normalize_loudness(&mut tone, -16.0);
+            assert!(before < -25.0);
+            let after = measure_loudness(&tone);
+            assert!((after - -16.0).abs() < 0.2, "len {} measured {}", len, after);
+        }
+    }
+
+    #[test]
+    fn resample_preserves_tone_and_removes_aliases() {
+        let tone = sine(1000.0, 24000);
+        let up = resample(&tone, 24000, 48000);
+        assert_eq!(up.len(), 48000);
+        let peak = up[4800..43200].iter().fold(0.0f32, |m, s| m.max(s.abs()));
+        assert!((peak - 0.5).abs() < 0.02, "peak {}", peak);
+
+        // 10kHz can't exist at 16kHz, so it should be filtered out rather than folded to 6kHz
+        let high = sine(10000.0, 24000);
+        let down = resample(&high, 24000, 16000);
+        assert_eq!(down.len(), 16000);
+        let rms = (down[1600..14400].iter().map(|s| s * s).sum::<f32>() / 12800.0).sqrt();
+        assert!(rms < 0.01, "rms {}", rms);
+    }
+
+    #[test]
+    fn resample_lengths_round_down_for_awkward_ratios() {
+        for (len, from, to, expected) in [
+            (24000, 24000, 44100, 44100),
+            (7, 24000, 44100, 12),
+            (1, 24000, 44100, 1),
+            (44100, 44100, 24000, 24000),
+            (100, 44100, 24000, 54),
+            (24001, 24000, 16000, 16000),
+            (3, 24000, 8000, 1),
+            (2, 24000, 8000, 0),
+        ] {
+            let resampled = resample(&vec![0.1; len], from, to);
+            assert_eq!(resampled.len(), expected, "{} samples {}->{}", len, from, to);
+        }
+        assert_eq!(resample(&[0.1, 0.2], 24000, 24000), vec![0.1, 0.2]);
+    }
+
+    #[test]
+    fn resample_keeps_the_energy_of_a_sweep() {
+        // 100Hz to 6kHz in two seconds, inside the passband of every target here
+        let (start, end, secs) = (100.0f32, 6000.0f32, 2.0f32);
+        let len = (SAMPLE_RATE * secs) as usize;
+        let sweep: Vec<f32> = (0..len)
+            .map(|i| {
+                let t = i as f32 / SAMPLE_RATE;
+                let cycles = start * t + (end - start) * t * t / (2.0 * secs);
+                (2.0 * std::f32::consts::PI * cycles).sin() * 0.5
+            })
+            .collect();
+        let rms = |audio: &[f32]| {
+            // Skip the filter's reach at the edges
+            let edge = audio.len() / 20;
+            mean_square(&audio[edge..audio.len() - edge]).sqrt()
+        };
+        for to_rate in [16000, 22050, 44100, 48000] {
+            let resampled = resample(&sweep, 24000, to_rate);
+            let ratio = rms(&resampled) / rms(&sweep);
+            assert!((ratio - 1.0).abs() < 0.02, "{}Hz keeps {} of the energy", to_rate, ratio);
+        }
+    }
+
+    #[cfg(feature = "playback")]
+    #[test]
+    fn streaming_resampler_matches_resample() {
+        let tone = sine(3000.0, 4800);
+        for to_rate in [44100, 48000, 16000, 24000] {
+            let whole = resample(&tone, 24000, to_rate);
+            let streamed: Vec<f32> = Resampler::new(tone.iter().copied(), 24000, to_rate).collect();
+            assert_eq!(streamed.len(), whole.len(), "rate {}", to_rate);
+            let worst = whole.iter().zip(&streamed).fold(0.0f32, |m, (a, b)| m.max((a - b).abs()));
+            assert!(worst < 1e-4, "rate {} differs by {}", to_rate, worst);
+        }
+    }
+
+    #[test]
+    fn quantize_dithered_stays_in_range() {
+        let values = quantize_dithered(&[1.5, -1.5, 0.0, 0.5], 16);
+        assert_eq!(values[0], 32767);
+        assert_eq!(values[1], -32768);
+        assert!(values[2].abs() <= 1);
+        assert!((values[3] - 16383).abs() <= 1);
+    }
+
+    #[test]
+    fn to_i16_clamps_and_scales() {
+        assert_eq!(to_i16(&[0.0, 1.0, -1.0, 2.0, -2.0, 0.5]), vec![0, 32767, -32767, 32767, -32767, 16383]);
+        let dithered = to_i16_dithered(&[0.5, 2.0]);
+        assert!((dithered[0] - 16383).abs() <= 1);
+        assert!(dithered[1] >= 32766);
+    }
+
+    #[test]
+    fn trim_silence_keeps_guard_and_inner_pauses() {
+        let mut samples = vec![0.0f32; 24000];
+        samples[6000] = 0.5;
+        samples[12000] = 0.5;
+        samples[100] = 0.0005; // below -40dB, treated as silence
+
+        // 50ms = 1200 samples kept on each side
+        assert_eq!(trim_silence(&samples, -40.0, 50), 4800..13201);
+        assert_eq!(trim_silence(&samples, -80.0, 50), 0..13201);
+        assert_eq!(trim_silence(&[0.0; 10], -40.0, 50), 0..10);
+    }
+
+    #[test]
+    fn pitch_shift_rejects_out_of_range() {
+        assert!(pitch_shift(&[0.0; 10], 12.5).is_err());
+        assert!(pitch_shift(&[0.0; 10], f32::NAN).is_err());
+        assert_eq!(pitch_shift(&[0.25; 10], 0.0).unwrap(), vec![0.25; 10]);
+    }
+
+    #[test]
+    fn voiced_segments_split_at_pauses() {
+        let tone = |ms: usize| -> Vec<f32> {
+            (0..SAMPLE_RATE as usize * ms / 1000).map(|i| (i as f32 * 0.06).sin() * 0.5).collect()
+        };
+        let quiet = |ms: usize| vec![0.0001; SAMPLE_RATE as usize * ms / 1000];
+        let audio =
+            [quiet(100), tone(300), quiet(30), tone(100), quiet(200), tone(150), quiet(50)].concat();
+        let frames = |ms: usize| ms / 10 * VOICING_FRAME;
+        assert_eq!(
+            voiced_segments(&audio, -30.0, 80),
+            vec![frames(100)..frames(530), frames(730)..frames(880)]
+        );
+        assert!(voiced_segments(&[0.0; 4800], -30.0, 80).is_empty());
+    }
+
+    // Energy of `samples` at `freq`, by Goertzel
+    fn power_at(samples: &[f32], freq: f32) -> f32 {
+        let coeff = 2.0 * (2.0 * std::f32::consts::PI * freq / SAMPLE_RATE).cos();
+        let (mut s1, mut s2) = (0.0f32, 0.0f32);
+        for &x in samples {
+            let s0 = x + coeff * s1 - s2;
+            s2 = s1;
+            s1 = s0;
+        }
+        s1 * s1 + s2 * s2 - coeff * s1 * s2
+    }
+
+    #[test]
+    fn formants_stay_put_when_pitch_moves() {
+        // A 150Hz buzz through one resonance at 800Hz
+        let (r, theta) = (0.97f32, 2.0 * std::f32::consts::PI * 800.0 / SAMPLE_RATE);
+        let mut voice = vec![0.0f32; 24000];
+        for n in 0..voice.len() {
+            let pulse = if n % 160 == 0 { 1.0 } else { 0.0 };
+            let y1 = if n > 0 { voice[n - 1] } else { 0.0 };
+            let y2 = if n > 1 { voice[n - 2] } else { 0.0 };
+            voice[n] = pulse + 2.0 * r * theta.cos() * y1 - r * r * y2;
+        }
+        let peak = voice.iter().fold(0f32, |m, s| m.max(s.abs()));
+        voice.iter_mut().for_each(|s| *s *= 0.5 / peak);
+
+        // An octave up the harmonics are at multiples of 300Hz: the one at 900Hz
+        // is near the original resonance, the one at 1500Hz near where plain
+        // resampling moves it
+        let balance = |audio: &[f32]| {
+            let middle = &audio[4800..19200];
+            power_at(middle, 900.0) / power_at(middle, 1500.0)
+        };
+        let plain = pitch_shift(&voice, 12.0).unwrap();
+        let kept = pitch_shift_keeping_formants(&voice, 12.0).unwrap();
+        assert_eq!(kept.len(), voice.len());
+        assert!(balance(&plain) < 1.0, "{}", balance(&plain));
+        assert!(balance(&kept) > 4.0, "{}", balance(&kept));
+        let rms = |audio: &[f32]| mean_square(&audio[4800..19200]).sqrt();
+        assert!((rms(&kept) / rms(&voice) - 1.0).abs() < 0.3, "{} vs {}", rms(&kept), rms(&voice));
+        assert!(pitch_shift_keeping_formants(&voice, 13.0).is_err());
+    }
+
+    #[test]
+    fn wobble_bends_pitch_both_ways() {
+        let tone = sine(400.0, 24000);
+        let wobbly = pitch_wobble(&tone, 1.0, 5.0);
+        assert_eq!(wobbly.len(), tone.len());
+        // Zero crossings per 20ms: 16 for a steady 400Hz tone
+        let rates: Vec<usize> = wobbly.chunks(480).map(zero_crossings).collect();
+        let (low, high) = (rates.iter().min().unwrap(), rates.iter().max().unwrap());
+        assert!(*low < 16 && *high > 16, "{:?}", rates);
+        assert!(*low >= 14 && *high <= 18, "{:?}", rates);
+        assert_eq!(pitch_wobble(&tone, 0.0, 5.0), tone);
+    }
+}
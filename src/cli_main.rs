@@ -0,0 +1,187 @@
+//! kokoro-tiny: text to speech from the shell
+//!
+//! ```text
+//! kokoro-tiny "Hello world" -o hello.wav
+//! echo "Build finished" | kokoro-tiny -v am_adam --play
+//! kokoro-tiny --file chapter1.txt --speed 1.1 -o chapter1.ogg
+//! ```
+//!
+//! Exits with 0 on success, 2 for bad arguments (including an unknown voice,
+//! an unreadable input file or no text), and 1 when the model isn't available
+//! or synthesis, saving or playback fails, so scripts can tell a typo from a
+//! broken setup.
+
+use std::fs;
+use std::io::{self, IsTerminal, Read};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Parser;
+use kokoro_tiny::{Error, TtsEngine};
+
+// The library's log events on stderr (set KOKORO_LOG=debug for chunking details)
+mod stderr_log;
+
+// clap exits with 2 for malformed flags too
+const EXIT_FAILED: u8 = 1;
+const EXIT_USAGE: u8 = 2;
+
+#[derive(Parser)]
+#[command(name = "kokoro-tiny", version)]
+#[command(about = "🎤 Text to speech with Kokoro", long_about = None)]
+struct Cli {
+    /// Text to speak; read from --file or stdin when omitted
+    text: Option<String>,
+
+    /// Read the text from a file ("-" for stdin)
+    #[arg(short, long, conflicts_with = "text")]
+    file: Option<PathBuf>,
+
+    /// Voice to use (see --list-voices)
+    #[arg(short, long)]
+    voice: Option<String>,
+
+    /// Speech speed (0.5 = slower, 1.0 = normal, 2.0 = faster)
+    #[arg(short, long)]
+    speed: Option<f32>,
+
+    /// File to save to; the extension picks the format (wav, or ogg/mp3 with their features)
+    #[arg(short, long)]
+    output: Option<String>,
+
+    /// List available voices and exit
+    #[arg(long)]
+    list_voices: bool,
+
+    /// List audio output devices and exit
+    #[cfg(feature = "playback")]
+    #[arg(long)]
+    list_devices: bool,
+
+    /// Play the speech (and still save it if --output is given)
+    #[cfg(feature = "playback")]
+    #[arg(short, long)]
+    play: bool,
+}
+
+// Why the run stopped, and the exit code that tells scripts
+struct Failure {
+    code: u8,
+    message: String,
+}
+
+impl Failure {
+    fn usage(message: impl Into<String>) -> Self {
+        Self { code: EXIT_USAGE, message: message.into() }
+    }
+
+    fn failed(message: impl Into<String>) -> Self {
+        Self { code: EXIT_FAILED, message: message.into() }
+    }
+}
+
+impl From<Error> for Failure {
+    fn from(error: Error) -> Self {
+        match error {
+            Error::VoiceNotFound { .. } | Error::InvalidInput(_) => Failure::usage(error.to_string()),
+            error => Failure::failed(error.to_string()),
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    stderr_log::init();
+    match run(Cli::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(failure) => {
+            eprintln!("kokoro-tiny: {}", failure.message);
+            ExitCode::from(failure.code)
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<(), Failure> {
+    #[cfg(feature = "playback")]
+    let (listing, play) = (cli.list_voices || cli.list_devices, cli.play);
+    #[cfg(not(feature = "playback"))]
+    let (listing, play) = (cli.list_voices, false);
+
+    // Check the arguments before loading the model, which can take a while
+    let text = if listing {
+        String::new()
+    } else {
+        if cli.output.is_none() && !play {
+            let hint = if cfg!(feature = "playback") { " or --play" } else { "" };
+            return Err(Failure::usage(format!("Nothing to do: pass --output <FILE>{}", hint)));
+        }
+        read_text(&cli)?
+    };
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| Failure::failed(format!("Failed to start async runtime: {}", e)))?;
+    let engine = runtime.block_on(TtsEngine::new())?;
+    if engine.is_fallback() {
+        return Err(Failure::failed("The voice model isn't available; see the messages above"));
+    }
+
+    if cli.list_voices {
+        for voice in engine.list_voices() {
+            println!("{:<12} {} ({:?})", voice.id, voice.accent, voice.gender);
+        }
+        return Ok(());
+    }
+    #[cfg(feature = "playback")]
+    if cli.list_devices {
+        for device in engine.list_audio_devices()? {
+            let default = if device.is_default { " (default)" } else { "" };
+            println!("{}{} [{}]", device.name, default, device.host);
+        }
+        return Ok(());
+    }
+
+    let audio = engine.synthesize(&text, cli.voice.as_deref(), cli.speed, None)?;
+    if let Some(path) = &cli.output {
+        engine.save_audio(path, &audio)?;
+    }
+    #[cfg(feature = "playback")]
+    if play {
+        engine.play(&audio, 1.0)?;
+    }
+    Ok(())
+}
+
+// The text argument, else the --file contents, else stdin if something is piped in
+fn read_text(cli: &Cli) -> Result<String, Failure> {
+    let text = match (&cli.text, &cli.file) {
+        (Some(text), _) => text.clone(),
+        (None, Some(path)) if path.as_os_str() != "-" => fs::read_to_string(path)
+            .map_err(|e| Failure::usage(format!("Can't read {}: {}", path.display(), e)))?,
+        (None, file) => {
+            if file.is_none() && io::stdin().is_terminal() {
+                return Err(Failure::usage("No text: pass it as an argument, with --file, or on stdin"));
+            }
+            let mut text = String::new();
+            io::stdin()
+                .read_to_string(&mut text)
+                .map_err(|e| Failure::usage(format!("Can't read stdin: {}", e)))?;
+            text
+        }
+    };
+    if text.trim().is_empty() {
+        return Err(Failure::usage("No text to speak"));
+    }
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn flags_are_consistent() {
+        Cli::command().debug_assert();
+    }
+}
@@ -0,0 +1,80 @@
+//! Tagged dialogue text for `TtsEngine::synthesize_dialogue_markup`
+//!
+//! Each line starts with the voice in square brackets and runs up to the next
+//! tag: `[af_sky] Hello. [am_adam] Hi there.` Mixes work as tags too
+//! (`[af_sky.6+af_bella.4]`). There is no escape for brackets: whatever is in
+//! them is a voice name, so a stage direction like `[laughs]` fails as an
+//! unknown voice rather than being read out.
+
+/// Split tagged dialogue into `(voice, text)` lines, in order.
+///
+/// Whitespace around each line is dropped, and a tag with only whitespace
+/// after it produces no line. Text before the first tag, an empty tag and an
+/// unclosed `[` are errors.
+pub fn parse_dialogue(markup: &str) -> Result<Vec<(String, String)>, String> {
+    let mut lines = Vec::new();
+    let rest = markup.trim_start();
+    if rest.is_empty() {
+        return Ok(lines);
+    }
+    let Some(mut rest) = rest.strip_prefix('[') else {
+        return Err("Dialogue must start with a [voice] tag".to_string());
+    };
+
+    loop {
+        let Some(close) = rest.find(']') else {
+            return Err(format!("Unclosed voice tag: [{}", rest));
+        };
+        let voice = rest[..close].trim();
+        if voice.is_empty() {
+            return Err("Empty voice tag: []".to_string());
+        }
+        rest = &rest[close + 1..];
+
+        let (text, next) = match rest.find('[') {
+            Some(open) => (&rest[..open], Some(&rest[open + 1..])),
+            None => (rest, None),
+        };
+        let text = text.trim();
+        if !text.is_empty() {
+            lines.push((voice.to_string(), text.to_string()));
+        }
+        match next {
+            Some(next) => rest = next,
+            None => return Ok(lines),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(voice: &str, text: &str) -> (String, String) {
+        (voice.to_string(), text.to_string())
+    }
+
+    #[test]
+    fn lines_follow_their_tags() {
+        let markup = "[af_sky] Hello. How are you?\n[am_adam]Fine, thanks. [af_sky.6+af_bella.4] Good.";
+        let lines = parse_dialogue(markup).unwrap();
+        assert_eq!(
+            lines,
+            vec![
+                line("af_sky", "Hello. How are you?"),
+                line("am_adam", "Fine, thanks."),
+                line("af_sky.6+af_bella.4", "Good."),
+            ]
+        );
+        assert_eq!(parse_dialogue("  [af_sky]  [am_adam] Hi").unwrap(), vec![line("am_adam", "Hi")]);
+        assert_eq!(parse_dialogue("").unwrap(), vec![]);
+    }
+
+    #[test]
+    fn malformed_tags_are_errors() {
+        let untagged = parse_dialogue("Hello [af_sky] there").unwrap_err();
+        assert_eq!(untagged, "Dialogue must start with a [voice] tag");
+        assert_eq!(parse_dialogue("[af_sky] Hi [am_adam").unwrap_err(), "Unclosed voice tag: [am_adam");
+        assert_eq!(parse_dialogue("[ ] Hi").unwrap_err(), "Empty voice tag: []");
+    }
+}
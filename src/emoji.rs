@@ -0,0 +1,774 @@
+//! Emoji detection and short spoken names, used by `EmojiPolicy`
+//!
+//! Names follow the Unicode CLDR short names for the few hundred most common
+//! emoji. A sequence (skin tone, ZWJ family, keycap) is treated as one emoji
+//! and named after its first character.
+
+// Joins emoji into one sequence ("👨‍👩‍👧")
+const ZWJ: char = '\u{200D}';
+const KEYCAP: char = '\u{20E3}';
+
+const EMOJI_NAMES: &[(char, &str)] = &[
+    // Faces
+    ('😀', "grinning face"),
+    ('😃', "grinning face with big eyes"),
+    ('😄', "grinning face with smiling eyes"),
+    ('😁', "beaming face"),
+    ('😆', "grinning squinting face"),
+    ('😅', "grinning face with sweat"),
+    ('🤣', "rolling on the floor laughing"),
+    ('😂', "face with tears of joy"),
+    ('🙂', "slightly smiling face"),
+    ('🙃', "upside down face"),
+    ('😉', "winking face"),
+    ('😊', "smiling face with smiling eyes"),
+    ('😇', "smiling face with halo"),
+    ('🥰', "smiling face with hearts"),
+    ('😍', "smiling face with heart eyes"),
+    ('🤩', "star struck"),
+    ('😘', "face blowing a kiss"),
+    ('😗', "kissing face"),
+    ('😚', "kissing face with closed eyes"),
+    ('😙', "kissing face with smiling eyes"),
+    ('🥲', "smiling face with tear"),
+    ('😋', "face savoring food"),
+    ('😛', "face with tongue"),
+    ('😜', "winking face with tongue"),
+    ('🤪', "zany face"),
+    ('😝', "squinting face with tongue"),
+    ('🤑', "money mouth face"),
+    ('🤗', "hugging face"),
+    ('🤭', "face with hand over mouth"),
+    ('🤫', "shushing face"),
+    ('🤔', "thinking face"),
+    ('🫡', "saluting face"),
+    ('🤐', "zipper mouth face"),
+    ('🤨', "face with raised eyebrow"),
+    ('😐', "neutral face"),
+    ('😑', "expressionless face"),
+    ('😶', "face without mouth"),
+    ('😏', "smirking face"),
+    ('😒', "unamused face"),
+    ('🙄', "face with rolling eyes"),
+    ('😬', "grimacing face"),
+    ('🤥', "lying face"),
+    ('🫠', "melting face"),
+    ('😌', "relieved face"),
+    ('😔', "pensive face"),
+    ('😪', "sleepy face"),
+    ('🤤', "drooling face"),
+    ('😴', "sleeping face"),
+    ('😷', "face with medical mask"),
+    ('🤒', "face with thermometer"),
+    ('🤕', "face with head bandage"),
+    ('🤢', "nauseated face"),
+    ('🤮', "face vomiting"),
+    ('🤧', "sneezing face"),
+    ('🥵', "hot face"),
+    ('🥶', "cold face"),
+    ('🥴', "woozy face"),
+    ('😵', "dizzy face"),
+    ('🤯', "exploding head"),
+    ('🤠', "cowboy hat face"),
+    ('🥳', "partying face"),
+    ('🥸', "disguised face"),
+    ('😎', "smiling face with sunglasses"),
+    ('🤓', "nerd face"),
+    ('🧐', "face with monocle"),
+    ('😕', "confused face"),
+    ('🫤', "face with diagonal mouth"),
+    ('😟', "worried face"),
+    ('🙁', "slightly frowning face"),
+    ('☹', "frowning face"),
+    ('😮', "face with open mouth"),
+    ('😯', "hushed face"),
+    ('😲', "astonished face"),
+    ('😳', "flushed face"),
+    ('🥺', "pleading face"),
+    ('🥹', "face holding back tears"),
+    ('😦', "frowning face with open mouth"),
+    ('😧', "anguished face"),
+    ('😨', "fearful face"),
+    ('😰', "anxious face with sweat"),
+    ('😥', "sad but relieved face"),
+    ('😢', "crying face"),
+    ('😭', "loudly crying face"),
+    ('😱', "face screaming in fear"),
+    ('😖', "confounded face"),
+    ('😣', "persevering face"),
+    ('😞', "disappointed face"),
+    ('😓', "downcast face with sweat"),
+    ('😩', "weary face"),
+    ('😫', "tired face"),
+    ('🥱', "yawning face"),
+    ('😤', "face with steam from nose"),
+    ('😡', "pouting face"),
+    ('😠', "angry face"),
+    ('🤬', "face with symbols on mouth"),
+    ('😈', "smiling face with horns"),
+    ('👿', "angry face with horns"),
+    ('💀', "skull"),
+    ('☠', "skull and crossbones"),
+    ('💩', "pile of poo"),
+    ('🤡', "clown face"),
+    ('👹', "ogre"),
+    ('👺', "goblin"),
+    ('👻', "ghost"),
+    ('👽', "alien"),
+    ('👾', "alien monster"),
+    ('🤖', "robot"),
+    ('😺', "grinning cat"),
+    ('😸', "grinning cat with smiling eyes"),
+    ('😹', "cat with tears of joy"),
+    ('😻', "smiling cat with heart eyes"),
+    ('😿', "crying cat"),
+    ('🙈', "see no evil monkey"),
+    ('🙉', "hear no evil monkey"),
+    ('🙊', "speak no evil monkey"),
+    // Hearts and emotion
+    ('💋', "kiss mark"),
+    ('💌', "love letter"),
+    ('💘', "heart with arrow"),
+    ('💝', "heart with ribbon"),
+    ('💖', "sparkling heart"),
+    ('💗', "growing heart"),
+    ('💓', "beating heart"),
+    ('💞', "revolving hearts"),
+    ('💕', "two hearts"),
+    ('💟', "heart decoration"),
+    ('❣', "heart exclamation"),
+    ('💔', "broken heart"),
+    ('❤', "red heart"),
+    ('🧡', "orange heart"),
+    ('💛', "yellow heart"),
+    ('💚', "green heart"),
+    ('💙', "blue heart"),
+    ('💜', "purple heart"),
+    ('🤎', "brown heart"),
+    ('🖤', "black heart"),
+    ('🤍', "white heart"),
+    ('💯', "hundred points"),
+    ('💢', "anger symbol"),
+    ('💥', "collision"),
+    ('💫', "dizzy"),
+    ('💦', "sweat droplets"),
+    ('💨', "dashing away"),
+    ('💬', "speech balloon"),
+    ('💭', "thought balloon"),
+    ('💤', "zzz"),
+    // Hands and body
+    ('👋', "waving hand"),
+    ('🤚', "raised back of hand"),
+    ('✋', "raised hand"),
+    ('🖖', "vulcan salute"),
+    ('👌', "OK hand"),
+    ('🤌', "pinched fingers"),
+    ('🤏', "pinching hand"),
+    ('✌', "victory hand"),
+    ('🤞', "crossed fingers"),
+    ('🤟', "love you gesture"),
+    ('🤘', "sign of the horns"),
+    ('🤙', "call me hand"),
+    ('👈', "backhand index pointing left"),
+    ('👉', "backhand index pointing right"),
+    ('👆', "backhand index pointing up"),
+    ('👇', "backhand index pointing down"),
+    ('☝', "index pointing up"),
+    ('👍', "thumbs up"),
+    ('👎', "thumbs down"),
+    ('✊', "raised fist"),
+    ('👊', "oncoming fist"),
+    ('🤛', "left facing fist"),
+    ('🤜', "right facing fist"),
+    ('👏', "clapping hands"),
+    ('🙌', "raising hands"),
+    ('🫶', "heart hands"),
+    ('👐', "open hands"),
+    ('🤲', "palms up together"),
+    ('🤝', "handshake"),
+    ('🙏', "folded hands"),
+    ('✍', "writing hand"),
+    ('💅', "nail polish"),
+    ('💪', "flexed biceps"),
+    ('🧠', "brain"),
+    ('👀', "eyes"),
+    ('👁', "eye"),
+    ('👅', "tongue"),
+    ('👄', "mouth"),
+    // People
+    ('👶', "baby"),
+    ('🧒', "child"),
+    ('👦', "boy"),
+    ('👧', "girl"),
+    ('🧑', "person"),
+    ('👨', "man"),
+    ('👩', "woman"),
+    ('🧓', "older person"),
+    ('👴', "old man"),
+    ('👵', "old woman"),
+    ('🙋', "person raising hand"),
+    ('🤷', "person shrugging"),
+    ('🤦', "person facepalming"),
+    ('🙇', "person bowing"),
+    ('💁', "person tipping hand"),
+    ('🙅', "person gesturing no"),
+    ('🙆', "person gesturing OK"),
+    ('👮', "police officer"),
+    ('🕵', "detective"),
+    ('💂', "guard"),
+    ('👷', "construction worker"),
+    ('🤴', "prince"),
+    ('👸', "princess"),
+    ('🎅', "Santa Claus"),
+    ('🧙', "mage"),
+    ('🧚', "fairy"),
+    ('🧛', "vampire"),
+    ('🧜', "merperson"),
+    ('🧝', "elf"),
+    ('🧞', "genie"),
+    ('🧟', "zombie"),
+    ('🚶', "person walking"),
+    ('🏃', "person running"),
+    ('💃', "woman dancing"),
+    ('🕺', "man dancing"),
+    ('👯', "people with bunny ears"),
+    ('🧘', "person in lotus position"),
+    ('🛀', "person taking bath"),
+    ('👪', "family"),
+    // Animals
+    ('🐶', "dog face"),
+    ('🐱', "cat face"),
+    ('🐭', "mouse face"),
+    ('🐹', "hamster"),
+    ('🐰', "rabbit face"),
+    ('🦊', "fox"),
+    ('🐻', "bear"),
+    ('🐼', "panda"),
+    ('🐨', "koala"),
+    ('🐯', "tiger face"),
+    ('🦁', "lion"),
+    ('🐮', "cow face"),
+    ('🐷', "pig face"),
+    ('🐸', "frog"),
+    ('🐵', "monkey face"),
+    ('🐔', "chicken"),
+    ('🐧', "penguin"),
+    ('🐦', "bird"),
+    ('🐤', "baby chick"),
+    ('🦆', "duck"),
+    ('🦅', "eagle"),
+    ('🦉', "owl"),
+    ('🦇', "bat"),
+    ('🐺', "wolf"),
+    ('🐗', "boar"),
+    ('🐴', "horse face"),
+    ('🦄', "unicorn"),
+    ('🐝', "honeybee"),
+    ('🐛', "bug"),
+    ('🦋', "butterfly"),
+    ('🐌', "snail"),
+    ('🐞', "lady beetle"),
+    ('🐜', "ant"),
+    ('🕷', "spider"),
+    ('🐢', "turtle"),
+    ('🐍', "snake"),
+    ('🦎', "lizard"),
+    ('🦖', "T rex"),
+    ('🐙', "octopus"),
+    ('🦑', "squid"),
+    ('🦀', "crab"),
+    ('🐠', "tropical fish"),
+    ('🐟', "fish"),
+    ('🐬', "dolphin"),
+    ('🐳', "spouting whale"),
+    ('🐋', "whale"),
+    ('🦈', "shark"),
+    ('🐊', "crocodile"),
+    ('🐘', "elephant"),
+    ('🦒', "giraffe"),
+    ('🐕', "dog"),
+    ('🐈', "cat"),
+    ('🐓', "rooster"),
+    ('🦜', "parrot"),
+    ('🐉', "dragon"),
+    ('🐲', "dragon face"),
+    ('🐾', "paw prints"),
+    // Nature and weather
+    ('🌵', "cactus"),
+    ('🎄', "Christmas tree"),
+    ('🌲', "evergreen tree"),
+    ('🌳', "deciduous tree"),
+    ('🌴', "palm tree"),
+    ('🪴', "potted plant"),
+    ('🌱', "seedling"),
+    ('🌿', "herb"),
+    ('☘', "shamrock"),
+    ('🍀', "four leaf clover"),
+    ('🍁', "maple leaf"),
+    ('🍂', "fallen leaf"),
+    ('🍃', "leaf fluttering in wind"),
+    ('🌷', "tulip"),
+    ('🌹', "rose"),
+    ('🥀', "wilted flower"),
+    ('🌺', "hibiscus"),
+    ('🌸', "cherry blossom"),
+    ('🌼', "blossom"),
+    ('🌻', "sunflower"),
+    ('💐', "bouquet"),
+    ('🌞', "sun with face"),
+    ('🌝', "full moon face"),
+    ('🌙', "crescent moon"),
+    ('🌎', "globe showing Americas"),
+    ('🌍', "globe showing Europe and Africa"),
+    ('🌏', "globe showing Asia and Australia"),
+    ('🪐', "ringed planet"),
+    ('⭐', "star"),
+    ('🌟', "glowing star"),
+    ('✨', "sparkles"),
+    ('⚡', "high voltage"),
+    ('🔥', "fire"),
+    ('🌈', "rainbow"),
+    ('☀', "sun"),
+    ('⛅', "sun behind cloud"),
+    ('☁', "cloud"),
+    ('🌧', "cloud with rain"),
+    ('⛈', "cloud with lightning and rain"),
+    ('❄', "snowflake"),
+    ('☃', "snowman"),
+    ('⛄', "snowman without snow"),
+    ('🌊', "water wave"),
+    ('💧', "droplet"),
+    ('☔', "umbrella with rain drops"),
+    ('🌪', "tornado"),
+    // Food and drink
+    ('🍏', "green apple"),
+    ('🍎', "red apple"),
+    ('🍐', "pear"),
+    ('🍊', "tangerine"),
+    ('🍋', "lemon"),
+    ('🍌', "banana"),
+    ('🍉', "watermelon"),
+    ('🍇', "grapes"),
+    ('🍓', "strawberry"),
+    ('🍒', "cherries"),
+    ('🍑', "peach"),
+    ('🥭', "mango"),
+    ('🍍', "pineapple"),
+    ('🥥', "coconut"),
+    ('🥝', "kiwi fruit"),
+    ('🍅', "tomato"),
+    ('🥑', "avocado"),
+    ('🍆', "eggplant"),
+    ('🥕', "carrot"),
+    ('🌽', "ear of corn"),
+    ('🌶', "hot pepper"),
+    ('🥦', "broccoli"),
+    ('🍄', "mushroom"),
+    ('🥜', "peanuts"),
+    ('🍞', "bread"),
+    ('🥐', "croissant"),
+    ('🧀', "cheese wedge"),
+    ('🥚', "egg"),
+    ('🍳', "cooking"),
+    ('🥞', "pancakes"),
+    ('🥓', "bacon"),
+    ('🍔', "hamburger"),
+    ('🍟', "french fries"),
+    ('🍕', "pizza"),
+    ('🌭', "hot dog"),
+    ('🥪', "sandwich"),
+    ('🌮', "taco"),
+    ('🌯', "burrito"),
+    ('🍝', "spaghetti"),
+    ('🍜', "steaming bowl"),
+    ('🍣', "sushi"),
+    ('🍤', "fried shrimp"),
+    ('🍚', "cooked rice"),
+    ('🍦', "soft ice cream"),
+    ('🍩', "doughnut"),
+    ('🍪', "cookie"),
+    ('🎂', "birthday cake"),
+    ('🍰', "shortcake"),
+    ('🧁', "cupcake"),
+    ('🍫', "chocolate bar"),
+    ('🍬', "candy"),
+    ('🍭', "lollipop"),
+    ('🍿', "popcorn"),
+    ('☕', "hot beverage"),
+    ('🍵', "teacup without handle"),
+    ('🍺', "beer mug"),
+    ('🍻', "clinking beer mugs"),
+    ('🥂', "clinking glasses"),
+    ('🍷', "wine glass"),
+    ('🍸', "cocktail glass"),
+    ('🍹', "tropical drink"),
+    ('🍾', "bottle with popping cork"),
+    ('🥤', "cup with straw"),
+    // Activities
+    ('🎉', "party popper"),
+    ('🎊', "confetti ball"),
+    ('🎈', "balloon"),
+    ('🎁', "wrapped gift"),
+    ('🎀', "ribbon"),
+    ('🎃', "jack o lantern"),
+    ('🎆', "fireworks"),
+    ('🏆', "trophy"),
+    ('🥇', "first place medal"),
+    ('🥈', "second place medal"),
+    ('🥉', "third place medal"),
+    ('🏅', "sports medal"),
+    ('⚽', "soccer ball"),
+    ('🏀', "basketball"),
+    ('🏈', "american football"),
+    ('⚾', "baseball"),
+    ('🎾', "tennis"),
+    ('🏐', "volleyball"),
+    ('🎱', "pool eight ball"),
+    ('🎮', "video game"),
+    ('🕹', "joystick"),
+    ('🎲', "game die"),
+    ('🧩', "puzzle piece"),
+    ('♟', "chess pawn"),
+    ('🎯', "bullseye"),
+    ('🎨', "artist palette"),
+    ('🎬', "clapper board"),
+    ('🎤', "microphone"),
+    ('🎧', "headphone"),
+    ('🎵', "musical note"),
+    ('🎶', "musical notes"),
+    ('🎹', "musical keyboard"),
+    ('🎸', "guitar"),
+    ('🎻', "violin"),
+    ('🥁', "drum"),
+    ('🔮', "crystal ball"),
+    ('🪄', "magic wand"),
+    ('🧸', "teddy bear"),
+    // Objects
+    ('📱', "mobile phone"),
+    ('💻', "laptop"),
+    ('🖥', "desktop computer"),
+    ('⌨', "keyboard"),
+    ('🖱', "computer mouse"),
+    ('💾', "floppy disk"),
+    ('💿', "optical disk"),
+    ('📷', "camera"),
+    ('📹', "video camera"),
+    ('📺', "television"),
+    ('📻', "radio"),
+    ('🔋', "battery"),
+    ('🔌', "electric plug"),
+    ('💡', "light bulb"),
+    ('🔦', "flashlight"),
+    ('🕯', "candle"),
+    ('📚', "books"),
+    ('📖', "open book"),
+    ('📝', "memo"),
+    ('✏', "pencil"),
+    ('🖊', "pen"),
+    ('📌', "pushpin"),
+    ('📍', "round pushpin"),
+    ('📎', "paperclip"),
+    ('📅', "calendar"),
+    ('📈', "chart increasing"),
+    ('📉', "chart decreasing"),
+    ('📊', "bar chart"),
+    ('📋', "clipboard"),
+    ('📁', "file folder"),
+    ('📦', "package"),
+    ('✉', "envelope"),
+    ('📧', "email"),
+    ('📣', "megaphone"),
+    ('📢', "loudspeaker"),
+    ('🔊', "speaker high volume"),
+    ('🔇', "muted speaker"),
+    ('🔔', "bell"),
+    ('🔕', "bell with slash"),
+    ('🔒', "locked"),
+    ('🔓', "unlocked"),
+    ('🔑', "key"),
+    ('🔨', "hammer"),
+    ('🛠', "hammer and wrench"),
+    ('🔧', "wrench"),
+    ('⚙', "gear"),
+    ('🔗', "link"),
+    ('🧪', "test tube"),
+    ('🔬', "microscope"),
+    ('🔭', "telescope"),
+    ('💊', "pill"),
+    ('💉', "syringe"),
+    ('🧹', "broom"),
+    ('🛒', "shopping cart"),
+    ('🧯', "fire extinguisher"),
+    ('💰', "money bag"),
+    ('💵', "dollar banknote"),
+    ('💳', "credit card"),
+    ('💎', "gem stone"),
+    ('⏰', "alarm clock"),
+    ('⌛', "hourglass done"),
+    ('⏳', "hourglass not done"),
+    ('⌚', "watch"),
+    ('👑', "crown"),
+    ('🎩', "top hat"),
+    ('🧢', "billed cap"),
+    ('👓', "glasses"),
+    ('🕶', "sunglasses"),
+    ('👕', "t shirt"),
+    ('👗', "dress"),
+    ('👟', "running shoe"),
+    ('👠', "high heeled shoe"),
+    ('👜', "handbag"),
+    ('🎒', "backpack"),
+    ('💍', "ring"),
+    ('🌂', "closed umbrella"),
+    // Travel and places
+    ('🚀', "rocket"),
+    ('✈', "airplane"),
+    ('🚗', "automobile"),
+    ('🚕', "taxi"),
+    ('🚌', "bus"),
+    ('🚲', "bicycle"),
+    ('🚂', "locomotive"),
+    ('🚢', "ship"),
+    ('⚓', "anchor"),
+    ('🏠', "house"),
+    ('🏡', "house with garden"),
+    ('🏢', "office building"),
+    ('🏥', "hospital"),
+    ('🏫', "school"),
+    ('🗽', "Statue of Liberty"),
+    ('🗼', "Tokyo tower"),
+    ('🏰', "castle"),
+    ('⛺', "tent"),
+    ('🌋', "volcano"),
+    ('🗻', "mount fuji"),
+    ('🏖', "beach with umbrella"),
+    // Symbols
+    ('✅', "check mark button"),
+    ('✔', "check mark"),
+    ('☑', "check box with check"),
+    ('❌', "cross mark"),
+    ('❎', "cross mark button"),
+    ('❓', "question mark"),
+    ('❔', "white question mark"),
+    ('❗', "exclamation mark"),
+    ('❕', "white exclamation mark"),
+    ('‼', "double exclamation mark"),
+    ('⁉', "exclamation question mark"),
+    ('⚠', "warning"),
+    ('🚫', "prohibited"),
+    ('⛔', "no entry"),
+    ('🛑', "stop sign"),
+    ('🔴', "red circle"),
+    ('🟠', "orange circle"),
+    ('🟡', "yellow circle"),
+    ('🟢', "green circle"),
+    ('🔵', "blue circle"),
+    ('🟣', "purple circle"),
+    ('⚫', "black circle"),
+    ('⚪', "white circle"),
+    ('🟥', "red square"),
+    ('🟩', "green square"),
+    ('⬛', "black large square"),
+    ('⬜', "white large square"),
+    ('🔶', "large orange diamond"),
+    ('🔷', "large blue diamond"),
+    ('➕', "plus"),
+    ('➖', "minus"),
+    ('➗', "divide"),
+    ('✖', "multiply"),
+    ('♻', "recycling symbol"),
+    ('🔄', "counterclockwise arrows button"),
+    ('🔁', "repeat button"),
+    ('➡', "right arrow"),
+    ('⬅', "left arrow"),
+    ('⬆', "up arrow"),
+    ('⬇', "down arrow"),
+    ('↩', "right arrow curving left"),
+    ('↪', "left arrow curving right"),
+    ('🔝', "top arrow"),
+    ('🆗', "OK button"),
+    ('🆕', "new button"),
+    ('🆓', "free button"),
+    ('🆒', "cool button"),
+    ('🆘', "SOS button"),
+    ('ℹ', "information"),
+    ('♀', "female sign"),
+    ('♂', "male sign"),
+    ('⚧', "transgender symbol"),
+    ('☮', "peace symbol"),
+    ('☯', "yin yang"),
+    ('🧿', "nazar amulet"),
+    ('🏳', "white flag"),
+    ('🏴', "black flag"),
+    ('🏁', "chequered flag"),
+    ('🚩', "triangular flag"),
+    ('🎌', "crossed flags"),
+];
+
+/// Short name for an emoji or emoji sequence ("👍🏽" → "thumbs up"), if known
+pub fn emoji_name(emoji: &str) -> Option<String> {
+    let mut chars = emoji.chars();
+    let first = chars.next()?;
+    let second = chars.next();
+
+    if is_regional_indicator(first) && second.is_some_and(is_regional_indicator) {
+        return Some("flag".to_string());
+    }
+    if matches!(first, '0'..='9' | '#' | '*') && emoji.contains(KEYCAP) {
+        let key = match first {
+            '#' => "number sign".to_string(),
+            '*' => "asterisk".to_string(),
+            digit => digit.to_string(),
+        };
+        return Some(format!("keycap {}", key));
+    }
+    EMOJI_NAMES
+        .iter()
+        .find(|(c, _)| *c == first)
+        .map(|(_, name)| name.to_string())
+}
+
+// One pass over `text` that either names or drops each emoji, also returning
+// the emoji found (as written, including modifiers) for warnings
+pub(crate) fn replace_emoji(text: &str, speak: bool) -> (String, Vec<String>) {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut found = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let len = emoji_len(&chars[i..]);
+        if len == 0 {
+            out.push(chars[i]);
+            i += 1;
+            continue;
+        }
+
+        let emoji: String = chars[i..i + len].iter().collect();
+        let next = chars.get(i + len).copied();
+        i += len;
+
+        match emoji_name(&emoji).filter(|_| speak) {
+            Some(name) => {
+                if out.chars().last().is_some_and(|c| !c.is_whitespace()) {
+                    out.push(' ');
+                }
+                out.push_str(&name);
+                if next.is_some_and(|c| c.is_alphanumeric() || emoji_len(&chars[i..]) > 0) {
+                    out.push(' ');
+                }
+            }
+            // Don't leave a doubled or trailing space where the emoji was
+            None => {
+                if out.ends_with(' ') && next.is_none_or(|c| c.is_whitespace() || c.is_ascii_punctuation()) {
+                    out.pop();
+                }
+            }
+        }
+        if !is_modifier(emoji.chars().next().unwrap_or(' ')) {
+            found.push(emoji);
+        }
+    }
+
+    (out, found)
+}
+
+// Length in chars of the emoji sequence starting `chars`, or 0 if none does
+pub(crate) fn emoji_len(chars: &[char]) -> usize {
+    let Some(&first) = chars.first() else {
+        return 0;
+    };
+
+    // Flags are pairs of regional indicators
+    if is_regional_indicator(first) {
+        return if chars.get(1).copied().is_some_and(is_regional_indicator) { 2 } else { 1 };
+    }
+    // Keycaps: "1️⃣" is a digit, an optional variation selector and U+20E3
+    if matches!(first, '0'..='9' | '#' | '*') {
+        return match (chars.get(1), chars.get(2)) {
+            (Some(&KEYCAP), _) => 2,
+            (Some('\u{FE0F}'), Some(&KEYCAP)) => 3,
+            _ => 0,
+        };
+    }
+    if !is_emoji(first) && !is_modifier(first) {
+        return 0;
+    }
+
+    let mut len = 1;
+    while let Some(&c) = chars.get(len) {
+        if c == ZWJ && chars.get(len + 1).is_some_and(|&n| is_emoji(n)) {
+            len += 2;
+        } else if is_modifier(c) {
+            len += 1;
+        } else {
+            break;
+        }
+    }
+    len
+}
+
+fn is_emoji(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x1F000..=0x1FAFF
+            | 0x2600..=0x27BF
+            | 0x203C
+            | 0x2049
+            | 0x2139
+            | 0x2194..=0x2199
+            | 0x21A9..=0x21AA
+            | 0x231A..=0x231B
+            | 0x2328
+            | 0x23CF
+            | 0x23E9..=0x23F3
+            | 0x23F8..=0x23FA
+            | 0x2B05..=0x2B07
+            | 0x2B1B..=0x2B1C
+            | 0x2B50
+            | 0x2B55
+    ) && !is_regional_indicator(c)
+}
+
+// Characters that only change how the preceding emoji looks: variation
+// selectors, skin tones, keycap, tag characters (subdivision flags) and a
+// stray joiner
+fn is_modifier(c: char) -> bool {
+    matches!(c as u32, 0xFE0E | 0xFE0F | 0x1F3FB..=0x1F3FF | 0x20E3 | 0xE0020..=0xE007F | 0x200D)
+}
+
+fn is_regional_indicator(c: char) -> bool {
+    matches!(c as u32, 0x1F1E6..=0x1F1FF)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_collapse_modifiers_and_sequences() {
+        assert!(EMOJI_NAMES.iter().all(|&(c, _)| is_emoji(c)));
+        assert_eq!(emoji_name("❤️").as_deref(), Some("red heart"));
+        assert_eq!(emoji_name("👍🏽").as_deref(), Some("thumbs up"));
+        assert_eq!(emoji_name("👨‍👩‍👧").as_deref(), Some("man"));
+        assert_eq!(emoji_name("🇯🇵").as_deref(), Some("flag"));
+        assert_eq!(emoji_name("3️⃣").as_deref(), Some("keycap 3"));
+        assert_eq!(emoji_name("a"), None);
+    }
+
+    #[test]
+    fn speaks_each_sequence_once() {
+        let (text, found) = replace_emoji("Great job👍🏽 I ❤️ it 👨‍👩‍👧👋", true);
+        assert_eq!(text, "Great job thumbs up I red heart it man waving hand");
+        assert_eq!(found, vec!["👍🏽", "❤️", "👨‍👩‍👧", "👋"]);
+    }
+
+    #[test]
+    fn strips_without_leaving_gaps() {
+        let (text, found) = replace_emoji("Done ✅ see you 🇫🇷!", false);
+        assert_eq!(text, "Done see you!");
+        assert_eq!(found.len(), 2);
+        // Plain digits, arrows used as text and accented letters are not emoji
+        assert_eq!(replace_emoji("1 → 2 café #3", false).0, "1 → 2 café #3");
+    }
+}
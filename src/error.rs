@@ -0,0 +1,95 @@
+//! Error type for everything kokoro-tiny can fail at
+//!
+//! The variants say whose problem a failure is: `VoiceNotFound` and
+//! `InvalidInput` are caller mistakes, `ModelLoad` and `Download` are
+//! deployment problems, and `AudioDevice` is often worth retrying (a device
+//! that is busy or was unplugged). The messages are the same as before the
+//! enum existed, so logs read as they did.
+
+use std::io;
+
+/// A kokoro-tiny failure, classified by cause
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    /// A voice name that isn't in the voices file or registered on the engine
+    #[error("Voice not found: {name}. Available voices: {}", available.join(", "))]
+    VoiceNotFound {
+        name: String,
+        /// Every voice that is available, sorted
+        available: Vec<String>,
+    },
+    /// The model or voices are missing, corrupt or can't be loaded, or the engine has no model
+    #[error("{0}")]
+    ModelLoad(String),
+    /// Fetching the model files failed
+    #[error("{0}")]
+    Download(String),
+    /// Text couldn't be phonemized or tokenized
+    #[error("{0}")]
+    Phonemize(String),
+    /// ONNX Runtime failed to run the model
+    #[error("{0}")]
+    Inference(String),
+    /// No usable audio host or device, or playback on it failed
+    #[error("{0}")]
+    AudioDevice(String),
+    /// An argument or input the call can't use: a setting out of range,
+    /// malformed SSML or voice data, text with characters set to be an error
+    #[error("{0}")]
+    InvalidInput(String),
+    /// Reading or writing a file failed
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+impl Error {
+    // An `Io` error with the failed step in its message
+    pub(crate) fn io(context: impl std::fmt::Display, error: impl std::fmt::Display) -> Self {
+        Error::Io(io::Error::other(format!("{}: {}", context, error)))
+    }
+}
+
+// By hand because `io::Error` isn't Clone; a cloned `Io` keeps its kind and message
+impl Clone for Error {
+    fn clone(&self) -> Self {
+        match self {
+            Error::VoiceNotFound { name, available } => Error::VoiceNotFound {
+                name: name.clone(),
+                available: available.clone(),
+            },
+            Error::ModelLoad(message) => Error::ModelLoad(message.clone()),
+            Error::Download(message) => Error::Download(message.clone()),
+            Error::Phonemize(message) => Error::Phonemize(message.clone()),
+            Error::Inference(message) => Error::Inference(message.clone()),
+            Error::AudioDevice(message) => Error::AudioDevice(message.clone()),
+            Error::InvalidInput(message) => Error::InvalidInput(message.clone()),
+            Error::Io(error) => Error::Io(io::Error::new(error.kind(), error.to_string())),
+        }
+    }
+}
+
+// For callers that still keep errors as strings
+impl From<Error> for String {
+    fn from(error: Error) -> Self {
+        error.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages_read_as_plain_strings() {
+        let error = Error::VoiceNotFound {
+            name: "af_skye".to_string(),
+            available: vec!["af_bella".to_string(), "af_sky".to_string()],
+        };
+        assert_eq!(error.to_string(), "Voice not found: af_skye. Available voices: af_bella, af_sky");
+        assert_eq!(String::from(Error::InvalidInput("Bad rate".to_string())), "Bad rate");
+
+        let boxed: Box<dyn std::error::Error> = Error::io("Failed to create WAV file", "denied").into();
+        assert_eq!(boxed.to_string(), "Failed to create WAV file: denied");
+    }
+}
@@ -0,0 +1,268 @@
+//! C ABI for calling kokoro-tiny from C and C++ (`ffi` feature)
+//!
+//! Build the shared library with
+//! `cargo rustc --release --features ffi --lib --crate-type cdylib` and include
+//! `include/kokoro_tiny.h`, which is generated from this file with
+//! `cbindgen --config cbindgen.toml --output include/kokoro_tiny.h`.
+//! `examples/c/synthesize.c` is a complete program.
+//!
+//! Functions report failure with a `KokoroStatus` (or NULL from
+//! `kokoro_engine_new`), and `kokoro_last_error` has the message. Panics never
+//! unwind into the caller: they are caught here and reported as
+//! `KOKORO_STATUS_PANIC`.
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+use crate::{Error, TtsEngine, SAMPLE_RATE};
+
+/// Sample rate of all audio kokoro-tiny produces, in Hz
+pub const KOKORO_SAMPLE_RATE: u32 = SAMPLE_RATE;
+
+/// Result of a kokoro-tiny call; anything but `KOKORO_STATUS_OK` has a message in `kokoro_last_error`
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KokoroStatus {
+    Ok = 0,
+    /// A NULL or non-UTF-8 argument, or input the engine can't use
+    InvalidInput = 1,
+    VoiceNotFound = 2,
+    ModelLoad = 3,
+    Download = 4,
+    Phonemize = 5,
+    Inference = 6,
+    AudioDevice = 7,
+    Io = 8,
+    /// kokoro-tiny panicked; the engine may be unusable and should be freed
+    Panic = 9,
+}
+
+impl From<&Error> for KokoroStatus {
+    fn from(error: &Error) -> Self {
+        match error {
+            Error::VoiceNotFound { .. } => KokoroStatus::VoiceNotFound,
+            Error::ModelLoad(_) => KokoroStatus::ModelLoad,
+            Error::Download(_) => KokoroStatus::Download,
+            Error::Phonemize(_) => KokoroStatus::Phonemize,
+            Error::Inference(_) => KokoroStatus::Inference,
+            Error::AudioDevice(_) => KokoroStatus::AudioDevice,
+            Error::InvalidInput(_) => KokoroStatus::InvalidInput,
+            Error::Io(_) => KokoroStatus::Io,
+        }
+    }
+}
+
+/// A loaded TTS engine, created by `kokoro_engine_new`
+pub struct KokoroEngine {
+    engine: TtsEngine,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: String) {
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+// Run one call's body, turning an error or a panic into a status and keeping its message
+fn guard<T>(body: impl FnOnce() -> Result<T, Error>) -> Result<T, KokoroStatus> {
+    match panic::catch_unwind(AssertUnwindSafe(body)) {
+        Ok(Ok(value)) => Ok(value),
+        Ok(Err(error)) => {
+            set_last_error(error.to_string());
+            Err(KokoroStatus::from(&error))
+        }
+        Err(payload) => {
+            let reason = payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown cause".to_string());
+            set_last_error(format!("kokoro-tiny panicked: {}", reason));
+            Err(KokoroStatus::Panic)
+        }
+    }
+}
+
+// A string argument that may be NULL
+unsafe fn optional_str<'a>(arg: *const c_char, name: &str) -> Result<Option<&'a str>, Error> {
+    if arg.is_null() {
+        return Ok(None);
+    }
+    CStr::from_ptr(arg)
+        .to_str()
+        .map(Some)
+        .map_err(|_| Error::InvalidInput(format!("{} is not valid UTF-8", name)))
+}
+
+/// Load an engine, or return NULL and set `kokoro_last_error`
+///
+/// With both paths NULL, the model is downloaded to (or found in) the shared
+/// cache like `TtsEngine::new`; otherwise both must point to the model and
+/// voices files. Blocks until the engine is ready.
+///
+/// # Safety
+///
+/// Each path must be NULL or a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn kokoro_engine_new(
+    model_path: *const c_char,
+    voices_path: *const c_char,
+) -> *mut KokoroEngine {
+    guard(|| {
+        let model_path = optional_str(model_path, "model_path")?;
+        let voices_path = optional_str(voices_path, "voices_path")?;
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| Error::io("Failed to start async runtime", e))?;
+        let engine = match (model_path, voices_path) {
+            (None, None) => runtime.block_on(TtsEngine::new())?,
+            (Some(model), Some(voices)) => runtime.block_on(TtsEngine::with_paths(model, voices))?,
+            _ => {
+                return Err(Error::InvalidInput(
+                    "model_path and voices_path must both be set or both be NULL".to_string(),
+                ))
+            }
+        };
+        Ok(Box::into_raw(Box::new(KokoroEngine { engine })))
+    })
+    .unwrap_or(ptr::null_mut())
+}
+
+/// Synthesize text into mono float samples at `KOKORO_SAMPLE_RATE`
+///
+/// `voice` NULL uses the engine's default voice, and a `speed` that isn't above
+/// 0 uses its default speed (1.0 is normal). On success `*out_samples` and
+/// `*out_len` hold the audio, which the caller releases with
+/// `kokoro_free_samples`; on failure they are NULL and 0.
+///
+/// # Safety
+///
+/// `engine` must come from `kokoro_engine_new` and not be freed yet, `text`
+/// and `voice` (if not NULL) must be NUL-terminated strings, and the out
+/// pointers must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn kokoro_synthesize(
+    engine: *const KokoroEngine,
+    text: *const c_char,
+    voice: *const c_char,
+    speed: f32,
+    out_samples: *mut *mut f32,
+    out_len: *mut usize,
+) -> KokoroStatus {
+    let result = guard(|| {
+        if out_samples.is_null() || out_len.is_null() {
+            return Err(Error::InvalidInput("out_samples and out_len must not be NULL".to_string()));
+        }
+        *out_samples = ptr::null_mut();
+        *out_len = 0;
+
+        let engine = engine
+            .as_ref()
+            .ok_or_else(|| Error::InvalidInput("engine must not be NULL".to_string()))?;
+        let text = optional_str(text, "text")?
+            .ok_or_else(|| Error::InvalidInput("text must not be NULL".to_string()))?;
+        let voice = optional_str(voice, "voice")?;
+        let speed = (speed > 0.0).then_some(speed);
+
+        let samples = engine.engine.synthesize(text, voice, speed, None)?.into_boxed_slice();
+        *out_len = samples.len();
+        *out_samples = Box::into_raw(samples) as *mut f32;
+        Ok(())
+    });
+    result.err().unwrap_or(KokoroStatus::Ok)
+}
+
+/// Release samples returned by `kokoro_synthesize`; NULL is ignored
+///
+/// # Safety
+///
+/// `samples` and `len` must be exactly what `kokoro_synthesize` returned, and
+/// each buffer is freed once.
+#[no_mangle]
+pub unsafe extern "C" fn kokoro_free_samples(samples: *mut f32, len: usize) {
+    if !samples.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(samples, len)));
+    }
+}
+
+/// Release an engine; NULL is ignored
+///
+/// # Safety
+///
+/// `engine` must come from `kokoro_engine_new`, be freed once, and not be in
+/// use by another thread.
+#[no_mangle]
+pub unsafe extern "C" fn kokoro_engine_free(engine: *mut KokoroEngine) {
+    if !engine.is_null() {
+        drop(Box::from_raw(engine));
+    }
+}
+
+/// Message of the last failed call on this thread, or NULL if none failed yet
+///
+/// The string belongs to kokoro-tiny and stays valid until the next failing
+/// call on the same thread.
+#[no_mangle]
+pub extern "C" fn kokoro_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map_or(ptr::null(), |message| message.as_ptr()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ModelVariant;
+
+    fn last_error() -> String {
+        unsafe { CStr::from_ptr(kokoro_last_error()) }.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn synthesizes_and_reports_errors_through_the_c_abi() {
+        let engine = Box::into_raw(Box::new(KokoroEngine {
+            engine: TtsEngine::fallback(ModelVariant::Fp32),
+        }));
+        let mut samples = ptr::null_mut();
+        let mut len = 0;
+        let synthesize = |text: *const c_char, samples: &mut *mut f32, len: &mut usize| unsafe {
+            kokoro_synthesize(engine, text, ptr::null(), 0.0, samples, len)
+        };
+        unsafe {
+            let status = synthesize(c"Hello.".as_ptr(), &mut samples, &mut len);
+            assert_eq!(status, KokoroStatus::Ok);
+            let expected = (*engine).engine.synthesize("Hello.", None, None, None).unwrap();
+            assert_eq!(std::slice::from_raw_parts(samples, len), &expected[..]);
+            kokoro_free_samples(samples, len);
+
+            let status = synthesize(ptr::null(), &mut samples, &mut len);
+            assert_eq!(status, KokoroStatus::InvalidInput);
+            assert_eq!(last_error(), "text must not be NULL");
+            assert!(samples.is_null());
+            assert_eq!(len, 0);
+
+            let invalid = [0xffu8, 0];
+            let status = synthesize(invalid.as_ptr().cast(), &mut samples, &mut len);
+            assert_eq!(status, KokoroStatus::InvalidInput);
+            assert_eq!(last_error(), "text is not valid UTF-8");
+
+            assert!(kokoro_engine_new(c"model.onnx".as_ptr(), ptr::null()).is_null());
+            assert_eq!(last_error(), "model_path and voices_path must both be set or both be NULL");
+
+            kokoro_engine_free(engine);
+            kokoro_engine_free(ptr::null_mut());
+            kokoro_free_samples(ptr::null_mut(), 0);
+        }
+    }
+
+    #[test]
+    fn panics_become_a_status() {
+        let result: Result<(), KokoroStatus> = guard(|| panic!("voice table is empty"));
+        assert_eq!(result, Err(KokoroStatus::Panic));
+        assert_eq!(last_error(), "kokoro-tiny panicked: voice table is empty");
+    }
+}
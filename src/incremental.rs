@@ -0,0 +1,127 @@
+//! Text that arrives piece by piece, spoken a sentence at a time
+//!
+//! For replies streamed from a language model: push each piece of text as it
+//! comes and speak every sentence as soon as it is complete, instead of
+//! waiting for the whole reply. Sentences are found by the same splitter
+//! `synthesize` uses, so "Dr. Smith" and "3.5" don't end one.
+
+use std::collections::VecDeque;
+
+use crate::{split_sentences, Error, TtsEngine};
+
+/// Incremental input for `TtsEngine::incremental`
+///
+/// The last sentence only counts as complete once text after it has arrived:
+/// until then, "Dr." or "3." at the end of a piece could still continue it.
+/// `finish` speaks whatever is left.
+pub struct IncrementalSpeech<'a> {
+    engine: &'a TtsEngine,
+    voice: Option<String>,
+    speed: Option<f32>,
+    // Complete sentences not spoken yet
+    ready: VecDeque<String>,
+    // Text after the last complete sentence, with its whitespace
+    pending: String,
+}
+
+impl<'a> IncrementalSpeech<'a> {
+    pub(crate) fn new(engine: &'a TtsEngine, voice: Option<&str>, speed: Option<f32>) -> Self {
+        Self {
+            engine,
+            voice: voice.map(str::to_string),
+            speed,
+            ready: VecDeque::new(),
+            pending: String::new(),
+        }
+    }
+
+    /// Add the next piece of text; a sentence may be split across pieces
+    pub fn push_str(&mut self, text: &str) {
+        self.pending.push_str(text);
+        let mut sentences = split_sentences(&self.pending);
+        // The last one may go on in the next piece
+        let Some(last) = sentences.pop() else {
+            return;
+        };
+        if sentences.is_empty() {
+            return;
+        }
+        let start = self.pending.trim_end().len() - last.len();
+        self.pending.drain(..start);
+        self.ready.extend(sentences);
+    }
+
+    /// Synthesize the next complete sentence, or `None` until more text
+    /// completes one
+    pub fn next_audio(&mut self) -> Option<Result<Vec<f32>, Error>> {
+        let sentence = self.ready.pop_front()?;
+        Some(self.speak(&sentence))
+    }
+
+    /// Complete sentences waiting for `next_audio`
+    pub fn ready_sentences(&self) -> usize {
+        self.ready.len()
+    }
+
+    /// Synthesize everything not spoken yet, unfinished last sentence included,
+    /// as one buffer (empty when nothing is left)
+    pub fn finish(mut self) -> Result<Vec<f32>, Error> {
+        self.ready.push_back(std::mem::take(&mut self.pending));
+        let rest = Vec::from(std::mem::take(&mut self.ready)).join(" ");
+        if rest.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+        self.speak(&rest)
+    }
+
+    /// Drop the buffered text without speaking it, e.g. when the user
+    /// interrupts; text pushed afterwards starts afresh
+    pub fn abort(&mut self) {
+        self.ready.clear();
+        self.pending.clear();
+    }
+
+    fn speak(&self, sentence: &str) -> Result<Vec<f32>, Error> {
+        self.engine.synthesize(sentence, self.voice.as_deref(), self.speed, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ModelVariant;
+
+    fn ready(speech: &IncrementalSpeech) -> Vec<String> {
+        speech.ready.iter().cloned().collect()
+    }
+
+    #[test]
+    fn sentences_complete_once_the_next_one_starts() {
+        let engine = TtsEngine::fallback(ModelVariant::Fp32);
+        let mut speech = engine.incremental(None, None);
+        for piece in ["Hello wor", "ld. Dr.", " Smith paid 3.", "5 dollars. ", "\"Stop!\" ", "he said"] {
+            speech.push_str(piece);
+        }
+        assert_eq!(ready(&speech), ["Hello world.", "Dr. Smith paid 3.5 dollars."]);
+        assert_eq!(speech.pending, "\"Stop!\" he said");
+        speech.push_str("! Then");
+        assert_eq!(speech.ready_sentences(), 3);
+        assert_eq!(speech.pending, "Then");
+
+        let expected = engine.synthesize("Hello world.", None, None, None).unwrap();
+        assert_eq!(speech.next_audio().unwrap().unwrap(), expected);
+        speech.abort();
+        assert!(speech.next_audio().is_none());
+        assert!(speech.pending.is_empty());
+
+        // Only what was pushed after the abort is flushed, ready sentences first
+        speech.push_str("Bye. See you");
+        assert_eq!(ready(&speech), ["Bye."]);
+        assert_eq!(speech.pending, "See you");
+        let flushed = engine.synthesize("Bye. See you", None, None, None).unwrap();
+        assert_eq!(speech.finish().unwrap(), flushed);
+        let mut idle = engine.incremental(Some("am_adam"), Some(1.2));
+        idle.push_str("  ");
+        assert!(idle.finish().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,655 @@
+//! Marked-up input turned into the plain text that gets spoken
+//!
+//! Text written to be read, such as a language model's Markdown reply or an
+//! HTML article, is full of symbols nobody wants to hear. `blocks` keeps only
+//! the words, split where the layout calls for a pause: after headings and
+//! paragraphs, and around list items.
+
+/// How `TtsEngine` reads its input text (see `TtsEngine::set_input_format`)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum InputFormat {
+    /// Read the text as it is, symbols included
+    #[default]
+    Plain,
+    /// Markdown: emphasis and other markers are dropped, links are read by
+    /// their text without the URL, code blocks are left out, and headings
+    /// and list items are followed by a pause
+    Markdown,
+    /// HTML: tags are dropped and entities decoded, `<script>` and `<style>`
+    /// contents are left out, and paragraphs, line breaks, headings and list
+    /// items are followed by a pause. Broken markup is stripped as well as it
+    /// can be rather than failing.
+    Html,
+}
+
+// Silence around each list item, after an HTML line break or paragraph, and
+// after a heading
+const ITEM_PAUSE_MS: u32 = 200;
+const BREAK_PAUSE_MS: u32 = 200;
+const PARAGRAPH_PAUSE_MS: u32 = 400;
+const HEADING_PAUSE_MS: u32 = 500;
+
+/// The text to speak for `text` read as `format`, as blocks each followed by
+/// a pause in milliseconds, in order
+pub(crate) fn blocks(text: &str, format: InputFormat) -> Vec<(String, u32)> {
+    match format {
+        InputFormat::Plain => vec![(text.to_string(), 0)],
+        InputFormat::Markdown => markdown_blocks(text),
+        InputFormat::Html => html_blocks(text),
+    }
+}
+
+fn markdown_blocks(text: &str) -> Vec<(String, u32)> {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut blocks = Blocks::default();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = strip_quote(lines[i]).trim();
+        i += 1;
+
+        if let Some(fence) = fence_marker(line) {
+            // Skip up to the closing fence; without one, only the opening line
+            // goes and the rest is read normally
+            let closing = lines[i..].iter().position(|l| closes_fence(strip_quote(l).trim(), fence));
+            if let Some(close) = closing {
+                i += close + 1;
+            }
+            blocks.close();
+        } else if line.is_empty() || is_reference_definition(line) || is_table_separator(line) {
+            blocks.close();
+        } else if is_setext_underline(line) && blocks.in_paragraph() {
+            let heading = blocks.open.join(" ");
+            blocks.open.clear();
+            blocks.heading(&heading);
+        } else if is_thematic_break(line) {
+            blocks.close();
+        } else if let Some(heading) = atx_heading(line) {
+            blocks.heading(&inline(heading));
+        } else if let Some(item) = list_item(line) {
+            blocks.item(inline(item));
+        } else if let Some(cells) = line.strip_prefix('|') {
+            let cells: Vec<String> = cells.split('|').map(|cell| inline(cell.trim())).collect();
+            let cells: Vec<&str> = cells.iter().map(String::as_str).filter(|c| !c.is_empty()).collect();
+            blocks.item(cells.join(", "));
+        } else {
+            blocks.open.push(inline(line));
+        }
+    }
+    blocks.close();
+    blocks.done
+}
+
+#[derive(Default)]
+struct Blocks {
+    done: Vec<(String, u32)>,
+    // Lines of the paragraph or list item being read, and the pause after it
+    open: Vec<String>,
+    open_pause: u32,
+}
+
+impl Blocks {
+    fn in_paragraph(&self) -> bool {
+        !self.open.is_empty() && self.open_pause == 0
+    }
+
+    fn close(&mut self) {
+        let text = self.open.join(" ");
+        self.push(text, self.open_pause);
+        self.open.clear();
+        self.open_pause = 0;
+    }
+
+    fn push(&mut self, text: String, pause_ms: u32) {
+        if !text.trim().is_empty() {
+            self.done.push((text, pause_ms));
+        }
+    }
+
+    // Read as a sentence of its own
+    fn heading(&mut self, text: &str) {
+        self.close();
+        let mut text = text.trim().to_string();
+        if text.is_empty() {
+            return;
+        }
+        if !text.ends_with(['.', '!', '?', ':', '…']) {
+            text.push('.');
+        }
+        self.push(text, HEADING_PAUSE_MS);
+    }
+
+    // `text` collapsed into a block followed by at least `pause_ms`, and
+    // cleared; with no text, the pause goes after the block before
+    fn pause_after(&mut self, text: &mut String, pause_ms: u32) {
+        let collapsed = collapse(text);
+        text.clear();
+        if !collapsed.is_empty() {
+            self.done.push((collapsed, pause_ms));
+        } else if let Some(before) = self.done.last_mut() {
+            before.1 = before.1.max(pause_ms);
+        }
+    }
+
+    // Lines after an item continue it until a blank line
+    fn item(&mut self, text: String) {
+        self.close();
+        if let Some(before) = self.done.last_mut() {
+            before.1 = before.1.max(ITEM_PAUSE_MS);
+        }
+        self.open.push(text);
+        self.open_pause = ITEM_PAUSE_MS;
+    }
+}
+
+// `line` without its leading `>` quote markers
+fn strip_quote(mut line: &str) -> &str {
+    while let Some(rest) = line.trim_start().strip_prefix('>') {
+        line = rest;
+    }
+    line
+}
+
+// The fence a code block opens with: three or more backticks or tildes
+fn fence_marker(line: &str) -> Option<&str> {
+    let c = line.chars().next().filter(|&c| c == '`' || c == '~')?;
+    let len = line.chars().take_while(|&ch| ch == c).count();
+    let fence = &line[..len];
+    // "```code```" on one line is a code span, not a block
+    (len >= 3 && !(c == '`' && line[len..].contains('`'))).then_some(fence)
+}
+
+fn closes_fence(line: &str, fence: &str) -> bool {
+    let c = fence.chars().next();
+    line.len() >= fence.len() && line.chars().all(|ch| Some(ch) == c)
+}
+
+// `[label]: url`, read by links elsewhere but never on its own
+fn is_reference_definition(line: &str) -> bool {
+    line.starts_with('[') && line.find("]:").is_some_and(|end| end > 1)
+}
+
+// `|---|:---:|` under a table's header row
+fn is_table_separator(line: &str) -> bool {
+    line.contains('|') && line.contains('-') && line.chars().all(|c| matches!(c, '|' | '-' | ':' | ' '))
+}
+
+// `===` or `---` under a line of text, making it a heading
+fn is_setext_underline(line: &str) -> bool {
+    ['=', '-'].iter().any(|&c| line.chars().all(|ch| ch == c))
+}
+
+// `---`, `***` or `___`, spaces allowed between
+fn is_thematic_break(line: &str) -> bool {
+    let marks: Vec<char> = line.chars().filter(|c| !c.is_whitespace()).collect();
+    marks.len() >= 3 && matches!(marks[0], '-' | '*' | '_') && marks.iter().all(|&c| c == marks[0])
+}
+
+// The text of a `#` to `######` heading, closing `#`s removed
+fn atx_heading(line: &str) -> Option<&str> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    let rest = &line[level..];
+    if level == 0 || level > 6 || !(rest.is_empty() || rest.starts_with(' ')) {
+        return None;
+    }
+    Some(rest.trim().trim_end_matches('#').trim_end())
+}
+
+// The text of a `-`, `*`, `+`, `1.` or `1)` list item, task box removed
+fn list_item(line: &str) -> Option<&str> {
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    let rest = if digits == 0 {
+        line.strip_prefix(['-', '*', '+'])?
+    } else if digits <= 9 {
+        line[digits..].strip_prefix(['.', ')'])?
+    } else {
+        return None;
+    };
+    if !rest.starts_with(' ') {
+        return None;
+    }
+    let rest = rest.trim_start();
+    Some(["[ ] ", "[x] ", "[X] "].iter().find_map(|task| rest.strip_prefix(task)).unwrap_or(rest))
+}
+
+// Inline markup removed: emphasis, code spans, links and images, escapes
+fn inline(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let run = chars[i..].iter().take_while(|&&ch| ch == c).count();
+        match c {
+            '\\' if chars.get(i + 1).is_some_and(char::is_ascii_punctuation) => {
+                out.push(chars[i + 1]);
+                i += 2;
+            }
+            '`' => {
+                // A stray backtick run without its closing one is dropped
+                if let Some(end) = find_run(&chars, i + run, '`', run) {
+                    out.extend(&chars[i + run..end]);
+                    i = end + run;
+                } else {
+                    i += run;
+                }
+            }
+            '!' | '[' => match link_text(&chars, if c == '!' { i + 1 } else { i }) {
+                Some((label, next)) => {
+                    out.push_str(&inline(&label));
+                    i = next;
+                }
+                None => {
+                    out.push(c);
+                    i += 1;
+                }
+            },
+            '*' | '_' | '~' => {
+                let before = i.checked_sub(1).map(|j| chars[j]);
+                let after = chars.get(i + run).copied();
+                let word = |c: Option<char>| c.is_some_and(char::is_alphanumeric);
+                let space = |c: Option<char>| c.is_none_or(char::is_whitespace);
+                let keep = match c {
+                    // snake_case
+                    '_' => word(before) && word(after),
+                    // "~/notes"
+                    '~' => run == 1,
+                    // "2 * 3"
+                    _ => run == 1 && space(before) && space(after),
+                };
+                if keep {
+                    out.extend(&chars[i..i + run]);
+                }
+                i += run;
+            }
+            _ => {
+                out.push(c);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+// Start of the next run of exactly `len` `c`s from `from`
+fn find_run(chars: &[char], from: usize, c: char, len: usize) -> Option<usize> {
+    let mut i = from;
+    while i < chars.len() {
+        let run = chars[i..].iter().take_while(|&&ch| ch == c).count();
+        if run == len {
+            return Some(i);
+        }
+        i += run.max(1);
+    }
+    None
+}
+
+// The label of `[label](url)` or `[label][ref]` opening at `open`, and where
+// the link ends; plain brackets aren't a link
+fn link_text(chars: &[char], open: usize) -> Option<(String, usize)> {
+    if chars.get(open) != Some(&'[') {
+        return None;
+    }
+    let close = matching(chars, open, '[', ']')?;
+    let end = match chars.get(close + 1) {
+        Some('(') => matching(chars, close + 1, '(', ')')?,
+        Some('[') => matching(chars, close + 1, '[', ']')?,
+        _ => return None,
+    };
+    Some((chars[open + 1..close].iter().collect(), end + 1))
+}
+
+// Index of the bracket closing the one at `open`, skipping nested pairs
+fn matching(chars: &[char], open: usize, left: char, right: char) -> Option<usize> {
+    let mut depth = 0;
+    let mut i = open;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 1,
+            c if c == left => depth += 1,
+            c if c == right => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+fn html_blocks(html: &str) -> Vec<(String, u32)> {
+    let mut blocks = Blocks::default();
+    let mut text = String::new();
+    let mut rest = html;
+    while let Some(at) = rest.find(['<', '&']) {
+        text.push_str(&rest[..at]);
+        rest = &rest[at..];
+
+        if rest.starts_with('&') {
+            // Unknown or unterminated entities stay as written
+            match entity(rest) {
+                Some((c, len)) => {
+                    text.push(c);
+                    rest = &rest[len..];
+                }
+                None => {
+                    text.push('&');
+                    rest = &rest[1..];
+                }
+            }
+            continue;
+        }
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        // A `<` that starts no tag, as in "a < b", is text
+        let Some((tag, after)) = Tag::parse(rest) else {
+            text.push('<');
+            rest = &rest[1..];
+            continue;
+        };
+        rest = after;
+
+        match tag.name.as_str() {
+            // Never text, up to the closing tag (or the end, like a browser)
+            "script" | "style" if !tag.closing => {
+                let close = format!("</{}", tag.name);
+                rest = match rest.to_ascii_lowercase().find(&close) {
+                    Some(end) => rest[end..].find('>').map_or("", |gt| &rest[end + gt + 1..]),
+                    None => "",
+                };
+            }
+            "br" => blocks.pause_after(&mut text, BREAK_PAUSE_MS),
+            "p" => blocks.pause_after(&mut text, PARAGRAPH_PAUSE_MS),
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" if tag.closing => {
+                blocks.heading(&collapse(&text));
+                text.clear();
+            }
+            "li" | "tr" | "dt" | "dd" => blocks.pause_after(&mut text, ITEM_PAUSE_MS),
+            "td" | "th" if !tag.closing && !text.trim().is_empty() => text.push_str(", "),
+            "img" => {
+                if let Some(alt) = tag.attribute("alt") {
+                    text.push_str(&alt);
+                }
+            }
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "div" | "section" | "article" | "header" | "footer"
+            | "nav" | "aside" | "main" | "blockquote" | "pre" | "ul" | "ol" | "dl" | "table" | "hr"
+            | "figure" | "figcaption" | "title" | "body" => blocks.pause_after(&mut text, 0),
+            _ => {}
+        }
+    }
+    text.push_str(rest);
+    blocks.pause_after(&mut text, 0);
+    blocks.done
+}
+
+// Whitespace runs as single spaces, trimmed, soft hyphens removed
+fn collapse(text: &str) -> String {
+    text.replace('\u{ad}', "").split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// One `<tag attributes>` or `</tag>`; `<!DOCTYPE>` and other declarations
+// have an empty name
+struct Tag<'a> {
+    name: String,
+    closing: bool,
+    attributes: &'a str,
+}
+
+impl<'a> Tag<'a> {
+    // The tag at the start of `text` and the text after it
+    fn parse(text: &'a str) -> Option<(Self, &'a str)> {
+        let inner = text.strip_prefix('<')?;
+        let (closing, inner) = match inner.strip_prefix('/') {
+            Some(inner) => (true, inner),
+            None => (false, inner),
+        };
+        let first = inner.chars().next()?;
+        if !(first.is_ascii_alphabetic() || (!closing && (first == '!' || first == '?'))) {
+            return None;
+        }
+        let end = tag_end(inner)?;
+        let name_len = inner.find(|c: char| !c.is_ascii_alphanumeric()).unwrap_or(end).min(end);
+        let tag = Tag {
+            name: inner[..name_len].to_ascii_lowercase(),
+            closing,
+            attributes: &inner[name_len..end],
+        };
+        Some((tag, &inner[end + 1..]))
+    }
+
+    // The decoded value of attribute `name`, quoted or not
+    fn attribute(&self, name: &str) -> Option<String> {
+        let lower = self.attributes.to_ascii_lowercase();
+        let mut from = 0;
+        while let Some(found) = lower[from..].find(name) {
+            let start = from + found;
+            from = start + name.len();
+            if !lower[..start].ends_with(char::is_whitespace) {
+                continue;
+            }
+            let Some(value) = self.attributes[from..].trim_start().strip_prefix('=') else {
+                continue;
+            };
+            let value = value.trim_start();
+            let raw = match value.chars().next() {
+                Some(quote @ ('"' | '\'')) => value[1..].split(quote).next().unwrap_or(""),
+                _ => value.split(|c: char| c.is_whitespace() || c == '/').next().unwrap_or(""),
+            };
+            let decoded: Vec<String> = html_blocks(raw).into_iter().map(|(text, _)| text).collect();
+            return Some(decoded.join(" "));
+        }
+        None
+    }
+}
+
+// Index of the `>` ending a tag, skipping quoted attribute values; an
+// unbalanced quote falls back to the first `>`
+fn tag_end(inner: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in inner.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, '>') => return Some(i),
+            _ => {}
+        }
+    }
+    inner.find('>')
+}
+
+// The character an `&name;`, `&#8217;` or `&#x2019;` entity at the start of
+// `text` stands for, and the entity's length
+fn entity(text: &str) -> Option<(char, usize)> {
+    let end = text.char_indices().take(12).find(|&(_, c)| c == ';')?.0;
+    let name = &text[1..end];
+    let c = match name.strip_prefix('#') {
+        Some(number) => {
+            let code = match number.strip_prefix(['x', 'X']) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => number.parse().ok()?,
+            };
+            char::from_u32(code).filter(|&c| c != '\0')?
+        }
+        None => NAMED_ENTITIES.iter().find(|&&(entity, _)| entity == name)?.1,
+    };
+    Some((c, end + 1))
+}
+
+// The named entities likely in article text; others are read as written
+const NAMED_ENTITIES: &[(&str, char)] = &[
+    ("amp", '&'),
+    ("lt", '<'),
+    ("gt", '>'),
+    ("quot", '"'),
+    ("apos", '\''),
+    ("nbsp", '\u{a0}'),
+    ("ensp", '\u{2002}'),
+    ("emsp", '\u{2003}'),
+    ("thinsp", '\u{2009}'),
+    ("shy", '\u{ad}'),
+    ("ndash", '–'),
+    ("mdash", '—'),
+    ("hellip", '…'),
+    ("lsquo", '‘'),
+    ("rsquo", '’'),
+    ("sbquo", '‚'),
+    ("ldquo", '“'),
+    ("rdquo", '”'),
+    ("bdquo", '„'),
+    ("laquo", '«'),
+    ("raquo", '»'),
+    ("iexcl", '¡'),
+    ("iquest", '¿'),
+    ("copy", '©'),
+    ("reg", '®'),
+    ("trade", '™'),
+    ("deg", '°'),
+    ("euro", '€'),
+    ("pound", '£'),
+    ("yen", '¥'),
+    ("cent", '¢'),
+    ("sect", '§'),
+    ("para", '¶'),
+    ("times", '×'),
+    ("divide", '÷'),
+    ("plusmn", '±'),
+    ("middot", '·'),
+    ("bull", '•'),
+    ("prime", '′'),
+    ("frac12", '½'),
+    ("frac14", '¼'),
+    ("frac34", '¾'),
+    ("aacute", 'á'),
+    ("eacute", 'é'),
+    ("iacute", 'í'),
+    ("oacute", 'ó'),
+    ("uacute", 'ú'),
+    ("Aacute", 'Á'),
+    ("Eacute", 'É'),
+    ("Iacute", 'Í'),
+    ("Oacute", 'Ó'),
+    ("Uacute", 'Ú'),
+    ("agrave", 'à'),
+    ("egrave", 'è'),
+    ("acirc", 'â'),
+    ("ecirc", 'ê'),
+    ("ocirc", 'ô'),
+    ("auml", 'ä'),
+    ("ouml", 'ö'),
+    ("uuml", 'ü'),
+    ("Auml", 'Ä'),
+    ("Ouml", 'Ö'),
+    ("Uuml", 'Ü'),
+    ("ntilde", 'ñ'),
+    ("Ntilde", 'Ñ'),
+    ("ccedil", 'ç'),
+    ("Ccedil", 'Ç'),
+    ("szlig", 'ß'),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn markdown(text: &str) -> Vec<(String, u32)> {
+        blocks(text, InputFormat::Markdown)
+    }
+
+    fn block(text: &str, pause_ms: u32) -> (String, u32) {
+        (text.to_string(), pause_ms)
+    }
+
+    #[test]
+    fn markers_links_and_code_are_not_read() {
+        let text = "This is **bold**, _very_ ~~old~~ and `code`.\n\
+                    See [the docs](https://example.com/a_(b)) or ![a cat](cat.png) [here][1].\n\n\
+                    [1]: https://example.com\n\
+                    Keep snake_case, 2 * 3 and \\*stars\\*. A [note] stays.";
+        assert_eq!(
+            markdown(text),
+            vec![
+                block("This is bold, very old and code. See the docs or a cat here.", 0),
+                block("Keep snake_case, 2 * 3 and *stars*. A [note] stays.", 0),
+            ]
+        );
+        assert_eq!(blocks("**as is**", InputFormat::Plain), vec![block("**as is**", 0)]);
+    }
+
+    #[test]
+    fn headings_and_list_items_get_their_own_pause() {
+        let text = "# Shopping list #\nWe need:\n- [x] apples\n* pears,\n  ripe ones\n2) *plums*\n\n\
+                    Done\n===\n> Quoted\n---\n| Fruit | Price |\n|---|:-:|\n| Apple | $1 |";
+        assert_eq!(
+            markdown(text),
+            vec![
+                block("Shopping list.", HEADING_PAUSE_MS),
+                block("We need:", ITEM_PAUSE_MS),
+                block("apples", ITEM_PAUSE_MS),
+                block("pears, ripe ones", ITEM_PAUSE_MS),
+                block("plums", ITEM_PAUSE_MS),
+                block("Done.", HEADING_PAUSE_MS),
+                block("Quoted.", HEADING_PAUSE_MS),
+                block("Fruit, Price", ITEM_PAUSE_MS),
+                block("Apple, $1", ITEM_PAUSE_MS),
+            ]
+        );
+        assert_eq!(markdown("***\n#hashtag\n#"), vec![block("#hashtag", 0)]);
+    }
+
+    #[test]
+    fn code_blocks_are_skipped_up_to_their_fence() {
+        let text = "Run this:\n```rust\nlet x = 1;\n```\n~~~~\n```\nstill code\n~~~~\n\
+                    Then ```this``` works.";
+        assert_eq!(markdown(text), vec![block("Run this:", 0), block("Then this works.", 0)]);
+        // An unclosed fence doesn't swallow the rest
+        let unclosed = "Intro.\n```python\nprint(1)\n\nMore text.";
+        let expected = vec![block("Intro.", 0), block("print(1)", 0), block("More text.", 0)];
+        assert_eq!(markdown(unclosed), expected);
+    }
+
+    fn html(text: &str) -> Vec<(String, u32)> {
+        blocks(text, InputFormat::Html)
+    }
+
+    #[test]
+    fn html_is_read_without_tags_or_entities() {
+        let page = "<!DOCTYPE html><html><head><title>News</title>\n<style>p { color: red }</style>\n\
+                    <script type=\"text/javascript\">if (a < b && c) {}</script></head><body>\n\
+                    <h2 class=\"x\">Tom &amp; Jerry&#8217;s&nbsp;return</h2>\n<!-- <p>hidden</p> -->\n\
+                    <p>It&#x2019;s <b>back</b>,\n   <a href=\"/a?b=1&amp;c=2\">today</a>.</p>\n\
+                    <p>Line one<br/>Line two <img src=\"x.png\" alt=\"&quot;Hi&quot;\"></p>\n\
+                    <ul><li>One</li><li>Two</li></ul><table><tr><th>A</th><th>B</th></tr></table>\n\
+                    <p></p></body></html>";
+        assert_eq!(
+            html(page),
+            vec![
+                block("News", 0),
+                block("Tom & Jerry’s return.", HEADING_PAUSE_MS),
+                block("It’s back, today.", PARAGRAPH_PAUSE_MS),
+                block("Line one", BREAK_PAUSE_MS),
+                block("Line two \"Hi\"", PARAGRAPH_PAUSE_MS),
+                block("One", ITEM_PAUSE_MS),
+                block("Two", ITEM_PAUSE_MS),
+                block("A, B", PARAGRAPH_PAUSE_MS),
+            ]
+        );
+    }
+
+    #[test]
+    fn broken_html_is_stripped_as_far_as_it_goes() {
+        assert_eq!(
+            html("if a < b &amp c &bogus; &#xZZ; <b>bold</i> <p class=\"open>x</p> y <b"),
+            vec![
+                block("if a < b &amp c &bogus; &#xZZ; bold", PARAGRAPH_PAUSE_MS),
+                block("x", PARAGRAPH_PAUSE_MS),
+                block("y <b", 0),
+            ]
+        );
+        assert_eq!(html("Before<script>never(); <p>read"), vec![block("Before", 0)]);
+        assert_eq!(html("Before<!-- never closed <p>read"), vec![block("Before", 0)]);
+    }
+}
@@ -20,15 +20,18 @@
 //! }
 //! ```
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fs::{self, File};
 use std::io::Write;
+use std::ops::{ControlFlow, Range};
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
 use std::thread;
 use std::time::Duration;
 
 use espeak_rs::text_to_phonemes;
+use tracing::{debug, info, trace, warn, Instrument};
 
 // MEM-8 Integration module
 pub mod mem8_bridge;
@@ -41,6 +44,58 @@ pub mod mem8_voice;
 
 // MCP Server module for AI collaboration
 pub mod mcp_server;
+
+// Minimal SSML subset (breaks, prosody rate, say-as, sub)
+pub mod ssml;
+
+// `[voice] text` markup for multi-speaker dialogue
+pub mod dialogue;
+
+// Text normalization (numbers to words) ahead of phonemization
+pub mod text;
+
+// SRT and WebVTT output for `TtsEngine::synthesize_with_subtitles`
+pub mod subtitles;
+
+// Speaking text as it streams in, a sentence at a time
+pub mod incremental;
+
+// Markdown and HTML input turned into the plain text to speak (see `InputFormat`)
+mod input;
+
+// Inline pause markers like `[pause:1500ms]` (see `PauseMarkup`)
+mod pause_markup;
+
+// Audio post-processing (pitch shift, loudness, resampling, PCM conversion)
+pub mod audio;
+
+// Emoji detection and spoken names (see `EmojiPolicy`)
+pub mod emoji;
+
+// Audio output devices and live playback while synthesizing
+#[cfg(feature = "playback")]
+pub mod playback;
+
+// C ABI for embedding in C and C++ programs
+#[cfg(feature = "ffi")]
+pub mod ffi;
+
+// SHA-256 for verifying downloaded model files
+mod checksum;
+
+// OpenAI-compatible `/v1/audio/speech` HTTP endpoint
+#[cfg(feature = "server")]
+pub mod server;
+
+// Error type returned across the crate
+mod error;
+pub use error::Error;
+pub use subtitles::SubtitleFormat;
+pub use incremental::IncrementalSpeech;
+pub use input::InputFormat;
+pub use pause_markup::PauseMarkup;
+pub use text::UrlStyle;
+pub use mem8_bridge::EmotionType;
 use ndarray::{ArrayBase, IxDyn, OwnedRepr};
 use ndarray_npy::NpzReader;
 use ort::{
@@ -49,7 +104,7 @@ use ort::{
 };
 
 #[cfg(feature = "playback")]
-use rodio::{Decoder, OutputStream, Sink};
+use rodio::Sink;
 
 // Cursor is used for in-memory audio operations, not just playback
 use std::io::Cursor;
@@ -59,18 +114,79 @@ use enigo::{Enigo, Key, Keyboard, Settings};
 
 // Constants - Model files stored in GitHub LFS
 const MODEL_URL: &str = "https://github.com/8b-is/kokoro-tiny/raw/main/models/0.onnx";
+// Dynamic int8 quantization of the same model, about a quarter of the size
+const QUANTIZED_MODEL_URL: &str =
+    "https://github.com/thewh1teagle/kokoro-onnx/releases/download/model-files-v1.0/kokoro-v1.0.int8.onnx";
 const VOICES_URL: &str = "https://github.com/8b-is/kokoro-tiny/raw/main/models/0.bin";
+// SHA-256 of the files at those URLs, as recorded in their Git LFS pointers
+const MODEL_SHA256: &str = "7d5df8ecf7d4b1878015a32686053fd0eebe2bc377234608764cc0ef3636a6c5";
+const VOICES_SHA256: &str = "bca610b8308e8d99f32e6fe4197e7ec01679264efed0cac9140fe9c29f1fbf7d";
+const DOWNLOAD_ATTEMPTS: u32 = 3; // Tries per file when the download doesn't match its checksum
+// Network retries per download, waiting RETRY_DELAY, then twice as long each time
+const NETWORK_RETRIES: u32 = 5;
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+const READ_TIMEOUT: Duration = Duration::from_secs(60); // A stalled transfer is retried
 const SAMPLE_RATE: u32 = 24000; // Kokoro model sample rate
 const DEFAULT_VOICE: &str = "af_sky";
+const WARM_UP_TEXT: &str = "Hello."; // Short, but runs phonemization and the whole model
 const DEFAULT_SPEED: f32 = 1.0; // User-facing normal speed (maps to model 0.65)
-const DEFAULT_LANG: &str = "en";
+const DEFAULT_LANG: &str = "en"; // Normalization/phonemizer language for voices without a known prefix
 const SPEED_SCALE: f32 = 1.0; // Model speed = user speed * this scale factor
 const LONG_TEXT_THRESHOLD: usize = 120;
+const LONG_TEXT_WARNING: usize = 10_000; // Characters above which `validate` warns
 const MAX_CHARS_PER_CHUNK: usize = 180;
-const CHUNK_CROSSFADE_MS: usize = 45;
+const CHUNK_CROSSFADE_MS: u32 = 10; // Default overlap where chunk audio is joined
+const MAX_CHUNK_CROSSFADE_MS: u32 = 100; // Longer overlaps would blend whole syllables
+const SILENCE_KEEP_MS: usize = 50; // Audio kept around the first/last audible sample when trimming
+const EDGE_RAMP_MS: u32 = 2; // Ramp from/to zero at the very ends of cleaned-up audio
+const EDGE_SILENCE_DB: f32 = -50.0; // Silence level at dialogue line edges and for `set_edge_silence`
+const DIALOGUE_GAP_MS: u32 = 300; // Default silence between dialogue lines
+const BABY_STATE_VERSION: u32 = 2; // Format `BabyTts::save_state` writes; bump on any change
+// Phoneme tokens (pads included) the bundled voices speak per second at speed
+// 1.0 in English prose, for `TtsEngine::estimate`
+const SPOKEN_TOKENS_PER_SEC: f32 = 17.0;
+
+// `BabyTts::learn_from_audio`: what counts as sound (dB below the loudest
+// 10ms frame) and as a pause between words, how much longer or shorter than
+// expected a recording may be, how far each recording moves a phoneme's
+// duration factor, and the range the factors stay in
+const LEARN_VOICED_DB: f32 = -35.0;
+const LEARN_WORD_GAP_MS: usize = 60;
+const LEARN_MAX_STRETCH: f32 = 3.0;
+const LEARN_RATE: f32 = 0.3;
+const LEARN_MIN_FACTOR: f32 = 0.5;
+const LEARN_MAX_FACTOR: f32 = 2.0;
+
+// Ogg Opus export: valid bitrate range, default for `save_audio`, the
+// standard encoder delay (in 48kHz samples) declared in the Opus header, and
+// the 20ms frame length at the model rate
+const OPUS_MIN_BITRATE: i32 = 6_000;
+const OPUS_MAX_BITRATE: i32 = 510_000;
+const OPUS_DEFAULT_BITRATE: i32 = 32_000;
+const OPUS_PRE_SKIP: u16 = 312;
+const OPUS_FRAME_SAMPLES: usize = SAMPLE_RATE as usize / 50;
 const MIN_ENGINE_SPEED: f32 = 0.35;
 const MAX_ENGINE_SPEED: f32 = 2.2;
 const PAD_TOKEN: char = '$'; // Padding token for beginning/end of phonemes
+const EDGE_PAD_COUNT: usize = 3; // Pad tokens added on each side of a chunk
+const STYLE_DIM: usize = 256; // Width of one Kokoro style vector
+const STYLE_ROWS: usize = 510; // Token-indexed style rows per bundled voice
+const MAX_MODEL_TOKENS: usize = 512; // Kokoro context length, pad tokens included
+const MAX_CHUNK_TOKENS: usize = MAX_MODEL_TOKENS - 2 * EDGE_PAD_COUNT; // Phoneme tokens per chunk, pads excluded
+// Smallest plausible model and voices files: anything under 1MB is not an ONNX
+// export (often a Git LFS pointer or an HTML error page), and a voices file
+// holds at least one voice
+const MIN_MODEL_BYTES: u64 = 1_000_000;
+const MIN_VOICES_BYTES: u64 = (STYLE_ROWS * STYLE_DIM * 4) as u64;
+// A download lock this long without progress was left behind by a dead process
+const STALE_DOWNLOAD: Duration = Duration::from_secs(120);
+// Token input and audio output names: kokoro-onnx exports (both variants) use
+// the first, the onnx-community (transformers.js) exports the second
+const TOKENS_INPUT_NAMES: [&str; 2] = ["tokens", "input_ids"];
+const AUDIO_OUTPUT_NAMES: [&str; 2] = ["audio", "waveform"];
+// Output names under which Kokoro exports provide per-token durations
+const DURATION_OUTPUT_NAMES: [&str; 3] = ["durations", "duration", "pred_dur"];
 
 // Fallback audio message - "Excuse me, I lost my voice. Give me time to get it back."
 // This is a pre-generated minimal WAV file that can play while downloading
@@ -104,16 +220,16 @@ fn load_cached_device() -> Option<String> {
 }
 
 #[cfg(feature = "playback")]
-fn save_cached_device(name: Option<&str>) -> Result<(), String> {
+fn save_cached_device(name: Option<&str>) -> Result<(), Error> {
     let path = cache_path();
     if let Some(n) = name {
         std::fs::create_dir_all(path.parent().unwrap_or(&get_cache_dir()))
-            .map_err(|e| format!("Failed to create cache dir: {}", e))?;
-        std::fs::write(&path, n).map_err(|e| format!("Failed to write cache: {}", e))?;
+            .map_err(|e| Error::io("Failed to create cache dir", e))?;
+        std::fs::write(&path, n).map_err(|e| Error::io("Failed to write cache", e))?;
     } else {
         // remove cache file
         if path.exists() {
-            std::fs::remove_file(&path).map_err(|e| format!("Failed to remove cache: {}", e))?;
+            std::fs::remove_file(&path).map_err(|e| Error::io("Failed to remove cache", e))?;
         }
     }
     Ok(())
@@ -147,14 +263,173 @@ fn pick_preferred_device(devices: &[String]) -> Option<String> {
         .cloned()
 }
 
+/// A loaded model and voices file, shareable between engines (see
+/// `TtsEngine::with_model`). Clones share the same memory, so an engine per
+/// voice or device costs only its own settings.
+///
+/// Example: `let model = KokoroModel::from_paths("0.onnx", "0.bin")?;` then
+/// `TtsEngine::with_model(model.clone())` for each engine.
+#[derive(Clone)]
+pub struct KokoroModel {
+    sessions: Arc<Vec<Mutex<Session>>>, // The first, then any inference workers
+    voices: Arc<HashMap<String, Vec<f32>>>,
+    variant: ModelVariant,
+    execution_provider: ExecutionProvider, // The one actually in use, after any fallback
+}
+
+impl KokoroModel {
+    /// Load local model and voices files without any network access; the
+    /// checks are those of `TtsEngine::from_paths`
+    pub fn from_paths(
+        model_path: impl AsRef<Path>,
+        voices_path: impl AsRef<Path>,
+    ) -> Result<Self, Error> {
+        let (model_path, voices_path) = (model_path.as_ref(), voices_path.as_ref());
+        let problems: Vec<String> = [
+            check_model_file("Model", model_path, MIN_MODEL_BYTES),
+            check_model_file("Voices", voices_path, MIN_VOICES_BYTES),
+        ]
+        .into_iter()
+        .filter_map(Result::err)
+        .map(|e| e.to_string())
+        .collect();
+        if !problems.is_empty() {
+            return Err(Error::ModelLoad(problems.join("; ")));
+        }
+        let variant = ModelVariant::from_file_name(model_path);
+        Self::load(model_path, voices_path, variant, &SessionConfig::default())
+    }
+
+    /// Build the model from bytes already in memory (an embedded or
+    /// unpacked copy of the model and voices files), without touching the
+    /// filesystem. The error says which of the two buffers is invalid.
+    /// A model with int8 quantization ops is reported as `ModelVariant::Quantized`.
+    pub fn from_memory(model_bytes: &[u8], voices_bytes: &[u8]) -> Result<Self, Error> {
+        let voices = read_voices(Cursor::new(voices_bytes))
+            .map_err(|e| Error::ModelLoad(format!("Invalid voices bytes: {}", e)))?;
+        let variant = ModelVariant::from_model_bytes(model_bytes);
+        Self::build(model_bytes, voices, variant, &SessionConfig::default())
+            .map_err(|e| Error::ModelLoad(format!("Invalid model bytes: {}", e)))
+    }
+
+    // Load the model and voices files
+    fn load(
+        model_path: &Path,
+        voices_path: &Path,
+        variant: ModelVariant,
+        config: &SessionConfig,
+    ) -> Result<Self, Error> {
+        let _span = tracing::info_span!("load_model", path = %model_path.display()).entered();
+        let model_bytes = std::fs::read(model_path)
+            .map_err(|e| Error::ModelLoad(format!("Failed to read model file: {}", e)))?;
+        let model = Self::build(&model_bytes, load_voices(voices_path)?, variant, config)?;
+        info!(
+            ?variant,
+            provider = %model.execution_provider,
+            workers = config.workers,
+            voices = model.voices.len(),
+            "Loaded the model"
+        );
+        Ok(model)
+    }
+
+    // Create the sessions `config` asks for on the model in `model_bytes`
+    fn build(
+        model_bytes: &[u8],
+        voices: HashMap<String, Vec<f32>>,
+        variant: ModelVariant,
+        config: &SessionConfig,
+    ) -> Result<Self, Error> {
+        let (session, execution_provider) = create_session(model_bytes, config)?;
+        // Workers use what the first session ended up on, without retrying a failed GPU
+        let worker_config = SessionConfig {
+            provider: execution_provider,
+            ..config.clone()
+        };
+        let mut sessions = vec![Mutex::new(session)];
+        for _ in 1..config.workers {
+            sessions.push(Mutex::new(create_session(model_bytes, &worker_config)?.0));
+        }
+
+        Ok(Self {
+            sessions: Arc::new(sessions),
+            voices: Arc::new(voices),
+            variant,
+            execution_provider,
+        })
+    }
+
+    /// Which model export is loaded
+    pub fn variant(&self) -> ModelVariant {
+        self.variant
+    }
+
+    /// Where the model runs (see `TtsEngine::execution_provider`)
+    pub fn execution_provider(&self) -> ExecutionProvider {
+        self.execution_provider
+    }
+
+    /// Sessions engines on this model share (see `TtsEngineBuilder::inference_workers`)
+    pub fn inference_workers(&self) -> usize {
+        self.sessions.len()
+    }
+}
+
+// Files a lazy engine loads on first use (see `TtsEngineBuilder::lazy`)
+struct LazyLoad {
+    model_path: PathBuf,
+    voices_path: PathBuf,
+    config: SessionConfig,
+}
+
 /// Main TTS engine struct
+///
+/// Synthesis takes `&self`, so one engine can serve several threads through an
+/// `Arc<TtsEngine>`. Each model run locks one ONNX session; calls beyond
+/// `TtsEngineBuilder::inference_workers` sessions wait for one to free up.
 pub struct TtsEngine {
-    session: Option<Arc<Mutex<Session>>>,
-    voices: HashMap<String, Vec<f32>>,
+    model: OnceLock<Result<KokoroModel, Error>>, // Unset in fallback mode and until a lazy load
+    lazy_load: Option<LazyLoad>, // Files `model` is loaded from on first use
+    parallel_chunks: bool, // Spread the chunks of long texts over all sessions
+    next_session: AtomicUsize, // Turn for the session to wait on when all are busy
+    custom_voices: HashMap<String, Vec<f32>>, // Blended and loaded voices, this engine only
     vocab: HashMap<char, i64>,
     fallback_mode: bool,
+    model_variant: ModelVariant,
+    execution_provider: ExecutionProvider, // The one actually in use, after any fallback
+    default_voice: String, // Used when a call doesn't name a voice
+    default_speed: f32, // Used when a call (or segment) doesn't set a speed
+    target_loudness: Option<f32>, // LUFS target for finished audio, None = off
+    trim_silence_db: Option<f32>, // Trim edge silence below this level, None = off
+    fade_ms: (u32, u32), // Fade-in and fade-out of finished audio
+    dc_cleanup: bool, // DC blocking and zeroed ends on finished audio
+    edge_silence_ms: Option<(u32, u32)>, // Exact leading/trailing silence, None = as synthesized
+    dialogue_gap_ms: u32, // Silence between the lines of `synthesize_dialogue`
+    pause_config: PauseConfig, // Extra silence after punctuation
+    voice_profiles: HashMap<String, VoiceProfile>, // Speed/pitch tuning by voice name
+    emotion_styles: HashMap<&'static str, EmotionStyle>, // `set_emotion_style` overrides, by name
+    normalizer: text::Normalizer, // English text expansion (numbers, abbreviations, acronyms)
+    pronunciations: HashMap<String, String>, // `add_pronunciation` phonemes, by lowercased word
+    emoji_policy: EmojiPolicy,
+    url_style: UrlStyle, // How web and email addresses in English text are read
+    input_format: InputFormat, // Markup stripped before anything else
+    pause_markup: Option<PauseMarkup>, // Inline pause markers, None = read as text
+    unknown_char_policy: UnknownCharPolicy,
+    max_chunk_tokens: usize, // Chunks with more phoneme tokens are split further
+    chunk_crossfade_ms: Option<u32>, // Overlap between joined chunks, None = plain append
+    processors: Processors, // `add_processor` chain for finished audio and streamed chunks
+    #[cfg(feature = "playback")]
+    audio_host: playback::AudioHost, // Backend that `audio_device` is looked up on
     #[cfg(feature = "playback")]
     audio_device: Option<String>, // Selected audio device name
+    #[cfg(feature = "playback")]
+    output_channels: Option<Vec<u16>>, // Device channels speech goes to, None = rodio's default
+    #[cfg(feature = "playback")]
+    playback_stop: playback::StopSignal, // Raised by `stop_playback`
+    #[cfg(feature = "playback")]
+    speech_queue: Option<playback::SpeechQueue>, // Started by the first `enqueue`
+    #[cfg(feature = "playback")]
+    urgent_mode: playback::UrgentMode,
 }
 
 /// Baby speech mode for mem8 - handles simple utterances
@@ -164,7 +439,310 @@ pub struct BabyTts {
     pub voice: String,
     pub speed: f32,
     pub gain: f32,
-    pub lang: String
+    pub lang: String,
+    /// Words heard in `learn_from_audio`, lowercased, with how often
+    pub vocabulary: BTreeMap<String, u32>,
+    /// How long each phoneme came out in `learn_from_audio` recordings,
+    /// relative to the engine's usual pace (2.0 = twice as long)
+    pub phoneme_durations: BTreeMap<char, f32>,
+    /// Turns the engine's adult voice into a child's for everything the baby
+    /// says; None speaks in the adult voice
+    pub child_voice: Option<ChildVoiceConfig>,
+}
+
+/// How far a `BabyTts` has learned to talk, set by its `max_words`
+///
+/// | Stage | `max_words` | `set_stage` sets |
+/// |---|---|---|
+/// | Babbling | 0 | 0 |
+/// | SingleWords | 1 | 1 |
+/// | TwoWordPhrases | 2 | 2 |
+/// | SimpleSentences | 3-9 | 3 |
+/// | FullSentences | 10 and up | 10 |
+///
+/// Each `grow()` adds a word, so it moves a stage forward up to
+/// SimpleSentences, and `new` starts at SimpleSentences (5 words).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DevelopmentStage {
+    /// Only babble sounds, whatever it is asked to say
+    Babbling,
+    SingleWords,
+    TwoWordPhrases,
+    SimpleSentences,
+    FullSentences,
+}
+
+impl DevelopmentStage {
+    fn of(max_words: usize) -> Self {
+        match max_words {
+            0 => DevelopmentStage::Babbling,
+            1 => DevelopmentStage::SingleWords,
+            2 => DevelopmentStage::TwoWordPhrases,
+            3..=9 => DevelopmentStage::SimpleSentences,
+            _ => DevelopmentStage::FullSentences,
+        }
+    }
+
+    // `max_words` when a baby enters this stage
+    fn first_word_limit(self) -> usize {
+        match self {
+            DevelopmentStage::Babbling => 0,
+            DevelopmentStage::SingleWords => 1,
+            DevelopmentStage::TwoWordPhrases => 2,
+            DevelopmentStage::SimpleSentences => 3,
+            DevelopmentStage::FullSentences => 10,
+        }
+    }
+}
+
+/// What `BabyTts::speak_detailed` said
+#[derive(Clone, Debug, PartialEq)]
+pub struct BabySpeech {
+    pub samples: Vec<f32>,
+    /// The text actually spoken, after the stage's limit
+    pub spoken: String,
+    /// Whether the input was cut down (or babbled) to fit the stage
+    pub reduced: bool,
+}
+
+/// What one `BabyTts::learn_from_audio` call picked up
+#[derive(Clone, Debug, PartialEq)]
+pub struct LearningReport {
+    /// Seconds from the first to the last sound in the recording
+    pub speech_secs: f32,
+    /// Seconds the text takes at the engine's usual pace
+    pub expected_secs: f32,
+    /// Words timed on their own: all of them when the recording has one
+    /// stretch of sound per word, otherwise 0 and the whole recording sets
+    /// the pace of every word
+    pub aligned_words: usize,
+    /// Phonemes whose duration factor changed, in phoneme order
+    pub updated: Vec<PhonemeUpdate>,
+    /// Words heard for the first time, lowercased
+    pub new_words: Vec<String>,
+}
+
+/// One phoneme's duration factor before and after `BabyTts::learn_from_audio`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PhonemeUpdate {
+    pub phoneme: char,
+    pub before: f32,
+    pub after: f32,
+}
+
+/// How `BabyTts` turns an adult voice into a young child's
+///
+/// The pitch goes up `semitones` with the formants kept where they were, so
+/// the voice sounds younger rather than sped up, speech runs `speed` times
+/// faster, and the pitch wavers by up to `wobble_semitones` about `wobble_hz`
+/// times a second, the way it does before a child's voice settles.
+///
+/// `at_stage` gives a voice that sounds older at each later stage, with pitch
+/// and wobble easing off; set it after `grow()` to age the baby:
+/// `baby.child_voice = Some(ChildVoiceConfig::at_stage(baby.stage()))`. The
+/// default is the SimpleSentences voice that `BabyTts::new` starts with.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ChildVoiceConfig {
+    /// Pitch shift, within ±12
+    pub semitones: f32,
+    /// Multiplies the baby's speed
+    pub speed: f32,
+    /// Largest pitch wobble, 0.0-2.0 semitones (0 = steady)
+    pub wobble_semitones: f32,
+    /// How fast the pitch wobbles, above 0 and up to 20Hz
+    pub wobble_hz: f32,
+}
+
+impl Default for ChildVoiceConfig {
+    fn default() -> Self {
+        Self::at_stage(DevelopmentStage::SimpleSentences)
+    }
+}
+
+impl ChildVoiceConfig {
+    /// The child voice for a baby at `stage`, from +6 semitones while
+    /// Babbling to +4 at FullSentences
+    pub fn at_stage(stage: DevelopmentStage) -> Self {
+        let (semitones, wobble_semitones) = match stage {
+            DevelopmentStage::Babbling => (6.0, 0.6),
+            DevelopmentStage::SingleWords => (5.5, 0.5),
+            DevelopmentStage::TwoWordPhrases => (5.0, 0.4),
+            DevelopmentStage::SimpleSentences => (4.5, 0.3),
+            DevelopmentStage::FullSentences => (4.0, 0.2),
+        };
+        Self { semitones, speed: 1.1, wobble_semitones, wobble_hz: 5.0 }
+    }
+
+    fn validate(&self) -> Result<(), Error> {
+        if !self.semitones.is_finite() || self.semitones.abs() > audio::MAX_PITCH_SEMITONES {
+            return Err(Error::InvalidInput(format!(
+                "Invalid child voice: pitch {} must be within ±{} semitones",
+                self.semitones,
+                audio::MAX_PITCH_SEMITONES
+            )));
+        }
+        if !self.speed.is_finite() || self.speed <= 0.0 {
+            return Err(Error::InvalidInput(format!(
+                "Invalid child voice: speed {} must be above 0",
+                self.speed
+            )));
+        }
+        let rate_in_range = self.wobble_hz > 0.0 && self.wobble_hz <= 20.0;
+        if !((0.0..=2.0).contains(&self.wobble_semitones) && rate_in_range) {
+            return Err(Error::InvalidInput(format!(
+                "Invalid child voice: wobble of {} semitones at {}Hz must be 0-2 semitones, up to 20Hz",
+                self.wobble_semitones, self.wobble_hz
+            )));
+        }
+        Ok(())
+    }
+
+    /// Make `samples` (24kHz speech) sound like this child; the length is kept
+    ///
+    /// Only the pitch and wobble apply here, since the speed has to go to the
+    /// model. `BabyTts` does both.
+    pub fn apply(&self, samples: &[f32]) -> Result<Vec<f32>, Error> {
+        self.validate()?;
+        let shifted =
+            audio::pitch_shift_keeping_formants(samples, self.semitones).map_err(Error::InvalidInput)?;
+        Ok(audio::pitch_wobble(&shifted, self.wobble_semitones, self.wobble_hz))
+    }
+}
+
+/// How `BabyTts::babble_with` babbles
+///
+/// An utterance is `min_syllables..=max_syllables` syllables from `syllables`,
+/// run together as one word. After the first, each syllable repeats the one
+/// before with probability `repetition` (canonical "bababa") and is otherwise
+/// drawn from the whole inventory (variegated "badaga"). The default babbles
+/// one or two of "ma", "ba", "da", "ga" and "goo", half the time repeating.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BabbleConfig {
+    /// Consonant-vowel syllables to babble with, written as espeak reads them
+    pub syllables: Vec<String>,
+    pub min_syllables: usize,
+    pub max_syllables: usize,
+    /// Chance, 0.0-1.0, that a syllable repeats the previous one
+    pub repetition: f32,
+    /// Seed for identical babbling on every call; None = different each call
+    pub seed: Option<u64>,
+}
+
+impl Default for BabbleConfig {
+    fn default() -> Self {
+        Self {
+            syllables: ["ma", "ba", "da", "ga", "goo"].map(String::from).to_vec(),
+            min_syllables: 1,
+            max_syllables: 2,
+            repetition: 0.5,
+            seed: None,
+        }
+    }
+}
+
+impl BabbleConfig {
+    fn validate(&self) -> Result<(), Error> {
+        if self.syllables.iter().all(|syllable| syllable.trim().is_empty()) {
+            return Err(Error::InvalidInput("Babble needs at least one syllable".to_string()));
+        }
+        if self.min_syllables == 0 || self.min_syllables > self.max_syllables {
+            return Err(Error::InvalidInput(format!(
+                "Babble length must be at least 1 syllable with min <= max, got {}..={}",
+                self.min_syllables, self.max_syllables
+            )));
+        }
+        if !(0.0..=1.0).contains(&self.repetition) {
+            return Err(Error::InvalidInput(format!(
+                "Babble repetition must be between 0.0 and 1.0, got {}",
+                self.repetition
+            )));
+        }
+        Ok(())
+    }
+
+    // One babbled word; the config must be valid
+    pub(crate) fn utterance(&self) -> String {
+        let syllables: Vec<&str> = self
+            .syllables
+            .iter()
+            .map(|syllable| syllable.trim())
+            .filter(|syllable| !syllable.is_empty())
+            .collect();
+        let mut rng = SeededRng::new(self.seed);
+        let length = self.min_syllables + rng.below(self.max_syllables - self.min_syllables + 1);
+        let mut word = String::new();
+        let mut previous = syllables[rng.below(syllables.len())];
+        word.push_str(previous);
+        for _ in 1..length {
+            if rng.next_f32() >= self.repetition {
+                previous = syllables[rng.below(syllables.len())];
+            }
+            word.push_str(previous);
+        }
+        word
+    }
+}
+
+// xorshift64*, repeatable from a seed, for babbling and the mem8 bridge's whims
+pub(crate) struct SeededRng(u64);
+
+impl SeededRng {
+    pub(crate) fn new(seed: Option<u64>) -> Self {
+        // Unseeded: the clock, plus a count so two in the same instant differ
+        static UNSEEDED: AtomicUsize = AtomicUsize::new(0);
+        let seed = seed.unwrap_or_else(|| {
+            let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH);
+            let count = UNSEEDED.fetch_add(1, Ordering::Relaxed) as u64;
+            now.map_or(0, |now| now.as_nanos() as u64) ^ count.wrapping_mul(0xBF58_476D_1CE4_E5B9)
+        });
+        // Spread the seed and keep the state off zero, where xorshift sticks
+        Self((seed ^ 0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    pub(crate) fn next_f32(&mut self) -> f32 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        // Top 24 bits, exactly representable, in [0, 1)
+        (self.0.wrapping_mul(0x2545_F491_4F6C_DD1D) >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    // Uniform in 0..n, n > 0
+    pub(crate) fn below(&mut self, n: usize) -> usize {
+        ((self.next_f32() * n as f32) as usize).min(n - 1)
+    }
+}
+
+// Words a toddler leaves out first
+const FUNCTION_WORDS: &[&str] = &[
+    "a", "an", "the", "i", "you", "me", "it", "is", "am", "are", "be", "to", "of", "and", "or",
+    "in", "on", "at", "please",
+];
+
+// A word without the punctuation around it ("mama's" keeps its apostrophe)
+fn word_core(word: &str) -> &str {
+    word.trim_matches(|c: char| !c.is_alphanumeric() && c != '\'')
+}
+
+// Phonemes espeak gives for a word, without pads, stress and length marks
+fn phoneme_symbols(phonemes: &str) -> impl Iterator<Item = char> + '_ {
+    phonemes
+        .chars()
+        .filter(|&c| c != PAD_TOKEN && c.is_alphabetic() && !matches!(c, 'ˈ' | 'ˌ' | 'ː'))
+}
+
+// What `BabyTts::save_state` writes, version 2; version 1 had no phoneme durations
+#[derive(serde::Serialize, serde::Deserialize)]
+struct BabyState {
+    version: u32,
+    max_words: usize,
+    voice: String,
+    speed: f32,
+    gain: f32,
+    lang: String,
+    vocabulary: BTreeMap<String, u32>,
+    #[serde(default)]
+    phoneme_durations: BTreeMap<char, f32>,
 }
 
 /// Options builder for synthesis parameters
@@ -175,7 +753,9 @@ pub struct SynthesizeOptions {
     pub voice: Option<String>,
     pub speed: f32,
     pub gain: f32,
-    pub lang: Option<String>
+    pub lang: Option<String>,
+    pub pitch: f32,
+    pub emotion: Option<EmotionType>,
 }
 
 impl Default for SynthesizeOptions {
@@ -184,7 +764,9 @@ impl Default for SynthesizeOptions {
             voice: None,
             speed: DEFAULT_SPEED,
             gain: 1.0,
-            lang: None
+            lang: None,
+            pitch: 0.0,
+            emotion: None,
         }
     }
 }
@@ -212,1209 +794,7190 @@ impl SynthesizeOptions {
         self.gain = gain;
         self
     }
-}
-
-impl TtsEngine {
-    /// Create a new TTS engine, downloading model files if necessary
-    /// Uses ~/.cache/k for shared model storage (minimal path!)
-    pub async fn new() -> Result<Self, String> {
-        let cache_dir = get_cache_dir();
-        let model_path = cache_dir.join("0.onnx");
-        let voices_path = cache_dir.join("0.bin");
 
-        Self::with_paths(
-            model_path.to_str().unwrap_or("0.onnx"),
-            voices_path.to_str().unwrap_or("0.bin"),
-        )
-        .await
+    /// Set pitch shift in semitones (0.0 = unchanged, ±12 max); duration is kept
+    pub fn pitch(mut self, semitones: f32) -> Self {
+        self.pitch = semitones;
+        self
     }
 
-    /// Create a new TTS engine with custom model paths
-    pub async fn with_paths(model_path: &str, voices_path: &str) -> Result<Self, String> {
-        // Ensure cache directory exists
-        if let Some(parent) = Path::new(model_path).parent() {
-            fs::create_dir_all(parent)
-                .map_err(|e| format!("Failed to create cache directory: {}", e))?;
-        }
-
-        // Check if we need to download
-        let need_download = !Path::new(model_path).exists() || !Path::new(voices_path).exists();
-
-        if need_download {
-            #[cfg(not(feature = "as-lib"))]
-            println!("🎤 First time setup - downloading voice model...");
-            #[cfg(not(feature = "as-lib"))]
-              println!("   (This only happens once, files will be cached in ~/.cache/k)");
-
-            // Auto-play fallback message while downloading (if playback is enabled)
-            #[cfg(feature = "playback")]
-            {
-                // Spawn a thread to play the fallback message
-                thread::spawn(|| {
-                    if let Err(e) = play_fallback_message() {
-                        eprintln!("   ℹ️  Could not play fallback message: {}", e);
-                    }
-                });
-            }
+    /// Speak with an emotion, e.g. `EmotionType::Joy(0.9)` (see `EmotionStyle`)
+    pub fn emotion(mut self, emotion: EmotionType) -> Self {
+        self.emotion = Some(emotion);
+        self
+    }
+}
 
-            // Try to download the files
-            let download_success = {
-                let mut success = true;
+/// Extra silence inserted after punctuation, in milliseconds
+///
+/// The model already pauses naturally at punctuation; these values are added on
+/// top of that. All zeros (the default) keeps the model's own pacing and
+/// synthesizes exactly as before. Non-zero values split the text after the
+/// matching marks (when followed by whitespace) so the silence can be inserted.
+///
+/// Example: `tts.set_pause_config(PauseConfig { period_ms: 300, ..Default::default() })`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PauseConfig {
+    /// After `,` `;` `:`
+    pub comma_ms: u32,
+    /// After `.` and `!`
+    pub period_ms: u32,
+    /// After `?`
+    pub question_ms: u32,
+    /// After `...` and `…`
+    pub ellipsis_ms: u32,
+}
 
-                // Download model if needed
-                if !Path::new(model_path).exists() {
-                    #[cfg(not(feature = "as-lib"))]
-                    println!("   📥 Downloading model (310MB)...");
-                    if let Err(e) = download_file(MODEL_URL, model_path).await {
-                        #[cfg(not(feature = "as-lib"))]
-                        eprintln!("   ❌ Failed to download model: {}", e);
-                        success = false;
-                    }
-                }
+/// How an emotion at full intensity changes speech (see `SynthesizeOptions::emotion`)
+///
+/// At lower intensities each setting moves proportionally from neutral, so
+/// `Joy(0.5)` is half as much faster, higher and louder as `Joy(1.0)`. The
+/// speed and energy multiply the call's speed and gain, and the pitch adds to
+/// its pitch shift. Defaults at full intensity:
+///
+/// | Emotion   | speed | pitch | energy |
+/// |-----------|-------|-------|--------|
+/// | Joy       | 1.10  | +2.0  | 1.15   |
+/// | Sadness   | 0.80  | -2.0  | 0.75   |
+/// | Fear      | 1.25  | +1.5  | 1.10   |
+/// | Curiosity | 1.05  | +1.0  | 1.00   |
+/// | Love      | 0.90  | +0.5  | 0.90   |
+/// | Confusion | 0.90  | -0.5  | 0.95   |
+/// | Neutral   | 1.00  |  0.0  | 1.00   |
+///
+/// Replace a row with `TtsEngine::set_emotion_style`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EmotionStyle {
+    /// Speed multiplier
+    pub speed: f32,
+    /// Pitch shift in semitones
+    pub pitch: f32,
+    /// Gain multiplier
+    pub energy: f32,
+}
 
-                // Download voices if needed
-                if success && !Path::new(voices_path).exists() {
-                    #[cfg(not(feature = "as-lib"))]
-                    println!("   📥 Downloading voices (27MB)...");
-                    if let Err(e) = download_file(VOICES_URL, voices_path).await {
-                        #[cfg(not(feature = "as-lib"))]
-                        eprintln!("   ❌ Failed to download voices: {}", e);
-                        success = false;
-                    }
-                }
+impl Default for EmotionStyle {
+    /// No change: how `Neutral` sounds
+    fn default() -> Self {
+        Self { speed: 1.0, pitch: 0.0, energy: 1.0 }
+    }
+}
 
-                if success {
-                    #[cfg(not(feature = "as-lib"))]
-                    println!("   ✅ Voice model downloaded successfully!");
-                }
+impl EmotionStyle {
+    /// The built-in full-intensity style for `emotion` (see the table above)
+    pub fn builtin(emotion: &EmotionType) -> Self {
+        let (speed, pitch, energy) = match emotion {
+            EmotionType::Joy(_) => (1.10, 2.0, 1.15),
+            EmotionType::Sadness(_) => (0.80, -2.0, 0.75),
+            EmotionType::Fear(_) => (1.25, 1.5, 1.10),
+            EmotionType::Curiosity(_) => (1.05, 1.0, 1.00),
+            EmotionType::Love(_) => (0.90, 0.5, 0.90),
+            EmotionType::Confusion(_) => (0.90, -0.5, 0.95),
+            EmotionType::Neutral => return Self::default(),
+        };
+        Self { speed, pitch, energy }
+    }
 
-                success
-            };
+    // This style `intensity` of the way from neutral
+    fn scaled(&self, intensity: f32) -> Self {
+        Self {
+            speed: 1.0 + (self.speed - 1.0) * intensity,
+            pitch: self.pitch * intensity,
+            energy: 1.0 + (self.energy - 1.0) * intensity,
+        }
+    }
 
-            // If download failed, return fallback engine
-            if !download_success {
-                #[cfg(not(feature = "as-lib"))]
-                eprintln!("\n⚠️  Using fallback mode. The model files are not available at:");
-                #[cfg(not(feature = "as-lib"))]
-                eprintln!("   - {}", MODEL_URL);
-                #[cfg(not(feature = "as-lib"))]
-                eprintln!("   - {}", VOICES_URL);
-                #[cfg(not(feature = "as-lib"))]
-                eprintln!("\n💡 Please manually download the model files to ~/.cache/k/");
-
-                return Ok(Self {
-                    session: None,
-                    voices: HashMap::new(),
-                    vocab: build_vocab(),
-                    fallback_mode: true,
-                    #[cfg(feature = "playback")]
-                    audio_device: None,
-                });
-            }
+    fn validate(&self) -> Result<(), Error> {
+        let positive = |value: f32| value.is_finite() && value > 0.0;
+        if !positive(self.speed) || !positive(self.energy) {
+            return Err(Error::InvalidInput(format!(
+                "Invalid emotion style: speed {} and energy {} must be above 0",
+                self.speed, self.energy
+            )));
+        }
+        if !self.pitch.is_finite() || self.pitch.abs() > audio::MAX_PITCH_SEMITONES {
+            return Err(Error::InvalidInput(format!(
+                "Invalid emotion style: pitch {} must be within ±{} semitones",
+                self.pitch,
+                audio::MAX_PITCH_SEMITONES
+            )));
         }
+        Ok(())
+    }
+}
 
-        // Load ONNX model
-        let model_bytes =
-            std::fs::read(model_path).map_err(|e| format!("Failed to read model file: {}", e))?;
-        let session = Session::builder()
-            .map_err(|e| format!("Failed to create session builder: {}", e))?
-            .with_optimization_level(GraphOptimizationLevel::Level3)
-            .map_err(|e| format!("Failed to set optimization level: {}", e))?
-            .commit_from_memory(&model_bytes)
-            .map_err(|e| format!("Failed to load model: {}", e))?;
+/// Tuning applied whenever a voice is used (see `TtsEngine::set_voice_profile`)
+///
+/// Example: `tts.set_voice_profile("af_sky", VoiceProfile { speed: 1.05, ..Default::default() })`
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct VoiceProfile {
+    /// Multiplies the speed of every call with this voice (1.0 = unchanged)
+    pub speed: f32,
+    /// Pitch shift in semitones (0.0 = unchanged, ±12 max); duration is kept
+    pub pitch: f32,
+}
 
-        // Load voices
-        let voices = load_voices(voices_path)?;
+impl Default for VoiceProfile {
+    fn default() -> Self {
+        Self { speed: 1.0, pitch: 0.0 }
+    }
+}
 
-        #[cfg_attr(not(feature = "playback"), allow(unused_mut))]
-        let mut engine = Self {
-            session: Some(Arc::new(Mutex::new(session))),
-            voices,
-            vocab: build_vocab(),
-            fallback_mode: false,
-            #[cfg(feature = "playback")]
-            audio_device: None,
-        };
+impl VoiceProfile {
+    fn validate(&self, voice: &str) -> Result<(), Error> {
+        if !(self.speed.is_finite() && self.speed > 0.0) {
+            return Err(Error::InvalidInput(format!(
+                "Invalid profile speed {} for {}: must be above 0",
+                self.speed, voice
+            )));
+        }
+        if !self.pitch.is_finite() || self.pitch.abs() > audio::MAX_PITCH_SEMITONES {
+            return Err(Error::InvalidInput(format!(
+                "Invalid profile pitch {} for {}: must be within ±{} semitones",
+                self.pitch,
+                voice,
+                audio::MAX_PITCH_SEMITONES
+            )));
+        }
+        Ok(())
+    }
+}
 
-        // Initialize audio device selection from cache or choose a preferred device
-        #[cfg(feature = "playback")]
-        {
-            if engine.audio_device.is_none() {
-                if let Some(cached) = load_cached_device() {
-                    engine.audio_device = Some(cached);
-                } else if let Ok(devs) = engine.list_audio_devices() {
-                    if let Some(pref) = pick_preferred_device(&devs) {
-                        // Persist preference but ignore errors
-                        let _ = save_cached_device(Some(&pref));
-                        engine.audio_device = Some(pref);
-                    }
-                }
-            }
+/// One style embedding in the form the model takes it: `StyleVector::DIM` values
+///
+/// Voices hold one of these per token count. `TtsEngine::style_for` fetches the
+/// one a chunk would get and `synthesize_with_style` speaks with any vector, so
+/// interpolating, perturbing or averaging styles happens in your code.
+///
+/// Example, a slightly exaggerated af_sky:
+/// `StyleVector::new(tts.style_for("af_sky", 40)?.into_vec().iter().map(|v| v * 1.1).collect())`
+#[derive(Clone, Debug, PartialEq)]
+pub struct StyleVector(Vec<f32>);
+
+impl StyleVector {
+    /// Number of values in a style vector
+    pub const DIM: usize = STYLE_DIM;
+
+    /// Wrap `values`, which must be `DIM` finite numbers
+    pub fn new(values: Vec<f32>) -> Result<Self, Error> {
+        if values.len() != Self::DIM {
+            return Err(Error::InvalidInput(format!(
+                "Style vector has {} values, the model takes {}",
+                values.len(),
+                Self::DIM
+            )));
         }
+        if let Some(i) = values.iter().position(|v| !v.is_finite()) {
+            return Err(Error::InvalidInput(format!("Style vector value {} is {}", i, values[i])));
+        }
+        Ok(Self(values))
+    }
 
-        Ok(engine)
+    pub fn as_slice(&self) -> &[f32] {
+        &self.0
     }
 
-    /// List all available voices
-    pub fn voices(&self) -> Vec<String> {
-        if self.fallback_mode {
-            vec!["fallback".to_string()]
-        } else {
-            self.voices.keys().cloned().collect()
-        }
+    pub fn into_vec(self) -> Vec<f32> {
+        self.0
     }
+}
 
-    /// List all available audio output devices (requires 'playback' feature)
-    #[cfg(feature = "playback")]
-    pub fn list_audio_devices(&self) -> Result<Vec<String>, String> {
-        use cpal::traits::{DeviceTrait, HostTrait};
+// Where the style vectors of one synthesis call come from
+#[derive(Clone, Copy)]
+enum StyleSource<'a> {
+    // A voice or mix (default voice if None), a row per chunk length, with its profile
+    Voice(Option<&'a str>),
+    // The same vector for every chunk, without any voice profile
+    Fixed(&'a StyleVector),
+}
 
-        let host = cpal::default_host();
-        let devices = host
-            .output_devices()
-            .map_err(|e| format!("Failed to get output devices: {}", e))?;
+/// Per-segment options for `synthesize_segments`; `None` fields use the engine defaults
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SegmentOptions {
+    pub speed: Option<f32>,
+}
 
-        let mut device_names = Vec::new();
-        for device in devices {
-            if let Ok(name) = device.name() {
-                device_names.push(name);
-            }
-        }
+impl SegmentOptions {
+    /// Create options that inherit everything from the engine
+    pub fn new() -> Self {
+        Self::default()
+    }
 
-        Ok(device_names)
+    /// Set user-facing speed for this segment (1.0 = normal)
+    pub fn speed(mut self, speed: f32) -> Self {
+        self.speed = Some(speed);
+        self
     }
+}
 
-    /// Set the audio output device by name (requires 'playback' feature)
-    /// Pass None to use system default
-    #[cfg(feature = "playback")]
-    pub fn set_audio_device(&mut self, device_name: Option<String>) -> Result<(), String> {
-        // Verify the device exists if a name was provided
-        if let Some(ref name) = device_name {
-            let available = self.list_audio_devices()?;
-            if !available.contains(name) {
-                return Err(format!(
-                    "Device '{}' not found. Available devices: {}",
-                    name,
-                    available.join(", ")
-                ));
-            }
-        }
+/// Output format for `save_wav_with_format` (always mono)
+///
+/// Example: `WavFormat { sample_rate: 16000, bit_depth: 16 }` for telephony.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WavFormat {
+    /// Output sample rate in Hz; audio is resampled from the model's 24kHz
+    pub sample_rate: u32,
+    /// 8, 16 or 24 for integer PCM (8/16 are dithered), 32 for float
+    pub bit_depth: u16,
+}
 
-        self.audio_device = device_name;
-        // Persist selection
-        #[cfg(feature = "playback")]
-        if let Err(e) = save_cached_device(self.audio_device.as_deref()) {
-            #[cfg(not(feature = "as-lib"))]
-            eprintln!("⚠️ Failed to save audio device selection: {}", e);
+impl Default for WavFormat {
+    /// The model's native 24kHz 16-bit format, the layout `save_wav` writes;
+    /// unlike `save_wav`, `save_wav_with_format` dithers the 16-bit samples, so
+    /// the bytes differ
+    fn default() -> Self {
+        Self {
+            sample_rate: SAMPLE_RATE,
+            bit_depth: 16,
         }
-        Ok(())
     }
+}
 
-    /// Get the currently selected audio device (requires 'playback' feature)
-    #[cfg(feature = "playback")]
-    pub fn get_audio_device(&self) -> Option<&str> {
-        self.audio_device.as_deref()
-    }
+/// Provenance written into a WAV's LIST/INFO chunk by `save_wav_with_metadata`.
+///
+/// `new()` fills in the software ("kokoro-tiny x.y.z") and today's date (UTC);
+/// fields left `None` aren't written.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WavMetadata {
+    /// `INAM`
+    pub title: Option<String>,
+    /// `IART`
+    pub artist: Option<String>,
+    /// `ISFT`
+    pub software: Option<String>,
+    /// `ICMT`, e.g. the source text and voice
+    pub comment: Option<String>,
+    /// `ICRD`, as YYYY-MM-DD
+    pub creation_date: Option<String>,
+}
 
-    /// Synthesize text to speech (simple form)
-    ///
-    /// This is the ergonomic two-argument form used by examples and callers:
-    /// - `text`: text to speak
-    /// - `voice`: optional voice name (defaults to `DEFAULT_VOICE`)
-    ///
-    /// For callers that need to control speed, use `synthesize_with_speed`.
-    pub fn synthesize(&mut self, text: &str, voice: Option<&str>, speed: Option<f32>, lang: Option<&str>) -> Result<Vec<f32>, String> {
-        // Forward to the speed-aware variant with the supplied or default user speed
-        self.synthesize_with_speed(text, voice, speed.unwrap_or(DEFAULT_SPEED), Some(lang.unwrap_or(DEFAULT_LANG)))
+impl WavMetadata {
+    /// Software and creation date filled in, everything else empty
+    pub fn new() -> Self {
+        Self {
+            software: Some(format!("kokoro-tiny {}", env!("CARGO_PKG_VERSION"))),
+            creation_date: Some(utc_date(std::time::SystemTime::now())),
+            ..Self::default()
+        }
     }
 
-    /// Backwards-compatible synthesize API which accepted an optional `speed`.
-    ///
-    /// This method preserves the original three-argument shape for compatibility
-    /// with older code that passed `Option<f32>` for speed.
-    #[deprecated(note = "use synthesize(text, voice) or synthesize_with_speed for custom speed")]
-    pub fn synthesize_with_optional_speed(
-        &mut self,
-        text: &str,
-        voice: Option<&str>,
-        speed: Option<f32>,
-        lang: Option<&str>
-    ) -> Result<Vec<f32>, String> {
-        self.synthesize_with_speed(text, voice, speed.unwrap_or(DEFAULT_SPEED), Some(lang.unwrap_or(DEFAULT_LANG)))
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
     }
 
-    /// Synthesize text to speech with custom speed
-    /// Speed: 0.5 = half speed (slower), 1.0 = normal, 2.0 = double speed (faster)
-    pub fn synthesize_with_speed(
-        &mut self,
-        text: &str,
-        voice: Option<&str>,
-        speed: f32,
-        lang: Option<&str>,
-    ) -> Result<Vec<f32>, String> {
-        self.synthesize_with_options(text, voice, speed, 1.0, Some(lang.unwrap_or(DEFAULT_LANG)))
+    pub fn artist(mut self, artist: impl Into<String>) -> Self {
+        self.artist = Some(artist.into());
+        self
     }
 
-    /// Synthesize using a builder-style options struct for better ergonomics.
-    ///
-    /// Example:
-    /// `tts.synthesize_with("Hello", SynthesizeOptions::default().voice("af_sky").speed(1.1))`
-    pub fn synthesize_with(
-        &mut self,
-        text: &str,
-        opts: SynthesizeOptions,
-    ) -> Result<Vec<f32>, String> {
-        let voice_opt = opts.voice.as_deref();
-        self.synthesize_with_options(text, voice_opt, opts.speed, opts.gain, Some(opts.lang.as_deref().unwrap_or(DEFAULT_LANG)))
+    pub fn comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = Some(comment.into());
+        self
     }
 
-    /// Process long text by splitting into chunks (alias for backwards compatibility)
-    /// This method exists for API compatibility - synthesize() already handles long text automatically
-    pub fn process_long_text(
-        &mut self,
-        text: &str,
-        voice: Option<&str>,
-        speed: Option<f32>,
-    ) -> Result<Vec<f32>, String> {
-        // Forward to speed-aware variant (use default if None)
-        self.synthesize_with_speed(text, voice, speed.unwrap_or(DEFAULT_SPEED), None)
+    // INFO entries in the order they are written
+    fn entries(&self) -> [(&[u8; 4], Option<&str>); 5] {
+        [
+            (b"INAM", self.title.as_deref()),
+            (b"IART", self.artist.as_deref()),
+            (b"ISFT", self.software.as_deref()),
+            (b"ICMT", self.comment.as_deref()),
+            (b"ICRD", self.creation_date.as_deref()),
+        ]
     }
+}
 
-    /// Synthesize speech from text with validation warnings (backwards compatibility)
-    /// Returns both the audio and any warnings about the text
-    pub fn synthesize_with_warnings(
-        &mut self,
-        text: &str,
-        voice: Option<&str>,
-        speed: Option<f32>,
-    ) -> Result<(Vec<f32>, Vec<String>), String> {
-        let mut warnings = Vec::new();
+/// What to do with emoji in input text
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EmojiPolicy {
+    /// Remove emoji before synthesis
+    #[default]
+    Strip,
+    /// Read a short English name ("👍" → "thumbs up"); unknown emoji are removed
+    Speak,
+    /// Remove emoji and list them in `synthesize_with_warnings`
+    Warn,
+}
 
-        if text.is_empty() {
-            warnings.push("Empty text provided".to_string());
-        }
+/// What to do with input characters the model can't pronounce, such as '©' or '§'.
+///
+/// Letters, digits, whitespace, the punctuation the model knows, word-joining
+/// marks (`- ' / ( )`) and the symbols espeak reads aloud (`& + = # * < > ~ _ @`)
+/// are always fine; emoji follow `EmojiPolicy`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UnknownCharPolicy {
+    /// Drop the character
+    #[default]
+    Skip,
+    /// Put this character in its place (e.g. ' ' or ',')
+    Replace(char),
+    /// Read its name ("copyright sign"); characters without a known name are dropped
+    SpellOut,
+    /// Fail synthesis, listing every offending character and its offset
+    Error,
+}
 
-        if text.len() > 10000 {
-            warnings.push(format!(
-                "Very long text ({} chars) may take a while to process",
-                text.len()
-            ));
-        }
+/// Something about the input worth knowing, from `validate` and `synthesize_with_warnings`.
+///
+/// `Display` gives the readable message; match on the variant to decide what
+/// to do about it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Warning {
+    /// Characters the model can't pronounce, handled per `UnknownCharPolicy`
+    UnknownCharacters {
+        chars: Vec<char>,
+        /// Char offset of each character in the input (in the text left
+        /// after removing markup and reading addresses out)
+        positions: Vec<usize>,
+    },
+    /// Input cut off at this token to fit the model. Long text is split into
+    /// chunks rather than cut, so synthesis doesn't report this today.
+    TextTruncated { at_token: usize },
+    /// Nothing was left to speak once the text was normalized
+    /// (empty input, or only emoji and unknown characters)
+    EmptyAfterNormalization,
+    /// The text was synthesized in this many chunks, joined with a crossfade
+    ChunkSplit { count: usize },
+    /// Emoji removed under `EmojiPolicy::Warn`, as written in the input
+    EmojiRemoved { emoji: Vec<String> },
+    /// Unusually long input (this many chars), which takes a while to synthesize
+    LongText { chars: usize },
+    /// Pause markers that couldn't be read (see `PauseMarkup`), as written;
+    /// they were removed rather than spoken
+    MalformedPauseMarkers { markers: Vec<String> },
+}
 
-        let audio = self.synthesize_with_speed(text, voice, speed.unwrap_or(DEFAULT_SPEED), None)?;
-        Ok((audio, warnings))
+impl std::fmt::Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Warning::UnknownCharacters { chars, positions } => {
+                let found: Vec<String> = chars
+                    .iter()
+                    .zip(positions)
+                    .map(|(c, at)| format!("'{}' (U+{:04X}) at char {}", c, *c as u32, at))
+                    .collect();
+                write!(f, "Unknown characters: {}", found.join(", "))
+            }
+            Warning::TextTruncated { at_token } => write!(f, "Text truncated at token {}", at_token),
+            Warning::EmptyAfterNormalization => write!(f, "No speakable text after normalization"),
+            Warning::ChunkSplit { count } => write!(f, "Text split into {} chunks", count),
+            Warning::EmojiRemoved { emoji } => {
+                write!(f, "Removed {} emoji: {}", emoji.len(), emoji.join(" "))
+            }
+            Warning::LongText { chars } => {
+                write!(f, "Very long text ({} chars) may take a while to process", chars)
+            }
+            Warning::MalformedPauseMarkers { markers } => {
+                write!(f, "Removed malformed pause markers: {}", markers.join(" "))
+            }
+        }
     }
+}
 
-    /// Synthesize text to speech with full options
-    /// Speed: 0.5 = half speed (slower), 1.0 = normal, 2.0 = double speed (faster)
-    /// Gain: 0.5 = quieter, 1.0 = normal, 2.0 = twice as loud (with soft clipping)
-    pub fn synthesize_with_options(
-        &mut self,
-        text: &str,
-        voice: Option<&str>,
-        speed: f32,
-        gain: f32,
-        lang: Option<&str>
-    ) -> Result<Vec<f32>, String> {
-        // If in fallback mode, return the excuse message audio
-        if self.fallback_mode {
-            // println!("🎤 Playing fallback message while downloading voice model...");
-            return wav_to_f32(FALLBACK_MESSAGE);
-        }
+/// One synthesized piece of a longer text, as delivered by `synthesize_streaming`
+#[derive(Clone, Debug)]
+pub struct AudioChunk {
+    /// Position of this chunk (0-based)
+    pub index: usize,
+    /// Total number of chunks the text was split into
+    pub total: usize,
+    /// The text this chunk was synthesized from
+    pub text: String,
+    /// Phoneme tokens the chunk was synthesized from, not counting the pad tokens
+    pub tokens: usize,
+    /// Audio samples at 24kHz mono
+    pub samples: Vec<f32>,
+    /// Silence inserted after the speech (see `PauseConfig`), included at the
+    /// end of `samples`
+    pub pause_ms: u32,
+}
 
-        let session = self
-            .session
-            .as_ref()
-            .ok_or_else(|| "TTS engine not initialized".to_string())?;
+/// Audio from `synthesize_detailed`, with where each chunk of the text ended up
+#[derive(Clone, Debug)]
+pub struct SynthesisResult {
+    /// Audio samples, mono
+    pub samples: Vec<f32>,
+    /// Samples per second (24kHz)
+    pub sample_rate: u32,
+    /// Length of `samples` in seconds
+    pub duration_secs: f32,
+    /// The normalized text that was spoken, chunks separated by spaces
+    pub text: String,
+    /// One entry per chunk, in order
+    pub chunks: Vec<ChunkInfo>,
+}
+
+/// Audio from `synthesize_dialogue`, with where each line ended up
+#[derive(Clone, Debug)]
+pub struct DialogueResult {
+    /// Audio samples, mono
+    pub samples: Vec<f32>,
+    /// Samples per second (24kHz)
+    pub sample_rate: u32,
+    /// Length of `samples` in seconds
+    pub duration_secs: f32,
+    /// One entry per spoken line, in order; empty in fallback mode
+    pub lines: Vec<DialogueLine>,
+}
 
-        // Map user-facing speed to model speed (user 1.0 = model 0.65)
-        let model_speed = speed * SPEED_SCALE;
-        let clamped_speed = model_speed.clamp(MIN_ENGINE_SPEED, MAX_ENGINE_SPEED);
-        let voice = voice.unwrap_or(DEFAULT_VOICE);
+/// Where one line of dialogue went (see `DialogueResult`), e.g. to cue subtitles
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DialogueLine {
+    /// Voice the line was spoken with
+    pub voice: String,
+    /// The line as given
+    pub text: String,
+    /// Samples of the line, not including the gap after it
+    pub sample_range: Range<usize>,
+}
 
-        // Parse voice style (e.g., "af_sky.8+af_bella.2" for mixing)
-        let style = self.parse_voice_style(voice)?;
+/// Where one chunk of the text went (see `SynthesisResult`)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChunkInfo {
+    /// Byte range of the chunk in `SynthesisResult::text`
+    pub text_range: Range<usize>,
+    /// Samples of the chunk, including the pause after it. Neighbouring chunks
+    /// overlap by the crossfade (see `TtsEngine::set_chunk_crossfade`), except
+    /// after a pause.
+    pub sample_range: Range<usize>,
+    /// Phoneme tokens, not counting the pad tokens; this is also the row of the
+    /// voice's style matrix that was used
+    pub token_count: usize,
+}
 
-        // Short form: synthesize in one pass for predictable cadence
-        if !needs_chunking(text) {
-            let mut audio = self.synthesize_segment(session, &style, text, clamped_speed, lang)?;
-            if gain != 1.0 {
-                audio = amplify_audio(&audio, gain);
-            }
-            return Ok(audio);
+impl SynthesisResult {
+    fn new() -> Self {
+        Self {
+            samples: Vec::new(),
+            sample_rate: SAMPLE_RATE,
+            duration_secs: 0.0,
+            text: String::new(),
+            chunks: Vec::new(),
         }
+    }
 
-        // Long-form synthesis path - chunk the text while preserving pacing
-        let prepared_chunks: Vec<String> = split_text_for_tts(text, MAX_CHARS_PER_CHUNK)
-            .into_iter()
-            .filter(|chunk| !chunk.trim().is_empty())
-            .collect();
+    // Crossfade the chunk onto the audio so far and record where it landed
+    fn push_chunk(&mut self, chunk: AudioChunk, join: &mut ChunkJoin) {
+        if !self.text.is_empty() {
+            self.text.push(' ');
+        }
+        let text_start = self.text.len();
+        self.text.push_str(&chunk.text);
+        join.append(&mut self.samples, &chunk.samples, chunk.pause_ms);
+        let sample_end = self.samples.len();
+        self.chunks.push(ChunkInfo {
+            text_range: text_start..self.text.len(),
+            sample_range: sample_end - chunk.samples.len()..sample_end,
+            token_count: chunk.tokens,
+        });
+        self.duration_secs = sample_end as f32 / self.sample_rate as f32;
+    }
 
-        if prepared_chunks.is_empty() {
-            return Err("No text provided for synthesis".to_string());
+    // Apply whole-buffer post-processing, keeping the chunk ranges on the same audio
+    fn post_process(&mut self, post: PostProcess) {
+        let removed = post.apply(&mut self.samples);
+        let len = self.samples.len();
+        let shift = |at: usize| at.saturating_add_signed(-removed).min(len);
+        for chunk in &mut self.chunks {
+            chunk.sample_range = shift(chunk.sample_range.start)..shift(chunk.sample_range.end);
         }
+        self.duration_secs = len as f32 / self.sample_rate as f32;
+    }
+}
 
-        let chunk_count = prepared_chunks.len();
-        #[cfg(not(feature = "as-lib"))]
-        eprintln!(
-            "📚 Long-form synthesis enabled: {} chars -> {} chunk(s) (≤ {} chars each)",
-            text.chars().count(),
-            chunk_count,
-            MAX_CHARS_PER_CHUNK
-        );
+/// One piece of text as it would be fed to the model (see `TtsEngine::chunk_text`)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TextChunk {
+    /// Normalized text of the chunk
+    pub text: String,
+    /// Phoneme tokens for the chunk, not counting the pad tokens
+    pub tokens: usize,
+    /// Extra silence inserted after the chunk (see `PauseConfig`)
+    pub pause_ms: u32,
+}
 
-        let overlap = chunk_crossfade_samples();
-        let mut combined_audio = Vec::new();
+/// How big a text is to speak, from `TtsEngine::estimate`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Estimate {
+    /// Phoneme tokens over all chunks, not counting the pad tokens
+    pub token_count: usize,
+    /// Chunks the text would be synthesized in
+    pub chunk_count: usize,
+    /// Roughly how long the audio will be, configured pauses included
+    pub approx_duration: Duration,
+}
 
-        for (idx, chunk) in prepared_chunks.iter().enumerate() {
-            #[cfg(not(feature = "as-lib"))]
-            eprintln!(
-                "   → Chunk {}/{} ({} chars)",
-                idx + 1,
-                chunk_count,
-                chunk.chars().count()
-            );
+/// What `TtsEngine::synthesize_to_file` wrote
+#[derive(Clone, Debug, PartialEq)]
+pub struct SynthesisSummary {
+    /// Length of the audio in the file
+    pub duration: Duration,
+    /// Size of the file in bytes
+    pub file_size: u64,
+}
 
-            let chunk_audio = self.synthesize_segment(session, &style, chunk, clamped_speed, lang)?;
-            append_with_crossfade(&mut combined_audio, &chunk_audio, overlap);
-        }
+/// Where one word of the input text lands in the synthesized audio
+///
+/// Sample indices refer to the final buffer returned by `synthesize_with_timing`
+/// (24kHz). Punctuation is merged into the word it follows ("world." is one
+/// word); a standalone punctuation word such as "—" gets a zero-length span.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WordTiming {
+    pub word: String,
+    pub start_sample: usize,
+    /// Exclusive end
+    pub end_sample: usize,
+}
 
-        if combined_audio.is_empty() {
-            return Err("Failed to synthesize combined audio".to_string());
-        }
+/// Progress of the first-start model download (see `TtsEngine::new_with_progress`)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DownloadProgress {
+    /// Bytes of `file` ("0.onnx", "0.bin") downloaded so far, counting a
+    /// resumed partial download, and its size when the server reports one
+    Downloading {
+        file: String,
+        downloaded: u64,
+        total: Option<u64>,
+    },
+    /// Both files are downloaded and verified; the model is loaded next
+    Complete,
+}
 
-        let mut final_audio = combined_audio;
-        if gain != 1.0 {
-            final_audio = amplify_audio(&final_audio, gain);
-        }
+/// Where the model and voices files live (see `TtsEngine::default_model_paths`)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ModelPaths {
+    /// Kokoro ONNX model (`0.onnx`, about 310MB)
+    pub model: PathBuf,
+    /// Voice style vectors (`0.bin`, about 27MB)
+    pub voices: PathBuf,
+}
 
-        Ok(final_audio)
+impl ModelPaths {
+    /// The standard file names inside a cache directory
+    pub fn in_dir(dir: impl AsRef<Path>) -> Self {
+        Self {
+            model: dir.as_ref().join("0.onnx"),
+            voices: dir.as_ref().join("0.bin"),
+        }
     }
 
-    fn synthesize_segment(
-        &self,
-        session: &Arc<Mutex<Session>>,
-        style: &[f32],
-        text: &str,
-        speed: f32,
-        lang: Option<&str>
-    ) -> Result<Vec<f32>, String> {
-        // Convert text to phonemes
-        let phonemes = text_to_phonemes(text, lang.unwrap_or(DEFAULT_LANG), None, true, false)
-            .map_err(|e| format!("Failed to convert text to phonemes: {}", e))?;
-
-        // Join phonemes with spaces and add padding tokens at beginning and end
-        // Spaces between phonemes create natural pauses for commas and periods
-        // Padding tokens are crucial to prevent word dropping at beginning and end
-        let mut phonemes_text = phonemes.join(" ");
-        // Add multiple padding tokens for better buffering
-        phonemes_text.insert_str(0, "$$$");
-        phonemes_text.push_str("$$$");
-
-        // Debug output only for long text
-        #[cfg(not(feature = "as-lib"))]
-        if text.len() > 50 {
-            eprintln!("   Text length: {} chars", text.len());
-            eprintln!("   Phonemes array: {} entries", phonemes.len());
-            eprintln!("   Phoneme text length: {} chars", phonemes_text.len());
+    /// Check both files against the published SHA-256 checksums of the
+    /// default model, e.g. before `TtsEngine::from_paths`, to catch a cached
+    /// file that was damaged after download. Hashing the model takes a
+    /// second or two, so loading doesn't do this by itself.
+    ///
+    /// Only meaningful for the default model: custom exports never match.
+    /// The quantized model has no published checksum, so only its voices
+    /// file is checked.
+    pub fn verify(&self) -> Result<(), Error> {
+        if let Some(sha256) = ModelVariant::from_file_name(&self.model).sha256() {
+            verify_model_file("Model", &self.model, sha256)?;
         }
+        verify_model_file("Voices", &self.voices, VOICES_SHA256)
+    }
 
-        let tokens = self.tokenize(phonemes_text);
-
-        // Run inference with user-specified speed directly
-        self.run_inference(session, tokens, style.to_vec(), speed)
+    /// Like `in_dir`, with the model file for `variant` (`0.int8.onnx` for
+    /// the quantized model). Both variants share the voices file.
+    pub fn in_dir_for(dir: impl AsRef<Path>, variant: ModelVariant) -> Self {
+        Self {
+            model: dir.as_ref().join(variant.file_name()),
+            voices: dir.as_ref().join("0.bin"),
+        }
     }
+}
 
-    /// Save audio as WAV file
-    pub fn save_wav(&self, path: &str, audio: &[f32]) -> Result<(), String> {
-        let spec = hound::WavSpec {
-            channels: 1,
-            sample_rate: SAMPLE_RATE,
-            bits_per_sample: 16,
-            sample_format: hound::SampleFormat::Int,
-        };
+/// Which Kokoro model export to use (see `TtsEngineBuilder::variant`)
+///
+/// Both use the same voices and produce 24kHz audio through the same
+/// `synthesize` calls. The quantized model is about a quarter of the size and
+/// usually much faster on CPU, and can sound slightly grainier (see the
+/// `model_variants` example to compare).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ModelVariant {
+    /// Full precision model (`0.onnx`, about 310MB)
+    #[default]
+    Fp32,
+    /// int8 quantized model (`0.int8.onnx`, about 88MB)
+    Quantized,
+}
 
-        let mut writer = hound::WavWriter::create(path, spec)
-            .map_err(|e| format!("Failed to create WAV file: {}", e))?;
+impl ModelVariant {
+    // Cache file name, download URL and expected SHA-256 (if published)
+    fn file_name(self) -> &'static str {
+        match self {
+            ModelVariant::Fp32 => "0.onnx",
+            ModelVariant::Quantized => "0.int8.onnx",
+        }
+    }
 
-        for &sample in audio {
-            let sample_i16 = (sample * 32767.0).clamp(-32768.0, 32767.0) as i16;
-            writer
-                .write_sample(sample_i16)
-                .map_err(|e| format!("Failed to write sample: {}", e))?;
+    fn url(self) -> &'static str {
+        match self {
+            ModelVariant::Fp32 => MODEL_URL,
+            ModelVariant::Quantized => QUANTIZED_MODEL_URL,
         }
+    }
 
-        writer
-            .finalize()
-            .map_err(|e| format!("Failed to finalize WAV: {}", e))?;
-        Ok(())
+    fn sha256(self) -> Option<&'static str> {
+        match self {
+            ModelVariant::Fp32 => Some(MODEL_SHA256),
+            ModelVariant::Quantized => None,
+        }
     }
 
-    /// Convert audio to WAV bytes in memory
-    pub fn to_wav_bytes(&self, audio: &[f32]) -> Result<Vec<u8>, String> {
-        let spec = hound::WavSpec {
-            channels: 1,
-            sample_rate: SAMPLE_RATE,
-            bits_per_sample: 16,
-            sample_format: hound::SampleFormat::Int,
-        };
+    // The variant a model file holds, judged by its name ("int8" or "quant"
+    // in it), for files loaded from paths the caller chose
+    fn from_file_name(path: &Path) -> Self {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        if name.contains("int8") || name.contains("quant") {
+            ModelVariant::Quantized
+        } else {
+            ModelVariant::Fp32
+        }
+    }
 
-        let mut cursor = Cursor::new(Vec::new());
-        {
-            let mut writer = hound::WavWriter::new(&mut cursor, spec)
-                .map_err(|e| format!("Failed to create WAV writer: {}", e))?;
+    // The variant a model in memory holds: int8 exports carry quantization
+    // ops ("DynamicQuantizeLinear", "MatMulInteger") the fp32 one lacks
+    fn from_model_bytes(model_bytes: &[u8]) -> Self {
+        const MARKER: &[u8] = b"QuantizeLinear";
+        if model_bytes.windows(MARKER.len()).any(|window| window == MARKER) {
+            ModelVariant::Quantized
+        } else {
+            ModelVariant::Fp32
+        }
+    }
+}
 
-            for &sample in audio {
-                let sample_i16 = (sample * 32767.0).clamp(-32768.0, 32767.0) as i16;
-                writer
-                    .write_sample(sample_i16)
-                    .map_err(|e| format!("Failed to write sample: {}", e))?;
-            }
+/// Hardware the model runs on (see `TtsEngineBuilder::execution_provider`)
+///
+/// GPU providers need ONNX Runtime built with them, enabled through this
+/// crate's `cuda`, `coreml` or `directml` feature. Without the feature, or
+/// when the provider fails to start (no driver, no device), the engine warns
+/// and runs on the CPU.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ExecutionProvider {
+    /// ONNX Runtime's CPU provider
+    #[default]
+    Cpu,
+    /// NVIDIA GPU by CUDA device index (feature `cuda`)
+    Cuda(i32),
+    /// Apple GPU and Neural Engine on macOS (feature `coreml`)
+    CoreMl,
+    /// DirectX 12 GPU on Windows by adapter index (feature `directml`)
+    DirectMl(i32),
+}
 
-            writer
-                .finalize()
-                .map_err(|e| format!("Failed to finalize WAV: {}", e))?;
+impl ExecutionProvider {
+    // Cargo feature that compiles the provider in
+    fn feature(self) -> &'static str {
+        match self {
+            ExecutionProvider::Cpu => "default",
+            ExecutionProvider::Cuda(_) => "cuda",
+            ExecutionProvider::CoreMl => "coreml",
+            ExecutionProvider::DirectMl(_) => "directml",
         }
-
-        Ok(cursor.into_inner())
     }
 
-    /// Save audio as MP3 file (requires 'symphonia-formats' feature)
-    #[cfg(feature = "symphonia-formats")]
-    pub fn save_mp3(&self, path: &str, audio: &[f32]) -> Result<(), String> {
-        // For MP3 encoding, convert to WAV first as symphonia is primarily a decoder
-        // A pure-Rust MP3 encoder would be better, but WAV is the safe fallback
-        let temp_wav = format!("{}.wav", path);
-        self.save_wav(&temp_wav, audio)?;
-        
-        #[cfg(not(feature = "as-lib"))]
-        eprintln!("ℹ️  Symphonia is a decoder. Saved as WAV instead: {}", path);
-        
-        std::fs::rename(&temp_wav, path)
-            .map_err(|e| format!("Failed to save audio file: {}", e))?;
-        Ok(())
+    // ort's provider to register, None for the CPU, or why this build has none
+    fn dispatch(self) -> Result<Option<ort::ep::ExecutionProviderDispatch>, Error> {
+        match self {
+            ExecutionProvider::Cpu => Ok(None),
+            #[cfg(feature = "cuda")]
+            ExecutionProvider::Cuda(device) => {
+                Ok(Some(ort::ep::CUDA::default().with_device_id(device).build().error_on_failure()))
+            }
+            #[cfg(feature = "coreml")]
+            ExecutionProvider::CoreMl => {
+                Ok(Some(ort::ep::CoreML::default().build().error_on_failure()))
+            }
+            #[cfg(feature = "directml")]
+            ExecutionProvider::DirectMl(device) => {
+                Ok(Some(ort::ep::DirectML::default().with_device_id(device).build().error_on_failure()))
+            }
+            #[allow(unreachable_patterns)]
+            other => Err(Error::ModelLoad(format!(
+                "kokoro-tiny was built without the `{}` feature",
+                other.feature()
+            ))),
+        }
     }
+}
 
-    /// Save audio as MP3 file (fallback for when symphonia-formats not available)
-    #[cfg(not(feature = "symphonia-formats"))]
-    pub fn save_mp3(&self, path: &str, audio: &[f32]) -> Result<(), String> {
-        Err("MP3 support requires 'symphonia-formats' feature. Use 'symphonia-formats' or save as WAV/OPUS.".to_string())
+impl std::fmt::Display for ExecutionProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExecutionProvider::Cpu => write!(f, "CPU"),
+            ExecutionProvider::Cuda(device) => write!(f, "CUDA (device {})", device),
+            ExecutionProvider::CoreMl => write!(f, "CoreML"),
+            ExecutionProvider::DirectMl(device) => write!(f, "DirectML (device {})", device),
+        }
     }
+}
 
-    /// Save audio as OPUS file (requires 'opus-format' feature)
-    #[cfg(feature = "opus-format")]
-    pub fn save_opus(&self, path: &str, audio: &[f32], bitrate: i32) -> Result<(), String> {
-        use audiopus::{coder::Encoder as OpusEncoder, Application, Bitrate, Channels, SampleRate};
+// How the ONNX Runtime session is set up (see `TtsEngineBuilder`); None
+// leaves a setting at ONNX Runtime's default
+#[derive(Clone, Debug, Default)]
+struct SessionConfig {
+    provider: ExecutionProvider,
+    intra_op_threads: Option<usize>,
+    inter_op_threads: Option<usize>,
+    spinning: Option<bool>,
+    cpu_affinity: Option<Vec<usize>>, // Logical CPUs (0-based), one per intra-op thread
+    workers: usize, // Sessions for parallel chunk inference; 0 and 1 both mean one
+}
 
-        // Convert sample rate from 24000 to 48000 (OPUS prefers 48kHz)
-        let samples_48k = resample_audio(audio, SAMPLE_RATE, 48000);
+/// Engine construction with download and engine settings (see `TtsEngine::builder`).
+/// Anything left unset is what `TtsEngine::new` uses.
+///
+/// Example: `TtsEngine::builder().default_voice("bf_emma").default_speed(1.1).build().await?`
+pub struct TtsEngineBuilder {
+    cache_dir: Option<PathBuf>,
+    paths: Option<ModelPaths>, // Explicit files, overriding `cache_dir` and the variant's file name
+    model_path: Option<PathBuf>, // Explicit model file only, the voices stay where they'd be
+    variant: ModelVariant,
+    lazy: bool,
+    default_voice: Option<String>,
+    default_speed: Option<f32>,
+    #[cfg(feature = "playback")]
+    audio_host: Option<playback::AudioHost>,
+    #[cfg(feature = "playback")]
+    playback_device: Option<String>,
+    session: SessionConfig,
+    proxy: Option<String>,
+    progress: Option<Box<dyn FnMut(DownloadProgress) + Send>>,
+}
 
-        // Convert to i16
-        let samples_i16: Vec<i16> = samples_48k
-            .iter()
-            .map(|&s| (s * 32767.0).clamp(-32768.0, 32767.0) as i16)
-            .collect();
+impl TtsEngineBuilder {
+    /// Load (and download to) these files instead of the default cache
+    pub fn model_paths(mut self, model: impl AsRef<Path>, voices: impl AsRef<Path>) -> Self {
+        self.paths = Some(ModelPaths {
+            model: model.as_ref().to_path_buf(),
+            voices: voices.as_ref().to_path_buf(),
+        });
+        self
+    }
 
-        // Create OPUS encoder
-        let mut encoder = OpusEncoder::new(SampleRate::Hz48000, Channels::Mono, Application::Audio)
-            .map_err(|e| format!("Failed to create OPUS encoder: {:?}", e))?;
+    /// Load (and download to) this model file, with the voices file in the
+    /// cache directory. Overrides the model half of `model_paths`.
+    pub fn model_path(mut self, model: impl AsRef<Path>) -> Self {
+        self.model_path = Some(model.as_ref().to_path_buf());
+        self
+    }
 
-        // Set bitrate
-        encoder
-            .set_bitrate(Bitrate::BitsPerSecond(bitrate))
-            .map_err(|e| format!("Failed to set OPUS bitrate: {:?}", e))?;
+    /// Keep the model files in `dir` (see `TtsEngine::with_cache_dir`)
+    pub fn cache_dir(mut self, dir: impl AsRef<Path>) -> Self {
+        self.cache_dir = Some(dir.as_ref().to_path_buf());
+        self
+    }
 
-        // Encode in chunks
-        let frame_size = 960; // 20ms at 48kHz
-        let mut opus_data = Vec::new();
-        let mut output = vec![0u8; 4000];
+    /// Use this model export (see `ModelVariant`); the default is `Fp32`.
+    /// With `model_paths`, the variant decides what is downloaded there.
+    pub fn variant(mut self, variant: ModelVariant) -> Self {
+        self.variant = variant;
+        self
+    }
 
-        for chunk in samples_i16.chunks(frame_size) {
-            if chunk.len() == frame_size {
-                let size = encoder
-                    .encode(chunk, &mut output)
-                    .map_err(|e| format!("Failed to encode OPUS frame: {:?}", e))?;
-                opus_data.extend_from_slice(&output[..size]);
-            }
-        }
+    /// Run the model on this hardware (see `ExecutionProvider`); the default
+    /// is the CPU. A provider that can't be used falls back to the CPU with
+    /// a warning, so check `TtsEngine::execution_provider` when benchmarking.
+    pub fn execution_provider(mut self, provider: ExecutionProvider) -> Self {
+        self.session.provider = provider;
+        self
+    }
 
-        std::fs::write(path, opus_data).map_err(|e| format!("Failed to write OPUS file: {}", e))?;
+    /// Threads ONNX Runtime uses inside one operator (a matrix multiply).
+    /// By default it starts one per physical core, which is right for a
+    /// single engine; give each of several engines its share instead. With 1
+    /// (and `inter_op_threads(1)`), inference runs on the calling thread only.
+    pub fn intra_op_threads(mut self, threads: usize) -> Self {
+        self.session.intra_op_threads = Some(threads.max(1));
+        self
+    }
 
-        Ok(())
+    /// Threads for running independent operators side by side. The Kokoro
+    /// graph is mostly sequential, so more than 1 rarely helps.
+    pub fn inter_op_threads(mut self, threads: usize) -> Self {
+        self.session.inter_op_threads = Some(threads.max(1));
+        self
     }
 
-    /// Save audio file with automatic format detection based on extension
-    pub fn save_audio(&self, path: &str, audio: &[f32]) -> Result<(), String> {
-        let extension = Path::new(path)
-            .extension()
-            .and_then(|e| e.to_str())
-            .unwrap_or("wav")
-            .to_lowercase();
+    /// Whether idle ONNX Runtime threads spin waiting for work (the default)
+    /// or sleep. Spinning lowers latency but burns CPU between calls, which
+    /// hurts when many engines share a machine.
+    pub fn thread_spinning(mut self, spin: bool) -> Self {
+        self.session.spinning = Some(spin);
+        self
+    }
 
-        match extension.as_str() {
-            "wav" => self.save_wav(path, audio),
+    /// Load `workers` copies of the model so the chunks of a long text are
+    /// inferred in parallel (default 1, sequential). Each copy takes the
+    /// model's memory again (about 310MB for fp32), and each uses its own
+    /// `intra_op_threads`, so split the cores between them. The audio is
+    /// identical to sequential synthesis; see `TtsEngine::set_parallel_chunks`.
+    /// An engine shared between threads also runs that many calls at once.
+    pub fn inference_workers(mut self, workers: usize) -> Self {
+        self.session.workers = workers.max(1);
+        self
+    }
 
-            #[cfg(feature = "symphonia-formats")]
-            "mp3" => self.save_mp3(path, audio),
-            #[cfg(not(feature = "symphonia-formats"))]
-            "mp3" => Err("MP3 support not enabled. Add 'symphonia-formats' feature to Cargo.toml".to_string()),
+    /// Pin inference to these logical CPUs (0-based), one intra-op thread
+    /// each; this sets `intra_op_threads` to `cpus.len()`. ONNX Runtime runs
+    /// the first share of the work on the calling thread, so pin that thread
+    /// to `cpus[0]` yourself for full isolation. An empty slice turns pinning
+    /// off again.
+    pub fn cpu_affinity(mut self, cpus: &[usize]) -> Self {
+        if cpus.is_empty() {
+            self.session.cpu_affinity = None;
+        } else {
+            self.session.intra_op_threads = Some(cpus.len());
+            self.session.cpu_affinity = Some(cpus.to_vec());
+        }
+        self
+    }
 
-            #[cfg(feature = "opus-format")]
-            "opus" => self.save_opus(path, audio, 24000),
-            #[cfg(not(feature = "opus-format"))]
-            "opus" => {
-                Err("OPUS support not enabled. Add 'opus-format' feature to Cargo.toml".to_string())
-            }
+    /// Load the model and voices on the first call that needs them instead of
+    /// in `build` (default off). For tools that often exit without speaking:
+    /// `build` only downloads missing files, and a file that fails to load
+    /// is an error from that first call (and every later one). Voice and
+    /// text checks such as `voices`, `chunk_text` and `default_voice` read
+    /// just the voices file's index and don't trigger the load; `warm_up`
+    /// does.
+    pub fn lazy(mut self, lazy: bool) -> Self {
+        self.lazy = lazy;
+        self
+    }
 
-            "flac" => Err("FLAC format not yet supported".to_string()),
+    /// Voice for calls that pass `None` (default "af_sky"); a blend like
+    /// "af_sky.6+af_bella.4" works too. `build` fails if the voices file
+    /// doesn't have it.
+    pub fn default_voice(mut self, voice: impl Into<String>) -> Self {
+        self.default_voice = Some(voice.into());
+        self
+    }
 
-            _ => Err(format!("Unsupported audio format: {}", extension)),
-        }
+    /// Speed for calls that pass `None` (default 1.0, see
+    /// `TtsEngine::set_default_speed`). `build` fails unless it's above 0.
+    pub fn default_speed(mut self, speed: f32) -> Self {
+        self.default_speed = Some(speed);
+        self
     }
 
-    /// Play audio directly through speakers (requires 'playback' feature)
+    /// Audio backend to play on (requires 'playback' feature, see
+    /// `TtsEngine::set_audio_host`). `build` fails if it isn't usable here.
     #[cfg(feature = "playback")]
-    pub fn play(&self, audio: &[f32], volume: f32) -> Result<(), String> {
-        self.play_with_ducking(audio, volume, false, 0.3)
+    pub fn audio_host(mut self, host: playback::AudioHost) -> Self {
+        self.audio_host = Some(host);
+        self
     }
 
-    /// Play audio with optional ducking (requires 'playback' feature)
-    /// Ducking reduces system volume before speaking, then restores it after
-    ///
-    /// # Arguments
-    /// * `audio` - Audio samples to play
-    /// * `volume` - Playback volume (0.0 to 1.0)
-    /// * `enable_ducking` - Whether to reduce other audio during playback
-    /// * `duck_level` - How much to reduce other audio (0.0 = mute, 1.0 = no change)
+    /// Play on this output device (requires 'playback' feature), matched as
+    /// in `TtsEngine::set_audio_device`. Unlike that call the choice isn't
+    /// saved for later runs. `build` fails if no single device matches.
     #[cfg(feature = "playback")]
-    pub fn play_with_ducking(
-        &self,
-        audio: &[f32],
-        volume: f32,
-        enable_ducking: bool,
-        duck_level: f32,
-    ) -> Result<(), String> {
-        // Duck audio if requested (reduce system volume)
-        #[cfg(feature = "ducking")]
-        if enable_ducking {
-            duck_system_audio(duck_level)?;
-            // Small delay to let the ducking take effect
-            thread::sleep(Duration::from_millis(50));
-        }
+    pub fn playback_device(mut self, name: impl Into<String>) -> Self {
+        self.playback_device = Some(name.into());
+        self
+    }
 
-        // Convert audio to WAV format in memory
-        let wav_data = self.to_wav_bytes(audio)?;
+    /// Download through this proxy (e.g. "http://proxy.corp:3128"). Without
+    /// it, `HTTPS_PROXY`/`HTTP_PROXY` and `NO_PROXY` from the environment apply.
+    pub fn proxy(mut self, url: impl Into<String>) -> Self {
+        self.proxy = Some(url.into());
+        self
+    }
 
-        // Setup audio output - use selected device or default
-        let (_stream, stream_handle) = if let Some(ref device_name) = self.audio_device {
-            // Find and use the specified device
-            use cpal::traits::{DeviceTrait, HostTrait};
-            let host = cpal::default_host();
-            let devices = host
-                .output_devices()
-                .map_err(|e| format!("Failed to get output devices: {}", e))?;
+    /// Report download progress (see `TtsEngine::new_with_progress`)
+    pub fn progress(mut self, progress: impl FnMut(DownloadProgress) + Send + 'static) -> Self {
+        self.progress = Some(Box::new(progress));
+        self
+    }
 
-            let mut found_device = None;
-            for device in devices {
-                if let Ok(name) = device.name() {
-                    if name == *device_name {
-                        found_device = Some(device);
-                        break;
-                    }
-                }
+    /// Download any missing files and load the engine, then apply the
+    /// defaults and playback settings. An invalid setting is an error here
+    /// rather than at the first `synthesize`.
+    pub async fn build(self) -> Result<TtsEngine, Error> {
+        if let Some(speed) = self.default_speed {
+            if !(speed.is_finite() && speed > 0.0) {
+                let message = format!("Invalid default speed {}: must be above 0", speed);
+                return Err(Error::InvalidInput(message));
             }
+        }
 
-            match found_device {
-                Some(device) => OutputStream::try_from_device(&device)
-                    .map_err(|e| format!("Failed to open device '{}': {}", device_name, e))?,
-                None => return Err(format!("Audio device '{}' not found", device_name)),
+        let mut paths = self.paths.unwrap_or_else(|| {
+            ModelPaths::in_dir_for(self.cache_dir.unwrap_or_else(get_cache_dir), self.variant)
+        });
+        if let Some(model) = self.model_path {
+            paths.model = model;
+        }
+        let not_utf8 = |what: &str| Error::InvalidInput(format!("{} path is not valid UTF-8", what));
+        let model = paths.model.to_str().ok_or_else(|| not_utf8("Model"))?;
+        let voices = paths.voices.to_str().ok_or_else(|| not_utf8("Voices"))?;
+        let progress = self.progress.unwrap_or_else(|| Box::new(|_| {}));
+        let proxy = self.proxy.as_deref();
+        let mut engine =
+            TtsEngine::open(model, voices, self.variant, &self.session, self.lazy, proxy, progress)
+                .await?;
+
+        if let Some(voice) = self.default_voice {
+            engine.set_default_voice(&voice)?;
+        }
+        if let Some(speed) = self.default_speed {
+            engine.set_default_speed(speed);
+        }
+        #[cfg(feature = "playback")]
+        {
+            if let Some(host) = self.audio_host {
+                engine.set_audio_host(host)?;
             }
+            if let Some(name) = self.playback_device {
+                let devices = engine.list_audio_devices()?;
+                let available: Vec<String> = devices.into_iter().map(|d| d.name).collect();
+                let matched = playback::match_device_name(&name, &available).map_err(Error::AudioDevice)?;
+                engine.audio_device = Some(matched);
+            }
+        }
+        Ok(engine)
+    }
+}
+
+/// Description of a voice available in the loaded voices file
+#[derive(Clone, Debug, PartialEq)]
+pub struct VoiceInfo {
+    /// Identifier to pass to `synthesize` (e.g. "af_sky")
+    pub id: String,
+    /// Language tag inferred from the voice prefix (e.g. "en-US"), "unknown" for custom names
+    pub language: String,
+    /// Human readable language/accent (e.g. "American English")
+    pub accent: String,
+    /// Speaker gender inferred from the voice prefix
+    pub gender: VoiceGender,
+    /// Tuning set with `TtsEngine::set_voice_profile`, if any
+    pub profile: Option<VoiceProfile>,
+}
+
+/// A voice checked to exist on the engine that made it (see `TtsEngine::voice`)
+///
+/// Cheap to clone. Voices are never removed from an engine, so a handle stays
+/// good for the engine's lifetime; on an engine without the voice it fails
+/// like a misspelt name would, with `Error::VoiceNotFound`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Voice {
+    name: Arc<str>,
+}
+
+impl Voice {
+    /// The voice name or mix, for the string-based calls: `Some(voice.name())`
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl std::fmt::Display for Voice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+impl AsRef<str> for Voice {
+    fn as_ref(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Speaker gender encoded in Kokoro voice names (`af_` = female, `am_` = male)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum VoiceGender {
+    Female,
+    Male,
+    Unknown,
+}
+
+impl VoiceInfo {
+    /// Build voice metadata from a Kokoro voice identifier like "bf_emma"
+    pub fn from_id(id: &str) -> Self {
+        let mut prefix = id.chars();
+        let (language, accent) = match prefix.next() {
+            Some('a') => ("en-US", "American English"),
+            Some('b') => ("en-GB", "British English"),
+            Some('e') => ("es", "Spanish"),
+            Some('f') => ("fr", "French"),
+            Some('h') => ("hi", "Hindi"),
+            Some('i') => ("it", "Italian"),
+            Some('j') => ("ja", "Japanese"),
+            Some('p') => ("pt-BR", "Brazilian Portuguese"),
+            Some('z') => ("zh", "Mandarin Chinese"),
+            _ => ("unknown", "Unknown"),
+        };
+        let gender = match (prefix.next(), prefix.next()) {
+            (Some('f'), Some('_')) => VoiceGender::Female,
+            (Some('m'), Some('_')) => VoiceGender::Male,
+            _ => VoiceGender::Unknown,
+        };
+
+        // Custom names (blends etc.) don't follow the prefix convention
+        let (language, accent) = if gender == VoiceGender::Unknown {
+            ("unknown", "Unknown")
         } else {
-            // Use system default
-            OutputStream::try_default().map_err(|e| format!("Failed to get audio output: {}", e))?
+            (language, accent)
         };
 
-        let sink = Sink::try_new(&stream_handle)
-            .map_err(|e| format!("Failed to create audio sink: {}", e))?;
+        Self {
+            id: id.to_string(),
+            language: language.to_string(),
+            accent: accent.to_string(),
+            gender,
+            profile: None,
+        }
+    }
+}
+
+// Quotes and brackets that close right after a sentence's final punctuation
+const SENTENCE_CLOSERS: &str = "\"”’')]»";
+// Punctuation outside the model vocabulary that espeak reads through as part of
+// (or between) words, so it never counts as an unknown character
+const WORD_MARKS: &str = "-–'’‘()[]{}/";
+// ASCII symbols outside the model vocabulary that espeak reads aloud ("and",
+// "plus", "sharp"), so they aren't unknown characters either
+const SPOKEN_SYMBOLS: &str = "&+=#*<>~_@";
+
+// An input character the model can't pronounce, with where it was found
+struct UnknownChar {
+    ch: char,
+    char_offset: usize,
+    byte_offset: usize,
+}
+
+impl UnknownChar {
+    fn describe(&self) -> String {
+        format!(
+            "Unknown character '{}' (U+{:04X}) at char {} (byte {})",
+            self.ch, self.ch as u32, self.char_offset, self.byte_offset
+        )
+    }
+}
 
-        // Set volume (0.0 to 1.0)
-        sink.set_volume(volume.clamp(0.0, 1.0));
+// espeak language for a voice (or blend, judged by its first voice) when the
+// caller doesn't pass one: British voices get British pronunciation
+// (non-rhotic endings, "bath", "schedule") instead of American rules
+fn lang_for_voice(voice: &str) -> &'static str {
+    let first = voice.split(['+', '.']).next().unwrap_or(voice);
+    match VoiceInfo::from_id(first).language.as_str() {
+        "en-US" => "en-us",
+        "en-GB" => "en-gb",
+        "es" => "es",
+        _ => DEFAULT_LANG,
+    }
+}
 
-        // Create decoder from WAV data
-        let cursor = Cursor::new(wav_data);
-        let decoder =
-            Decoder::new(cursor).map_err(|e| format!("Failed to create audio decoder: {}", e))?;
+// Prepared inputs for one synthesis call (see `TtsEngine::plan_synthesis`)
+struct SynthesisPlan {
+    speed: f32, // Model speed, voice profile included
+    pitch: f32, // Voice profile pitch shift for every chunk, in semitones
+    lang: String, // espeak language the chunks were phonemized with
+    chunks: Vec<PlannedChunk>,
+}
 
-        // Play the audio
-        sink.append(decoder);
-        sink.sleep_until_end();
+struct PlannedChunk {
+    text: String,
+    tokens: Vec<i64>,
+    style: Vec<f32>,
+    pause_ms: u32, // Extra silence after this chunk (see `PauseConfig`)
+}
 
-        // Restore audio if we ducked it
-        #[cfg(feature = "ducking")]
-        if enable_ducking {
-            // Small delay before restoring
-            thread::sleep(Duration::from_millis(50));
-            restore_system_audio(duck_level)?;
+// Inference and post-processing for one prepared text (see `deferred_synthesis`)
+type DeferredSynthesis = Box<dyn FnOnce() -> Result<Vec<f32>, Error> + Send>;
+
+impl TtsEngine {
+    /// Create a new TTS engine, downloading model files if necessary
+    /// Uses ~/.cache/k for shared model storage (minimal path!)
+    pub async fn new() -> Result<Self, Error> {
+        let paths = Self::default_model_paths();
+
+        Self::with_paths(
+            paths.model.to_str().unwrap_or("0.onnx"),
+            paths.voices.to_str().unwrap_or("0.bin"),
+        )
+        .await
+    }
+
+    /// Configure the engine before creating it: model location and variant,
+    /// execution provider and threads, default voice and speed, playback
+    /// device, download proxy and progress reporting (see `TtsEngineBuilder`)
+    pub fn builder() -> TtsEngineBuilder {
+        TtsEngineBuilder {
+            cache_dir: None,
+            paths: None,
+            model_path: None,
+            variant: ModelVariant::default(),
+            lazy: false,
+            default_voice: None,
+            default_speed: None,
+            #[cfg(feature = "playback")]
+            audio_host: None,
+            #[cfg(feature = "playback")]
+            playback_device: None,
+            session: SessionConfig::default(),
+            proxy: None,
+            progress: None,
         }
+    }
 
-        Ok(())
+    /// The files `new` loads, and downloads when they are missing. Copy the
+    /// model and voices there to deploy without network access.
+    pub fn default_model_paths() -> ModelPaths {
+        ModelPaths::in_dir(get_cache_dir())
     }
 
-    // Private helper methods
+    /// Like `new`, with the model files kept in `cache_dir` instead of
+    /// ~/.cache/k (e.g. a mounted volume). The directory is created if needed;
+    /// a download into a directory that isn't writable fails with an error
+    /// saying so.
+    ///
+    /// Engines sharing a cache directory can start together: each file is
+    /// downloaded once, under a lock, and the others wait for it.
+    pub async fn with_cache_dir(cache_dir: impl AsRef<Path>) -> Result<Self, Error> {
+        let paths = ModelPaths::in_dir(cache_dir.as_ref());
+        let utf8_cache_dir = || Error::InvalidInput("Cache directory path is not valid UTF-8".to_string());
+        let model = paths.model.to_str().ok_or_else(utf8_cache_dir)?;
+        let voices = paths.voices.to_str().ok_or_else(utf8_cache_dir)?;
+        Self::with_paths(model, voices).await
+    }
 
-    fn parse_voice_style(&self, voice_str: &str) -> Result<Vec<f32>, String> {
-        if self.fallback_mode {
-            // Return a dummy style vector for fallback mode
-            return Ok(vec![0.0; 256]);
-        }
+    /// Create a TTS engine from model and voices bytes already in memory,
+    /// e.g. embedded in the binary; nothing is read from disk or downloaded
+    /// (see `KokoroModel::from_memory`)
+    pub fn from_memory(model_bytes: &[u8], voices_bytes: &[u8]) -> Result<Self, Error> {
+        KokoroModel::from_memory(model_bytes, voices_bytes).map(Self::with_model)
+    }
 
-        let mut result = vec![0.0; 256];
-        let parts: Vec<&str> = voice_str.split('+').collect();
+    /// Create a TTS engine from local model and voices files without any
+    /// network access (for air-gapped machines).
+    ///
+    /// Unlike `with_paths`, missing files are an error rather than a download,
+    /// and there is no fallback mode: both files must exist and be large
+    /// enough to be real, and the error names the file that isn't.
+    ///
+    /// A model file with "int8" or "quant" in its name is reported as
+    /// `ModelVariant::Quantized` by `model_variant`.
+    pub fn from_paths(
+        model_path: impl AsRef<Path>,
+        voices_path: impl AsRef<Path>,
+    ) -> Result<Self, Error> {
+        KokoroModel::from_paths(model_path, voices_path).map(Self::with_model)
+    }
 
-        for part in parts {
-            let (voice_name, weight) = if part.contains('.') {
-                let pieces: Vec<&str> = part.split('.').collect();
-                if pieces.len() != 2 {
-                    return Err(format!("Invalid voice format: {}", part));
+    /// Like `new`, reporting download progress to `progress` (see
+    /// `DownloadProgress`) so a first start on a slow connection can show a
+    /// progress bar instead of looking hung
+    pub async fn new_with_progress(
+        progress: impl FnMut(DownloadProgress) + Send,
+    ) -> Result<Self, Error> {
+        let paths = Self::default_model_paths();
+
+        Self::with_paths_and_progress(
+            paths.model.to_str().unwrap_or("0.onnx"),
+            paths.voices.to_str().unwrap_or("0.bin"),
+            progress,
+        )
+        .await
+    }
+
+    /// Create a new TTS engine with custom model paths, downloading the files
+    /// there if they are missing (see `from_paths` for offline use)
+    pub async fn with_paths(model_path: &str, voices_path: &str) -> Result<Self, Error> {
+        Self::with_paths_and_progress(model_path, voices_path, |_| {}).await
+    }
+
+    /// Like `with_paths`, reporting download progress to `progress`.
+    ///
+    /// Files download to a `.part` file next to the target and an
+    /// interrupted download resumes from there on the next start.
+    /// `DownloadProgress::Complete` is reported once both files are present
+    /// and verified, also when nothing had to be downloaded.
+    pub async fn with_paths_and_progress(
+        model_path: &str,
+        voices_path: &str,
+        progress: impl FnMut(DownloadProgress) + Send,
+    ) -> Result<Self, Error> {
+        let session = SessionConfig::default();
+        Self::open(model_path, voices_path, ModelVariant::Fp32, &session, false, None, progress).await
+    }
+
+    // Download whatever is missing (the model in `variant`, through `proxy`
+    // if given, else the environment's), then load the engine as `session`
+    // says, now or on first use if `lazy`, or fall back
+    async fn open(
+        model_path: &str,
+        voices_path: &str,
+        variant: ModelVariant,
+        session: &SessionConfig,
+        lazy: bool,
+        proxy: Option<&str>,
+        mut progress: impl FnMut(DownloadProgress) + Send,
+    ) -> Result<Self, Error> {
+        // Ensure cache directory exists
+        let cache_dir = Path::new(model_path).parent().unwrap_or(Path::new("."));
+        fs::create_dir_all(cache_dir)
+            .map_err(|e| Error::io(format_args!("Failed to create cache directory {}", cache_dir.display()), e))?;
+
+        // Check if we need to download
+        let need_download = !Path::new(model_path).exists() || !Path::new(voices_path).exists();
+
+        if need_download {
+            check_writable(cache_dir)?;
+            let client = http_client(proxy)?;
+            info!(cache_dir = %cache_dir.display(), "First-time setup: downloading the voice model");
+
+            // Auto-play fallback message while downloading (if playback is enabled)
+            #[cfg(feature = "playback")]
+            {
+                // Spawn a thread to play the fallback message
+                thread::spawn(|| {
+                    if let Err(e) = play_fallback_message() {
+                        debug!(error = %e, "Could not play the fallback message");
+                    }
+                });
+            }
+
+            // Try to download the files
+            let download_success = {
+                let mut success = true;
+
+                // Download model if needed
+                if !Path::new(model_path).exists() {
+                    info!(url = variant.url(), "Downloading the model");
+                    let downloaded = download_file(
+                        &client,
+                        variant.url(),
+                        model_path,
+                        MIN_MODEL_BYTES,
+                        variant.sha256(),
+                        &mut progress,
+                    )
+                    .instrument(tracing::info_span!("download", file = "model"))
+                    .await;
+                    if let Err(e) = downloaded {
+                        warn!(error = %e, "Failed to download the model");
+                        success = false;
+                    }
                 }
-                let weight = pieces[1]
-                    .parse::<f32>()
-                    .map_err(|_| format!("Invalid weight: {}", pieces[1]))?;
-                (pieces[0], weight / 10.0)
-            } else {
-                (part, 1.0)
+
+                // Download voices if needed
+                if success && !Path::new(voices_path).exists() {
+                    info!(url = VOICES_URL, "Downloading the voices");
+                    let downloaded = download_file(
+                        &client,
+                        VOICES_URL,
+                        voices_path,
+                        MIN_VOICES_BYTES,
+                        Some(VOICES_SHA256),
+                        &mut progress,
+                    )
+                    .instrument(tracing::info_span!("download", file = "voices"))
+                    .await;
+                    if let Err(e) = downloaded {
+                        warn!(error = %e, "Failed to download the voices");
+                        success = false;
+                    }
+                }
+
+                if success {
+                    info!("Voice model downloaded");
+                }
+
+                success
             };
 
-            let voice_style = self
-                .voices
-                .get(voice_name)
-                .ok_or_else(|| format!("Voice not found: {}", voice_name))?;
+            // If download failed, return fallback engine
+            if !download_success {
+                warn!(
+                    model_url = variant.url(),
+                    voices_url = VOICES_URL,
+                    cache_dir = %cache_dir.display(),
+                    "Using fallback mode: download the model files into the cache directory yourself"
+                );
+
+                return Ok(Self::fallback(variant));
+            }
+        }
+
+        progress(DownloadProgress::Complete);
+        let (model_path, voices_path) = (Path::new(model_path), Path::new(voices_path));
+        if lazy {
+            return Ok(Self::load_on_first_use(model_path, voices_path, variant, session));
+        }
+        Self::load(model_path, voices_path, variant, session)
+    }
+
+    // Load the model and voices files into a ready engine
+    fn load(
+        model_path: &Path,
+        voices_path: &Path,
+        model_variant: ModelVariant,
+        config: &SessionConfig,
+    ) -> Result<Self, Error> {
+        KokoroModel::load(model_path, voices_path, model_variant, config).map(Self::with_model)
+    }
+
+    /// Create an engine on a model that is already loaded, sharing its
+    /// sessions and voices. This is near-instant and adds only the engine's
+    /// own settings, so an engine per default voice or audio device is cheap.
+    /// Voices added with `blend_voices` or `load_voice_from_file` stay local
+    /// to the engine that added them.
+    pub fn with_model(model: KokoroModel) -> Self {
+        Self {
+            fallback_mode: false,
+            execution_provider: model.execution_provider,
+            ..Self::fallback(model.variant)
+        }
+        .with_model_loaded(model)
+        .with_initial_device()
+    }
+
+    // Engine that loads the model and voices files on first use
+    fn load_on_first_use(
+        model_path: &Path,
+        voices_path: &Path,
+        model_variant: ModelVariant,
+        config: &SessionConfig,
+    ) -> Self {
+        Self {
+            lazy_load: Some(LazyLoad {
+                model_path: model_path.to_path_buf(),
+                voices_path: voices_path.to_path_buf(),
+                config: config.clone(),
+            }),
+            fallback_mode: false,
+            execution_provider: config.provider,
+            ..Self::fallback(model_variant)
+        }
+        .with_initial_device()
+    }
+
+    fn with_model_loaded(self, model: KokoroModel) -> Self {
+        Self {
+            model: OnceLock::from(Ok(model)),
+            ..self
+        }
+    }
 
-            for (i, val) in voice_style.iter().enumerate() {
-                if i < result.len() {
-                    result[i] += val * weight;
+    #[cfg_attr(not(feature = "playback"), allow(unused_mut))]
+    fn with_initial_device(mut self) -> Self {
+        // Initialize audio device selection from cache or choose a preferred device
+        #[cfg(feature = "playback")]
+        {
+            if self.audio_device.is_none() {
+                if let Some(cached) = load_cached_device() {
+                    self.audio_device = Some(cached);
+                } else if let Ok(devs) = self.list_audio_devices() {
+                    let names: Vec<String> = devs.into_iter().map(|d| d.name).collect();
+                    if let Some(pref) = pick_preferred_device(&names) {
+                        // Persist preference but ignore errors
+                        let _ = save_cached_device(Some(&pref));
+                        self.audio_device = Some(pref);
+                    }
                 }
             }
         }
+        self
+    }
 
-        Ok(result)
+    // The model, loading it first if the engine was built lazily
+    fn loaded_model(&self) -> Result<&KokoroModel, Error> {
+        let model = match &self.lazy_load {
+            Some(lazy) => self.model.get_or_init(|| {
+                KokoroModel::load(&lazy.model_path, &lazy.voices_path, self.model_variant, &lazy.config)
+            }),
+            None => self
+                .model
+                .get()
+                .ok_or_else(|| Error::ModelLoad("TTS engine not initialized".to_string()))?,
+        };
+        model.as_ref().map_err(Clone::clone)
+    }
+
+    // The model if it is loaded, without loading it
+    fn model_if_loaded(&self) -> Option<&KokoroModel> {
+        self.model.get()?.as_ref().ok()
+    }
+
+    /// Whether the model is in memory: always after construction, unless the
+    /// engine is in fallback mode or was built `lazy` and hasn't needed it yet
+    pub fn is_model_loaded(&self) -> bool {
+        self.model_if_loaded().is_some()
+    }
+
+    // Engine without a model, which speaks the bundled fallback message
+    fn fallback(model_variant: ModelVariant) -> Self {
+        Self {
+            model: OnceLock::new(),
+            lazy_load: None,
+            parallel_chunks: true,
+            next_session: AtomicUsize::new(0),
+            custom_voices: HashMap::new(),
+            vocab: build_vocab(),
+            fallback_mode: true,
+            model_variant,
+            execution_provider: ExecutionProvider::Cpu,
+            default_voice: DEFAULT_VOICE.to_string(),
+            default_speed: DEFAULT_SPEED,
+            target_loudness: None,
+            trim_silence_db: None,
+            fade_ms: (0, 0),
+            dc_cleanup: true,
+            edge_silence_ms: None,
+            dialogue_gap_ms: DIALOGUE_GAP_MS,
+            pause_config: PauseConfig::default(),
+            voice_profiles: HashMap::new(),
+            emotion_styles: HashMap::new(),
+            normalizer: text::Normalizer::default(),
+            pronunciations: HashMap::new(),
+            emoji_policy: EmojiPolicy::default(),
+            url_style: UrlStyle::default(),
+            input_format: InputFormat::default(),
+            pause_markup: Some(PauseMarkup::default()),
+            unknown_char_policy: UnknownCharPolicy::default(),
+            max_chunk_tokens: MAX_CHUNK_TOKENS,
+            chunk_crossfade_ms: Some(CHUNK_CROSSFADE_MS),
+            processors: Processors::default(),
+            #[cfg(feature = "playback")]
+            audio_host: playback::AudioHost::Default,
+            #[cfg(feature = "playback")]
+            audio_device: None,
+            #[cfg(feature = "playback")]
+            output_channels: None,
+            #[cfg(feature = "playback")]
+            playback_stop: playback::StopSignal::default(),
+            #[cfg(feature = "playback")]
+            speech_queue: None,
+            #[cfg(feature = "playback")]
+            urgent_mode: playback::UrgentMode::default(),
+        }
+    }
+
+    /// Run a throwaway inference on every session and read through the voice
+    /// data, so the first real `synthesize` runs at steady-state speed instead
+    /// of paying for ONNX Runtime's lazy setup. Safe to call more than once.
+    /// Returns how long it took, for deployment logs; in fallback mode there
+    /// is nothing to warm up. A `lazy` engine loads its model here.
+    pub fn warm_up(&self) -> Result<Duration, Error> {
+        let started = std::time::Instant::now();
+        if self.fallback_mode {
+            return Ok(started.elapsed());
+        }
+
+        let model = self.loaded_model()?;
+        let touched: f32 = model
+            .voices
+            .values()
+            .chain(self.custom_voices.values())
+            .map(|style| style.iter().sum::<f32>())
+            .sum();
+        std::hint::black_box(touched);
+
+        let plan = self.plan_synthesis(WARM_UP_TEXT, None, self.default_speed, None)?;
+        for session in model.sessions.iter() {
+            for chunk in &plan.chunks {
+                let (tokens, style) = (chunk.tokens.clone(), chunk.style.clone());
+                infer(&mut *lock_session(session)?, tokens, style, plan.speed)?;
+            }
+        }
+        Ok(started.elapsed())
+    }
+
+    /// The loaded model, to create more engines on with `with_model`
+    /// (None in fallback mode or if a lazy load failed). A `lazy` engine
+    /// loads its model here.
+    pub fn model(&self) -> Option<KokoroModel> {
+        self.loaded_model().ok().cloned()
+    }
+
+    /// Which model export is loaded (in fallback mode: the one that was requested)
+    pub fn model_variant(&self) -> ModelVariant {
+        self.model_variant
+    }
+
+    /// Run the chunks of a long text in parallel on the engine's inference
+    /// workers (default on; only has an effect with
+    /// `TtsEngineBuilder::inference_workers` above 1). Turning it off keeps
+    /// the extra sessions loaded but idle.
+    pub fn set_parallel_chunks(&mut self, enabled: bool) {
+        self.parallel_chunks = enabled;
+    }
+
+    /// Sessions available for inference: 1 plus any extra workers (0 in
+    /// fallback mode or before a lazy load)
+    pub fn inference_workers(&self) -> usize {
+        self.model_if_loaded().map_or(0, |model| model.sessions.len())
+    }
+
+    // Lock a session for one model run: an idle one if there is any, else
+    // the next in turn, so concurrent calls through `&self` share the pool
+    fn idle_session(&self) -> Result<MutexGuard<'_, Session>, Error> {
+        let turn = self.next_session.fetch_add(1, Ordering::Relaxed);
+        lock_idle_session(&self.loaded_model()?.sessions, turn)
+    }
+
+    // The sessions chunk inference can be spread across right now
+    fn chunk_sessions(&self) -> Result<&[Mutex<Session>], Error> {
+        let sessions = &self.loaded_model()?.sessions;
+        let count = if self.parallel_chunks { sessions.len() } else { 1 };
+        Ok(&sessions[..count.min(sessions.len())])
+    }
+
+    /// Where the model actually runs: the requested provider, or `Cpu` if it
+    /// couldn't be used (see `TtsEngineBuilder::execution_provider`). Before
+    /// a lazy engine loads, the requested one.
+    pub fn execution_provider(&self) -> ExecutionProvider {
+        self.model_if_loaded()
+            .map_or(self.execution_provider, |model| model.execution_provider)
+    }
+
+    /// Whether the model couldn't be loaded (or downloaded), so synthesis
+    /// returns the built-in "lost my voice" message instead of the text
+    pub fn is_fallback(&self) -> bool {
+        self.fallback_mode
+    }
+
+    /// List all available voices
+    pub fn voices(&self) -> Vec<String> {
+        if self.fallback_mode {
+            vec!["fallback".to_string()]
+        } else {
+            self.voice_names()
+        }
+    }
+
+    /// Look up a voice (or a mix like "af_sky.6+af_bella.4") once, so a typo
+    /// fails here instead of deep into a batch job.
+    ///
+    /// Example: `let sky = tts.voice("af_sky")?;` and then
+    /// `tts.synthesize_with_voice(text, &sky, None)`; every call taking a voice
+    /// name accepts `Some(sky.name())`.
+    pub fn voice(&self, name: &str) -> Result<Voice, Error> {
+        self.check_voice(name)?;
+        Ok(Voice { name: name.into() })
+    }
+
+    // Fail like synthesis would if `voice` isn't available, without loading a lazy model
+    fn check_voice(&self, voice: &str) -> Result<(), Error> {
+        if self.lazy_load.is_some() && !self.is_model_loaded() {
+            self.check_voice_names(voice)
+        } else {
+            self.parse_voice_style(voice, 0).map(drop)
+        }
+    }
+
+    /// List the voices in the loaded voices file with language/accent and gender,
+    /// sorted by identifier. Empty in fallback mode, since no voices are loaded.
+    pub fn list_voices(&self) -> Vec<VoiceInfo> {
+        if self.fallback_mode {
+            return Vec::new();
+        }
+
+        let mut infos: Vec<VoiceInfo> = self
+            .voice_names()
+            .iter()
+            .map(|id| VoiceInfo {
+                profile: self.voice_profiles.get(id).copied(),
+                ..VoiceInfo::from_id(id)
+            })
+            .collect();
+        infos.sort_by(|a, b| a.id.cmp(&b.id));
+        infos
+    }
+
+    /// Blend two voices into a new voice registered under `name`.
+    ///
+    /// `weight` is how far to move from `first` towards `second`: 0.0 is pure
+    /// `first`, 1.0 is pure `second`, 0.5 an even mix. Every token-count style
+    /// row is interpolated, so the blend behaves like a bundled voice at any
+    /// text length. Registering under an existing name replaces that voice.
+    ///
+    /// Example: `tts.blend_voices("af_sky", "af_bella", 0.6, "my_blend")?` and then
+    /// `tts.synthesize(text, Some("my_blend"), None, None)`.
+    pub fn blend_voices(
+        &mut self,
+        first: &str,
+        second: &str,
+        weight: f32,
+        name: &str,
+    ) -> Result<(), Error> {
+        if self.fallback_mode {
+            return Err(Error::ModelLoad(
+                "Cannot blend voices: voice data is not loaded (fallback mode)".to_string(),
+            ));
+        }
+        if !(0.0..=1.0).contains(&weight) {
+            let message = format!("Blend weight must be between 0.0 and 1.0, got {}", weight);
+            return Err(Error::InvalidInput(message));
+        }
+        validate_voice_name(name)?;
+
+        let a = self.voice_data(first)?;
+        let b = self.voice_data(second)?;
+
+        if a.len() != b.len() {
+            return Err(Error::InvalidInput(format!(
+                "Cannot blend '{}' and '{}': incompatible style dimensions ({} vs {} values)",
+                first,
+                second,
+                a.len(),
+                b.len()
+            )));
+        }
+
+        let blended = blend_styles(a, b, weight);
+        self.custom_voices.insert(name.to_string(), blended);
+        Ok(())
+    }
+
+    /// Load a custom voice style from disk and register it under `name`.
+    ///
+    /// Accepts `.npy` arrays or raw little-endian f32 blobs (`.bin`). The data must
+    /// be either a single 256-value style vector or a 510x256 token-indexed style
+    /// matrix like the bundled voices, so token-count style selection still applies.
+    pub fn load_voice_from_file(&mut self, name: &str, path: &str) -> Result<(), Error> {
+        let bytes = fs::read(path)
+            .map_err(|e| Error::io(format!("Failed to read voice file '{}'", path), e))?;
+        self.load_voice_from_bytes(name, &bytes)
+    }
+
+    /// Register a custom voice style from in-memory `.npy` or raw f32 bytes.
+    /// See `load_voice_from_file` for the accepted shapes.
+    pub fn load_voice_from_bytes(&mut self, name: &str, bytes: &[u8]) -> Result<(), Error> {
+        if self.fallback_mode {
+            return Err(Error::ModelLoad(
+                "Cannot load voices: model is not loaded (fallback mode)".to_string(),
+            ));
+        }
+        validate_voice_name(name)?;
+
+        let style = parse_voice_bytes(bytes)?;
+        self.custom_voices.insert(name.to_string(), style);
+        Ok(())
+    }
+
+    /// List all available audio output devices with their host, whether each
+    /// is the default, and the rates and channel counts it supports
+    /// (requires 'playback' feature)
+    #[cfg(feature = "playback")]
+    pub fn list_audio_devices(&self) -> Result<Vec<playback::AudioDeviceInfo>, Error> {
+        playback::list_devices(self.audio_host).map_err(Error::AudioDevice)
+    }
+
+    /// List the output devices on a specific audio host, whichever host is
+    /// selected (requires 'playback' feature)
+    #[cfg(feature = "playback")]
+    pub fn list_audio_devices_on(
+        &self,
+        host: playback::AudioHost,
+    ) -> Result<Vec<playback::AudioDeviceInfo>, Error> {
+        playback::check_host(host).map_err(Error::AudioDevice)?;
+        playback::list_devices(host).map_err(Error::AudioDevice)
+    }
+
+    /// Audio hosts usable on this machine, `AudioHost::Default` first
+    /// (requires 'playback' feature)
+    #[cfg(feature = "playback")]
+    pub fn list_audio_hosts(&self) -> Vec<playback::AudioHost> {
+        playback::available_hosts()
+    }
+
+    /// Choose the audio backend devices are listed and opened on (requires
+    /// 'playback' feature), e.g. `AudioHost::PulseAudio` to keep ALSA from
+    /// grabbing the card exclusively. Fails with the available hosts listed
+    /// if `host` isn't usable here.
+    ///
+    /// The selected device is looked up on the new host; if it isn't there,
+    /// playback uses the host's default device with a warning.
+    #[cfg(feature = "playback")]
+    pub fn set_audio_host(&mut self, host: playback::AudioHost) -> Result<(), Error> {
+        playback::check_host(host).map_err(Error::AudioDevice)?;
+        self.audio_host = host;
+        Ok(())
+    }
+
+    /// The audio host in use (requires 'playback' feature)
+    #[cfg(feature = "playback")]
+    pub fn audio_host(&self) -> playback::AudioHost {
+        self.audio_host
+    }
+
+    /// Set the audio output device by name (requires 'playback' feature)
+    /// Pass None to use system default
+    ///
+    /// A name that isn't an exact match selects the one device whose name
+    /// contains it, ignoring case ("scarlett"). Several matches, or none, is
+    /// an error naming the candidates.
+    #[cfg(feature = "playback")]
+    pub fn set_audio_device(&mut self, device_name: Option<String>) -> Result<(), Error> {
+        // Resolve to a full device name if one was provided
+        let device_name = match device_name {
+            Some(name) => {
+                let devices = self.list_audio_devices()?;
+                let available: Vec<String> = devices.into_iter().map(|d| d.name).collect();
+                Some(playback::match_device_name(&name, &available).map_err(Error::AudioDevice)?)
+            }
+            None => None,
+        };
+
+        self.audio_device = device_name;
+        // Persist selection
+        #[cfg(feature = "playback")]
+        if let Err(e) = save_cached_device(self.audio_device.as_deref()) {
+            warn!(error = %e, "Failed to save the audio device selection");
+        }
+        Ok(())
+    }
+
+    /// Set the audio output device by its position in `list_audio_devices`,
+    /// counting from 0 (requires 'playback' feature)
+    #[cfg(feature = "playback")]
+    pub fn set_audio_device_by_index(&mut self, index: usize) -> Result<(), Error> {
+        let available = self.list_audio_devices()?;
+        let device = available.get(index).ok_or_else(|| {
+            Error::InvalidInput(format!(
+                "Device index {} out of range ({} devices available)",
+                index,
+                available.len()
+            ))
+        })?;
+        self.set_audio_device(Some(device.name.clone()))
+    }
+
+    /// Enumerate output devices again and report whether the selected device
+    /// is present (requires 'playback' feature). Always true with no selection.
+    ///
+    /// Playback already re-enumerates each time it opens the device and falls
+    /// back to the default while the selected one is missing, so this is for
+    /// checking that a device is back (e.g. after replugging it).
+    #[cfg(feature = "playback")]
+    pub fn reset_audio_device(&self) -> Result<bool, Error> {
+        let available = self.list_audio_devices()?;
+        Ok(self
+            .audio_device
+            .as_ref()
+            .is_none_or(|name| available.iter().any(|device| &device.name == name)))
+    }
+
+    /// Sample rate playback on the selected device (or the default) runs at
+    /// (requires 'playback' feature). The model's 24kHz output is resampled
+    /// to it, preferring 48kHz or 44.1kHz when the device supports them.
+    #[cfg(feature = "playback")]
+    pub fn output_sample_rate(&self) -> Result<u32, Error> {
+        playback::output_rate(&self.output_target()).map_err(Error::AudioDevice)
+    }
+
+    // Host and device that playback should open
+    #[cfg(feature = "playback")]
+    fn output_target(&self) -> playback::OutputTarget {
+        playback::OutputTarget {
+            host: self.audio_host,
+            device_name: self.audio_device.clone(),
+            channel_map: self.output_channels.clone(),
+        }
+    }
+
+    /// Send speech only to these output channels of the device, counting from
+    /// 0, with silence on the rest (requires 'playback' feature). For example
+    /// `&[2, 3]` puts it on outputs 3/4 of a multi-channel interface. The
+    /// device is opened with all of its channels. An empty slice goes back
+    /// to the default mono-to-all-channels output.
+    ///
+    /// Checked against the selected device now, and again whenever it is opened.
+    #[cfg(feature = "playback")]
+    pub fn set_output_channels(&mut self, channels: &[u16]) -> Result<(), Error> {
+        let map = (!channels.is_empty()).then(|| channels.to_vec());
+        let target = playback::OutputTarget {
+            channel_map: map.clone(),
+            ..self.output_target()
+        };
+        playback::check_channel_map(&target).map_err(Error::AudioDevice)?;
+        self.output_channels = map;
+        Ok(())
+    }
+
+    /// The output channels speech is sent to, None for the default
+    /// (requires 'playback' feature)
+    #[cfg(feature = "playback")]
+    pub fn output_channels(&self) -> Option<&[u16]> {
+        self.output_channels.as_deref()
+    }
+
+    /// Get the currently selected audio device (requires 'playback' feature)
+    #[cfg(feature = "playback")]
+    pub fn get_audio_device(&self) -> Option<&str> {
+        self.audio_device.as_deref()
+    }
+
+    /// Set the voice used when a call passes `None` for voice ("af_sky" unless
+    /// changed). Fails with the available voices listed if it isn't loaded.
+    pub fn set_default_voice(&mut self, voice: &str) -> Result<(), Error> {
+        self.check_voice(voice)?;
+        self.default_voice = voice.to_string();
+        Ok(())
+    }
+
+    /// Voice used when a call passes `None` for voice
+    pub fn default_voice(&self) -> &str {
+        &self.default_voice
+    }
+
+    /// Set the speed used when a call passes `None` for speed (1.0 = normal)
+    pub fn set_default_speed(&mut self, speed: f32) {
+        self.default_speed = speed;
+    }
+
+    /// Speed used when a call passes `None` for speed
+    pub fn default_speed(&self) -> f32 {
+        self.default_speed
+    }
+
+    /// Normalize finished audio to an integrated loudness in LUFS (e.g. -16.0 for
+    /// podcasts), or `None` to leave levels untouched (the default).
+    ///
+    /// Applies to calls that return a whole buffer; `synthesize_streaming` chunks
+    /// are left alone. Use `audio::measure_loudness` to log the result.
+    pub fn set_target_loudness(&mut self, lufs: Option<f32>) {
+        self.target_loudness = lufs;
+    }
+
+    /// Trim leading/trailing silence quieter than `threshold_db` (dBFS, e.g. -45.0)
+    /// from finished audio, or `None` to keep it (the default).
+    ///
+    /// 50ms is kept on each side of the first/last audible sample so soft onsets
+    /// aren't cut, and pauses inside the utterance are never touched. Like
+    /// loudness normalization this applies to whole-buffer calls, not streamed chunks.
+    pub fn set_trim_silence(&mut self, threshold_db: Option<f32>) {
+        self.trim_silence_db = threshold_db;
+    }
+
+    /// Fade finished audio in over `fade_in_ms` and out over `fade_out_ms`
+    /// (default 0 for both, no fade), with equal-power ramps.
+    ///
+    /// Fades are applied last, after silence trimming and loudness
+    /// normalization, so they shape the speech itself rather than silence that
+    /// is about to be cut. A fade longer than the audio covers all of it. Like
+    /// the other post-processing this applies to whole-buffer calls, not
+    /// streamed chunks.
+    pub fn set_fade(&mut self, fade_in_ms: u32, fade_out_ms: u32) {
+        self.fade_ms = (fade_in_ms, fade_out_ms);
+    }
+
+    /// Current fade-in and fade-out lengths in ms
+    pub fn fade(&self) -> (u32, u32) {
+        self.fade_ms
+    }
+
+    /// Clean up finished audio for editing (on by default): remove DC offset
+    /// with a 20Hz high-pass before any other post-processing, and ramp the
+    /// first and last 2ms from and to zero after it, so clips concatenated or
+    /// looped elsewhere don't tick.
+    ///
+    /// Neither is audible in speech. Turn it off to compare against raw model
+    /// output. Unlike the other post-processing this also applies to
+    /// `synthesize_to_wav` and the server; `synthesize_streaming` chunks are
+    /// still left alone.
+    pub fn set_dc_cleanup(&mut self, enabled: bool) {
+        self.dc_cleanup = enabled;
+    }
+
+    /// Whether finished audio gets DC removal and zeroed ends
+    pub fn dc_cleanup(&self) -> bool {
+        self.dc_cleanup
+    }
+
+    /// Give finished audio exactly `leading_ms` of silence before the speech and
+    /// `trailing_ms` after it, e.g. 200ms up front for an amplifier relay to
+    /// click on, or 0 for an API that wants speech from the first sample.
+    ///
+    /// Existing edge silence (anything below the `set_trim_silence` level, or
+    /// -50 dBFS without one) is cut down or padded with zeros to the requested
+    /// length, so the result doesn't depend on how much padding the model made.
+    /// This runs after trimming, loudness and fades. Like them it applies to
+    /// whole-buffer calls, not streamed chunks.
+    pub fn set_edge_silence(&mut self, leading_ms: u32, trailing_ms: u32) {
+        self.edge_silence_ms = Some((leading_ms, trailing_ms));
+    }
+
+    /// Go back to keeping whatever edge silence synthesis produces (the default)
+    pub fn clear_edge_silence(&mut self) {
+        self.edge_silence_ms = None;
+    }
+
+    /// Current leading and trailing silence in ms, if set
+    pub fn edge_silence(&self) -> Option<(u32, u32)> {
+        self.edge_silence_ms
+    }
+
+    /// Set extra silence inserted after punctuation (see `PauseConfig`).
+    /// The default adds nothing, leaving pacing entirely to the model.
+    pub fn set_pause_config(&mut self, config: PauseConfig) {
+        self.pause_config = config;
+    }
+
+    /// Current punctuation pause settings
+    pub fn pause_config(&self) -> PauseConfig {
+        self.pause_config
+    }
+
+    /// Silence between the lines of `synthesize_dialogue` (default 300ms)
+    pub fn set_dialogue_gap(&mut self, ms: u32) {
+        self.dialogue_gap_ms = ms;
+    }
+
+    /// Current silence between dialogue lines, in ms
+    pub fn dialogue_gap(&self) -> u32 {
+        self.dialogue_gap_ms
+    }
+
+    /// Tune a voice once instead of at every call site: the profile's speed
+    /// multiplies the speed of every call that uses `voice` (per-call, segment
+    /// or default speed alike), and its pitch shift is applied to the result.
+    ///
+    /// `voice` is matched by exact name, including blends registered with
+    /// `blend_voices`, and doesn't have to be loaded yet. Setting a profile
+    /// again replaces it; `list_voices` shows which voices have one.
+    pub fn set_voice_profile(&mut self, voice: &str, profile: VoiceProfile) -> Result<(), Error> {
+        profile.validate(voice)?;
+        self.voice_profiles.insert(voice.to_string(), profile);
+        Ok(())
+    }
+
+    /// Profile set for `voice`, if any
+    pub fn voice_profile(&self, voice: &str) -> Option<VoiceProfile> {
+        self.voice_profiles.get(voice).copied()
+    }
+
+    /// Stop tuning `voice`, returning the profile it had
+    pub fn remove_voice_profile(&mut self, voice: &str) -> Option<VoiceProfile> {
+        self.voice_profiles.remove(voice)
+    }
+
+    /// All voice profiles as JSON, to share tuning between deployments, e.g.
+    /// `{"af_sky": {"speed": 1.05, "pitch": 0.0}}`
+    pub fn export_voice_profiles(&self) -> String {
+        let sorted: BTreeMap<_, _> = self.voice_profiles.iter().collect();
+        serde_json::to_string_pretty(&sorted).unwrap_or_default()
+    }
+
+    /// Add the profiles from `export_voice_profiles` JSON, replacing any already
+    /// set for the same voices. Fields left out keep their defaults; nothing is
+    /// imported if any profile is invalid.
+    pub fn import_voice_profiles(&mut self, json: &str) -> Result<(), Error> {
+        let profiles: HashMap<String, VoiceProfile> = serde_json::from_str(json)
+            .map_err(|e| Error::InvalidInput(format!("Invalid voice profiles: {}", e)))?;
+        for (voice, profile) in &profiles {
+            profile.validate(voice)?;
+        }
+        self.voice_profiles.extend(profiles);
+        Ok(())
+    }
+
+    /// Use `style` for every intensity of `emotion`'s kind: it is the style at
+    /// full intensity, and the intensity in `emotion` itself is ignored.
+    ///
+    /// Example: `tts.set_emotion_style(&EmotionType::Joy(1.0), EmotionStyle { speed: 1.3, ..Default::default() })`
+    pub fn set_emotion_style(
+        &mut self,
+        emotion: &EmotionType,
+        style: EmotionStyle,
+    ) -> Result<(), Error> {
+        style.validate()?;
+        self.emotion_styles.insert(emotion.name(), style);
+        Ok(())
+    }
+
+    /// The speed, pitch and energy `emotion` applies at its intensity, with
+    /// any `set_emotion_style` override
+    pub fn emotion_style(&self, emotion: &EmotionType) -> EmotionStyle {
+        let full = self
+            .emotion_styles
+            .get(emotion.name())
+            .copied()
+            .unwrap_or_else(|| EmotionStyle::builtin(emotion));
+        full.scaled(emotion.intensity())
+    }
+
+    // Profile of `voice`, or one that changes nothing
+    fn profile_for(&self, voice: &str) -> VoiceProfile {
+        self.voice_profile(voice).unwrap_or_default()
+    }
+
+    /// Read `abbreviation` as `expansion` in English text, e.g.
+    /// `tts.add_abbreviation("MEM8", "mem eight")`. Custom entries override the
+    /// built-in table (Dr., St., e.g., ...); matching is case-sensitive.
+    pub fn add_abbreviation(&mut self, abbreviation: &str, expansion: &str) {
+        self.normalizer.add_abbreviation(abbreviation, expansion);
+    }
+
+    /// Read the all-caps `acronym` as `spoken` in English text, e.g.
+    /// `tts.add_acronym("SQL", "sequel")`. Without an override, acronyms from a
+    /// small built-in list ("NASA") are pronounced and others are spelled out.
+    pub fn add_acronym(&mut self, acronym: &str, spoken: &str) {
+        self.normalizer.add_acronym(acronym, spoken);
+    }
+
+    /// Always read `word` as `phonemes`, written in the model's phoneme
+    /// alphabet (espeak-ng style IPA), e.g. `tts.add_pronunciation("Nguyen",
+    /// "ŋwˈɪn")`, for names and loanwords espeak gets wrong.
+    ///
+    /// Matching is on whole words, ignoring case, after text normalization,
+    /// in every language; the phonemes go to the model as given and replace
+    /// built-in heteronym readings too. Errors if `word` isn't a single word
+    /// or `phonemes` has characters the model doesn't know.
+    pub fn add_pronunciation(&mut self, word: &str, phonemes: &str) -> Result<(), Error> {
+        if word.is_empty() || !word.chars().all(|c| c.is_alphanumeric() || c == '\'') {
+            let message = format!("Pronunciations are for single words (got {:?})", word);
+            return Err(Error::InvalidInput(message));
+        }
+        let unknown = phonemes.chars().find(|c| c.is_whitespace() || !self.vocab.contains_key(c));
+        if phonemes.is_empty() || unknown.is_some() {
+            return Err(Error::InvalidInput(format!(
+                "Pronunciation for {:?} must be phonemes without spaces (got {:?})",
+                word, phonemes
+            )));
+        }
+        self.pronunciations.insert(word.to_lowercase(), phonemes.to_string());
+        Ok(())
+    }
+
+    /// Choose how web and email addresses in English text are read (default
+    /// `UrlStyle::Domain`: "https://www.example.com/docs" → "example dot com").
+    /// Email addresses are read in full ("help at example dot com") unless
+    /// the style is `UrlStyle::Skip`.
+    pub fn set_url_style(&mut self, style: UrlStyle) {
+        self.url_style = style;
+    }
+
+    /// Current address reading
+    pub fn url_style(&self) -> UrlStyle {
+        self.url_style
+    }
+
+    /// Choose how emoji in the input text are handled (default `EmojiPolicy::Strip`).
+    /// Skin tones and joined sequences count as one emoji.
+    pub fn set_emoji_policy(&mut self, policy: EmojiPolicy) {
+        self.emoji_policy = policy;
+    }
+
+    /// Current emoji handling
+    pub fn emoji_policy(&self) -> EmojiPolicy {
+        self.emoji_policy
+    }
+
+    /// Choose how input text is read (default `InputFormat::Plain`, every
+    /// character as written). With `InputFormat::Markdown`, text such as
+    /// `**Note:** see [the docs](https://...)` is read as "Note: see the docs";
+    /// with `InputFormat::Html`, `<p>It&#8217;s <b>back</b></p>` as "It’s back".
+    pub fn set_input_format(&mut self, format: InputFormat) {
+        self.input_format = format;
+    }
+
+    /// Current input format
+    pub fn input_format(&self) -> InputFormat {
+        self.input_format
+    }
+
+    /// Choose the inline pause markers (default `[pause:` ... `]`): in
+    /// "Attention. [pause:1500ms] Boarding" the marker becomes that much
+    /// silence, up to 10 seconds, and is never read out. Malformed markers are
+    /// removed with a warning. `None` reads the markers as ordinary text.
+    pub fn set_pause_markup(&mut self, markup: Option<PauseMarkup>) {
+        self.pause_markup = markup;
+    }
+
+    /// Current inline pause markers, None if they are read as text
+    pub fn pause_markup(&self) -> Option<&PauseMarkup> {
+        self.pause_markup.as_ref()
+    }
+
+    /// Choose how input characters the model can't pronounce are handled
+    /// (default `UnknownCharPolicy::Skip`). `synthesize_with_warnings` reports
+    /// each one with its offset under every policy.
+    pub fn set_unknown_char_policy(&mut self, policy: UnknownCharPolicy) {
+        self.unknown_char_policy = policy;
+    }
+
+    /// Current handling of unpronounceable characters
+    pub fn unknown_char_policy(&self) -> UnknownCharPolicy {
+        self.unknown_char_policy
+    }
+
+    /// Limit how many phoneme tokens go to the model in one pass (default and
+    /// maximum: 506, the model's context minus padding).
+    ///
+    /// Chunks over the limit are split at sentence ends first, then after
+    /// commas, semicolons and colons, then between words; a word is only cut
+    /// when it doesn't fit on its own. `chunk_text` shows where the splits land.
+    pub fn set_max_chunk_tokens(&mut self, tokens: usize) -> Result<(), Error> {
+        if tokens == 0 || tokens > MAX_CHUNK_TOKENS {
+            return Err(Error::InvalidInput(format!(
+                "Max chunk tokens must be between 1 and {} (got {})",
+                MAX_CHUNK_TOKENS, tokens
+            )));
+        }
+        self.max_chunk_tokens = tokens;
+        Ok(())
+    }
+
+    /// Current phoneme token limit per chunk
+    pub fn max_chunk_tokens(&self) -> usize {
+        self.max_chunk_tokens
+    }
+
+    /// Crossfade `ms` of audio wherever chunks of one utterance are joined, or
+    /// `None` to append them as they are (default 10ms, at most 100ms).
+    ///
+    /// Only internal joins are faded; the very start and end of the output are
+    /// left alone, and so are joins at an inserted pause, which keeps its full
+    /// length. `synthesize_streaming` never crossfades, so callers stitching
+    /// chunks themselves get them untouched.
+    pub fn set_chunk_crossfade(&mut self, ms: Option<u32>) -> Result<(), Error> {
+        if let Some(ms) = ms.filter(|&ms| ms > MAX_CHUNK_CROSSFADE_MS) {
+            return Err(Error::InvalidInput(format!(
+                "Chunk crossfade must be at most {}ms (got {})",
+                MAX_CHUNK_CROSSFADE_MS, ms
+            )));
+        }
+        self.chunk_crossfade_ms = ms;
+        Ok(())
+    }
+
+    /// Current crossfade at chunk joins
+    pub fn chunk_crossfade(&self) -> Option<u32> {
+        self.chunk_crossfade_ms
+    }
+
+    // Crossfade length in samples at the model sample rate
+    fn crossfade_samples(&self) -> usize {
+        self.chunk_crossfade_ms.map_or(0, |ms| silence(ms).len())
+    }
+
+    // Snapshot of the whole-buffer post-processing settings
+    fn post_process(&self) -> PostProcess {
+        PostProcess {
+            trim_silence_db: self.trim_silence_db,
+            target_loudness: self.target_loudness,
+            fade_in: silence(self.fade_ms.0).len(),
+            fade_out: silence(self.fade_ms.1).len(),
+            dc_cleanup: self.dc_cleanup,
+            edge_silence: self.edge_silence_ms.map(|(leading, trailing)| {
+                (silence(leading).len(), silence(trailing).len())
+            }),
+            processors: self.processors.clone(),
+        }
+    }
+
+    /// Add a processor to run on the finished audio of every synthesis and
+    /// playback, after the processors added before it
+    ///
+    /// Streaming calls (`synthesize_streaming`, `speak_streaming`,
+    /// `synthesize_to_wav` and the server's streamed responses) run the
+    /// processors on each chunk as it is emitted; see `audio::AudioProcessor`.
+    /// Audio passed in to `play` or `save_audio` isn't processed again.
+    /// Chunk and word timings describe the audio before processing, so a
+    /// processor that changes the length shifts them.
+    ///
+    /// Example: `tts.add_processor(Box::new(kokoro_tiny::audio::Gain(0.5)))`
+    pub fn add_processor(&mut self, processor: Box<dyn audio::AudioProcessor>) {
+        self.processors.0.lock().unwrap_or_else(|e| e.into_inner()).push(processor);
+    }
+
+    /// Remove every processor `add_processor` added
+    pub fn clear_processors(&mut self) {
+        self.processors.0.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+
+    /// How many processors `add_processor` added
+    pub fn processor_count(&self) -> usize {
+        self.processors.len()
+    }
+
+    /// Synthesize text to speech (simple form)
+    ///
+    /// This is the ergonomic two-argument form used by examples and callers:
+    /// - `text`: text to speak
+    /// - `voice`: optional voice name (defaults to `default_voice()`)
+    ///
+    /// For callers that need to control speed, use `synthesize_with_speed`.
+    pub fn synthesize(&self, text: &str, voice: Option<&str>, speed: Option<f32>, lang: Option<&str>) -> Result<Vec<f32>, Error> {
+        // Forward to the speed-aware variant with the supplied or default user speed
+        self.synthesize_with_speed(text, voice, speed.unwrap_or(self.default_speed), lang)
+    }
+
+    /// `synthesize` with a voice from `voice`, at the default language of that voice
+    pub fn synthesize_with_voice(
+        &self,
+        text: &str,
+        voice: &Voice,
+        speed: Option<f32>,
+    ) -> Result<Vec<f32>, Error> {
+        self.synthesize(text, Some(voice.name()), speed, None)
+    }
+
+    /// `synthesize` with `style` for every chunk instead of a voice's style
+    ///
+    /// The text is read in the default voice's language, and no voice profile
+    /// applies. Chunks of different lengths all get the same vector, where a
+    /// voice would give each the row for its token count (see `style_for`).
+    pub fn synthesize_with_style(
+        &self,
+        text: &str,
+        style: &StyleVector,
+        speed: Option<f32>,
+    ) -> Result<Vec<f32>, Error> {
+        let speed = speed.unwrap_or(self.default_speed);
+        let mut result = self.synthesize_joined(text, StyleSource::Fixed(style), speed, 1.0, None)?;
+        result.post_process(self.post_process());
+        Ok(result.samples)
+    }
+
+    /// The style vector `voice` (or a mix like "af_sky.6+af_bella.4") gives a
+    /// chunk of `token_count` phonemes, as `chunk_text` counts them
+    ///
+    /// In fallback mode every style is zeros.
+    pub fn style_for(&self, voice: &str, token_count: usize) -> Result<StyleVector, Error> {
+        self.parse_voice_style(voice, token_count).map(StyleVector)
+    }
+
+    /// Backwards-compatible synthesize API which accepted an optional `speed`.
+    ///
+    /// This method preserves the original three-argument shape for compatibility
+    /// with older code that passed `Option<f32>` for speed.
+    #[deprecated(note = "use synthesize(text, voice) or synthesize_with_speed for custom speed")]
+    pub fn synthesize_with_optional_speed(
+        &self,
+        text: &str,
+        voice: Option<&str>,
+        speed: Option<f32>,
+        lang: Option<&str>
+    ) -> Result<Vec<f32>, Error> {
+        self.synthesize_with_speed(text, voice, speed.unwrap_or(self.default_speed), lang)
+    }
+
+    /// Synthesize text to speech with custom speed
+    /// Speed: 0.5 = half speed (slower), 1.0 = normal, 2.0 = double speed (faster)
+    ///
+    /// Speed goes into the model, which lengthens or shortens each phoneme, so
+    /// it's the same voice talking slower or faster rather than the audio being
+    /// stretched afterwards. Speeds are clamped to 0.35-2.2, where the model
+    /// still sounds natural; NaN, zero and negative speeds are an error.
+    pub fn synthesize_with_speed(
+        &self,
+        text: &str,
+        voice: Option<&str>,
+        speed: f32,
+        lang: Option<&str>,
+    ) -> Result<Vec<f32>, Error> {
+        self.synthesize_with_options(text, voice, speed, 1.0, lang)
+    }
+
+    /// Synthesize using a builder-style options struct for better ergonomics.
+    ///
+    /// Example:
+    /// `tts.synthesize_with("Hello", SynthesizeOptions::default().voice("af_sky").speed(1.1).pitch(3.0))`
+    pub fn synthesize_with(
+        &self,
+        text: &str,
+        opts: SynthesizeOptions,
+    ) -> Result<Vec<f32>, Error> {
+        // Validate pitch up front so a bad value doesn't cost a full inference
+        audio::pitch_shift(&[], opts.pitch).map_err(Error::InvalidInput)?;
+
+        let emotion = opts.emotion.as_ref().map(|e| self.emotion_style(e)).unwrap_or_default();
+        let (speed, gain) = (opts.speed * emotion.speed, opts.gain * emotion.energy);
+        // The emotion can push an in-range pitch just past the limit
+        let limit = audio::MAX_PITCH_SEMITONES;
+        let pitch = (opts.pitch + emotion.pitch).clamp(-limit, limit);
+
+        let voice_opt = opts.voice.as_deref();
+        let audio = self.synthesize_with_options(text, voice_opt, speed, gain, opts.lang.as_deref())?;
+        audio::pitch_shift(&audio, pitch).map_err(Error::InvalidInput)
+    }
+
+    /// Process long text by splitting into chunks (alias for backwards compatibility)
+    /// This method exists for API compatibility - synthesize() already handles long text automatically
+    pub fn process_long_text(
+        &self,
+        text: &str,
+        voice: Option<&str>,
+        speed: Option<f32>,
+    ) -> Result<Vec<f32>, Error> {
+        // Forward to speed-aware variant (use default if None)
+        self.synthesize_with_speed(text, voice, speed.unwrap_or(self.default_speed), None)
+    }
+
+    /// Synthesize speech from text, also returning warnings about the text:
+    /// unknown characters, removed emoji, how many chunks it took.
+    ///
+    /// Text with nothing left to speak after normalization gives empty audio
+    /// and `Warning::EmptyAfterNormalization` (instead of the pad-only near
+    /// silence `synthesize` returns).
+    pub fn synthesize_with_warnings(
+        &self,
+        text: &str,
+        voice: Option<&str>,
+        speed: Option<f32>,
+    ) -> Result<(Vec<f32>, Vec<Warning>), Error> {
+        let warnings = self.validate_in(text, lang_for_voice(voice.unwrap_or(&self.default_voice)));
+        if warnings.contains(&Warning::EmptyAfterNormalization) {
+            return Ok((Vec::new(), warnings));
+        }
+        let audio = self.synthesize_with_speed(text, voice, speed.unwrap_or(self.default_speed), None)?;
+        Ok((audio, warnings))
+    }
+
+    /// Check text without synthesizing it, e.g. when it is submitted: the same
+    /// warnings `synthesize_with_warnings` would give with the default voice.
+    ///
+    /// Text goes through the same normalization and chunking as synthesis, so
+    /// the two agree; no inference runs and no model is needed. Under
+    /// `UnknownCharPolicy::Error`, `Warning::UnknownCharacters` means synthesis
+    /// will fail.
+    pub fn validate(&self, text: &str) -> Vec<Warning> {
+        self.validate_in(text, lang_for_voice(&self.default_voice))
+    }
+
+    // `validate` for text read with `lang`
+    fn validate_in(&self, text: &str, lang: &str) -> Vec<Warning> {
+        let mut warnings = Vec::new();
+
+        let chars = text.chars().count();
+        if chars > LONG_TEXT_WARNING {
+            warnings.push(Warning::LongText { chars });
+        }
+
+        // Markup that is never read can't be unknown
+        let blocks = input::blocks(text, self.input_format);
+        let plain = blocks.into_iter().map(|(block, _)| block).collect::<Vec<_>>().join("\n");
+        let (segments, malformed) = self.pause_segments(&plain);
+        if !malformed.is_empty() {
+            warnings.push(Warning::MalformedPauseMarkers { markers: malformed });
+        }
+        let plain: String = segments.into_iter().map(|(segment, _)| segment).collect();
+        let plain = self.read_symbols(&plain, lang);
+        let unknown = self.unknown_chars(&plain);
+        if !unknown.is_empty() {
+            warnings.push(Warning::UnknownCharacters {
+                chars: unknown.iter().map(|u| u.ch).collect(),
+                positions: unknown.iter().map(|u| u.char_offset).collect(),
+            });
+        }
+
+        if self.emoji_policy == EmojiPolicy::Warn {
+            let (_, found) = emoji::replace_emoji(&plain, false);
+            if !found.is_empty() {
+                warnings.push(Warning::EmojiRemoved { emoji: found });
+            }
+        }
+
+        // Fails only on what is already reported above, or in espeak
+        match self.text_chunks(text, Some(lang)) {
+            Ok(chunks) if chunks.iter().all(|(chunk, _)| chunk.trim().is_empty()) => {
+                warnings.push(Warning::EmptyAfterNormalization)
+            }
+            Ok(chunks) => {
+                let count = chunks.iter().filter(|(chunk, ms)| !is_silence(chunk, *ms)).count();
+                if count > 1 {
+                    warnings.push(Warning::ChunkSplit { count })
+                }
+            }
+            _ => {}
+        }
+        warnings
+    }
+
+    /// Synthesize text to speech with full options
+    /// Speed: 0.5 = half speed (slower), 1.0 = normal, 2.0 = double speed (faster)
+    /// Gain: 0.5 = quieter, 1.0 = normal, 2.0 = twice as loud (with soft clipping)
+    pub fn synthesize_with_options(
+        &self,
+        text: &str,
+        voice: Option<&str>,
+        speed: f32,
+        gain: f32,
+        lang: Option<&str>
+    ) -> Result<Vec<f32>, Error> {
+        let mut result = self.synthesize_joined(text, StyleSource::Voice(voice), speed, gain, lang)?;
+        result.post_process(self.post_process());
+        Ok(result.samples)
+    }
+
+    /// Synthesize text like `synthesize`, also returning the duration and where
+    /// each chunk of the text landed in the audio: its text, its samples and
+    /// how many tokens it had. Useful for progress displays, for slicing the
+    /// audio per chunk, and for checking where chunk boundaries fell.
+    pub fn synthesize_detailed(
+        &self,
+        text: &str,
+        voice: Option<&str>,
+        speed: Option<f32>,
+        lang: Option<&str>,
+    ) -> Result<SynthesisResult, Error> {
+        let speed = speed.unwrap_or(self.default_speed);
+        let mut result = self.synthesize_joined(text, StyleSource::Voice(voice), speed, 1.0, lang)?;
+        result.post_process(self.post_process());
+        Ok(result)
+    }
+
+    // Synthesize all chunks of `text` into one crossfaded buffer, without the
+    // whole-buffer post-processing that public entry points apply at the end
+    fn synthesize_joined(
+        &self,
+        text: &str,
+        style: StyleSource<'_>,
+        speed: f32,
+        gain: f32,
+        lang: Option<&str>,
+    ) -> Result<SynthesisResult, Error> {
+        let mut join = ChunkJoin::new(self.crossfade_samples());
+        let mut result = SynthesisResult::new();
+
+        let chunk_count = self.synthesize_styled_chunks(text, style, speed, gain, lang, &mut |chunk| {
+            result.push_chunk(chunk, &mut join);
+            ControlFlow::Continue(())
+        })?;
+
+        if chunk_count > 1 && result.samples.is_empty() {
+            return Err(Error::Inference("Failed to synthesize combined audio".to_string()));
+        }
+
+        Ok(result)
+    }
+
+    /// Speak text that arrives in pieces, such as a reply streamed from a
+    /// language model, a sentence at a time as each one is complete
+    ///
+    /// Example:
+    /// ```no_run
+    /// # use kokoro_tiny::{Error, TtsEngine};
+    /// # fn demo(tts: &TtsEngine, tokens: &[&str], play: impl Fn(&[f32])) -> Result<(), Error> {
+    /// let mut speech = tts.incremental(Some("af_sky"), None);
+    /// for token in tokens {
+    ///     speech.push_str(token);
+    ///     while let Some(audio) = speech.next_audio() {
+    ///         play(&audio?);
+    ///     }
+    /// }
+    /// play(&speech.finish()?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn incremental(&self, voice: Option<&str>, speed: Option<f32>) -> IncrementalSpeech<'_> {
+        IncrementalSpeech::new(self, voice, speed)
+    }
+
+    /// Synthesize text chunk by chunk, handing each chunk to `on_chunk` as soon as
+    /// its inference finishes.
+    ///
+    /// Long text is split exactly like `synthesize` does, so every chunk gets the
+    /// same padding and punctuation handling and the audio sounds identical. The
+    /// only difference is that chunks are delivered separately instead of being
+    /// crossfaded into one buffer, so playback of the first sentence can start
+    /// while later sentences are still being generated.
+    ///
+    /// Example:
+    /// ```no_run
+    /// # async fn demo() -> Result<(), kokoro_tiny::Error> {
+    /// let mut tts = kokoro_tiny::TtsEngine::new().await?;
+    /// tts.synthesize_streaming("First sentence. Second one.", None, None, |chunk| {
+    ///     println!("chunk {}/{}: '{}' ({} samples)", chunk.index + 1, chunk.total, chunk.text, chunk.samples.len());
+    /// })?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn synthesize_streaming<F>(
+        &self,
+        text: &str,
+        voice: Option<&str>,
+        speed: Option<f32>,
+        mut on_chunk: F,
+    ) -> Result<(), Error>
+    where
+        F: FnMut(AudioChunk),
+    {
+        self.synthesize_chunks(
+            text,
+            voice,
+            speed.unwrap_or(self.default_speed),
+            1.0,
+            None,
+            &mut |mut chunk| {
+                self.processors.run(&mut chunk.samples);
+                on_chunk(chunk);
+                ControlFlow::Continue(())
+            },
+        )?;
+        Ok(())
+    }
+
+    /// Synthesize text straight to 16-bit PCM (24kHz mono).
+    ///
+    /// Same audio as `synthesize`, converted with `audio::to_i16`; use
+    /// `audio::to_i16_dithered` on the `synthesize` output if you want dither.
+    pub fn synthesize_i16(&self, text: &str, voice: Option<&str>, speed: Option<f32>) -> Result<Vec<i16>, Error> {
+        let audio = self.synthesize(text, voice, speed, None)?;
+        Ok(audio::to_i16(&audio))
+    }
+
+    /// Synthesize from a phoneme (IPA) string, skipping text normalization and G2P.
+    ///
+    /// Every character must be in the model vocabulary; otherwise an error lists
+    /// each offending character with its position instead of silently dropping
+    /// tokens. Pad tokens are added at both ends like for text input. The input
+    /// has to fit one model pass (510 phonemes), since there is no text to split on.
+    ///
+    /// Example: `tts.synthesize_phonemes("həlˈoʊ wˈɜːld", None, None)`
+    pub fn synthesize_phonemes(
+        &self,
+        phonemes: &str,
+        voice: Option<&str>,
+        speed: Option<f32>,
+    ) -> Result<Vec<f32>, Error> {
+        let tokens = self.phoneme_tokens(phonemes)?;
+
+        if self.fallback_mode {
+            return wav_to_f32(FALLBACK_MESSAGE);
+        }
+        let voice = voice.unwrap_or(&self.default_voice);
+        let style = self.parse_voice_style(voice, tokens.len())?;
+        let mut padded = vec![0i64; EDGE_PAD_COUNT];
+        padded.extend_from_slice(&tokens);
+        padded.extend(std::iter::repeat_n(0i64, EDGE_PAD_COUNT));
+
+        let profile = self.profile_for(voice);
+        let speed = model_speed(speed.unwrap_or(self.default_speed) * profile.speed)?;
+        let (audio, _) = infer(&mut *self.idle_session()?, padded, style, speed)?;
+        let mut audio = voice_pitch(audio, profile.pitch)?;
+        self.post_process().apply(&mut audio);
+        Ok(audio)
+    }
+
+    /// Synthesize text and report when each word starts and ends in the audio.
+    ///
+    /// Uses the model's per-token durations when the ONNX export provides them;
+    /// the bundled model only outputs audio, in which case token times are spread
+    /// evenly over each chunk, which is close enough for caption highlighting.
+    /// Words are mapped to the exact phoneme tokens used for synthesis, and chunk
+    /// offsets account for the crossfade, so the audio is identical to `synthesize`.
+    pub fn synthesize_with_timing(
+        &self,
+        text: &str,
+        voice: Option<&str>,
+        speed: Option<f32>,
+    ) -> Result<(Vec<f32>, Vec<WordTiming>), Error> {
+        if self.fallback_mode {
+            return Ok((wav_to_f32(FALLBACK_MESSAGE)?, Vec::new()));
+        }
+
+        let plan = self.plan_synthesis(text, voice, speed.unwrap_or(self.default_speed), None)?;
+        let space_id = self.vocab.get(&' ').copied().unwrap_or(0);
+        let mut join = ChunkJoin::new(self.crossfade_samples());
+        let mut combined_audio = Vec::new();
+        let mut timings = Vec::new();
+
+        for chunk in plan.chunks {
+            let parts = chunk
+                .text
+                .split_whitespace()
+                .map(|word| phoneme_word_count(word, &plan.lang))
+                .collect::<Result<Vec<_>, Error>>()?;
+            let display = text::strip_inline_phonemes(&chunk.text);
+            let words: Vec<&str> = display.split_whitespace().collect();
+            let spans = phoneme_word_spans(&chunk.tokens, space_id);
+            let token_count = chunk.tokens.len();
+
+            let (samples, durations) =
+                infer(&mut *self.idle_session()?, chunk.tokens, chunk.style, plan.speed)?;
+            let mut samples = voice_pitch(samples, plan.pitch)?;
+            let bounds = token_boundaries(token_count, samples.len(), durations.as_deref());
+            append_pause(&mut samples, chunk.pause_ms);
+
+            join.append(&mut combined_audio, &samples, chunk.pause_ms);
+            let offset = combined_audio.len() - samples.len();
+            timings.extend(word_timings(&words, &parts, &spans, &bounds, offset));
+        }
+
+        // Keep timings pointing at the same audio after leading silence is trimmed or padded
+        let removed = self.post_process().apply(&mut combined_audio);
+        let shift = |at: usize| at.saturating_add_signed(-removed).min(combined_audio.len());
+        for timing in &mut timings {
+            timing.start_sample = shift(timing.start_sample);
+            timing.end_sample = shift(timing.end_sample);
+        }
+        Ok((combined_audio, timings))
+    }
+
+    /// Synthesize text along with subtitles for it, one cue per sentence
+    ///
+    /// Each cue runs from the start of its sentence's first word to the end of
+    /// its last word in the returned audio, using the timings of
+    /// `synthesize_with_timing`, so they don't drift over long texts. A
+    /// sentence spoken in several chunks still gets one cue. Cue text is the
+    /// sentence as written ("42" stays "42"), not the normalized form that was
+    /// spoken; see `subtitles::render` for the formats. In fallback mode there
+    /// are no cues.
+    ///
+    /// Example:
+    /// `let (audio, srt) = tts.synthesize_with_subtitles(script, None, None, SubtitleFormat::Srt)?;`
+    pub fn synthesize_with_subtitles(
+        &self,
+        text: &str,
+        voice: Option<&str>,
+        speed: Option<f32>,
+        format: SubtitleFormat,
+    ) -> Result<(Vec<f32>, String), Error> {
+        let (audio, timings) = self.synthesize_with_timing(text, voice, speed)?;
+        let lang = lang_for_voice(voice.unwrap_or(&self.default_voice));
+        let sentences = input::blocks(text, self.input_format)
+            .iter()
+            .flat_map(|(block, _)| split_sentences(&self.strip_pause_markers(block)))
+            .map(|sentence| Ok((self.spoken_word_count(&sentence, lang)?, sentence)))
+            .collect::<Result<Vec<_>, Error>>()?;
+        Ok((audio, subtitles::render(&sentence_cues(&sentences, &timings), format)))
+    }
+
+    // How many words `synthesize_with_timing` reports for `text` said on its own
+    fn spoken_word_count(&self, text: &str, lang: &str) -> Result<usize, Error> {
+        let chunks = self.plain_text_chunks(text, lang)?;
+        Ok(chunks
+            .iter()
+            .map(|(chunk, _)| text::strip_inline_phonemes(chunk).split_whitespace().count())
+            .sum())
+    }
+
+    /// Synthesize several text segments with their own options into one buffer.
+    ///
+    /// Each segment is chunked and synthesized like `synthesize`, and all chunks
+    /// are joined with the same crossfade used inside a single call, so there are
+    /// no seams at segment boundaries. Options left as `None` fall back to the
+    /// engine defaults (see `set_default_speed`).
+    ///
+    /// Example:
+    /// ```no_run
+    /// # async fn demo() -> Result<(), kokoro_tiny::Error> {
+    /// use kokoro_tiny::{SegmentOptions, TtsEngine};
+    /// let mut tts = TtsEngine::new().await?;
+    /// let audio = tts.synthesize_segments(
+    ///     &[
+    ///         ("And he shoots... he scores!", SegmentOptions::new().speed(1.4)),
+    ///         ("What a moment for the club.", SegmentOptions::new().speed(0.85)),
+    ///         ("Back to midfield.", SegmentOptions::default()),
+    ///     ],
+    ///     Some("am_adam"),
+    /// )?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn synthesize_segments(
+        &self,
+        segments: &[(&str, SegmentOptions)],
+        voice: Option<&str>,
+    ) -> Result<Vec<f32>, Error> {
+        // Play the fallback message once rather than once per segment
+        if self.fallback_mode {
+            return wav_to_f32(FALLBACK_MESSAGE);
+        }
+
+        let mut join = ChunkJoin::new(self.crossfade_samples());
+        let mut combined_audio = Vec::new();
+
+        for (text, opts) in segments {
+            if text.trim().is_empty() {
+                continue;
+            }
+            let speed = opts.speed.unwrap_or(self.default_speed);
+            self.synthesize_chunks(text, voice, speed, 1.0, None, &mut |chunk| {
+                join.append(&mut combined_audio, &chunk.samples, chunk.pause_ms);
+                ControlFlow::Continue(())
+            })?;
+        }
+
+        self.post_process().apply(&mut combined_audio);
+        Ok(combined_audio)
+    }
+
+    /// Synthesize a conversation: each `(voice, text)` line in its own voice,
+    /// with the same gap between every two lines (see `set_dialogue_gap`).
+    ///
+    /// Lines are synthesized separately and never crossfaded into each other,
+    /// so no voice's style bleeds into the next line; the model's own edge
+    /// silence is trimmed from each line (keeping 50ms) before the gap goes in.
+    /// Every voice is checked before anything is synthesized, so a typo in the
+    /// last line fails straight away. Post-processing applies to the whole
+    /// conversation, with `lines` kept pointing at the same audio.
+    ///
+    /// Example:
+    /// `tts.synthesize_dialogue(&[("af_sky", "Hello."), ("am_adam", "Hi there.")])`
+    pub fn synthesize_dialogue(&self, lines: &[(&str, &str)]) -> Result<DialogueResult, Error> {
+        for (voice, _) in lines {
+            self.check_voice(voice)?;
+        }
+        let mut result = DialogueResult {
+            samples: Vec::new(),
+            sample_rate: SAMPLE_RATE,
+            duration_secs: 0.0,
+            lines: Vec::new(),
+        };
+        let overlap = self.crossfade_samples();
+        for (voice, text) in lines {
+            if text.trim().is_empty() {
+                continue;
+            }
+            // Play the fallback message once rather than once per line
+            if self.fallback_mode {
+                result.samples = wav_to_f32(FALLBACK_MESSAGE)?;
+                break;
+            }
+            let mut line = Vec::new();
+            let mut join = ChunkJoin::new(overlap);
+            self.synthesize_chunks(text, Some(voice), self.default_speed, 1.0, None, &mut |chunk| {
+                join.append(&mut line, &chunk.samples, chunk.pause_ms);
+                ControlFlow::Continue(())
+            })?;
+            let speech = audio::trim_silence(&line, EDGE_SILENCE_DB, SILENCE_KEEP_MS);
+
+            if !result.lines.is_empty() {
+                result.samples.extend(silence(self.dialogue_gap_ms));
+            }
+            let start = result.samples.len();
+            result.samples.extend_from_slice(&line[speech]);
+            result.lines.push(DialogueLine {
+                voice: voice.to_string(),
+                text: text.to_string(),
+                sample_range: start..result.samples.len(),
+            });
+        }
+
+        let removed = self.post_process().apply(&mut result.samples);
+        let len = result.samples.len();
+        let shift = |at: usize| at.saturating_add_signed(-removed).min(len);
+        for line in &mut result.lines {
+            line.sample_range = shift(line.sample_range.start)..shift(line.sample_range.end);
+        }
+        result.duration_secs = len as f32 / SAMPLE_RATE as f32;
+        Ok(result)
+    }
+
+    /// `synthesize_dialogue` for tagged text like `[af_sky] Hello. [am_adam] Hi there.`
+    /// (see the `dialogue` module for the format)
+    pub fn synthesize_dialogue_markup(&self, markup: &str) -> Result<DialogueResult, Error> {
+        let lines = dialogue::parse_dialogue(markup).map_err(Error::InvalidInput)?;
+        let lines: Vec<(&str, &str)> =
+            lines.iter().map(|(voice, text)| (voice.as_str(), text.as_str())).collect();
+        self.synthesize_dialogue(&lines)
+    }
+
+    /// Synthesize a minimal SSML document.
+    ///
+    /// Supports `<speak>`, `<break>`, `<prosody rate>`, `<say-as interpret-as>`
+    /// and `<sub alias>` (see the `ssml` module); any other tag is an error.
+    /// A document that is just `<speak>text</speak>` produces the same audio as
+    /// `synthesize(text, voice, None, None)`.
+    ///
+    /// Example:
+    /// `tts.synthesize_ssml(r#"<speak>Hi.<break time="500ms"/><prosody rate="slow">Slowly.</prosody></speak>"#, None)`
+    pub fn synthesize_ssml(&self, ssml: &str, voice: Option<&str>) -> Result<Vec<f32>, Error> {
+        let segments = ssml::parse_ssml(ssml).map_err(Error::InvalidInput)?;
+        let overlap = self.crossfade_samples();
+        let mut combined_audio = Vec::new();
+        let mut after_break = true;
+
+        for segment in segments {
+            match segment {
+                ssml::SsmlSegment::Text { text, rate } => {
+                    let style = StyleSource::Voice(voice);
+                    let audio = self
+                        .synthesize_joined(&text, style, self.default_speed * rate, 1.0, None)?
+                        .samples;
+                    // Don't crossfade speech into a break; it would eat into the silence
+                    if after_break {
+                        combined_audio.extend_from_slice(&audio);
+                    } else {
+                        append_with_crossfade(&mut combined_audio, &audio, overlap);
+                    }
+                    after_break = false;
+                }
+                ssml::SsmlSegment::Break { ms } => {
+                    combined_audio.extend(silence(ms));
+                    after_break = true;
+                }
+            }
+        }
+
+        self.post_process().apply(&mut combined_audio);
+        Ok(combined_audio)
+    }
+
+    /// Synthesize text without blocking the async runtime.
+    ///
+    /// Text preparation happens on the calling task (it is cheap), while ONNX
+    /// inference runs on tokio's blocking thread pool. Takes `&self`, so several
+    /// requests can be in flight at once; they queue internally on the model
+    /// session instead of requiring the caller to wrap the engine in a `Mutex`.
+    /// The resulting audio is identical to `synthesize`.
+    pub async fn synthesize_async(
+        &self,
+        text: &str,
+        voice: Option<&str>,
+        speed: Option<f32>,
+    ) -> Result<Vec<f32>, Error> {
+        if self.fallback_mode {
+            return wav_to_f32(FALLBACK_MESSAGE);
+        }
+
+        let speed = speed.unwrap_or(self.default_speed);
+        let render = self.deferred_synthesis(text, voice, speed, 1.0, None)?;
+        tokio::task::spawn_blocking(render)
+            .await
+            .map_err(|e| Error::Inference(format!("Synthesis task failed: {}", e)))?
+    }
+
+    /// Synthesize many short texts with one voice and speed, e.g. to
+    /// pre-render phone menu prompts. Every text gets exactly the audio
+    /// `synthesize` would give it, with its own result, so one bad input
+    /// doesn't fail the rest.
+    ///
+    /// Cheaper than calling `synthesize` in a loop: the voice is checked
+    /// once, the model session is locked once for the whole batch, and a
+    /// chunk that appears in several texts (a shared "Press one for sales.")
+    /// runs through the model once. Kokoro infers one sequence per run, so
+    /// the texts themselves are not stacked into one model call.
+    pub fn synthesize_batch(
+        &self,
+        texts: &[&str],
+        voice: Option<&str>,
+        speed: Option<f32>,
+    ) -> Vec<Result<Vec<f32>, Error>> {
+        let speed = speed.unwrap_or(self.default_speed);
+        if self.fallback_mode {
+            return texts
+                .iter()
+                .map(|text| self.synthesize_with_speed(text, voice, speed, None))
+                .collect();
+        }
+        let shared_error = |error: Error| texts.iter().map(|_| Err(error.clone())).collect();
+        if let Err(e) = self.parse_voice_style(voice.unwrap_or(&self.default_voice), 0) {
+            return shared_error(e);
+        }
+
+        let plans: Vec<Result<SynthesisPlan, Error>> = texts
+            .iter()
+            .map(|text| self.plan_synthesis(text, voice, speed, None))
+            .collect();
+        // Chunks used more than once, with how many uses are still to come;
+        // only their audio is kept, and only until the last use
+        let mut uses: HashMap<&[i64], usize> = HashMap::new();
+        for plan in plans.iter().flatten() {
+            for chunk in &plan.chunks {
+                *uses.entry(&chunk.tokens).or_default() += 1;
+            }
+        }
+        uses.retain(|_, count| *count > 1);
+        let mut rendered: HashMap<Vec<i64>, Vec<f32>> = HashMap::new();
+
+        let mut session = match self.idle_session() {
+            Ok(session) => session,
+            Err(e) => return shared_error(e),
+        };
+        let post = self.post_process();
+        let overlap = self.crossfade_samples();
+        let mut render = |plan: &SynthesisPlan| -> Result<Vec<f32>, Error> {
+            let mut join = ChunkJoin::new(overlap);
+            let mut combined_audio = Vec::new();
+            for chunk in &plan.chunks {
+                let remaining = uses.get_mut(chunk.tokens.as_slice());
+                let mut samples = match rendered.get(&chunk.tokens) {
+                    Some(samples) => samples.clone(),
+                    None => {
+                        let tokens = chunk.tokens.clone();
+                        let samples = infer(&mut session, tokens, chunk.style.clone(), plan.speed)?.0;
+                        voice_pitch(samples, plan.pitch)?
+                    }
+                };
+                if let Some(remaining) = remaining {
+                    *remaining -= 1;
+                    if *remaining == 0 {
+                        rendered.remove(&chunk.tokens);
+                    } else {
+                        rendered.entry(chunk.tokens.clone()).or_insert_with(|| samples.clone());
+                    }
+                }
+                append_pause(&mut samples, chunk.pause_ms);
+                join.append(&mut combined_audio, &samples, chunk.pause_ms);
+            }
+            post.apply(&mut combined_audio);
+            Ok(combined_audio)
+        };
+        plans
+            .iter()
+            .map(|plan| plan.as_ref().map_err(Clone::clone).and_then(&mut render))
+            .collect()
+    }
+
+    // Do the cheap part of synthesis (text, voice, plan) now and return the
+    // inference part, which owns everything it needs and can run on any thread
+    fn deferred_synthesis(
+        &self,
+        text: &str,
+        voice: Option<&str>,
+        speed: f32,
+        gain: f32,
+        lang: Option<&str>,
+    ) -> Result<DeferredSynthesis, Error> {
+        if self.fallback_mode {
+            self.text_chunks(text, lang)?;
+            return Ok(Box::new(|| wav_to_f32(FALLBACK_MESSAGE)));
+        }
+
+        let sessions = self.loaded_model()?.sessions.clone();
+        let turn = self.next_session.fetch_add(1, Ordering::Relaxed);
+
+        let plan = self.plan_synthesis(text, voice, speed, lang)?;
+
+        let post = self.post_process();
+        let overlap = self.crossfade_samples();
+        Ok(Box::new(move || {
+            let mut join = ChunkJoin::new(overlap);
+            let mut combined_audio = Vec::new();
+            for chunk in plan.chunks {
+                let mut session = lock_idle_session(&sessions, turn)?;
+                let (samples, _) = infer(&mut session, chunk.tokens, chunk.style, plan.speed)?;
+                let mut samples = voice_pitch(samples, plan.pitch)?;
+                if gain != 1.0 {
+                    samples = amplify_audio(&samples, gain);
+                }
+                append_pause(&mut samples, chunk.pause_ms);
+                join.append(&mut combined_audio, &samples, chunk.pause_ms);
+            }
+            post.apply(&mut combined_audio);
+            Ok(combined_audio)
+        }))
+    }
+
+    // Audio as `synthesize` joins it (post-processing limited to the DC cleanup),
+    // handed on piece by piece as soon as each is final, for `synthesize_to_wav`
+    // and the server
+    fn synthesize_joined_stream(
+        &self,
+        text: &str,
+        voice: Option<&str>,
+        speed: Option<f32>,
+        on_audio: &mut dyn FnMut(&[f32]) -> ControlFlow<()>,
+    ) -> Result<(), Error> {
+        let edge_ramp = self.dc_cleanup.then(|| silence(EDGE_RAMP_MS).len());
+        let mut stream = CrossfadeStream::new(self.crossfade_samples(), edge_ramp);
+        let mut stopped = false;
+        let speed = speed.unwrap_or(self.default_speed);
+        self.synthesize_chunks(text, voice, speed, 1.0, None, &mut |mut chunk| {
+            self.processors.run(&mut chunk.samples);
+            let flow = on_audio(&stream.push(&chunk.samples, chunk.pause_ms));
+            stopped = flow.is_break();
+            flow
+        })?;
+        if !stopped {
+            let _ = on_audio(&stream.finish());
+        }
+        Ok(())
+    }
+
+    /// Shared chunk loop behind `synthesize_with_options` and `synthesize_streaming`.
+    /// Returns the number of chunks that were emitted.
+    fn synthesize_chunks(
+        &self,
+        text: &str,
+        voice: Option<&str>,
+        speed: f32,
+        gain: f32,
+        lang: Option<&str>,
+        on_chunk: &mut dyn FnMut(AudioChunk) -> ControlFlow<()>,
+    ) -> Result<usize, Error> {
+        self.synthesize_styled_chunks(text, StyleSource::Voice(voice), speed, gain, lang, on_chunk)
+    }
+
+    // `synthesize_chunks` with the styles from `style`
+    fn synthesize_styled_chunks(
+        &self,
+        text: &str,
+        style: StyleSource<'_>,
+        speed: f32,
+        gain: f32,
+        lang: Option<&str>,
+        on_chunk: &mut dyn FnMut(AudioChunk) -> ControlFlow<()>,
+    ) -> Result<usize, Error> {
+        // If in fallback mode, return the excuse message audio
+        if self.fallback_mode {
+            // Still reject text and speeds real synthesis would reject
+            model_speed(speed)?;
+            self.text_chunks(text, lang)?;
+            let _ = on_chunk(AudioChunk {
+                index: 0,
+                total: 1,
+                text: text.to_string(),
+                tokens: 0,
+                samples: wav_to_f32(FALLBACK_MESSAGE)?,
+                pause_ms: 0,
+            });
+            return Ok(1);
+        }
+
+        let sessions = self.chunk_sessions()?;
+        if sessions.is_empty() {
+            return Err(Error::ModelLoad("TTS engine not initialized".to_string()));
+        }
+
+        let plan = self.plan_styled_synthesis(text, style, speed, lang)?;
+        let chunk_count = plan.chunks.len();
+        if needs_chunking(text) {
+            debug!(
+                chars = text.chars().count(),
+                chunks = chunk_count,
+                max_chars = MAX_CHARS_PER_CHUNK,
+                "Long-form synthesis"
+            );
+        }
+
+        let mut emit = |idx: usize, mut samples: Vec<f32>| {
+            let chunk = &plan.chunks[idx];
+            debug!(
+                chunk = idx + 1,
+                of = chunk_count,
+                chars = chunk.text.chars().count(),
+                tokens = chunk.tokens.len(),
+                "Synthesized chunk"
+            );
+
+            if gain != 1.0 {
+                samples = amplify_audio(&samples, gain);
+            }
+            append_pause(&mut samples, chunk.pause_ms);
+
+            // Break: the caller wants no more chunks (playback was stopped)
+            on_chunk(AudioChunk {
+                index: idx,
+                total: chunk_count,
+                text: text::strip_inline_phonemes(&chunk.text),
+                tokens: chunk.tokens.len().saturating_sub(2 * EDGE_PAD_COUNT),
+                samples,
+                pause_ms: chunk.pause_ms,
+            })
+        };
+
+        if sessions.len() > 1 && chunk_count > 1 {
+            infer_in_parallel(sessions, &plan, &mut emit)?;
+        } else {
+            for (idx, chunk) in plan.chunks.iter().enumerate() {
+                let tokens = chunk.tokens.clone();
+                let style = chunk.style.clone();
+                let (samples, _) = infer(&mut *self.idle_session()?, tokens, style, plan.speed)?;
+                if emit(idx, voice_pitch(samples, plan.pitch)?).is_break() {
+                    break;
+                }
+            }
+        }
+
+        Ok(chunk_count)
+    }
+
+    // Resolve voice, speed and chunk tokens for one call up front. Everything the
+    // model needs ends up owned by the plan, so inference can run off this thread.
+    fn plan_synthesis(
+        &self,
+        text: &str,
+        voice: Option<&str>,
+        speed: f32,
+        lang: Option<&str>,
+    ) -> Result<SynthesisPlan, Error> {
+        self.plan_styled_synthesis(text, StyleSource::Voice(voice), speed, lang)
+    }
+
+    fn plan_styled_synthesis(
+        &self,
+        text: &str,
+        style: StyleSource<'_>,
+        speed: f32,
+        lang: Option<&str>,
+    ) -> Result<SynthesisPlan, Error> {
+        let (voice, profile) = match style {
+            StyleSource::Voice(voice) => {
+                let voice = voice.unwrap_or(&self.default_voice);
+                (voice, self.profile_for(voice))
+            }
+            StyleSource::Fixed(_) => (self.default_voice.as_str(), VoiceProfile::default()),
+        };
+        let clamped_speed = model_speed(speed * profile.speed)?;
+        let lang = lang.unwrap_or_else(|| lang_for_voice(voice));
+
+        let chunks = self
+            .text_chunks(text, Some(lang))?
+            .into_iter()
+            .map(|(chunk, pause_ms)| {
+                let tokens = if is_silence(&chunk, pause_ms) {
+                    Vec::new()
+                } else {
+                    self.chunk_tokens(&chunk, Some(lang))?
+                };
+                let style = match style {
+                    // Parse voice style (e.g., "af_sky.8+af_bella.2" for mixing)
+                    StyleSource::Voice(_) => {
+                        let token_count = tokens.len().saturating_sub(2 * EDGE_PAD_COUNT);
+                        self.parse_voice_style(voice, token_count)?
+                    }
+                    StyleSource::Fixed(style) => style.0.clone(),
+                };
+                Ok(PlannedChunk { text: chunk, tokens, style, pause_ms })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        if chunks.is_empty() {
+            return Err(Error::InvalidInput("No text provided for synthesis".to_string()));
+        }
+
+        Ok(SynthesisPlan {
+            speed: clamped_speed,
+            pitch: profile.pitch,
+            lang: lang.to_string(),
+            chunks,
+        })
+    }
+
+    /// Show the phoneme strings the model would be fed for `text`, one per chunk.
+    ///
+    /// Text is split exactly like `synthesize` splits it and each string includes
+    /// the `$` pad tokens added at both ends, so `tokens_for` on a returned string
+    /// gives the exact token ids used for that chunk. Handy for pronunciation bug
+    /// reports ("the word X becomes phonemes Y").
+    ///
+    /// Without `lang` the default voice's dialect is used; pass e.g. `Some("en-gb")`
+    /// to see what a British voice would be fed.
+    pub fn phonemize(&self, text: &str, lang: Option<&str>) -> Result<Vec<String>, Error> {
+        let lang = Some(lang.unwrap_or_else(|| lang_for_voice(&self.default_voice)));
+        self.text_chunks(text, lang)?
+            .iter()
+            .filter(|(chunk, pause_ms)| !is_silence(chunk, *pause_ms))
+            .map(|(chunk, _)| self.chunk_phonemes(chunk, lang))
+            .collect()
+    }
+
+    /// Show how `synthesize` would split `text` into chunks, after normalization,
+    /// with each chunk's token count. Useful when tuning `set_max_chunk_tokens`.
+    pub fn chunk_text(&self, text: &str, lang: Option<&str>) -> Result<Vec<TextChunk>, Error> {
+        let lang = lang.unwrap_or_else(|| lang_for_voice(&self.default_voice));
+        self.text_chunks(text, Some(lang))?
+            .into_iter()
+            .map(|(chunk, pause_ms)| {
+                Ok(TextChunk {
+                    tokens: phoneme_token_count(&chunk, lang)?,
+                    text: text::strip_inline_phonemes(&chunk),
+                    pause_ms,
+                })
+            })
+            .collect()
+    }
+
+    /// Estimate how long `text` will take to speak without synthesizing it.
+    ///
+    /// Normalization, phonemization and chunking run exactly as in `synthesize`,
+    /// so the token and chunk counts are exact; the duration assumes a fixed
+    /// speaking rate per token. For English prose that is usually within 15% of
+    /// the real length. Lists, numbers read digit by digit and very short texts
+    /// can be further off. Runs no inference and needs no model, only espeak.
+    pub fn estimate(&self, text: &str, speed: Option<f32>) -> Result<Estimate, Error> {
+        let chunks = self.chunk_text(text, None)?;
+        let token_count: usize = chunks.iter().map(|chunk| chunk.tokens).sum();
+        let pause_ms: u32 = chunks.iter().map(|chunk| chunk.pause_ms).sum();
+        let spoken_tokens = token_count + 2 * EDGE_PAD_COUNT * chunks.len();
+        let speed = speed.unwrap_or(self.default_speed) * self.profile_for(&self.default_voice).speed;
+        let speed = model_speed(speed)? / SPEED_SCALE;
+        let speech = Duration::from_secs_f32(spoken_tokens as f32 / (SPOKEN_TOKENS_PER_SEC * speed));
+        Ok(Estimate {
+            token_count,
+            chunk_count: chunks.len(),
+            approx_duration: speech + Duration::from_millis(pause_ms.into()),
+        })
+    }
+
+    // Normalized, model-sized pieces of `text` with the extra pause that follows
+    // each one. Without configured pauses or a lowered token limit the split is
+    // exactly `prepare_chunks`. A pause marker before any text becomes an empty
+    // piece, synthesized as silence only (see `is_silence`).
+    fn text_chunks(&self, text: &str, lang: Option<&str>) -> Result<Vec<(String, u32)>, Error> {
+        let lang = lang.unwrap_or(DEFAULT_LANG);
+        // Each piece with its pause, and the pause markers right after it
+        let mut pieces: Vec<(String, u32, u32)> = Vec::new();
+        let mut leading_ms = 0;
+        for (block, pause_ms) in input::blocks(text, self.input_format) {
+            let block_start = pieces.len();
+            let (segments, malformed) = self.pause_segments(&block);
+            for marker in &malformed {
+                warn!(marker = marker.as_str(), "Removed malformed pause marker");
+            }
+            for (segment, marked_ms) in segments {
+                if !segment.trim().is_empty() {
+                    let segment_pieces = self.plain_text_chunks(segment.trim(), lang)?;
+                    pieces.extend(segment_pieces.into_iter().map(|(piece, ms)| (piece, ms, 0)));
+                }
+                match pieces.last_mut() {
+                    Some(last) => last.2 = (last.2 + marked_ms).min(ssml::MAX_BREAK_MS),
+                    None => leading_ms = (leading_ms + marked_ms).min(ssml::MAX_BREAK_MS),
+                }
+            }
+            if let Some(last) = pieces[block_start..].last_mut() {
+                last.1 = last.1.max(pause_ms);
+            }
+        }
+        // No pause after the very end of the text, unless one is marked there
+        if let Some(last) = pieces.last_mut() {
+            last.1 = 0;
+        }
+        let mut chunks: Vec<(String, u32)> = pieces
+            .into_iter()
+            .map(|(piece, pause_ms, marked_ms)| {
+                (piece, if marked_ms > 0 { marked_ms } else { pause_ms })
+            })
+            .collect();
+        if leading_ms > 0 {
+            chunks.insert(0, (String::new(), leading_ms));
+        }
+        Ok(chunks)
+    }
+
+    // `text` split at its pause markers (see `pause_markup::split`), or whole
+    // if markers are off
+    fn pause_segments(&self, text: &str) -> (Vec<(String, u32)>, Vec<String>) {
+        match &self.pause_markup {
+            Some(markup) => pause_markup::split(text, markup),
+            None => (vec![(text.to_string(), 0)], Vec::new()),
+        }
+    }
+
+    // `text` with its pause markers, well-formed or not, taken out
+    fn strip_pause_markers(&self, text: &str) -> String {
+        self.pause_segments(text).0.into_iter().map(|(segment, _)| segment).collect()
+    }
+
+    // `text_chunks` for text already stripped of markup, pauses included
+    fn plain_text_chunks(&self, text: &str, lang: &str) -> Result<Vec<(String, u32)>, Error> {
+        let text = self.read_symbols(text, lang);
+        let text = self.replace_unknown_chars(&text)?;
+        let (text, _) = emoji::replace_emoji(&text, self.emoji_policy == EmojiPolicy::Speak);
+        let text = normalize_for_lang(&self.normalizer, &text, lang);
+        let text = text::mark_pronunciations(&text, &self.pronunciations);
+        let mut count = |piece: &str| phoneme_token_count(piece, lang);
+        let mut pieces = Vec::new();
+        for chunk in prepare_chunks(&text) {
+            for (piece, pause_ms) in split_at_pauses(&chunk, &self.pause_config) {
+                let mut parts = split_to_fit(&piece, self.max_chunk_tokens, &mut count)?;
+                let last = parts.pop();
+                pieces.extend(parts.into_iter().map(|part| (part, 0)));
+                pieces.extend(last.map(|part| (part, pause_ms)));
+            }
+        }
+        Ok(pieces)
+    }
+
+    // Addresses (per `url_style`), phone numbers and units read out ahead of
+    // the unknown character check, which would drop their '%' and '°', and
+    // before espeak would read their '@' and '+' one by one; only English has
+    // words for them
+    fn read_symbols(&self, text: &str, lang: &str) -> String {
+        if lang.starts_with("en") {
+            let text = text::read_addresses(text, self.url_style);
+            text::expand_units(&text::read_phone_numbers(&text))
+        } else {
+            text.to_string()
+        }
+    }
+
+    // Characters of the raw input the model has no way to pronounce
+    fn unknown_chars(&self, text: &str) -> Vec<UnknownChar> {
+        let chars: Vec<(usize, char)> = text.char_indices().collect();
+        let plain: Vec<char> = chars.iter().map(|&(_, c)| c).collect();
+        let mut unknown = Vec::new();
+        let mut i = 0;
+        while i < chars.len() {
+            let emoji_len = emoji::emoji_len(&plain[i..]);
+            if emoji_len > 0 {
+                i += emoji_len;
+                continue;
+            }
+            let (byte_offset, c) = chars[i];
+            let speakable = c.is_alphanumeric()
+                || c.is_whitespace()
+                || WORD_MARKS.contains(c)
+                || SPOKEN_SYMBOLS.contains(c)
+                || self.vocab.contains_key(&c);
+            if !speakable {
+                unknown.push(UnknownChar { ch: c, char_offset: i, byte_offset });
+            }
+            i += 1;
+        }
+        unknown
+    }
+
+    // Apply `unknown_char_policy` to the raw input
+    fn replace_unknown_chars(&self, text: &str) -> Result<String, Error> {
+        let unknown = self.unknown_chars(text);
+        if unknown.is_empty() {
+            return Ok(text.to_string());
+        }
+        if self.unknown_char_policy == UnknownCharPolicy::Error {
+            let list: Vec<String> = unknown.iter().map(UnknownChar::describe).collect();
+            return Err(Error::InvalidInput(list.join("; ")));
+        }
+
+        let mut out = String::with_capacity(text.len());
+        let mut last = 0;
+        for u in &unknown {
+            out.push_str(&text[last..u.byte_offset]);
+            last = u.byte_offset + u.ch.len_utf8();
+            match self.unknown_char_policy {
+                UnknownCharPolicy::Replace(c) => out.push(c),
+                UnknownCharPolicy::SpellOut => {
+                    if let Some(name) = text::symbol_name(u.ch) {
+                        let glued = |c: Option<char>| c.is_some_and(|c| !c.is_whitespace());
+                        if glued(out.chars().last()) {
+                            out.push(' ');
+                        }
+                        out.push_str(name);
+                        if glued(text[last..].chars().next()) {
+                            out.push(' ');
+                        }
+                    }
+                }
+                // "a © b" leaves one space, not two
+                UnknownCharPolicy::Skip if out.ends_with(' ') && text[last..].starts_with(' ') => {
+                    last += 1
+                }
+                _ => {}
+            }
+        }
+        out.push_str(&text[last..]);
+        Ok(out)
+    }
+
+    /// Convert a phoneme string (as returned by `phonemize`) to model token ids.
+    /// Characters outside the model vocabulary map to the pad token (0).
+    pub fn tokens_for(&self, phonemes: &str) -> Vec<i64> {
+        phonemes
+            .chars()
+            .map(|c| *self.vocab.get(&c).unwrap_or(&0))
+            .collect()
+    }
+
+    // Strictly convert user-supplied phonemes to tokens, rejecting anything the
+    // model doesn't know and anything too long for a single pass
+    fn phoneme_tokens(&self, phonemes: &str) -> Result<Vec<i64>, Error> {
+        let mut tokens = Vec::with_capacity(phonemes.len());
+        let mut unknown = Vec::new();
+        for (pos, c) in phonemes.chars().enumerate() {
+            match self.vocab.get(&c) {
+                Some(&id) => tokens.push(id),
+                None => unknown.push(format!("'{}' (U+{:04X}) at position {}", c, c as u32, pos)),
+            }
+        }
+
+        if !unknown.is_empty() {
+            let message = format!("Unsupported phoneme characters: {}", unknown.join(", "));
+            return Err(Error::InvalidInput(message));
+        }
+        if tokens.is_empty() {
+            return Err(Error::InvalidInput("No phonemes provided for synthesis".to_string()));
+        }
+        if tokens.len() + 2 * EDGE_PAD_COUNT > MAX_MODEL_TOKENS {
+            return Err(Error::InvalidInput(format!(
+                "Phoneme input too long: {} tokens (max {})",
+                tokens.len(),
+                MAX_CHUNK_TOKENS
+            )));
+        }
+        Ok(tokens)
+    }
+
+    // Phonemize one chunk of text and turn it into model tokens (with padding)
+    fn chunk_tokens(&self, text: &str, lang: Option<&str>) -> Result<Vec<i64>, Error> {
+        let phonemes = self.chunk_phonemes(text, lang)?;
+        Ok(self.tokens_for(&phonemes))
+    }
+
+    // Phoneme string for one chunk, padded the way the model expects
+    fn chunk_phonemes(&self, text: &str, lang: Option<&str>) -> Result<String, Error> {
+        // Convert text to phonemes
+        let phonemes = phonemize_normalized(text, lang.unwrap_or(DEFAULT_LANG))?;
+
+        // Join phonemes with spaces and add padding tokens at beginning and end
+        // Spaces between phonemes create natural pauses for commas and periods
+        // Padding tokens are crucial to prevent word dropping at beginning and end
+        let mut phonemes_text = phonemes.join(" ");
+        // Add multiple padding tokens for better buffering
+        let padding = PAD_TOKEN.to_string().repeat(EDGE_PAD_COUNT);
+        phonemes_text.insert_str(0, &padding);
+        phonemes_text.push_str(&padding);
+
+        trace!(
+            chars = text.len(),
+            words = phonemes.len(),
+            phoneme_chars = phonemes_text.len(),
+            "Phonemized chunk"
+        );
+
+        Ok(phonemes_text)
+    }
+
+    /// Save audio as WAV file
+    pub fn save_wav(&self, path: &str, audio: &[f32]) -> Result<(), Error> {
+        let file = std::fs::File::create(path)
+            .map_err(|e| Error::io("Failed to create WAV file", e))?;
+        write_wav_to(std::io::BufWriter::new(file), audio, SAMPLE_RATE)
+    }
+
+    /// Write audio as WAV to any seekable writer (an in-memory buffer, an upload
+    /// body, ...). Produces exactly the same bytes as `save_wav`.
+    pub fn write_wav<W: Write + std::io::Seek>(&self, writer: W, audio: &[f32]) -> Result<(), Error> {
+        write_wav_to(writer, audio, SAMPLE_RATE)
+    }
+
+    /// Save audio as a WAV file in a specific sample rate and bit depth.
+    ///
+    /// Resamples with a windowed-sinc filter (no aliasing when going down to
+    /// e.g. 16kHz) and applies TPDF dither when reducing to 8 or 16 bits.
+    /// `save_wav` keeps writing the model's native 24kHz 16-bit format.
+    pub fn save_wav_with_format(&self, path: &str, audio: &[f32], format: WavFormat) -> Result<(), Error> {
+        if !(8000..=192_000).contains(&format.sample_rate) {
+            return Err(Error::InvalidInput(format!(
+                "Unsupported WAV sample rate {} Hz (expected 8000-192000)",
+                format.sample_rate
+            )));
+        }
+        let sample_format = match format.bit_depth {
+            8 | 16 | 24 => hound::SampleFormat::Int,
+            32 => hound::SampleFormat::Float,
+            other => {
+                return Err(Error::InvalidInput(format!(
+                    "Unsupported WAV bit depth {} (expected 8, 16, 24 or 32)",
+                    other
+                )))
+            }
+        };
+
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: format.sample_rate,
+            bits_per_sample: format.bit_depth,
+            sample_format,
+        };
+        let samples = audio::resample(audio, SAMPLE_RATE, format.sample_rate);
+
+        let mut writer = hound::WavWriter::create(path, spec)
+            .map_err(|e| Error::io("Failed to create WAV file", e))?;
+
+        let written = match format.bit_depth {
+            32 => samples
+                .iter()
+                .try_for_each(|&s| writer.write_sample(s.clamp(-1.0, 1.0))),
+            24 => samples
+                .iter()
+                .try_for_each(|&s| writer.write_sample((s.clamp(-1.0, 1.0) * 8_388_607.0).round() as i32)),
+            8 => audio::quantize_dithered(&samples, 8)
+                .into_iter()
+                .try_for_each(|s| writer.write_sample(s as i8)),
+            _ => audio::quantize_dithered(&samples, 16)
+                .into_iter()
+                .try_for_each(|s| writer.write_sample(s as i16)),
+        };
+        written.map_err(|e| Error::io("Failed to write sample", e))?;
+
+        writer
+            .finalize()
+            .map_err(|e| Error::io("Failed to finalize WAV", e))?;
+        Ok(())
+    }
+
+    /// Save audio as WAV like `save_wav`, plus a LIST/INFO chunk with the
+    /// title, artist, software, comment and date from `metadata`.
+    ///
+    /// Example:
+    /// `tts.save_wav_with_metadata("hi.wav", &audio, &WavMetadata::new().comment("af_sky: Hello"))`
+    pub fn save_wav_with_metadata(
+        &self,
+        path: &str,
+        audio: &[f32],
+        metadata: &WavMetadata,
+    ) -> Result<(), Error> {
+        let mut wav = samples_to_wav_bytes(audio, SAMPLE_RATE)?;
+        append_info_chunk(&mut wav, metadata);
+        fs::write(path, wav).map_err(|e| Error::io("Failed to write WAV file", e))
+    }
+
+    /// Synthesize text straight into a WAV file, writing each chunk as soon as it
+    /// is ready, so memory use doesn't grow with the length of the text (an
+    /// hour-long narration holds one chunk at a time). Returns the length of the
+    /// audio written.
+    ///
+    /// The audio is the same as `save_wav` of `synthesize`, except that trimming
+    /// (`set_trim_silence`), loudness normalization (`set_target_loudness`),
+    /// fades (`set_fade`) and edge silence (`set_edge_silence`), which need the
+    /// whole buffer, aren't applied; the DC cleanup (`set_dc_cleanup`) is. The header is updated after
+    /// every chunk, so if the process dies partway the file is a valid WAV of
+    /// everything up to the last finished chunk.
+    pub fn synthesize_to_wav(
+        &self,
+        path: &str,
+        text: &str,
+        voice: Option<&str>,
+        speed: Option<f32>,
+    ) -> Result<Duration, Error> {
+        let written = self.stream_to_wav(path, text, voice, speed)?;
+        Ok(Duration::from_secs_f64(written as f64 / f64::from(SAMPLE_RATE)))
+    }
+
+    /// Synthesize text into an audio file, the format picked by the extension
+    /// like `save_audio` picks it, and report how long and how big it came out
+    ///
+    /// A `.wav` (or extensionless) path is written chunk by chunk as
+    /// `synthesize_to_wav` writes it, so long texts never sit in memory whole;
+    /// the whole-buffer post-processing listed there doesn't apply. Other
+    /// formats need the whole buffer for encoding and get exactly
+    /// `save_audio` of `synthesize`. An extension without a codec (or one
+    /// whose feature is off) fails before anything is synthesized.
+    ///
+    /// Example: `let summary = tts.synthesize_to_file("hello.wav", "Hello!", None, None)?;`
+    pub fn synthesize_to_file(
+        &self,
+        path: &str,
+        text: &str,
+        voice: Option<&str>,
+        speed: Option<f32>,
+    ) -> Result<SynthesisSummary, Error> {
+        let save = audio_saver(path)?;
+        let samples = if audio_extension(path) == "wav" {
+            self.stream_to_wav(path, text, voice, speed)?
+        } else {
+            let audio = self.synthesize(text, voice, speed, None)?;
+            save(self, path, &audio)?;
+            audio.len()
+        };
+        let file_size = fs::metadata(path)
+            .map_err(|e| Error::io(format_args!("Failed to read size of {}", path), e))?
+            .len();
+        Ok(SynthesisSummary {
+            duration: Duration::from_secs_f64(samples as f64 / f64::from(SAMPLE_RATE)),
+            file_size,
+        })
+    }
+
+    // `synthesize_to_wav`, returning the number of samples written
+    fn stream_to_wav(
+        &self,
+        path: &str,
+        text: &str,
+        voice: Option<&str>,
+        speed: Option<f32>,
+    ) -> Result<usize, Error> {
+        let file = File::create(path).map_err(|e| Error::io("Failed to create WAV file", e))?;
+        let mut writer = native_wav_writer(std::io::BufWriter::new(file), SAMPLE_RATE)?;
+        let mut written = 0;
+        let mut failed = None;
+
+        self.synthesize_joined_stream(text, voice, speed, &mut |audio| {
+            let checkpoint = write_i16_samples(&mut writer, audio)
+                .and_then(|_| writer.flush().map_err(|e| Error::io("Failed to write WAV", e)));
+            written += audio.len();
+            match checkpoint {
+                Ok(()) => ControlFlow::Continue(()),
+                Err(e) => {
+                    failed = Some(e);
+                    ControlFlow::Break(())
+                }
+            }
+        })?;
+        if let Some(e) = failed {
+            return Err(e);
+        }
+
+        writer
+            .finalize()
+            .map_err(|e| Error::io("Failed to finalize WAV", e))?;
+        Ok(written)
+    }
+
+    /// Convert audio to WAV bytes in memory (same bytes as `save_wav` writes)
+    pub fn to_wav_bytes(&self, audio: &[f32]) -> Result<Vec<u8>, Error> {
+        samples_to_wav_bytes(audio, SAMPLE_RATE)
+    }
+
+    /// Save audio as MP3 file (requires 'symphonia-formats' feature)
+    #[cfg(feature = "symphonia-formats")]
+    pub fn save_mp3(&self, path: &str, audio: &[f32]) -> Result<(), Error> {
+        // For MP3 encoding, convert to WAV first as symphonia is primarily a decoder
+        // A pure-Rust MP3 encoder would be better, but WAV is the safe fallback
+        let temp_wav = format!("{}.wav", path);
+        self.save_wav(&temp_wav, audio)?;
+        warn!(path, "Symphonia can't encode MP3, so the file holds WAV data");
+
+        std::fs::rename(&temp_wav, path)
+            .map_err(|e| Error::io("Failed to save audio file", e))?;
+        Ok(())
+    }
+
+    /// Save audio as MP3 file (fallback for when symphonia-formats not available)
+    #[cfg(not(feature = "symphonia-formats"))]
+    pub fn save_mp3(&self, path: &str, audio: &[f32]) -> Result<(), Error> {
+        Err(Error::InvalidInput("MP3 support requires 'symphonia-formats' feature. Use 'symphonia-formats' or save as WAV/OPUS.".to_string()))
+    }
+
+    /// Save audio as OPUS file (requires 'opus-format' feature)
+    #[cfg(feature = "opus-format")]
+    pub fn save_opus(&self, path: &str, audio: &[f32], bitrate: i32) -> Result<(), Error> {
+        use audiopus::{coder::Encoder as OpusEncoder, Application, Bitrate, Channels, SampleRate};
+
+        // Convert sample rate from 24000 to 48000 (OPUS prefers 48kHz)
+        let samples_48k = audio::resample(audio, SAMPLE_RATE, 48000);
+
+        // Convert to i16
+        let samples_i16: Vec<i16> = samples_48k
+            .iter()
+            .map(|&s| (s * 32767.0).clamp(-32768.0, 32767.0) as i16)
+            .collect();
+
+        // Create OPUS encoder
+        let mut encoder = OpusEncoder::new(SampleRate::Hz48000, Channels::Mono, Application::Audio)
+            .map_err(|e| Error::InvalidInput(format!("Failed to create OPUS encoder: {:?}", e)))?;
+
+        // Set bitrate
+        encoder
+            .set_bitrate(Bitrate::BitsPerSecond(bitrate))
+            .map_err(|e| Error::InvalidInput(format!("Failed to set OPUS bitrate: {:?}", e)))?;
+
+        // Encode in chunks
+        let frame_size = 960; // 20ms at 48kHz
+        let mut opus_data = Vec::new();
+        let mut output = vec![0u8; 4000];
+
+        for chunk in samples_i16.chunks(frame_size) {
+            if chunk.len() == frame_size {
+                let size = encoder
+                    .encode(chunk, &mut output)
+                    .map_err(|e| Error::InvalidInput(format!("Failed to encode OPUS frame: {:?}", e)))?;
+                opus_data.extend_from_slice(&output[..size]);
+            }
+        }
+
+        std::fs::write(path, opus_data).map_err(|e| Error::io("Failed to write OPUS file", e))?;
+
+        Ok(())
+    }
+
+    /// Encode audio as 20ms Opus packets (requires 'opus-format' feature)
+    ///
+    /// One packet per frame, ready to send over WebRTC or a WebSocket as is.
+    /// The model's 24kHz is a rate Opus encodes natively, so nothing is
+    /// resampled: each frame is 480 samples and the last one is zero-padded.
+    /// Decoded audio starts with the encoder's delay, 312 samples at 48kHz (156
+    /// at 24kHz), which the receiver should drop; that is the pre-skip `save_ogg`
+    /// writes into its header so browsers don't clip or shift the start.
+    /// Bitrates outside Opus' 6-510 kbps range are rejected rather than clamped.
+    #[cfg(feature = "opus-format")]
+    pub fn encode_opus(&self, audio: &[f32], bitrate: i32) -> Result<Vec<Vec<u8>>, Error> {
+        use audiopus::{coder::Encoder as OpusEncoder, Application, Bitrate, Channels, SampleRate};
+
+        if !(OPUS_MIN_BITRATE..=OPUS_MAX_BITRATE).contains(&bitrate) {
+            return Err(Error::InvalidInput(format!(
+                "Unsupported Opus bitrate {} bps (expected {}-{})",
+                bitrate, OPUS_MIN_BITRATE, OPUS_MAX_BITRATE
+            )));
+        }
+
+        let mut encoder = OpusEncoder::new(SampleRate::Hz24000, Channels::Mono, Application::Audio)
+            .map_err(|e| Error::InvalidInput(format!("Failed to create OPUS encoder: {:?}", e)))?;
+        encoder
+            .set_bitrate(Bitrate::BitsPerSecond(bitrate))
+            .map_err(|e| Error::InvalidInput(format!("Failed to set OPUS bitrate: {:?}", e)))?;
+
+        let samples = audio::to_i16(audio);
+        let mut output = vec![0u8; 4000];
+        let mut padded = vec![0i16; OPUS_FRAME_SAMPLES];
+        let mut packets = Vec::with_capacity(samples.len().div_ceil(OPUS_FRAME_SAMPLES));
+        for frame in samples.chunks(OPUS_FRAME_SAMPLES) {
+            let frame = if frame.len() == OPUS_FRAME_SAMPLES {
+                frame
+            } else {
+                padded[..frame.len()].copy_from_slice(frame);
+                &padded[..]
+            };
+            let size = encoder
+                .encode(frame, &mut output)
+                .map_err(|e| Error::InvalidInput(format!("Failed to encode OPUS frame: {:?}", e)))?;
+            packets.push(output[..size].to_vec());
+        }
+        Ok(packets)
+    }
+
+    /// Save audio as an Ogg Opus file (requires 'ogg-format' feature)
+    ///
+    /// Encodes the engine's 24kHz mono output directly, so no conversion is needed
+    /// on the caller's side. The result plays in browsers and is typically 10x
+    /// smaller than WAV at 32 kbps. The packets are those of `encode_opus`.
+    #[cfg(feature = "ogg-format")]
+    pub fn save_ogg(&self, path: &str, audio: &[f32], bitrate: i32) -> Result<(), Error> {
+        let packets = self.encode_opus(audio, bitrate)?;
+        let file = File::create(path).map_err(|e| Error::io("Failed to create OGG file", e))?;
+        let mut writer = write_ogg_opus(std::io::BufWriter::new(file), &packets, audio.len())
+            .map_err(|e| Error::io("Failed to write OGG page", e))?;
+        writer
+            .flush()
+            .map_err(|e| Error::io("Failed to write OGG page", e))
+    }
+
+    /// Save audio file with automatic format detection based on extension
+    pub fn save_audio(&self, path: &str, audio: &[f32]) -> Result<(), Error> {
+        audio_saver(path)?(self, path, audio)
+    }
+
+    /// Play audio directly through speakers (requires 'playback' feature).
+    /// Blocks until the audio has played or `stop_playback` cuts it short.
+    #[cfg(feature = "playback")]
+    pub fn play(&self, audio: &[f32], volume: f32) -> Result<playback::PlaybackEnd, Error> {
+        self.play_with_ducking(audio, volume, false, 0.3)
+    }
+
+    /// Like `play`, plus warnings about problems that were worked around. If
+    /// the selected device is gone (unplugged, USB bus reset) it is retried a
+    /// few times and then the system default device plays the audio instead;
+    /// that comes back as a warning rather than an error.
+    #[cfg(feature = "playback")]
+    pub fn play_with_warnings(
+        &self,
+        audio: &[f32],
+        volume: f32,
+    ) -> Result<(playback::PlaybackEnd, Vec<String>), Error> {
+        let handle = self.play_async(audio, volume)?;
+        let warnings = handle.warnings().to_vec();
+        Ok((handle.wait()?, warnings))
+    }
+
+    /// Start playing audio on a background thread and return right away
+    /// (requires 'playback' feature).
+    ///
+    /// The handle reports when playback is done and can wait for or stop it;
+    /// dropping it lets the audio play to the end. `stop_playback` stops it too.
+    #[cfg(feature = "playback")]
+    pub fn play_async(&self, audio: &[f32], volume: f32) -> Result<playback::PlaybackHandle, Error> {
+        playback::play_in_background(
+            audio.to_vec(),
+            volume,
+            self.output_target(),
+            self.playback_stop.watch(),
+        )
+        .map_err(Error::AudioDevice)
+    }
+
+    /// Play audio with optional ducking (requires 'playback' feature)
+    /// Ducking reduces system volume before speaking, then restores it after
+    ///
+    /// # Arguments
+    /// * `audio` - Audio samples to play
+    /// * `volume` - Playback volume (0.0 to 1.0)
+    /// * `enable_ducking` - Whether to reduce other audio during playback
+    /// * `duck_level` - How much to reduce other audio (0.0 = mute, 1.0 = no change)
+    #[cfg(feature = "playback")]
+    pub fn play_with_ducking(
+        &self,
+        audio: &[f32],
+        volume: f32,
+        enable_ducking: bool,
+        duck_level: f32,
+    ) -> Result<playback::PlaybackEnd, Error> {
+        // Duck audio if requested (reduce system volume)
+        #[cfg(feature = "ducking")]
+        if enable_ducking {
+            duck_system_audio(duck_level)?;
+            // Small delay to let the ducking take effect
+            thread::sleep(Duration::from_millis(50));
+        }
+
+        // Play the audio on the selected device (or default) and wait for it
+        let end = self.play_with_warnings(audio, volume).map(|(end, warnings)| {
+            for warning in warnings {
+                warn!("{}", warning);
+            }
+            end
+        });
+
+        // Restore audio if we ducked it, even when the device failed to open
+        #[cfg(feature = "ducking")]
+        if enable_ducking {
+            // Small delay before restoring
+            thread::sleep(Duration::from_millis(50));
+            restore_system_audio(duck_level)?;
+        }
+
+        end
+    }
+
+    /// Queue text to be spoken in the background (requires 'playback' feature).
+    ///
+    /// Returns as soon as the text is prepared; a worker thread then runs
+    /// inference and plays queued utterances one at a time, in order.
+    /// `Priority::Urgent` items go ahead of normal ones, and with
+    /// `UrgentMode::Interrupt` also cut off a normal utterance that is playing.
+    /// Uses the current voice settings and audio device, at full volume.
+    #[cfg(feature = "playback")]
+    pub fn enqueue(
+        &mut self,
+        text: &str,
+        voice: Option<&str>,
+        priority: playback::Priority,
+    ) -> Result<(), Error> {
+        let render = self.deferred_synthesis(text, voice, self.default_speed, 1.0, None)?;
+        let stop = self.playback_stop.clone();
+        let target = self.output_target();
+        self.speech_queue
+            .get_or_insert_with(|| playback::SpeechQueue::start(stop))
+            .push(render, priority, self.urgent_mode, target);
+        Ok(())
+    }
+
+    /// Choose whether urgent queued items wait for the current utterance
+    /// (`UrgentMode::AfterCurrent`, the default) or interrupt it
+    #[cfg(feature = "playback")]
+    pub fn set_urgent_mode(&mut self, mode: playback::UrgentMode) {
+        self.urgent_mode = mode;
+    }
+
+    /// Drop every queued utterance that hasn't started. The one being spoken
+    /// finishes; call `stop_playback` as well to cut it off.
+    #[cfg(feature = "playback")]
+    pub fn clear_queue(&self) {
+        if let Some(queue) = &self.speech_queue {
+            queue.clear();
+        }
+    }
+
+    /// Utterances waiting in the queue, not counting the one being spoken
+    #[cfg(feature = "playback")]
+    pub fn queue_len(&self) -> usize {
+        self.speech_queue.as_ref().map_or(0, playback::SpeechQueue::len)
+    }
+
+    /// Stop everything this engine is playing (requires 'playback' feature).
+    ///
+    /// Audio fades out over 50ms, and blocked `play`/`speak_streaming` calls
+    /// return `playback::PlaybackEnd::Stopped`. The speech queue moves on to its
+    /// next item (see `clear_queue`). The engine is `Sync`, so share it (e.g. in
+    /// an `Arc`) to call this from another thread. Playback started after the
+    /// call is not affected.
+    #[cfg(feature = "playback")]
+    pub fn stop_playback(&self) {
+        self.playback_stop.stop();
+    }
+
+    /// Speak text through the audio device while it is still being synthesized
+    /// (requires 'playback' feature).
+    ///
+    /// Each chunk is queued for playback as soon as its inference finishes, so
+    /// the first sentence is heard while later ones are generated. If inference
+    /// falls behind, silence fills the gap instead of a glitch. Chunks are
+    /// crossfaded like `synthesize` output; as with `synthesize_streaming`,
+    /// loudness normalization and silence trimming don't apply. Returns once the
+    /// last sample has played, or right after `stop_playback` (which also skips
+    /// the chunks not synthesized yet).
+    #[cfg(feature = "playback")]
+    pub fn speak_streaming(
+        &mut self,
+        text: &str,
+        voice: Option<&str>,
+        speed: Option<f32>,
+    ) -> Result<playback::PlaybackEnd, Error> {
+        let (output, warning) =
+            playback::open_output(&self.output_target()).map_err(Error::AudioDevice)?;
+        if let Some(warning) = warning {
+            warn!("{}", warning);
+        }
+        let sink = Sink::try_new(&output.handle)
+            .map_err(|e| Error::AudioDevice(format!("Failed to create audio sink: {}", e)))?;
+        let started = std::time::Instant::now();
+        let mut pushed = 0;
+        let buffer = playback::LiveBuffer::default();
+        let watch = self.playback_stop.watch();
+        let source = playback::Stoppable::new(buffer.source(), watch.clone());
+        output.append(&sink, source);
+
+        // Each chunk's tail waits for the next chunk so the two can be crossfaded
+        let overlap = self.crossfade_samples();
+        let mut join = ChunkJoin::new(overlap);
+        let mut tail = Vec::new();
+        let processors = self.processors.clone();
+        let result = self.synthesize_chunks(
+            text,
+            voice,
+            speed.unwrap_or(self.default_speed),
+            1.0,
+            None,
+            &mut |mut chunk| {
+                if watch.is_stopped() {
+                    return ControlFlow::Break(());
+                }
+                processors.run(&mut chunk.samples);
+                join.append(&mut tail, &chunk.samples, chunk.pause_ms);
+                let ready = tail.len().saturating_sub(overlap);
+                buffer.push(&tail[..ready]);
+                pushed += ready;
+                tail.drain(..ready);
+                ControlFlow::Continue(())
+            },
+        );
+        buffer.push(&tail);
+        buffer.close();
+
+        if let Err(e) = result {
+            sink.stop();
+            return Err(e);
+        }
+        playback::wait_for_sink(&sink, playback::live_deadline(started, pushed + tail.len()))
+            .map_err(Error::AudioDevice)?;
+        Ok(watch.end())
+    }
+
+    // Private helper methods
+
+    // Resolve a voice (or "a.4+b.6" mix) to the style vector for a sequence of
+    // `token_count` phonemes. Voices store one style row per token count, so each
+    // chunk gets the row matching its own length.
+    fn parse_voice_style(&self, voice_str: &str, token_count: usize) -> Result<Vec<f32>, Error> {
+        if self.fallback_mode {
+            // Return a dummy style vector for fallback mode
+            return Ok(vec![0.0; STYLE_DIM]);
+        }
+
+        let mut result = vec![0.0; STYLE_DIM];
+        let parts: Vec<&str> = voice_str.split('+').collect();
+
+        for part in parts {
+            let (voice_name, weight) = if part.contains('.') {
+                let pieces: Vec<&str> = part.split('.').collect();
+                if pieces.len() != 2 {
+                    return Err(Error::InvalidInput(format!("Invalid voice format: {}", part)));
+                }
+                let weight = pieces[1]
+                    .parse::<f32>()
+                    .map_err(|_| Error::InvalidInput(format!("Invalid weight: {}", pieces[1])))?;
+                (pieces[0], weight / 10.0)
+            } else {
+                (part, 1.0)
+            };
+
+            let voice_style = self.voice_data(voice_name)?;
+
+            for (acc, val) in result.iter_mut().zip(style_row(voice_style, token_count)) {
+                *acc += val * weight;
+            }
+        }
+
+        Ok(result)
+    }
+
+    // A voice's style data; the engine's own voices shadow the model's
+    fn voice_data(&self, name: &str) -> Result<&Vec<f32>, Error> {
+        if let Some(style) = self.custom_voices.get(name) {
+            return Ok(style);
+        }
+        let model = self.loaded_model()?;
+        model.voices.get(name).ok_or_else(|| self.unknown_voice_error(name))
+    }
+
+    // Every voice name, each once. Before a lazy load they come from the
+    // voices file's index, without reading the voice data.
+    fn voice_names(&self) -> Vec<String> {
+        let shared = match (self.model_if_loaded(), &self.lazy_load) {
+            (Some(model), _) => model.voices.keys().cloned().collect(),
+            (None, Some(lazy)) => load_voice_names(&lazy.voices_path).unwrap_or_default(),
+            (None, None) => Vec::new(),
+        };
+        let mut names: Vec<String> = self.custom_voices.keys().cloned().collect();
+        names.extend(shared.into_iter().filter(|id| !self.custom_voices.contains_key(id)));
+        names
+    }
+
+    // Check the voices a name or blend ("af_sky.6+af_bella.4") refers to by
+    // name alone, so a lazy engine needn't load to validate its default
+    fn check_voice_names(&self, voice: &str) -> Result<(), Error> {
+        let names = self.voice_names();
+        for part in voice.split('+') {
+            let name = part.split('.').next().unwrap_or(part);
+            if !names.iter().any(|known| known == name) {
+                return Err(self.unknown_voice_error(name));
+            }
+        }
+        Ok(())
+    }
+
+    fn unknown_voice_error(&self, name: &str) -> Error {
+        let mut available = self.voice_names();
+        available.sort_unstable();
+        Error::VoiceNotFound {
+            name: name.to_string(),
+            available,
+        }
+    }
+}
+
+// Helper functions
+
+// Run the Kokoro model on one token sequence. Takes only owned/shared data so it
+// can run on a blocking thread; concurrent callers queue on the session lock.
+fn run_inference(
+    session: &Mutex<Session>,
+    tokens: Vec<i64>,
+    style: Vec<f32>,
+    speed: f32,
+) -> Result<Vec<f32>, Error> {
+    run_inference_with_durations(session, tokens, style, speed).map(|(audio, _)| audio)
+}
+
+// Apply a voice profile's pitch to one chunk of model output
+fn voice_pitch(samples: Vec<f32>, semitones: f32) -> Result<Vec<f32>, Error> {
+    if semitones == 0.0 {
+        return Ok(samples);
+    }
+    audio::pitch_shift(&samples, semitones).map_err(Error::InvalidInput)
+}
+
+// Infer the chunks of `plan` on all `sessions` at once, each worker taking the
+// next chunk in line, and hand the audio to `on_result` in chunk order. Once
+// `on_result` breaks or a chunk fails, no further chunks are started.
+fn infer_in_parallel(
+    sessions: &[Mutex<Session>],
+    plan: &SynthesisPlan,
+    on_result: &mut dyn FnMut(usize, Vec<f32>) -> ControlFlow<()>,
+) -> Result<(), Error> {
+    use std::sync::atomic::AtomicBool;
+
+    let next = AtomicUsize::new(0);
+    let stop = AtomicBool::new(false);
+    thread::scope(|scope| {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        for session in sessions.iter().take(plan.chunks.len()) {
+            let (sender, next, stop) = (sender.clone(), &next, &stop);
+            scope.spawn(move || {
+                while !stop.load(Ordering::Relaxed) {
+                    let idx = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(chunk) = plan.chunks.get(idx) else { break };
+                    let tokens = chunk.tokens.clone();
+                    let result = run_inference(session, tokens, chunk.style.clone(), plan.speed)
+                        .and_then(|samples| voice_pitch(samples, plan.pitch));
+                    let failed = result.is_err();
+                    if sender.send((idx, result)).is_err() || failed {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+
+        // Chunks that finished ahead of an earlier one wait here
+        let mut finished = std::collections::BTreeMap::new();
+        let mut in_order = 0;
+        for (idx, result) in &receiver {
+            finished.insert(idx, result);
+            while let Some(result) = finished.remove(&in_order) {
+                in_order += 1;
+                let flow = result.map(|samples| on_result(in_order - 1, samples));
+                if !matches!(flow, Ok(ControlFlow::Continue(()))) {
+                    stop.store(true, Ordering::Relaxed);
+                    return flow.map(|_| ());
+                }
+            }
+        }
+        Ok(())
+    })
+}
+
+// Like `run_inference`, also returning per-token durations if the model exports them
+fn run_inference_with_durations(
+    session: &Mutex<Session>,
+    tokens: Vec<i64>,
+    style: Vec<f32>,
+    speed: f32,
+) -> Result<(Vec<f32>, Option<Vec<f32>>), Error> {
+    let mut session = lock_session(session)?;
+    infer(&mut session, tokens, style, speed)
+}
+
+fn lock_session(session: &Mutex<Session>) -> Result<MutexGuard<'_, Session>, Error> {
+    session
+        .lock()
+        .map_err(|e| Error::Inference(format!("Failed to lock session: {}", e)))
+}
+
+// The first of `sessions` that isn't running, or else wait for the one at
+// `turn` (taken modulo the pool size)
+fn lock_idle_session(
+    sessions: &[Mutex<Session>],
+    turn: usize,
+) -> Result<MutexGuard<'_, Session>, Error> {
+    if sessions.is_empty() {
+        return Err(Error::ModelLoad("TTS engine not initialized".to_string()));
+    }
+    if let Some(idle) = sessions.iter().find_map(|session| session.try_lock().ok()) {
+        return Ok(idle);
+    }
+    lock_session(&sessions[turn % sessions.len()])
+}
+
+// One model run on an already locked session (see `run_inference_with_durations`)
+fn infer(
+    session: &mut Session,
+    tokens: Vec<i64>,
+    style: Vec<f32>,
+    speed: f32,
+) -> Result<(Vec<f32>, Option<Vec<f32>>), Error> {
+    let token_count = tokens.len(); // Save count before moving
+    // A silent chunk (see `is_silence`) has nothing to run
+    if token_count == 0 {
+        return Ok((Vec::new(), None));
+    }
+    let _span = tracing::info_span!("inference", tokens = token_count).entered();
+
+    // Prepare tokens tensor
+    let tokens_array = ndarray::Array2::from_shape_vec((1, tokens.len()), tokens)
+        .map_err(|e| Error::Inference(format!("Failed to create tokens array: {}", e)))?;
+    let tokens_tensor = Tensor::from_array(tokens_array)
+        .map_err(|e| Error::Inference(format!("Failed to create tokens tensor: {}", e)))?;
+
+    // Prepare style tensor
+    let style_array = ndarray::Array2::from_shape_vec((1, style.len()), style)
+        .map_err(|e| Error::Inference(format!("Failed to create style array: {}", e)))?;
+    let style_tensor = Tensor::from_array(style_array)
+        .map_err(|e| Error::Inference(format!("Failed to create style tensor: {}", e)))?;
+
+    // Prepare speed tensor
+    let speed_array = ndarray::Array1::from_vec(vec![speed]);
+    let speed_tensor = Tensor::from_array(speed_array)
+        .map_err(|e| Error::Inference(format!("Failed to create speed tensor: {}", e)))?;
+
+    // Create inputs
+    use std::borrow::Cow;
+    let input_names: Vec<&str> = session.inputs().iter().map(|input| input.name()).collect();
+    let tokens_input = io_name(&input_names, &TOKENS_INPUT_NAMES);
+    let inputs = SessionInputs::from(vec![
+        (
+            Cow::Owned(tokens_input.to_string()),
+            SessionInputValue::Owned(Value::from(tokens_tensor)),
+        ),
+        (
+            Cow::Borrowed("style"),
+            SessionInputValue::Owned(Value::from(style_tensor)),
+        ),
+        (
+            Cow::Borrowed("speed"),
+            SessionInputValue::Owned(Value::from(speed_tensor)),
+        ),
+    ]);
+
+    // Run inference
+    let outputs = session
+        .run(inputs)
+        .map_err(|e| Error::Inference(format!("Failed to run inference: {}", e)))?;
+
+    // Extract audio
+    let output_names: Vec<&str> = outputs.keys().collect();
+    let audio_output = io_name(&output_names, &AUDIO_OUTPUT_NAMES);
+    let (shape, data) = outputs[audio_output]
+        .try_extract_tensor::<f32>()
+        .map_err(|e| Error::Inference(format!("Failed to extract audio tensor: {}", e)))?;
+
+    let data_vec = data.to_vec();
+    trace!(shape = ?shape, samples = data_vec.len(), "Model output");
+
+    // Some Kokoro exports also return the predicted duration of every token
+    let durations = DURATION_OUTPUT_NAMES
+        .iter()
+        .find_map(|name| outputs.get(*name))
+        .and_then(|value| {
+            if let Ok((_, d)) = value.try_extract_tensor::<i64>() {
+                Some(d.iter().map(|&v| v as f32).collect::<Vec<f32>>())
+            } else {
+                value.try_extract_tensor::<f32>().ok().map(|(_, d)| d.to_vec())
+            }
+        })
+        .filter(|d| d.len() == token_count);
+
+    Ok((data_vec, durations))
+}
+
+// The first of `candidates` among a model's input or output `names`, or the
+// first candidate (whose lookup then fails with ort's own error)
+fn io_name(names: &[&str], candidates: &[&'static str]) -> &'static str {
+    candidates
+        .iter()
+        .find(|candidate| names.contains(candidate))
+        .unwrap_or(&candidates[0])
+}
+
+// How many space-separated phoneme words espeak produces for one text word
+fn phoneme_word_count(word: &str, lang: &str) -> Result<usize, Error> {
+    Ok(phonemize_normalized(word, lang)?.join(" ").split_whitespace().count())
+}
+
+// Token ranges of the phoneme words in a padded chunk (pads and spaces separate words)
+fn phoneme_word_spans(tokens: &[i64], space_id: i64) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+    let mut start = None;
+    for (i, &token) in tokens.iter().enumerate() {
+        let boundary = token == space_id || token == 0;
+        match (boundary, start) {
+            (false, None) => start = Some(i),
+            (true, Some(s)) => {
+                spans.push((s, i));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        spans.push((s, tokens.len()));
+    }
+    spans
+}
+
+// Sample position where each token starts (plus the end), spreading the chunk's
+// audio over tokens by predicted duration, or evenly without durations
+fn token_boundaries(token_count: usize, audio_len: usize, durations: Option<&[f32]>) -> Vec<usize> {
+    let weights: Vec<f32> = match durations {
+        Some(d) if d.len() == token_count && d.iter().sum::<f32>() > 0.0 => d.to_vec(),
+        _ => vec![1.0; token_count],
+    };
+    let total: f32 = weights.iter().sum();
+
+    let mut bounds = Vec::with_capacity(token_count + 1);
+    let mut acc = 0.0f32;
+    bounds.push(0);
+    for w in weights {
+        acc += w;
+        bounds.push(((acc / total) * audio_len as f32).round() as usize);
+    }
+    bounds
+}
+
+// A cue for each `(spoken words, text)` sentence, over that many consecutive
+// word timings. If the counts don't add up (normalization read a sentence
+// differently in context), the last sentence takes whatever timings are left.
+fn sentence_cues(sentences: &[(usize, String)], timings: &[WordTiming]) -> Vec<subtitles::Cue> {
+    let at = |sample: usize| Duration::from_secs_f64(sample as f64 / f64::from(SAMPLE_RATE));
+    let spoken: Vec<&(usize, String)> = sentences.iter().filter(|(words, _)| *words > 0).collect();
+    let mut cues = Vec::new();
+    let (mut next, mut previous_end) = (0, 0);
+    for (i, (words, sentence)) in spoken.iter().enumerate() {
+        let last = i + 1 == spoken.len();
+        let end_word = if last { timings.len() } else { (next + words).min(timings.len()) };
+        let final_word = end_word.checked_sub(1).and_then(|i| timings.get(i));
+        let (Some(first), Some(final_word)) = (timings.get(next), final_word) else {
+            break;
+        };
+        // Crossfaded chunks can overlap a little; cues never do
+        let start = first.start_sample.max(previous_end);
+        let end = final_word.end_sample.max(start);
+        cues.push(subtitles::Cue { start: at(start), end: at(end), text: sentence.clone() });
+        (next, previous_end) = (end_word, end);
+    }
+    cues
+}
+
+// Map text words onto phoneme-word spans. `parts[i]` is how many phoneme words
+// word i produces on its own; if those don't add up to the spans espeak produced
+// in context (numbers, abbreviations), spans are shared out by character position.
+fn word_timings(
+    words: &[&str],
+    parts: &[usize],
+    spans: &[(usize, usize)],
+    bounds: &[usize],
+    offset: usize,
+) -> Vec<WordTiming> {
+    let ranges: Vec<(usize, usize)> = if parts.iter().sum::<usize>() == spans.len() {
+        let mut next = 0;
+        parts
+            .iter()
+            .map(|&n| {
+                let range = (next, next + n);
+                next += n;
+                range
+            })
+            .collect()
+    } else {
+        let total_chars: usize = words.iter().map(|w| w.chars().count()).sum::<usize>().max(1);
+        let mut chars_before = 0;
+        words
+            .iter()
+            .map(|w| {
+                let start = chars_before * spans.len() / total_chars;
+                chars_before += w.chars().count();
+                (start, chars_before * spans.len() / total_chars)
+            })
+            .collect()
+    };
+
+    let end_of_audio = bounds.last().copied().unwrap_or(0);
+    let span_start = |i: usize| spans.get(i).map(|s| bounds[s.0]).unwrap_or(end_of_audio);
+
+    words
+        .iter()
+        .zip(ranges)
+        .map(|(word, (first, last))| {
+            let start = span_start(first);
+            let end = if last > first { bounds[spans[last - 1].1] } else { start };
+            WordTiming {
+                word: word.to_string(),
+                start_sample: offset + start,
+                end_sample: offset + end,
+            }
+        })
+        .collect()
+}
+
+// espeak-ng keeps global state (selected voice, output buffers), so phonemization
+// from concurrent synthesis calls must not interleave
+static ESPEAK_LOCK: Mutex<()> = Mutex::new(());
+
+// Phonemize normalized text: words go through espeak, inline phonemes picked
+// during normalization (heteronyms) are used as they are
+fn phonemize_normalized(text: &str, lang: &str) -> Result<Vec<String>, Error> {
+    let _span = tracing::debug_span!("phonemize", lang, chars = text.len()).entered();
+    let mut phonemes = Vec::new();
+    for piece in text::split_inline_phonemes(text) {
+        match piece {
+            text::Piece::Text(words) if words.trim().is_empty() => {}
+            text::Piece::Text(words) => phonemes.extend(phonemize_text(words, lang)?),
+            text::Piece::Phonemes(inline) => phonemes.push(inline.to_string()),
+        }
+    }
+    Ok(phonemes)
+}
+
+// Phoneme tokens the model would get for normalized text, pads excluded
+fn phoneme_token_count(text: &str, lang: &str) -> Result<usize, Error> {
+    Ok(phonemize_normalized(text, lang)?.join(" ").chars().count())
+}
+
+// All grapheme-to-phoneme conversion goes through espeak-ng; only heteronyms
+// and `add_pronunciation` words skip it, as inline phonemes
+fn phonemize_text(text: &str, lang: &str) -> Result<Vec<String>, Error> {
+    let _guard = ESPEAK_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    text_to_phonemes(text, lang, None, true, false)
+        .map_err(|e| Error::Phonemize(format!("Failed to convert text to phonemes: {}", e)))
+}
+
+// Build proper vocabulary for tokenization (matching original Kokoros)
+fn build_vocab() -> HashMap<char, i64> {
+    let pad = "$";
+    let punctuation = r#";:,.!?¡¿—…"«»"" "#;
+    let letters = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+    let letters_ipa = "ɑɐɒæɓʙβɔɕçɗɖðʤəɘɚɛɜɝɞɟʄɡɠɢʛɦɧħɥʜɨɪʝɭɬɫɮʟɱɯɰŋɳɲɴøɵɸθœɶʘɹɺɾɻʀʁɽʂʃʈʧʉʊʋⱱʌɣɤʍχʎʏʑʐʒʔʡʕʢǀǁǂǃˈˌːˑʼʴʰʱʲʷˠˤ˞↓↑→↗↘'̩'ᵻ";
+
+    let symbols: String = [pad, punctuation, letters, letters_ipa].concat();
+
+    symbols
+        .chars()
+        .enumerate()
+        .map(|(idx, c)| (c, idx as i64))
+        .collect()
+}
+
+// Check one of the model files before loading it: present, a file, and at
+// least `min_bytes` long. `what` names it in the error ("Model", "Voices").
+fn check_model_file(what: &str, path: &Path, min_bytes: u64) -> Result<(), Error> {
+    let metadata = fs::metadata(path)
+        .map_err(|_| Error::ModelLoad(format!("{} file not found: {}", what, path.display())))?;
+    if !metadata.is_file() {
+        return Err(Error::ModelLoad(format!("{} path is not a file: {}", what, path.display())));
+    }
+    if metadata.len() < min_bytes {
+        return Err(Error::ModelLoad(format!(
+            "{} file {} is only {} bytes, too small to be real (an unfetched Git LFS pointer?)",
+            what,
+            path.display(),
+            metadata.len()
+        )));
+    }
+    Ok(())
+}
+
+// Compare a model file with its published SHA-256. `what` names it in the
+// error, which tells the user how to get a good copy.
+fn verify_model_file(what: &str, path: &Path, sha256: &str) -> Result<(), Error> {
+    let actual = checksum::sha256_file(path)
+        .map_err(|e| {
+            Error::ModelLoad(format!("Failed to read {} file {}: {}", what.to_lowercase(), path.display(), e))
+        })?;
+    if actual != sha256 {
+        return Err(Error::ModelLoad(format!(
+            "{} file is corrupt: expected SHA-256 {}, got {}; delete {} to re-download",
+            what,
+            sha256,
+            actual,
+            path.display()
+        )));
+    }
+    Ok(())
+}
+
+// Create the inference session on the configured provider, or on the CPU
+// with a warning when the provider isn't compiled in or fails to start.
+// Returns the provider in use.
+fn create_session(
+    model_bytes: &[u8],
+    config: &SessionConfig,
+) -> Result<(Session, ExecutionProvider), Error> {
+    let provider = config.provider;
+    if provider != ExecutionProvider::Cpu {
+        match provider.dispatch().and_then(|ep| build_session(model_bytes, config, ep)) {
+            Ok(session) => return Ok((session, provider)),
+            Err(e) => {
+                warn!(%provider, error = %e, "Execution provider not available, running on the CPU")
+            }
+        }
+    }
+    Ok((build_session(model_bytes, config, None)?, ExecutionProvider::Cpu))
+}
+
+fn build_session(
+    model_bytes: &[u8],
+    config: &SessionConfig,
+    provider: Option<ort::ep::ExecutionProviderDispatch>,
+) -> Result<Session, Error> {
+    let thread_error =
+        |e: ort::Error<_>| Error::ModelLoad(format!("Failed to configure threads: {}", e));
+    let mut builder = Session::builder()
+        .map_err(|e| Error::ModelLoad(format!("Failed to create session builder: {}", e)))?
+        .with_optimization_level(GraphOptimizationLevel::Level3)
+        .map_err(|e| Error::ModelLoad(format!("Failed to set optimization level: {}", e)))?;
+    if let Some(threads) = config.intra_op_threads {
+        builder = builder.with_intra_threads(threads).map_err(thread_error)?;
+    }
+    if let Some(threads) = config.inter_op_threads {
+        builder = builder.with_inter_threads(threads).map_err(thread_error)?;
+    }
+    if let Some(spin) = config.spinning {
+        builder = builder.with_intra_op_spinning(spin).map_err(thread_error)?;
+        builder = builder.with_inter_op_spinning(spin).map_err(thread_error)?;
+    }
+    if let Some(cpus) = config.cpu_affinity.as_ref().filter(|cpus| cpus.len() > 1) {
+        builder = builder
+            .with_config_entry("session.intra_op_thread_affinities", intra_op_affinities(cpus))
+            .map_err(thread_error)?;
+    }
+    if let Some(provider) = provider {
+        builder = builder
+            .with_execution_providers([provider])
+            .map_err(|e| Error::ModelLoad(format!("Failed to register execution provider: {}", e)))?;
+    }
+    builder
+        .commit_from_memory(model_bytes)
+        .map_err(|e| Error::ModelLoad(format!("Failed to load model: {}", e)))
+}
+
+// ONNX Runtime's affinity setting for intra-op threads pinned one per CPU:
+// 1-based processor ids separated by ';', for every thread but the caller's
+fn intra_op_affinities(cpus: &[usize]) -> String {
+    cpus.iter()
+        .skip(1)
+        .map(|cpu| (cpu + 1).to_string())
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+// Just the voice names in a voices file, from the NPZ index
+fn load_voice_names(path: &Path) -> Result<Vec<String>, Error> {
+    let file = File::open(path)
+        .map_err(|e| Error::ModelLoad(format!("Failed to open voices file: {}", e)))?;
+    let mut reader = NpzReader::new(file)
+        .map_err(|e| Error::ModelLoad(format!("Failed to create NPZ reader: {}", e)))?;
+    let names = reader
+        .names()
+        .map_err(|e| Error::ModelLoad(format!("Failed to read NPZ names: {:?}", e)))?;
+    Ok(names.iter().map(|name| name.trim_end_matches(".npy").to_string()).collect())
+}
+
+// Load voices from binary file
+fn load_voices(path: &Path) -> Result<HashMap<String, Vec<f32>>, Error> {
+    let file = File::open(path)
+        .map_err(|e| Error::ModelLoad(format!("Failed to open voices file: {}", e)))?;
+    read_voices(file)
+}
+
+// Parse a voices file (NPZ of one style matrix per voice)
+fn read_voices(
+    voices_file: impl std::io::Read + std::io::Seek,
+) -> Result<HashMap<String, Vec<f32>>, Error> {
+    let mut reader = NpzReader::new(voices_file)
+        .map_err(|e| Error::ModelLoad(format!("Failed to create NPZ reader: {}", e)))?;
+
+    let mut voices = HashMap::new();
+
+    for name in reader
+        .names()
+        .map_err(|e| Error::ModelLoad(format!("Failed to read NPZ names: {:?}", e)))?
+    {
+        let array: ArrayBase<OwnedRepr<f32>, IxDyn> = reader
+            .by_name(&name)
+            .map_err(|e| Error::ModelLoad(format!("Failed to read NPZ array {}: {:?}", name, e)))?;
+        let data: Vec<f32> = array.iter().cloned().collect();
+
+        // Clean up the name (remove .npy extension if present)
+        let clean_name = name.trim_end_matches(".npy");
+        voices.insert(clean_name.to_string(), data);
+    }
+
+    Ok(voices)
+}
+
+// Parse a single voice from `.npy` or raw little-endian f32 bytes and check that it
+// is shaped like a Kokoro style (one 256-wide row, or the full token-indexed matrix)
+fn parse_voice_bytes(bytes: &[u8]) -> Result<Vec<f32>, Error> {
+    use ndarray_npy::ReadNpyExt;
+
+    let (data, shape): (Vec<f32>, Vec<usize>) = if bytes.starts_with(b"\x93NUMPY") {
+        let array = ArrayBase::<OwnedRepr<f32>, IxDyn>::read_npy(bytes)
+            .map_err(|e| Error::InvalidInput(format!("Failed to read .npy voice data: {}", e)))?;
+        let shape = array.shape().to_vec();
+        (array.iter().cloned().collect(), shape)
+    } else {
+        if !bytes.len().is_multiple_of(4) {
+            return Err(Error::InvalidInput(format!(
+                "Raw voice data must be little-endian f32 values, but {} bytes is not a multiple of 4",
+                bytes.len()
+            )));
+        }
+        let data: Vec<f32> = bytes
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+        let len = data.len();
+        (data, vec![len])
+    };
+
+    let rows = data.len() / STYLE_DIM;
+    let last_dim_ok = shape.len() == 1 || shape.last() == Some(&STYLE_DIM);
+    if !data.len().is_multiple_of(STYLE_DIM) || !last_dim_ok || !(rows == 1 || rows == STYLE_ROWS) {
+        return Err(Error::InvalidInput(format!(
+            "Invalid voice shape {:?}: expected [{}] (single style) or [{}, 1, {}] (token-indexed styles)",
+            shape, STYLE_DIM, STYLE_ROWS, STYLE_DIM
+        )));
+    }
+
+    Ok(data)
+}
+
+fn validate_voice_name(name: &str) -> Result<(), Error> {
+    if name.is_empty() || name.contains('+') || name.contains('.') {
+        return Err(Error::InvalidInput(format!(
+            "Invalid voice name '{}': names must be non-empty and cannot contain '+' or '.'",
+            name
+        )));
+    }
+    Ok(())
+}
+
+// Pick the style row for a token count from a voice's (rows x 256) style matrix.
+// Counts past the last row reuse the last one; single-row voices always use row 0.
+fn style_row(voice_data: &[f32], token_count: usize) -> &[f32] {
+    let rows = (voice_data.len() / STYLE_DIM).max(1);
+    let row = token_count.min(rows - 1);
+    let start = row * STYLE_DIM;
+    &voice_data[start..(start + STYLE_DIM).min(voice_data.len())]
+}
+
+// Linear interpolation between two equally sized style matrices
+fn blend_styles(a: &[f32], b: &[f32], weight: f32) -> Vec<f32> {
+    a.iter()
+        .zip(b)
+        .map(|(x, y)| x * (1.0 - weight) + y * weight)
+        .collect()
+}
+
+// Fail early, with the directory named, when downloads couldn't be saved
+fn check_writable(dir: &Path) -> Result<(), Error> {
+    let probe = dir.join(format!(".write-test-{}", std::process::id()));
+    File::create(&probe)
+        .map_err(|e| Error::io(format!("Cache directory {} is not writable", dir.display()), e))?;
+    let _ = fs::remove_file(&probe);
+    Ok(())
+}
+
+// `path.lock`, held while `path` downloads so engines sharing a cache dir
+// don't write the same `.part` file at once. Removed again on drop.
+struct DownloadLock {
+    path: PathBuf,
+}
+
+impl DownloadLock {
+    // Wait until the lock is free. A lock whose download made no progress for
+    // `STALE_DOWNLOAD` was left by a process that died and is taken over.
+    async fn acquire(path: &str) -> Result<Self, Error> {
+        let lock_path = PathBuf::from(format!("{}.lock", path));
+        let part_path = PathBuf::from(format!("{}.part", path));
+        loop {
+            match fs::OpenOptions::new().write(true).create_new(true).open(&lock_path) {
+                Ok(mut file) => {
+                    let _ = write!(file, "{}", std::process::id());
+                    return Ok(Self { path: lock_path });
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    let last_activity = [&part_path, &lock_path]
+                        .iter()
+                        .filter_map(|p| fs::metadata(p).and_then(|m| m.modified()).ok())
+                        .max();
+                    let stale = last_activity
+                        .and_then(|time| time.elapsed().ok())
+                        .is_some_and(|idle| idle > STALE_DOWNLOAD);
+                    if stale {
+                        let _ = fs::remove_file(&lock_path);
+                        continue;
+                    }
+                    tokio::time::sleep(Duration::from_millis(500)).await;
+                }
+                Err(e) => {
+                    let context = format!("Failed to create lock file {}", lock_path.display());
+                    return Err(Error::io(context, e));
+                }
+            }
+        }
+    }
+}
+
+impl Drop for DownloadLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+// Download `url` to `path` through `path.part`, resuming a partial file left
+// by an interrupted run, and verify the result is at least `min_bytes` with
+// SHA-256 `sha256` (when known). A download that doesn't match is deleted and
+// fetched again, up to `DOWNLOAD_ATTEMPTS` times.
+async fn download_file(
+    client: &reqwest::Client,
+    url: &str,
+    path: &str,
+    min_bytes: u64,
+    sha256: Option<&str>,
+    progress: &mut (impl FnMut(DownloadProgress) + Send),
+) -> Result<(), Box<dyn std::error::Error>> {
+    let _lock = DownloadLock::acquire(path).await?;
+    // Another engine may have finished it while this one waited for the lock
+    if Path::new(path).exists() {
+        return Ok(());
+    }
+    let part_path = format!("{}.part", path);
+    let file_name = Path::new(path)
+        .file_name()
+        .map_or_else(|| path.to_string(), |name| name.to_string_lossy().into_owned());
+
+    let mut attempt = 1;
+    loop {
+        fetch_with_retry(client, url, &part_path, &file_name, progress).await?;
+        let part = Path::new(&part_path);
+        let checked = check_model_file(&file_name, part, min_bytes)
+            .and_then(|()| sha256.map_or(Ok(()), |sha256| verify_model_file(&file_name, part, sha256)));
+        match checked {
+            Ok(()) => break,
+            Err(e) => {
+                // Not worth resuming: it is complete, just not the right file
+                let _ = fs::remove_file(&part_path);
+                if attempt >= DOWNLOAD_ATTEMPTS {
+                    return Err(e.into());
+                }
+                warn!(file = %file_name, error = %e, "Download failed verification, fetching it again");
+                attempt += 1;
+            }
+        }
+    }
+    fs::rename(&part_path, path)?;
+    Ok(())
+}
+
+// HTTP client for model downloads: `proxy` if given, else the proxy
+// environment variables reqwest reads by default
+fn http_client(proxy: Option<&str>) -> Result<reqwest::Client, Error> {
+    let mut builder = reqwest::Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .read_timeout(READ_TIMEOUT);
+    if let Some(url) = proxy {
+        let proxy = reqwest::Proxy::all(url)
+            .map_err(|e| Error::InvalidInput(format!("Invalid proxy URL {}: {}", url, e)))?;
+        builder = builder.proxy(proxy);
+    }
+    builder
+        .build()
+        .map_err(|e| Error::Download(format!("Failed to create HTTP client: {}", e)))
+}
+
+// `fetch_part`, retrying transient failures after RETRY_DELAY, 2x, 4x, ...
+// Each retry resumes from what the part file already holds.
+async fn fetch_with_retry(
+    client: &reqwest::Client,
+    url: &str,
+    part_path: &str,
+    file_name: &str,
+    progress: &mut (impl FnMut(DownloadProgress) + Send),
+) -> Result<(), Error> {
+    let mut retries = 0;
+    loop {
+        let error = match fetch_part(client, url, part_path, file_name, progress).await {
+            Ok(()) => return Ok(()),
+            Err(e) => e,
+        };
+        let message = describe_download_error(url, &*error);
+        if retries >= NETWORK_RETRIES || !is_transient(&*error) {
+            return Err(Error::Download(message));
+        }
+        let delay = RETRY_DELAY * 2u32.pow(retries);
+        warn!(retry_in_secs = delay.as_secs(), "{}", message);
+        tokio::time::sleep(delay).await;
+        retries += 1;
+    }
+}
+
+// Failures worth retrying: timeouts, refused or dropped connections, a
+// transfer cut short, and server-side HTTP errors. Bad URLs, permissions
+// (4xx) and local disk errors fail straight away.
+fn is_transient(error: &(dyn std::error::Error + 'static)) -> bool {
+    if let Some(error) = error.downcast_ref::<reqwest::Error>() {
+        return match error.status() {
+            Some(status) => {
+                status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+            }
+            None => !is_tls_error(error),
+        };
+    }
+    error
+        .downcast_ref::<std::io::Error>()
+        .is_some_and(|error| error.kind() == std::io::ErrorKind::UnexpectedEof)
+}
+
+fn is_tls_error(error: &reqwest::Error) -> bool {
+    let detail = error_chain(error).to_lowercase();
+    detail.contains("certificate") || detail.contains("tls") || detail.contains("handshake")
+}
+
+// An error followed by its causes, which reqwest keeps out of its own message
+fn error_chain(error: &(dyn std::error::Error + 'static)) -> String {
+    let mut parts = vec![error.to_string()];
+    let mut source = error.source();
+    while let Some(cause) = source {
+        parts.push(cause.to_string());
+        source = cause.source();
+    }
+    parts.join(": ")
+}
+
+// Say which step of a download failed (DNS, connection, TLS, HTTP status,
+// transfer) along with the underlying cause
+fn describe_download_error(url: &str, error: &(dyn std::error::Error + 'static)) -> String {
+    let Some(http) = error.downcast_ref::<reqwest::Error>() else {
+        return error_chain(error);
+    };
+    if let Some(status) = http.status() {
+        return format!("{} returned HTTP {}", url, status);
+    }
+    let detail = error_chain(http);
+    let step = if detail.contains("dns error") {
+        "DNS lookup failed"
+    } else if is_tls_error(http) {
+        "TLS handshake failed"
+    } else if http.is_timeout() {
+        "Timed out"
+    } else if http.is_connect() {
+        "Connection failed"
+    } else {
+        "Transfer failed"
+    };
+    let mut message = format!("{} for {}: {}", step, url, detail);
+    let proxy_set = ["HTTPS_PROXY", "https_proxy", "HTTP_PROXY", "http_proxy", "ALL_PROXY"]
+        .iter()
+        .any(|name| std::env::var_os(name).is_some());
+    if http.is_connect() && !proxy_set {
+        message.push_str(" (behind a proxy? set HTTPS_PROXY or use TtsEngineBuilder::proxy)");
+    }
+    message
+}
+
+// Fetch `url` into `part_path`, continuing what is already there when the
+// server supports ranges. An interrupted transfer leaves the part file in place.
+async fn fetch_part(
+    client: &reqwest::Client,
+    url: &str,
+    part_path: &str,
+    file_name: &str,
+    progress: &mut (impl FnMut(DownloadProgress) + Send),
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut downloaded = fs::metadata(part_path).map_or(0, |m| m.len());
+
+    let mut request = client.get(url);
+    if downloaded > 0 {
+        request = request.header(reqwest::header::RANGE, format!("bytes={}-", downloaded));
+    }
+    let mut response = request.send().await?;
+    if response.status() == reqwest::StatusCode::RANGE_NOT_SATISFIABLE {
+        // The partial file doesn't fit what the server has now; start over
+        response = client.get(url).send().await?;
+    }
+    let mut response = response.error_for_status()?;
+
+    // Only 206 continues the partial file; a full response replaces it
+    let resumed = response.status() == reqwest::StatusCode::PARTIAL_CONTENT;
+    if !resumed {
+        downloaded = 0;
+    }
+    let total = response.content_length().map(|len| len + downloaded);
+    let mut file = if resumed {
+        fs::OpenOptions::new().append(true).open(part_path)?
+    } else {
+        File::create(part_path)?
+    };
+
+    let mut report = |downloaded| {
+        progress(DownloadProgress::Downloading {
+            file: file_name.to_string(),
+            downloaded,
+            total,
+        })
+    };
+    report(downloaded);
+    while let Some(chunk) = response.chunk().await? {
+        file.write_all(&chunk)?;
+        downloaded += chunk.len() as u64;
+        report(downloaded);
+    }
+
+    if total.is_some_and(|total| downloaded != total) {
+        let message = format!("download of {} stopped at {} bytes", file_name, downloaded);
+        return Err(std::io::Error::new(std::io::ErrorKind::UnexpectedEof, message).into());
+    }
+    Ok(())
+}
+
+// Play the fallback message (used during first-time download)
+#[cfg(feature = "playback")]
+fn play_fallback_message() -> Result<(), Error> {
+    debug!("Playing the fallback message");
+
+    // Decode the fallback WAV to audio samples
+    let audio = wav_to_f32(FALLBACK_MESSAGE)?;
+    
+    // Play on the default device, resampled to its rate like any other playback
+    let watch = playback::StopSignal::default().watch();
+    let target = playback::OutputTarget::default();
+    playback::play_in_background(audio, 0.8, target, watch)
+        .map_err(Error::AudioDevice)?
+        .wait()?;
+    
+    Ok(())
+}
+
+// Helper function to convert audio samples to WAV bytes
+fn samples_to_wav_bytes(audio: &[f32], sample_rate: u32) -> Result<Vec<u8>, Error> {
+    let mut cursor = Cursor::new(Vec::new());
+    write_wav_to(&mut cursor, audio, sample_rate)?;
+    Ok(cursor.into_inner())
+}
+
+// Single 16-bit mono WAV encoder shared by files, byte buffers and custom writers
+fn write_wav_to<W: Write + std::io::Seek>(writer: W, audio: &[f32], sample_rate: u32) -> Result<(), Error> {
+    let mut writer = native_wav_writer(writer, sample_rate)?;
+    write_i16_samples(&mut writer, audio)?;
+    writer
+        .finalize()
+        .map_err(|e| Error::io("Failed to finalize WAV", e))
+}
+
+fn native_wav_writer<W: Write + std::io::Seek>(
+    writer: W,
+    sample_rate: u32,
+) -> Result<hound::WavWriter<W>, Error> {
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    hound::WavWriter::new(writer, spec).map_err(|e| Error::io("Failed to create WAV writer", e))
+}
+
+fn write_i16_samples<W: Write + std::io::Seek>(
+    writer: &mut hound::WavWriter<W>,
+    audio: &[f32],
+) -> Result<(), Error> {
+    for sample in audio::to_i16(audio) {
+        writer
+            .write_sample(sample)
+            .map_err(|e| Error::io("Failed to write sample", e))?;
+    }
+    Ok(())
+}
+
+// Crossfades chunks as they arrive like `append_with_crossfade` does on a
+// whole buffer, holding back only the end of the last chunk for the next fade.
+// With an edge ramp it also does the DC cleanup of `PostProcess`, holding back
+// enough for the final ramp to zero.
+struct CrossfadeStream {
+    join: ChunkJoin,
+    tail: Vec<f32>,
+    cleanup: Option<EdgeCleanup>,
+}
+
+struct EdgeCleanup {
+    dc: audio::DcBlocker,
+    ramp: usize, // Samples
+    emitted: usize,
+}
+
+impl EdgeCleanup {
+    // Filter audio that is final, ramping up whatever falls in the first `ramp` samples
+    fn process(&mut self, samples: &mut [f32]) {
+        self.dc.process(samples);
+        let ramped = self.ramp.saturating_sub(self.emitted).min(samples.len());
+        for (i, sample) in samples[..ramped].iter_mut().enumerate() {
+            *sample *= audio::fade_gain(self.emitted + i, self.ramp);
+        }
+        self.emitted += samples.len();
+    }
+}
+
+impl CrossfadeStream {
+    fn new(overlap: usize, edge_ramp: Option<usize>) -> Self {
+        let cleanup = edge_ramp.map(|ramp| EdgeCleanup {
+            dc: audio::DcBlocker::new(),
+            ramp,
+            emitted: 0,
+        });
+        Self { join: ChunkJoin::new(overlap), tail: Vec::new(), cleanup }
+    }
+
+    // Join the next chunk on; returns the audio that is now final
+    fn push(&mut self, samples: &[f32], pause_ms: u32) -> Vec<f32> {
+        self.join.append(&mut self.tail, samples, pause_ms);
+        let hold = self.join.overlap.max(self.cleanup.as_ref().map_or(0, |c| c.ramp));
+        let keep = hold.min(self.tail.len());
+        let mut done: Vec<f32> = self.tail.drain(..self.tail.len() - keep).collect();
+        if let Some(cleanup) = &mut self.cleanup {
+            cleanup.process(&mut done);
+        }
+        done
+    }
+
+    fn finish(mut self) -> Vec<f32> {
+        if let Some(mut cleanup) = self.cleanup {
+            if cleanup.emitted == 0 {
+                // Shorter than the ramps: shrink them to fit, as `audio::fade` does
+                cleanup.dc.process(&mut self.tail);
+                audio::fade(&mut self.tail, cleanup.ramp, cleanup.ramp);
+            } else {
+                cleanup.process(&mut self.tail);
+                audio::fade(&mut self.tail, 0, cleanup.ramp);
+            }
+        }
+        self.tail
+    }
+}
+
+// Append a LIST/INFO chunk to a finished RIFF/WAVE file and fix up the RIFF
+// size. Every chunk is padded to an even length; the pad bytes aren't counted
+// in chunk sizes, the NUL terminating each string is.
+fn append_info_chunk(wav: &mut Vec<u8>, metadata: &WavMetadata) {
+    let mut info = b"INFO".to_vec();
+    for (id, value) in metadata.entries() {
+        let Some(value) = value else { continue };
+        let mut text: Vec<u8> = value.bytes().filter(|&b| b != 0).collect();
+        text.push(0);
+        info.extend_from_slice(id);
+        info.extend_from_slice(&(text.len() as u32).to_le_bytes());
+        info.extend_from_slice(&text);
+        if text.len() % 2 == 1 {
+            info.push(0);
+        }
+    }
+    if info.len() == 4 {
+        return;
+    }
+
+    if wav.len() % 2 == 1 {
+        wav.push(0);
+    }
+    wav.extend_from_slice(b"LIST");
+    wav.extend_from_slice(&(info.len() as u32).to_le_bytes());
+    wav.extend_from_slice(&info);
+    let riff_size = (wav.len() - 8) as u32;
+    wav[4..8].copy_from_slice(&riff_size.to_le_bytes());
+}
+
+// Calendar date (UTC) of a point in time, as YYYY-MM-DD
+fn utc_date(time: std::time::SystemTime) -> String {
+    let secs = time.duration_since(std::time::UNIX_EPOCH).map_or(0, |since| since.as_secs());
+    // Days since 1970-01-01 to a civil date (Howard Hinnant's algorithm)
+    let z = (secs / 86_400) as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+// Convert WAV bytes to f32 samples
+fn wav_to_f32(wav_bytes: &[u8]) -> Result<Vec<f32>, Error> {
+    let cursor = Cursor::new(wav_bytes);
+    let mut reader =
+        hound::WavReader::new(cursor).map_err(|e| Error::io("Failed to read WAV", e))?;
+
+    let samples: Result<Vec<f32>, _> = reader
+        .samples::<i16>()
+        .map(|s: Result<i16, _>| s.map(|sample| sample as f32 / 32768.0))
+        .collect();
+
+    samples.map_err(|e| Error::io("Failed to read samples", e))
+}
+
+// Ogg Opus stream (RFC 7845) of `encode_opus` packets for `sample_count` samples
+#[cfg(feature = "ogg-format")]
+fn write_ogg_opus<W: Write>(writer: W, packets: &[Vec<u8>], sample_count: usize) -> std::io::Result<W> {
+    use ogg::writing::{PacketWriteEndInfo, PacketWriter};
+
+    let mut writer = PacketWriter::new(writer);
+    let serial = 0x6b6f_6b6f; // "koko"
+
+    // Identification header (RFC 7845 section 5.1)
+    let mut head = b"OpusHead".to_vec();
+    head.push(1); // version
+    head.push(1); // channels
+    head.extend_from_slice(&OPUS_PRE_SKIP.to_le_bytes());
+    head.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    head.extend_from_slice(&0i16.to_le_bytes()); // output gain
+    head.push(0); // mapping family: mono/stereo
+    writer.write_packet(head.into_boxed_slice(), serial, PacketWriteEndInfo::EndPage, 0)?;
+
+    let mut tags = b"OpusTags".to_vec();
+    let vendor = b"kokoro-tiny";
+    tags.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+    tags.extend_from_slice(vendor);
+    tags.extend_from_slice(&0u32.to_le_bytes()); // no user comments
+    writer.write_packet(tags.into_boxed_slice(), serial, PacketWriteEndInfo::EndPage, 0)?;
+
+    // Granule positions always count 48kHz samples; the last one ends on the
+    // final input sample, so players trim the padding of the last frame
+    let granule_scale = 48000 / SAMPLE_RATE as u64;
+    for (i, packet) in packets.iter().enumerate() {
+        let last = i + 1 == packets.len();
+        let end_sample = if last {
+            sample_count as u64
+        } else {
+            ((i + 1) * OPUS_FRAME_SAMPLES) as u64
+        };
+        let granule = OPUS_PRE_SKIP as u64 + end_sample * granule_scale;
+        let end_info = if last {
+            PacketWriteEndInfo::EndStream
+        } else {
+            PacketWriteEndInfo::NormalPacket
+        };
+        writer.write_packet(packet.clone().into_boxed_slice(), serial, end_info, granule)?;
+    }
+
+    Ok(writer.into_inner())
+}
+
+// How `save_audio` writes one format
+type AudioSaver = fn(&TtsEngine, &str, &[f32]) -> Result<(), Error>;
+
+// Lowercased extension of `path`, "wav" if it has none
+fn audio_extension(path: &str) -> String {
+    Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("wav")
+        .to_lowercase()
+}
+
+// The writer for `path`'s format, or why it can't be written
+fn audio_saver(path: &str) -> Result<AudioSaver, Error> {
+    let extension = audio_extension(path);
+    match extension.as_str() {
+        "wav" => Ok(TtsEngine::save_wav),
+
+        #[cfg(feature = "symphonia-formats")]
+        "mp3" => Ok(TtsEngine::save_mp3),
+        #[cfg(not(feature = "symphonia-formats"))]
+        "mp3" => Err(Error::InvalidInput("MP3 support not enabled. Add 'symphonia-formats' feature to Cargo.toml".to_string())),
+
+        #[cfg(feature = "opus-format")]
+        "opus" => Ok(|engine, path, audio| engine.save_opus(path, audio, 24000)),
+        #[cfg(not(feature = "opus-format"))]
+        "opus" => {
+            Err(Error::InvalidInput("OPUS support not enabled. Add 'opus-format' feature to Cargo.toml".to_string()))
+        }
+
+        #[cfg(feature = "ogg-format")]
+        "ogg" => Ok(|engine, path, audio| engine.save_ogg(path, audio, OPUS_DEFAULT_BITRATE)),
+        #[cfg(not(feature = "ogg-format"))]
+        "ogg" => Err(Error::InvalidInput("OGG support not enabled. Add 'ogg-format' feature to Cargo.toml".to_string())),
+
+        "flac" => Err(Error::InvalidInput("FLAC format not yet supported".to_string())),
+
+        _ => Err(Error::InvalidInput(format!("Unsupported audio format: {}", extension))),
+    }
+}
+
+// Text normalization covers English and (numbers only) Spanish; other
+// languages go to espeak as written
+fn normalize_for_lang(normalizer: &text::Normalizer, text: &str, lang: &str) -> String {
+    if lang.starts_with("en-gb") {
+        normalizer.normalize_dialect(text, text::Dialect::British)
+    } else if lang.starts_with("en") {
+        normalizer.normalize(text)
+    } else if lang.starts_with("es") {
+        text::normalize_es(text)
+    } else {
+        text.to_string()
+    }
+}
+
+// Decide how text is fed to the model: short text goes in one pass for
+// predictable cadence, long text is split at sentence/clause boundaries
+fn prepare_chunks(text: &str) -> Vec<String> {
+    if !needs_chunking(text) {
+        return vec![text.to_string()];
+    }
+
+    split_text_for_tts(text, MAX_CHARS_PER_CHUNK)
+        .into_iter()
+        .filter(|chunk| !chunk.trim().is_empty())
+        .collect()
+}
+
+fn needs_chunking(text: &str) -> bool {
+    text.chars().count() > LONG_TEXT_THRESHOLD || text.lines().count() > 3
+}
+
+// Split text after punctuation that has a configured pause. Each piece keeps
+// its punctuation so the model still shapes the intonation around it.
+fn split_at_pauses(text: &str, pauses: &PauseConfig) -> Vec<(String, u32)> {
+    if *pauses == PauseConfig::default() {
+        return vec![(text.to_string(), 0)];
+    }
+
+    let chars: Vec<char> = text.chars().collect();
+    let mut pieces = Vec::new();
+    let mut current = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        current.push(c);
+
+        let (pause, len) = if c == '.' && chars[i..].starts_with(&['.', '.', '.']) {
+            current.push_str("..");
+            (pauses.ellipsis_ms, 3)
+        } else {
+            let pause = match c {
+                ',' | ';' | ':' => pauses.comma_ms,
+                '.' | '!' => pauses.period_ms,
+                '?' => pauses.question_ms,
+                '…' => pauses.ellipsis_ms,
+                _ => 0,
+            };
+            (pause, 1)
+        };
+        i += len;
+
+        let at_boundary = chars.get(i).is_none_or(|next| next.is_whitespace())
+            && !(c == '.' && len == 1 && text::ends_with_abbreviation(&current));
+        if pause > 0 && at_boundary && !current.trim().is_empty() {
+            pieces.push((current.trim().to_string(), pause));
+            current.clear();
+        }
+    }
+    if !current.trim().is_empty() {
+        pieces.push((current.trim().to_string(), 0));
+    }
+    pieces
+}
+
+// Extend a chunk with its configured pause, so the joined output grows by exactly `pause_ms`
+fn append_pause(samples: &mut Vec<f32>, pause_ms: u32) {
+    samples.extend(silence(pause_ms));
+}
+
+// An empty chunk with a pause is a leading pause marker: its audio is the
+// pause alone, with no model run
+fn is_silence(chunk: &str, pause_ms: u32) -> bool {
+    chunk.is_empty() && pause_ms > 0
+}
+
+// Map user-facing speed to the model's speed input (user 1.0 = model 1.0 * SPEED_SCALE).
+// The model scales its predicted phoneme durations by it, so the voice keeps its
+// pitch and timbre; outside MIN/MAX_ENGINE_SPEED it stops sounding natural, so
+// speeds there are clamped, and speeds that mean nothing are an error.
+fn model_speed(speed: f32) -> Result<f32, Error> {
+    if !(speed.is_finite() && speed > 0.0) {
+        return Err(Error::InvalidInput(format!("Invalid speed {}: must be above 0", speed)));
+    }
+    Ok((speed * SPEED_SCALE).clamp(MIN_ENGINE_SPEED, MAX_ENGINE_SPEED))
+}
+
+// Processors added with `TtsEngine::add_processor`, in order, shared with the
+// post-processing snapshots that deferred synthesis carries to other threads
+#[derive(Clone, Default)]
+struct Processors(Arc<Mutex<Vec<Box<dyn audio::AudioProcessor>>>>);
+
+impl Processors {
+    fn run(&self, samples: &mut Vec<f32>) {
+        // A processor that panicked once still gets the next audio
+        let mut processors = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        for processor in processors.iter_mut() {
+            processor.process(samples, SAMPLE_RATE);
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+}
+
+impl std::fmt::Debug for Processors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Processors({})", self.len())
+    }
+}
+
+// Whole-buffer post-processing for finished audio (streamed chunks skip all
+// but the processors)
+#[derive(Clone, Debug)]
+struct PostProcess {
+    trim_silence_db: Option<f32>,
+    target_loudness: Option<f32>,
+    fade_in: usize, // Samples
+    fade_out: usize,
+    dc_cleanup: bool,
+    edge_silence: Option<(usize, usize)>, // Samples
+    processors: Processors,
+}
+
+impl PostProcess {
+    // Remove DC, trim, then normalize (so edge silence doesn't skew short-clip
+    // loudness), then fade, set the edge silence and zero the ends of what is
+    // left, and last run the processors. Returns how many leading samples were
+    // removed (negative if added) before the processors.
+    fn apply(&self, audio: &mut Vec<f32>) -> isize {
+        let mut removed = 0;
+        if self.dc_cleanup {
+            audio::remove_dc(audio);
+        }
+        if let Some(threshold_db) = self.trim_silence_db {
+            let keep = audio::trim_silence(audio, threshold_db, SILENCE_KEEP_MS);
+            removed = keep.start as isize;
+            audio.truncate(keep.end);
+            audio.drain(..keep.start);
+        }
+        if let Some(lufs) = self.target_loudness {
+            audio::normalize_loudness(audio, lufs);
+        }
+        audio::fade(audio, self.fade_in, self.fade_out);
+        if let Some((leading, trailing)) = self.edge_silence {
+            let threshold_db = self.trim_silence_db.unwrap_or(EDGE_SILENCE_DB);
+            let speech = audio::trim_silence(audio, threshold_db, 0);
+            // The end first, so `speech.start` still points at the same sample
+            audio.resize(speech.end + trailing, 0.0);
+            if speech.start > leading {
+                audio.drain(..speech.start - leading);
+            } else {
+                audio.splice(..0, std::iter::repeat_n(0.0, leading - speech.start));
+            }
+            removed += speech.start as isize - leading as isize;
+        }
+        if self.dc_cleanup {
+            let ramp = silence(EDGE_RAMP_MS).len();
+            audio::fade(audio, ramp, ramp);
+        }
+        self.processors.run(audio);
+        removed
+    }
+}
+
+// Silence of the given length at the model sample rate
+fn silence(ms: u32) -> Vec<f32> {
+    vec![0.0; (SAMPLE_RATE as usize * ms as usize) / 1000]
+}
+
+// Joins chunks like `append_with_crossfade`, except at an inserted pause: the
+// chunk after one, or a chunk that is only a pause, goes on without overlap,
+// since fading across the pause would shorten it
+struct ChunkJoin {
+    overlap: usize,
+    after_pause: bool,
+}
+
+impl ChunkJoin {
+    fn new(overlap: usize) -> Self {
+        Self { overlap, after_pause: false }
+    }
+
+    // Join `samples`, which end in `pause_ms` of inserted silence, onto `buffer`
+    fn append(&mut self, buffer: &mut Vec<f32>, samples: &[f32], pause_ms: u32) {
+        if samples.is_empty() {
+            return;
+        }
+        let pause_len = (SAMPLE_RATE as usize * pause_ms as usize) / 1000;
+        let only_pause = pause_ms > 0 && samples.len() <= pause_len;
+        let overlap = if self.after_pause || only_pause { 0 } else { self.overlap };
+        append_with_crossfade(buffer, samples, overlap);
+        self.after_pause = pause_ms > 0;
+    }
+}
+
+fn append_with_crossfade(buffer: &mut Vec<f32>, next: &[f32], overlap_samples: usize) {
+    if next.is_empty() {
+        return;
+    }
+
+    if buffer.is_empty() || overlap_samples == 0 {
+        buffer.extend_from_slice(next);
+        return;
+    }
+
+    let overlap = overlap_samples.min(buffer.len()).min(next.len());
+    if overlap == 0 {
+        buffer.extend_from_slice(next);
+        return;
+    }
+
+    let start = buffer.len() - overlap;
+    for i in 0..overlap {
+        let fade_in = i as f32 / overlap as f32;
+        let fade_out = 1.0 - fade_in;
+        buffer[start + i] = buffer[start + i] * fade_out + next[i] * fade_in;
+    }
+
+    buffer.extend_from_slice(&next[overlap..]);
+}
+
+// Split text into chunks for better synthesis
+// Kokoro model handles shorter text better without dropping words
+fn split_text_for_tts(text: &str, max_chars: usize) -> Vec<String> {
+    // First try to split by sentences
+    let mut chunks = Vec::new();
+    let mut current_chunk = String::new();
+
+    for full_sentence in split_sentences(text) {
+
+        // If this sentence alone is too long, split it by commas or words
+        if full_sentence.len() > max_chars {
+            // Try splitting by commas first
+            let parts: Vec<&str> = full_sentence.split(',').collect();
+            if parts.len() > 1 {
+                for part in parts {
+                    if part.trim().len() > max_chars {
+                        // Still too long, split by words
+                        chunks.extend(split_by_words(part, max_chars));
+                    } else if !part.trim().is_empty() {
+                        chunks.push(part.trim().to_string());
+                    }
+                }
+            } else {
+                // No commas, split by words
+                chunks.extend(split_by_words(&full_sentence, max_chars));
+            }
+        }
+        // If adding this sentence would make chunk too long, save current and start new
+        else if !current_chunk.is_empty()
+            && current_chunk.len() + full_sentence.len() + 1 > max_chars
+        {
+            chunks.push(current_chunk.trim().to_string());
+            current_chunk = full_sentence;
+        }
+        // Add to current chunk
+        else {
+            if !current_chunk.is_empty() {
+                current_chunk.push(' ');
+            }
+            current_chunk.push_str(&full_sentence);
+        }
+    }
+
+    // Don't forget the last chunk
+    if !current_chunk.is_empty() {
+        chunks.push(current_chunk.trim().to_string());
+    }
+
+    // If no chunks were created (text had no sentence endings), split by words
+    if chunks.is_empty() && !text.trim().is_empty() {
+        chunks = split_by_words(text, max_chars);
+    }
+
+    chunks
+}
+
+// Split text into sentences, each keeping its closing punctuation and quotes.
+// A period only ends a sentence before whitespace (not "3.14") and not after
+// an abbreviation or initials like "Dr.", "e.g." or "J. R. R." (non-English
+// text isn't expanded first). An ellipsis or closing quote followed by a
+// lowercase word continues the sentence ("I was... thinking", "\"Stop!\" he said").
+fn split_sentences(text: &str) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut sentences = Vec::new();
+    let mut current = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        current.push(chars[i]);
+        i += 1;
+        if !matches!(chars[i - 1], '.' | '!' | '?' | '…') {
+            continue;
+        }
+        // "?!" and "..." stay together
+        while i < chars.len() && matches!(chars[i], '.' | '!' | '?' | '…') {
+            current.push(chars[i]);
+            i += 1;
+        }
+        let abbreviation = text::ends_with_abbreviation(&current);
+        let ellipsis = current.ends_with("...") || current.ends_with('…');
+        let mut quoted = false;
+        while i < chars.len() && SENTENCE_CLOSERS.contains(chars[i]) {
+            current.push(chars[i]);
+            i += 1;
+            quoted = true;
+        }
+        let at_boundary = chars.get(i).is_none_or(|c| c.is_whitespace());
+        let continues = (ellipsis || quoted)
+            && chars[i..]
+                .iter()
+                .find(|c| !c.is_whitespace())
+                .is_some_and(|c| c.is_lowercase());
+        if at_boundary && !abbreviation && !continues {
+            if !current.trim().is_empty() {
+                sentences.push(current.trim().to_string());
+            }
+            current.clear();
+        }
+    }
+    if !current.trim().is_empty() {
+        sentences.push(current.trim().to_string());
+    }
+    sentences
+}
+
+// Split text until every piece has at most `max_tokens` by `count`: first at
+// sentence ends, then after clause punctuation, then between words, and only
+// as a last resort between the characters of a word
+fn split_to_fit(
+    text: &str,
+    max_tokens: usize,
+    count: &mut dyn FnMut(&str) -> Result<usize, Error>,
+) -> Result<Vec<String>, Error> {
+    let tokens = count(text)?;
+    split_to_fit_at(text, tokens, 0, max_tokens, count)
+}
+
+fn split_to_fit_at(
+    text: &str,
+    tokens: usize,
+    level: usize,
+    max_tokens: usize,
+    count: &mut dyn FnMut(&str) -> Result<usize, Error>,
+) -> Result<Vec<String>, Error> {
+    if tokens <= max_tokens {
+        return Ok(vec![text.to_string()]);
+    }
+    let units = match level {
+        0 => split_sentences(text),
+        1 => split_clauses(text),
+        2 => text.split_whitespace().map(str::to_string).collect(),
+        3 => split_word_chars(text),
+        // A single character too long for the limit goes as it is
+        _ => return Ok(vec![text.to_string()]),
+    };
+    if level >= 2 {
+        return pack_to_fit(units, level, max_tokens, count);
+    }
+
+    // espeak phonemizes each sentence and clause on its own, so joined ones
+    // come to their tokens plus one per space: count every unit once and
+    // pack by the sums, rather than phonemizing every candidate piece
+    let mut groups: Vec<(Vec<String>, usize)> = Vec::new();
+    for unit in units {
+        let unit_tokens = count(&unit)?;
+        match groups.last_mut() {
+            Some((group, tokens)) if *tokens + 1 + unit_tokens <= max_tokens => {
+                group.push(unit);
+                *tokens += 1 + unit_tokens;
+            }
+            _ => groups.push((vec![unit], unit_tokens)),
+        }
+    }
+    let mut pieces = Vec::new();
+    for (group, tokens) in groups {
+        let joined = group.join(" ");
+        if group.len() == 1 {
+            pieces.extend(split_to_fit_at(&joined, tokens, level + 1, max_tokens, count)?);
+        } else if count(&joined)? <= max_tokens {
+            pieces.push(joined);
+        } else {
+            // The sum was off after all: pack this stretch piece by piece
+            pieces.extend(pack_to_fit(group, level, max_tokens, count)?);
+        }
+    }
+    Ok(pieces)
+}
+
+// Pack as many `units` of `level` as fit into each piece, counting every
+// candidate piece, and split the units that don't fit alone a level further
+fn pack_to_fit(
+    units: Vec<String>,
+    level: usize,
+    max_tokens: usize,
+    count: &mut dyn FnMut(&str) -> Result<usize, Error>,
+) -> Result<Vec<String>, Error> {
+    let separator = if level == 3 { "" } else { " " };
+    let mut pieces = Vec::new();
+    let mut current = String::new();
+    for unit in units {
+        if !current.is_empty() {
+            let joined = format!("{}{}{}", current, separator, unit);
+            if count(&joined)? <= max_tokens {
+                current = joined;
+                continue;
+            }
+            pieces.push(std::mem::take(&mut current));
+        }
+        let unit_tokens = count(&unit)?;
+        if unit_tokens <= max_tokens {
+            current = unit;
+        } else {
+            pieces.extend(split_to_fit_at(&unit, unit_tokens, level + 1, max_tokens, count)?);
+        }
+    }
+    if !current.is_empty() {
+        pieces.push(current);
+    }
+    Ok(pieces)
+}
+
+// Split a sentence after commas, semicolons and colons that end a clause
+// ("1,000" stays whole), each clause keeping its punctuation
+fn split_clauses(text: &str) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut clauses = Vec::new();
+    let mut current = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        current.push(c);
+        let at_boundary = chars.get(i + 1).is_none_or(|next| next.is_whitespace());
+        if matches!(c, ',' | ';' | ':') && at_boundary && !current.trim().is_empty() {
+            clauses.push(current.trim().to_string());
+            current.clear();
+        }
+    }
+    if !current.trim().is_empty() {
+        clauses.push(current.trim().to_string());
+    }
+    clauses
+}
+
+// Characters of a word, keeping inline phoneme markers ("⟦read|ɹˈɛd⟧") whole
+fn split_word_chars(word: &str) -> Vec<String> {
+    let mut units = Vec::new();
+    let mut marker: Option<String> = None;
+    for c in word.chars() {
+        match marker.as_mut() {
+            Some(m) => {
+                m.push(c);
+                if c == text::PHONEME_CLOSE {
+                    units.extend(marker.take());
+                }
+            }
+            None if c == text::PHONEME_OPEN => marker = Some(c.to_string()),
+            None => units.push(c.to_string()),
+        }
+    }
+    units.extend(marker);
+    units
+}
+
+// Split text by words when sentences are too long
+fn split_by_words(text: &str, max_chars: usize) -> Vec<String> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+
+    for word in words {
+        if current.len() + word.len() + 1 > max_chars && !current.is_empty() {
+            chunks.push(current.trim().to_string());
+            current = word.to_string();
+        } else {
+            if !current.is_empty() {
+                current.push(' ');
+            }
+            current.push_str(word);
+        }
+    }
+
+    if !current.is_empty() {
+        chunks.push(current.trim().to_string());
+    }
+
+    chunks
+}
+
+// Amplify audio - allows some clipping for maximum loudness
+fn amplify_audio(audio: &[f32], gain: f32) -> Vec<f32> {
+    audio
+        .iter()
+        .map(|&sample| {
+            let amplified = sample * gain;
+
+            // Simple hard clipping at the limits
+            // This allows maximum volume even if it distorts a bit
+            amplified.clamp(-1.0, 1.0)
+        })
+        .collect()
+}
+
+// BabyTts implementation for mem8 integration
+impl BabyTts {
+    /// Create a new baby TTS for mem8 learning
+    pub async fn new() -> Result<Self, Error> {
+        let engine = TtsEngine::new().await?;
+        Ok(Self {
+            engine,
+            max_words: 5,                // Babies start with short phrases
+            voice: "af_sky".to_string(), // Gentle voice for baby
+            speed: 0.9,                  // Slightly slower for clarity
+            gain: 1.8,                   // Louder for clarity
+            lang: DEFAULT_LANG.to_string(),
+            vocabulary: BTreeMap::new(),
+            phoneme_durations: BTreeMap::new(),
+            child_voice: Some(ChildVoiceConfig::default()),
+        })
+    }
+
+    /// Create with custom settings
+    pub async fn with_settings(
+        max_words: usize,
+        voice: &str,
+        speed: f32,
+        gain: f32,
+    ) -> Result<Self, Error> {
+        let engine = TtsEngine::new().await?;
+        Ok(Self {
+            engine,
+            max_words,
+            voice: voice.to_string(),
+            speed,
+            gain,
+            lang: DEFAULT_LANG.to_string(),
+            vocabulary: BTreeMap::new(),
+            phoneme_durations: BTreeMap::new(),
+            child_voice: Some(ChildVoiceConfig::default()),
+        })
+    }
+
+    /// Create a baby that picks up where `save_state` left off
+    pub async fn from_state(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let mut baby = Self::new().await?;
+        baby.load_state(path)?;
+        Ok(baby)
+    }
+
+    /// Save what the baby has learned (vocabulary, phoneme durations and growth) and its voice
+    /// settings as versioned JSON, to survive a restart
+    pub fn save_state(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let path = path.as_ref();
+        let state = BabyState {
+            version: BABY_STATE_VERSION,
+            max_words: self.max_words,
+            voice: self.voice.clone(),
+            speed: self.speed,
+            gain: self.gain,
+            lang: self.lang.clone(),
+            vocabulary: self.vocabulary.clone(),
+            phoneme_durations: self.phoneme_durations.clone(),
+        };
+        let json = serde_json::to_string_pretty(&state)
+            .map_err(|e| Error::io("Failed to encode baby state", e))?;
+        fs::write(path, json)
+            .map_err(|e| Error::io(format_args!("Failed to write baby state {}", path.display()), e))
+    }
+
+    /// Replace the baby's vocabulary, growth and voice settings with a saved
+    /// state; the engine stays as it is
+    ///
+    /// Fails without changing anything if the file isn't a baby state or was
+    /// written by a newer kokoro-tiny with a state version this one can't read.
+    /// Version 1 files, from before phoneme durations were learned, load with none.
+    pub fn load_state(&mut self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let path = path.as_ref();
+        let json = fs::read_to_string(path)
+            .map_err(|e| Error::io(format_args!("Failed to read baby state {}", path.display()), e))?;
+        let invalid = |reason: String| {
+            Error::InvalidInput(format!("{} is not a usable baby state: {}", path.display(), reason))
+        };
+        let value: serde_json::Value = serde_json::from_str(&json).map_err(|e| invalid(e.to_string()))?;
+        match value.get("version").and_then(serde_json::Value::as_u64) {
+            Some(version) if (1..=BABY_STATE_VERSION as u64).contains(&version) => {}
+            Some(0) => return Err(invalid("state version 0 does not exist".to_string())),
+            Some(version) => {
+                return Err(invalid(format!(
+                    "state version {} is newer than this kokoro-tiny reads (up to {})",
+                    version, BABY_STATE_VERSION
+                )))
+            }
+            None => return Err(invalid("no state version".to_string())),
+        }
+        let state: BabyState = serde_json::from_value(value).map_err(|e| invalid(e.to_string()))?;
+
+        self.max_words = state.max_words;
+        self.voice = state.voice;
+        self.speed = state.speed;
+        self.gain = state.gain;
+        self.lang = state.lang;
+        self.vocabulary = state.vocabulary;
+        self.phoneme_durations = state.phoneme_durations;
+        Ok(())
+    }
+
+    /// Speak a simple utterance (for mem8 baby learning)
+    ///
+    /// Text longer than the stage allows is cut down as `speak_detailed` describes.
+    pub fn speak(&mut self, text: &str) -> Result<Vec<f32>, Error> {
+        Ok(self.speak_detailed(text)?.samples)
+    }
+
+    /// `speak`, also reporting what was actually said
+    ///
+    /// A baby that is Babbling babbles instead. Otherwise, with more words than
+    /// `max_words`, it keeps the most salient ones in their original order: the
+    /// words it heard most in `learn_from_audio`, then content words before
+    /// function words ("the", "to", ...), then longer words, then earlier ones.
+    /// At SingleWords, "I want cookies" becomes "cookies".
+    ///
+    /// Once `learn_from_audio` has timed some phonemes, the speed follows
+    /// them: text whose phonemes came out twice as long is spoken at half
+    /// `speed`. The model takes one speed per utterance, so this is the mean
+    /// of the spoken phonemes' factors rather than a per-phoneme stretch.
+    pub fn speak_detailed(&mut self, text: &str) -> Result<BabySpeech, Error> {
+        let (spoken, reduced) = self.reduce(text);
+
+        let samples = self.say(&spoken, self.learned_speed(&spoken)?)?;
+        Ok(BabySpeech { samples, spoken, reduced })
+    }
+
+    /// Current developmental stage, from `max_words`
+    pub fn stage(&self) -> DevelopmentStage {
+        DevelopmentStage::of(self.max_words)
+    }
+
+    /// Jump to the start of `stage`; see `DevelopmentStage` for the word limits
+    pub fn set_stage(&mut self, stage: DevelopmentStage) {
+        self.max_words = stage.first_word_limit();
+    }
+
+    /// Get raw audio samples at 24kHz (for mem8 processing)
+    pub fn get_audio_params(&self) -> (u32, u16, u16) {
+        (SAMPLE_RATE, 1, 16) // 24kHz, mono, 16-bit
+    }
+
+    /// Learn from a recording of `text` (24kHz mono): remember its words, and
+    /// how long their phonemes took compared with the engine's usual pace
+    ///
+    /// The alignment is coarse. Quiet stretches of at least 60ms split the
+    /// recording into sounds; with one sound per word each word is timed on
+    /// its own, otherwise the whole recording sets the pace of every word.
+    /// Each phoneme in a word moves 30% of the way towards that word's
+    /// stretch, kept within 0.5-2.0 (see `phoneme_durations`).
+    ///
+    /// A silent recording, text without words, or speech more than three
+    /// times longer or shorter than the text should take is refused with
+    /// `InvalidInput`, and nothing is learned from it.
+    pub fn learn_from_audio(&mut self, audio: &[f32], text: &str) -> Result<LearningReport, Error> {
+        let words: Vec<&str> =
+            text.split_whitespace().map(word_core).filter(|word| !word.is_empty()).collect();
+        if words.is_empty() {
+            return Err(Error::InvalidInput("Nothing to learn: the text has no words".to_string()));
+        }
+        let segments = audio::voiced_segments(audio, LEARN_VOICED_DB, LEARN_WORD_GAP_MS);
+        let (Some(first), Some(last)) = (segments.first(), segments.last()) else {
+            return Err(Error::InvalidInput("Nothing to learn: the recording is silent".to_string()));
+        };
+        let seconds = |samples: usize| samples as f32 / SAMPLE_RATE as f32;
+        let speech_secs = seconds(last.end - first.start);
+
+        let mut phonemes = Vec::with_capacity(words.len());
+        for word in &words {
+            let word_phonemes = self.engine.phonemize(word, Some(&self.lang))?;
+            phonemes.push(word_phonemes.iter().flat_map(|p| phoneme_symbols(p)).collect::<String>());
+        }
+        let expected = |symbols: &str| symbols.chars().count() as f32 / SPOKEN_TOKENS_PER_SEC;
+        let expected_secs: f32 = phonemes.iter().map(|symbols| expected(symbols)).sum();
+        let stretch = speech_secs / expected_secs;
+        if expected_secs <= 0.0 || !(1.0 / LEARN_MAX_STRETCH..=LEARN_MAX_STRETCH).contains(&stretch) {
+            return Err(Error::InvalidInput(format!(
+                "Recording doesn't fit the text: {:.2}s of speech for about {:.2}s of \"{}\"",
+                speech_secs, expected_secs, text
+            )));
+        }
+        debug!(text, speech_secs, "Baby learning from a recording");
+
+        let aligned_words = if segments.len() == words.len() { words.len() } else { 0 };
+        let mut before = BTreeMap::new();
+        for (i, symbols) in phonemes.iter().enumerate() {
+            let ratio = match aligned_words {
+                0 => stretch,
+                _ => seconds(segments[i].len()) / expected(symbols),
+            };
+            let unique: BTreeSet<char> = symbols.chars().collect();
+            for phoneme in unique {
+                let factor = self.phoneme_durations.entry(phoneme).or_insert(1.0);
+                before.entry(phoneme).or_insert(*factor);
+                *factor += LEARN_RATE * (ratio - *factor);
+                *factor = factor.clamp(LEARN_MIN_FACTOR, LEARN_MAX_FACTOR);
+            }
+        }
+        let updated = before
+            .into_iter()
+            .map(|(phoneme, before)| {
+                PhonemeUpdate { phoneme, before, after: self.phoneme_durations[&phoneme] }
+            })
+            .filter(|update| update.after != update.before)
+            .collect();
+
+        let mut new_words = Vec::new();
+        for word in words {
+            let count = self.vocabulary.entry(word.to_lowercase()).or_insert(0);
+            if *count == 0 {
+                new_words.push(word.to_lowercase());
+            }
+            *count += 1;
+        }
+        Ok(LearningReport { speech_secs, expected_secs, aligned_words, updated, new_words })
+    }
+
+    // `speed`, slowed or sped up by the learned durations of the phonemes in `text`
+    fn learned_speed(&self, text: &str) -> Result<f32, Error> {
+        if self.phoneme_durations.is_empty() {
+            return Ok(self.speed);
+        }
+        let phonemes = self.engine.phonemize(text, Some(&self.lang))?;
+        let factors: Vec<f32> = phonemes
+            .iter()
+            .flat_map(|p| phoneme_symbols(p))
+            .map(|phoneme| self.phoneme_durations.get(&phoneme).copied().unwrap_or(1.0))
+            .collect();
+        match factors.len() {
+            0 => Ok(self.speed),
+            n => Ok(self.speed * n as f32 / factors.iter().sum::<f32>()),
+        }
+    }
+
+    /// Babble - generate random baby sounds (for early development stages)
+    ///
+    /// Different babbling on each call, as `BabbleConfig::default()` describes.
+    pub fn babble(&mut self) -> Result<Vec<f32>, Error> {
+        self.babble_with(&BabbleConfig::default())
+    }
+
+    /// Babble as `config` says, with the baby's voice settings at any stage
+    pub fn babble_with(&mut self, config: &BabbleConfig) -> Result<Vec<f32>, Error> {
+        config.validate()?;
+        self.say(&config.utterance(), self.speed)
+    }
+
+    // Synthesize with the baby's settings at `speed`, in its child voice
+    fn say(&self, text: &str, speed: f32) -> Result<Vec<f32>, Error> {
+        if let Some(child) = &self.child_voice {
+            child.validate()?;
+        }
+        let samples = self.engine.synthesize_with_options(
+            text,
+            Some(&self.voice),
+            self.child_speed(speed),
+            self.gain,
+            Some(&self.lang),
+        )?;
+        self.childlike(samples)
+    }
+
+    // `speed` with the child voice's speed-up
+    pub(crate) fn child_speed(&self, speed: f32) -> f32 {
+        speed * self.child_voice.map_or(1.0, |child| child.speed)
+    }
+
+    // Synthesized speech in the child voice; the fallback message stays as recorded
+    pub(crate) fn childlike(&self, samples: Vec<f32>) -> Result<Vec<f32>, Error> {
+        match &self.child_voice {
+            Some(child) if !self.engine.is_fallback() => child.apply(&samples),
+            _ => Ok(samples),
+        }
     }
 
-    fn tokenize(&self, text: String) -> Vec<i64> {
-        text.chars()
-            .map(|c| *self.vocab.get(&c).unwrap_or(&0))
-            .collect()
+    // Limit to max_words for baby speech, and whether that changed anything
+    fn reduce(&self, text: &str) -> (String, bool) {
+        if self.stage() == DevelopmentStage::Babbling {
+            return (BabbleConfig::default().utterance(), true);
+        }
+        let words: Vec<&str> = text.split_whitespace().collect();
+        if words.len() <= self.max_words {
+            return (text.to_string(), false);
+        }
+        debug!(max_words = self.max_words, "Baby mode: limiting the words spoken");
+
+        // Stable, so equally salient words keep their order
+        let mut ranked: Vec<usize> = (0..words.len()).collect();
+        ranked.sort_by_key(|&i| std::cmp::Reverse(self.salience(words[i])));
+        let mut kept = ranked[..self.max_words].to_vec();
+        kept.sort_unstable();
+        (kept.iter().map(|&i| words[i]).collect::<Vec<_>>().join(" "), true)
     }
 
-    fn run_inference(
-        &self,
-        session: &Arc<Mutex<Session>>,
-        tokens: Vec<i64>,
-        style: Vec<f32>,
-        speed: f32,
-    ) -> Result<Vec<f32>, String> {
-        let mut session = session
-            .lock()
-            .map_err(|e| format!("Failed to lock session: {}", e))?;
-
-        let token_count = tokens.len(); // Save count before moving
-
-        // Prepare tokens tensor
-        let tokens_array = ndarray::Array2::from_shape_vec((1, tokens.len()), tokens)
-            .map_err(|e| format!("Failed to create tokens array: {}", e))?;
-        let tokens_tensor = Tensor::from_array(tokens_array)
-            .map_err(|e| format!("Failed to create tokens tensor: {}", e))?;
-
-        // Prepare style tensor
-        let style_array = ndarray::Array2::from_shape_vec((1, style.len()), style)
-            .map_err(|e| format!("Failed to create style array: {}", e))?;
-        let style_tensor = Tensor::from_array(style_array)
-            .map_err(|e| format!("Failed to create style tensor: {}", e))?;
-
-        // Prepare speed tensor
-        let speed_array = ndarray::Array1::from_vec(vec![speed]);
-        let speed_tensor = Tensor::from_array(speed_array)
-            .map_err(|e| format!("Failed to create speed tensor: {}", e))?;
-
-        // Create inputs
-        use std::borrow::Cow;
-        let inputs = SessionInputs::from(vec![
-            (
-                Cow::Borrowed("tokens"),
-                SessionInputValue::Owned(Value::from(tokens_tensor)),
-            ),
-            (
-                Cow::Borrowed("style"),
-                SessionInputValue::Owned(Value::from(style_tensor)),
-            ),
-            (
-                Cow::Borrowed("speed"),
-                SessionInputValue::Owned(Value::from(speed_tensor)),
-            ),
-        ]);
+    // How much a word matters to the baby, highest first: how often it was
+    // heard, whether it's a content word, and its length
+    fn salience(&self, word: &str) -> (u32, bool, usize) {
+        let core = word_core(word).to_lowercase();
+        let heard = self.vocabulary.get(&core).copied().unwrap_or(0);
+        (heard, !FUNCTION_WORDS.contains(&core.as_str()), core.chars().count())
+    }
 
-        // Run inference
-        let outputs = session
-            .run(inputs)
-            .map_err(|e| format!("Failed to run inference: {}", e))?;
-
-        // Extract audio
-        let (shape, data) = outputs["audio"]
-            .try_extract_tensor::<f32>()
-            .map_err(|e| format!("Failed to extract audio tensor: {}", e))?;
-
-        // Debug output shape for longer text
-        let data_vec = data.to_vec();
-        #[cfg(not(feature = "as-lib"))]
-        if token_count > 100 {
-            eprintln!(
-                "   Output audio shape: {:?}, samples: {}",
-                shape,
-                data_vec.len()
-            );
-        }
+    /// Echo mode - repeat what was heard (for learning)
+    pub fn echo(&mut self, text: &str) -> Result<Vec<f32>, Error> {
+        // Simple echo with slightly different intonation
+        let echo_speed = self.speed * 1.1; // Slightly faster for echo
+        self.say(text, echo_speed)
+    }
 
-        Ok(data_vec)
+    /// Grow vocabulary - increase max words as baby learns
+    pub fn grow(&mut self) {
+        self.max_words = (self.max_words + 1).min(20); // Cap at 20 words for kokoro-tiny
+        debug!(max_words = self.max_words, "Baby grew: more words at once");
     }
 }
 
-// Helper functions
+// Audio ducking functions - reduce system volume during TTS playback
+#[cfg(feature = "ducking")]
+fn duck_system_audio(level: f32) -> Result<(), Error> {
+    // Calculate how many volume-down presses we need
+    // Assuming each press is ~6% volume change on most systems
+    let steps = ((1.0 - level) * 16.0) as u32; // 16 steps = ~100% volume range
 
-// Build proper vocabulary for tokenization (matching original Kokoros)
-fn build_vocab() -> HashMap<char, i64> {
-    let pad = "$";
-    let punctuation = r#";:,.!?¡¿—…"«»"" "#;
-    let letters = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
-    let letters_ipa = "ɑɐɒæɓʙβɔɕçɗɖðʤəɘɚɛɜɝɞɟʄɡɠɢʛɦɧħɥʜɨɪʝɭɬɫɮʟɱɯɰŋɳɲɴøɵɸθœɶʘɹɺɾɻʀʁɽʂʃʈʧʉʊʋⱱʌɣɤʍχʎʏʑʐʒʔʡʕʢǀǁǂǃˈˌːˑʼʴʰʱʲʷˠˤ˞↓↑→↗↘'̩'ᵻ";
+    let mut enigo = Enigo::new(&Settings::default())
+        .map_err(|e| format!("Failed to initialize Enigo: {:?}", e))?;
 
-    let symbols: String = [pad, punctuation, letters, letters_ipa].concat();
+    // Press volume down keys to reduce system volume
+    for _ in 0..steps {
+        enigo
+            .key(Key::VolumeDown, enigo::Direction::Click)
+            .map_err(|e| format!("Failed to press volume down: {:?}", e))?;
+        thread::sleep(Duration::from_millis(20)); // Small delay between key presses
+    }
 
-    symbols
-        .chars()
-        .enumerate()
-        .map(|(idx, c)| (c, idx as i64))
-        .collect()
+    Ok(())
 }
 
-// Load voices from binary file
-fn load_voices(path: &str) -> Result<HashMap<String, Vec<f32>>, String> {
-    let mut file = File::open(path).map_err(|e| format!("Failed to open voices file: {}", e))?;
-
-    let mut reader =
-        NpzReader::new(&mut file).map_err(|e| format!("Failed to create NPZ reader: {}", e))?;
-
-    let mut voices = HashMap::new();
+#[cfg(feature = "ducking")]
+fn restore_system_audio(level: f32) -> Result<(), Error> {
+    // Calculate how many volume-up presses to restore
+    let steps = ((1.0 - level) * 16.0) as u32;
 
-    for name in reader
-        .names()
-        .map_err(|e| format!("Failed to read NPZ names: {:?}", e))?
-    {
-        let array: ArrayBase<OwnedRepr<f32>, IxDyn> = reader
-            .by_name(&name)
-            .map_err(|e| format!("Failed to read NPZ array {}: {:?}", name, e))?;
-        let data: Vec<f32> = array.iter().cloned().collect();
+    let mut enigo = Enigo::new(&Settings::default())
+        .map_err(|e| format!("Failed to initialize Enigo: {:?}", e))?;
 
-        // Clean up the name (remove .npy extension if present)
-        let clean_name = name.trim_end_matches(".npy");
-        voices.insert(clean_name.to_string(), data);
+    // Press volume up keys to restore system volume
+    for _ in 0..steps {
+        enigo
+            .key(Key::VolumeUp, enigo::Direction::Click)
+            .map_err(|e| format!("Failed to press volume up: {:?}", e))?;
+        thread::sleep(Duration::from_millis(20)); // Small delay between key presses
     }
 
-    Ok(voices)
+    Ok(())
 }
 
-// Download file from URL
-async fn download_file(url: &str, path: &str) -> Result<(), Box<dyn std::error::Error>> {
-    let response = reqwest::get(url).await?;
-    let bytes = response.bytes().await?;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let mut file = File::create(path)?;
-    file.write_all(&bytes)?;
+    // Engine without a model, for exercising methods that don't run inference
+    fn offline_engine() -> TtsEngine {
+        TtsEngine::fallback(ModelVariant::Fp32)
+    }
 
-    Ok(())
-}
+    // Model with these voices (each style filled with one value) and no sessions
+    fn model_with_voices(voices: &[(&str, f32)]) -> KokoroModel {
+        KokoroModel {
+            sessions: Arc::new(Vec::new()),
+            voices: Arc::new(
+                voices
+                    .iter()
+                    .map(|&(name, value)| (name.to_string(), vec![value; STYLE_DIM]))
+                    .collect(),
+            ),
+            variant: ModelVariant::Fp32,
+            execution_provider: ExecutionProvider::Cpu,
+        }
+    }
 
-// Play the fallback message (used during first-time download)
-#[cfg(feature = "playback")]
-fn play_fallback_message() -> Result<(), String> {
-    eprintln!("   🔊 Playing welcome message...");
-    
-    // Decode the fallback WAV to audio samples
-    let audio = wav_to_f32(FALLBACK_MESSAGE)?;
-    
-    // Convert to WAV bytes for playback
-    let wav_data = samples_to_wav_bytes(&audio, SAMPLE_RATE)?;
-    
-    // Setup audio output with default device
-    let (_stream, stream_handle) = OutputStream::try_default()
-        .map_err(|e| format!("Failed to open audio stream: {}", e))?;
-    
-    // Create sink and play
-    let sink = Sink::try_new(&stream_handle)
-        .map_err(|e| format!("Failed to create audio sink: {}", e))?;
-    
-    let cursor = Cursor::new(wav_data);
-    let source = Decoder::new(cursor)
-        .map_err(|e| format!("Failed to decode audio: {}", e))?;
-    
-    sink.append(source);
-    sink.set_volume(0.8);
-    sink.sleep_until_end();
-    
-    Ok(())
-}
+    // `TtsEngine::with_model` without the audio device lookup
+    fn engine_on(model: KokoroModel) -> TtsEngine {
+        let variant = model.variant;
+        TtsEngine { fallback_mode: false, ..TtsEngine::fallback(variant) }.with_model_loaded(model)
+    }
 
-// Helper function to convert audio samples to WAV bytes
-fn samples_to_wav_bytes(audio: &[f32], sample_rate: u32) -> Result<Vec<u8>, String> {
-    let mut wav_data = Vec::new();
-    let mut cursor = Cursor::new(&mut wav_data);
-    
-    let spec = hound::WavSpec {
-        channels: 1,
-        sample_rate,
-        bits_per_sample: 16,
-        sample_format: hound::SampleFormat::Int,
-    };
-    
-    let mut writer = hound::WavWriter::new(&mut cursor, spec)
-        .map_err(|e| format!("Failed to create WAV writer: {}", e))?;
-    
-    for &sample in audio {
-        let amplitude = (sample * 32767.0).clamp(-32768.0, 32767.0) as i16;
-        writer
-            .write_sample(amplitude)
-            .map_err(|e| format!("Failed to write sample: {}", e))?;
+    #[test]
+    fn synthesize_i16_matches_converted_f32() {
+        // Fallback mode returns the bundled message, which goes through the same path
+        let engine = offline_engine();
+        let float = engine.synthesize("hello", None, None, None).unwrap();
+        let pcm = engine.synthesize_i16("hello", None, None).unwrap();
+        assert_eq!(pcm, audio::to_i16(&float));
     }
-    
-    writer
-        .finalize()
-        .map_err(|e| format!("Failed to finalize WAV: {}", e))?;
-    
-    drop(cursor);
-    Ok(wav_data)
-}
 
-// Convert WAV bytes to f32 samples
-fn wav_to_f32(wav_bytes: &[u8]) -> Result<Vec<f32>, String> {
-    let cursor = Cursor::new(wav_bytes);
-    let mut reader =
-        hound::WavReader::new(cursor).map_err(|e| format!("Failed to read WAV: {}", e))?;
+    #[test]
+    fn phonemize_pads_chunks_and_tokens_match() {
+        let engine = offline_engine();
+        let phonemes = engine.phonemize("hello", None).unwrap();
+        assert_eq!(phonemes.len(), 1);
+        assert!(phonemes[0].starts_with("$$$") && phonemes[0].ends_with("$$$"));
+
+        // Pad is id 0, punctuation follows it in the vocabulary
+        assert_eq!(engine.tokens_for("$.,$"), vec![0, 4, 3, 0]);
+        assert_eq!(
+            engine.tokens_for(&phonemes[0]),
+            engine.chunk_tokens("hello", None).unwrap()
+        );
+    }
 
-    let samples: Result<Vec<f32>, _> = reader
-        .samples::<i16>()
-        .map(|s: Result<i16, _>| s.map(|sample| sample as f32 / 32768.0))
-        .collect();
+    #[test]
+    fn phoneme_tokens_reject_unknown_characters() {
+        let engine = offline_engine();
+        assert_eq!(engine.phoneme_tokens("hə.").unwrap().len(), 3);
 
-    samples.map_err(|e| format!("Failed to read samples: {}", e))
-}
+        let err = engine.phoneme_tokens("ab9c#").unwrap_err().to_string();
+        assert!(err.contains("'9' (U+0039) at position 2"), "{}", err);
+        assert!(err.contains("'#' (U+0023) at position 4"), "{}", err);
 
-// Simple audio resampling (for OPUS)
-#[cfg(feature = "opus-format")]
-fn resample_audio(input: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
-    let ratio = to_rate as f32 / from_rate as f32;
-    let new_len = (input.len() as f32 * ratio) as usize;
-    let mut output = Vec::with_capacity(new_len);
+        assert!(engine.phoneme_tokens("").is_err());
+        assert!(engine.phoneme_tokens(&"a".repeat(MAX_MODEL_TOKENS)).is_err());
+    }
 
-    for i in 0..new_len {
-        let src_idx = i as f32 / ratio;
-        let idx_floor = src_idx.floor() as usize;
-        let idx_ceil = (idx_floor + 1).min(input.len() - 1);
-        let fraction = src_idx - idx_floor as f32;
+    #[test]
+    fn word_timings_follow_phoneme_spans() {
+        // "$$$ab cd. ef$$$" with space id 16
+        let tokens = [0, 0, 0, 1, 2, 16, 3, 4, 5, 16, 6, 7, 0, 0, 0];
+        let spans = phoneme_word_spans(&tokens, 16);
+        assert_eq!(spans, vec![(3, 5), (6, 9), (10, 12)]);
+
+        let bounds = token_boundaries(tokens.len(), 1500, None);
+        assert_eq!(bounds.len(), tokens.len() + 1);
+        assert_eq!(bounds[3], 300);
+
+        let timings = word_timings(&["ab", "cd.", "ef"], &[1, 1, 1], &spans, &bounds, 1000);
+        assert_eq!(timings[0].start_sample, 1300);
+        assert_eq!(timings[0].end_sample, 1500);
+        assert_eq!(timings[2].word, "ef");
+        assert_eq!(timings[2].end_sample, 2200);
+
+        // "1995" -> two phoneme words: counts disagree, fall back to character position
+        let timings = word_timings(&["in", "1995"], &[1, 1], &spans, &bounds, 0);
+        assert_eq!(timings[0].start_sample, 300);
+        assert_eq!(timings[1].start_sample, 600);
+        assert_eq!(timings[1].end_sample, 1200);
+    }
 
-        let sample = if idx_floor < input.len() {
-            input[idx_floor] * (1.0 - fraction) + input[idx_ceil] * fraction
-        } else {
-            0.0
-        };
+    #[test]
+    fn token_boundaries_use_durations() {
+        let bounds = token_boundaries(3, 600, Some(&[1.0, 2.0, 3.0]));
+        assert_eq!(bounds, vec![0, 100, 300, 600]);
+    }
 
-        output.push(sample);
+    #[test]
+    fn max_chunk_tokens_prefers_natural_boundaries() {
+        let mut engine = offline_engine();
+        assert!(engine.set_max_chunk_tokens(0).is_err());
+        assert!(engine.set_max_chunk_tokens(MAX_CHUNK_TOKENS + 1).is_err());
+        assert_eq!(engine.max_chunk_tokens(), MAX_CHUNK_TOKENS);
+
+        let text = "Short one. This sentence is long, with a clause; and another part. \
+                    Abcdefghijklmnopqrstuvwxyzabcdefgh";
+        assert_eq!(engine.chunk_text(text, None).unwrap().len(), 1);
+
+        engine.set_max_chunk_tokens(24).unwrap();
+        let chunks = engine.chunk_text(text, None).unwrap();
+        let texts: Vec<&str> = chunks.iter().map(|c| c.text.as_str()).collect();
+        assert_eq!(
+            texts,
+            [
+                "Short one.",
+                "This sentence is long,",
+                "with a clause;",
+                "and another part.",
+                "Abcdefghijklmnopqrstuvwx",
+                "yzabcdefgh",
+            ]
+        );
+        assert!(chunks.iter().all(|c| c.tokens <= 24));
+        assert_eq!(engine.phonemize(text, None).unwrap().len(), chunks.len());
     }
 
-    output
-}
+    #[test]
+    fn added_pronunciations_reach_the_model_as_given() {
+        let mut engine = offline_engine();
+        engine.add_pronunciation("Nguyen", "ŋwˈɪn").unwrap();
+        engine.add_pronunciation("read", "ɹˈiːd").unwrap();
+        let text = "Then nguyen has read (Nguyen's?) Nguyen.";
+        let phonemes = engine.phonemize(text, None).unwrap().join(" ");
+        assert_eq!(phonemes.matches("ŋwˈɪn").count(), 2, "{}", phonemes);
+        // Replaces the heteronym reading "has read" would get
+        assert!(phonemes.contains("ɹˈiːd") && !phonemes.contains("ɹˈɛd"), "{}", phonemes);
+        assert_eq!(engine.chunk_text(text, None).unwrap()[0].text, text);
+
+        assert!(matches!(engine.add_pronunciation("two words", "tˈuː"), Err(Error::InvalidInput(_))));
+        assert!(matches!(engine.add_pronunciation("Nguyen", "ŋ wɪn"), Err(Error::InvalidInput(_))));
+        assert!(matches!(engine.add_pronunciation("Nguyen", "ŋw1n"), Err(Error::InvalidInput(_))));
+        assert!(matches!(engine.add_pronunciation("Nguyen", ""), Err(Error::InvalidInput(_))));
+    }
 
-fn needs_chunking(text: &str) -> bool {
-    text.chars().count() > LONG_TEXT_THRESHOLD || text.lines().count() > 3
-}
+    #[test]
+    fn long_text_is_phonemized_about_once_per_sentence_to_split() {
+        let text = vec!["One short sentence here."; 40].join(" ");
+        let mut calls = 0;
+        let mut count = |piece: &str| {
+            calls += 1;
+            phoneme_token_count(piece, "en-us")
+        };
+        let pieces = split_to_fit(&text, 100, &mut count).unwrap();
+        // Four sentences and their spaces fit in 100 tokens, five don't
+        assert_eq!(pieces, vec![["One short sentence here."; 4].join(" "); 10]);
+        // The whole text, each sentence, and each packed piece once
+        assert_eq!(calls, 1 + 40 + 10);
+    }
 
-fn chunk_crossfade_samples() -> usize {
-    ((SAMPLE_RATE as usize) * CHUNK_CROSSFADE_MS) / 1000
-}
+    #[test]
+    fn finished_audio_is_centred_and_ends_at_zero_unless_opted_out() {
+        let offset: Vec<f32> = (0..24_000).map(|i| 0.1 + 0.3 * (i as f32 * 0.05).sin()).collect();
+        let mut engine = offline_engine();
+        assert!(engine.dc_cleanup());
+        let mut audio = offset.clone();
+        engine.post_process().apply(&mut audio);
+        assert_eq!((audio[0], audio[audio.len() - 1]), (0.0, 0.0));
+        let tail = &audio[12_000..];
+        assert!((tail.iter().sum::<f32>() / tail.len() as f32).abs() < 1e-3);
+
+        engine.set_dc_cleanup(false);
+        let mut raw = offset.clone();
+        engine.post_process().apply(&mut raw);
+        assert_eq!(raw, offset);
+    }
 
-fn append_with_crossfade(buffer: &mut Vec<f32>, next: &[f32], overlap_samples: usize) {
-    if next.is_empty() {
-        return;
+    #[test]
+    fn edge_silence_is_padded_or_cut_to_the_requested_length() {
+        let mut engine = offline_engine();
+        engine.set_dc_cleanup(false);
+        let mut speech = vec![0.0; 2400];
+        speech.extend(vec![0.5; 2400]);
+        speech.extend(vec![0.001; 2400]);
+        let audible = |audio: &[f32]| audio.iter().position(|&s| s == 0.5).unwrap();
+
+        engine.set_edge_silence(200, 50);
+        assert_eq!(engine.edge_silence(), Some((200, 50)));
+        let mut audio = speech.clone();
+        assert_eq!(engine.post_process().apply(&mut audio), -2400);
+        assert_eq!(audible(&audio), 4800);
+        assert_eq!(audio.len(), 4800 + 2400 + 1200);
+        // The quiet tail is cut down, not replaced
+        assert!(audio[7200..].iter().all(|&s| s == 0.001));
+
+        engine.set_edge_silence(0, 0);
+        let mut audio = speech.clone();
+        assert_eq!(engine.post_process().apply(&mut audio), 2400);
+        assert_eq!(audio, vec![0.5; 2400]);
+
+        engine.clear_edge_silence();
+        let mut audio = speech.clone();
+        engine.post_process().apply(&mut audio);
+        assert_eq!(audio, speech);
     }
 
-    if buffer.is_empty() || overlap_samples == 0 {
-        buffer.extend_from_slice(next);
-        return;
+    #[test]
+    fn fades_shape_the_audio_left_after_trimming() {
+        let mut engine = offline_engine();
+        engine.set_trim_silence(Some(-40.0));
+        engine.set_dc_cleanup(false);
+        engine.set_fade(100, 0);
+        assert_eq!(engine.fade(), (100, 0));
+
+        let mut audio = vec![0.0; 4800];
+        audio.extend(vec![0.5; 4800]);
+        engine.post_process().apply(&mut audio);
+        // 50ms of the silence survives trimming, so the 100ms fade-in is still
+        // halfway up where the speech starts
+        let keep = silence(SILENCE_KEEP_MS as u32).len();
+        assert_eq!(audio.len(), keep + 4800);
+        assert!((audio[keep] - 0.5 * std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-6);
+        assert!(audio[2400..].iter().all(|&s| s == 0.5));
     }
 
-    let overlap = overlap_samples.min(buffer.len()).min(next.len());
-    if overlap == 0 {
-        buffer.extend_from_slice(next);
-        return;
+    #[test]
+    fn synthesis_result_tracks_chunks_through_crossfade_and_trim() {
+        let chunk = |text: &str, tokens: usize, samples: Vec<f32>| AudioChunk {
+            index: 0,
+            total: 2,
+            text: text.to_string(),
+            tokens,
+            samples,
+            pause_ms: 0,
+        };
+        // 100ms of silence, then tone
+        let mut first = vec![0.0; 2400];
+        first.extend(vec![0.5; 4800]);
+        let mut result = SynthesisResult::new();
+        let mut join = ChunkJoin::new(240);
+        result.push_chunk(chunk("Hello.", 4, first), &mut join);
+        result.push_chunk(chunk("World.", 5, vec![0.5; 7200]), &mut join);
+        assert_eq!(result.text, "Hello. World.");
+        assert_eq!(&result.text[result.chunks[1].text_range.clone()], "World.");
+        assert_eq!(result.chunks[0].sample_range, 0..7200);
+        assert_eq!(result.chunks[1].sample_range, 6960..14160);
+        assert_eq!(result.chunks.iter().map(|c| c.token_count).collect::<Vec<_>>(), vec![4, 5]);
+
+        // Trimming leading silence moves every chunk back by the same amount
+        let removed = audio::trim_silence(&result.samples, -40.0, SILENCE_KEEP_MS).start;
+        assert!(removed > 0);
+        result.post_process(PostProcess {
+            trim_silence_db: Some(-40.0),
+            target_loudness: None,
+            fade_in: 0,
+            fade_out: 0,
+            dc_cleanup: false,
+            edge_silence: None,
+            processors: Processors::default(),
+        });
+        assert_eq!(result.chunks[0].sample_range, 0..7200 - removed);
+        assert_eq!(result.chunks[1].sample_range, 6960 - removed..14160 - removed);
+        assert_eq!(result.duration_secs, result.samples.len() as f32 / 24_000.0);
+
+        let detailed = offline_engine().synthesize_detailed("Hello there.", None, None, None).unwrap();
+        assert_eq!(detailed.chunks.len(), 1);
+        assert_eq!(detailed.chunks[0].sample_range, 0..detailed.samples.len());
     }
 
-    let start = buffer.len() - overlap;
-    for i in 0..overlap {
-        let fade_in = i as f32 / overlap as f32;
-        let fade_out = 1.0 - fade_in;
-        buffer[start + i] = buffer[start + i] * fade_out + next[i] * fade_in;
+    #[test]
+    fn processors_run_in_order_on_finished_and_streamed_audio() {
+        // Adds to every sample, so whether it ran before or after the gain shows
+        struct Offset(f32);
+        impl audio::AudioProcessor for Offset {
+            fn process(&mut self, samples: &mut Vec<f32>, sample_rate: u32) {
+                assert_eq!(sample_rate, SAMPLE_RATE);
+                samples.iter_mut().for_each(|sample| *sample += self.0);
+            }
+        }
+        let mut engine = offline_engine();
+        let plain = engine.synthesize("Hello.", None, None, None).unwrap();
+        let stream = |engine: &TtsEngine| {
+            let mut chunks = Vec::new();
+            engine.synthesize_streaming("Hello.", None, None, |chunk| chunks.push(chunk.samples)).unwrap();
+            chunks
+        };
+        let streamed_plain = stream(&engine);
+
+        engine.add_processor(Box::new(audio::Gain(2.0)));
+        engine.add_processor(Box::new(Offset(0.25)));
+        assert_eq!(engine.processor_count(), 2);
+        let processed = |audio: &[f32]| audio.iter().map(|s| s * 2.0 + 0.25).collect::<Vec<f32>>();
+        assert_eq!(engine.synthesize("Hello.", None, None, None).unwrap(), processed(&plain));
+        assert_eq!(stream(&engine), streamed_plain.iter().map(|chunk| processed(chunk)).collect::<Vec<_>>());
+
+        engine.clear_processors();
+        assert_eq!(engine.processor_count(), 0);
+        assert_eq!(engine.synthesize("Hello.", None, None, None).unwrap(), plain);
     }
 
-    buffer.extend_from_slice(&next[overlap..]);
-}
+    #[test]
+    fn estimate_counts_what_synthesis_would_run() {
+        let mut engine = offline_engine();
+        let text = "The quick brown fox jumps over the lazy dog. It was not amused.";
+        let estimate = engine.estimate(text, None).unwrap();
+        let chunks = engine.chunk_text(text, None).unwrap();
+        assert_eq!(estimate.chunk_count, chunks.len());
+        assert_eq!(estimate.token_count, chunks.iter().map(|c| c.tokens).sum::<usize>());
+        let secs = estimate.approx_duration.as_secs_f32();
+        assert!((2.0..8.0).contains(&secs), "{}", secs);
+
+        let faster = engine.estimate(text, Some(2.0)).unwrap();
+        assert_eq!(faster.token_count, estimate.token_count);
+        assert!((faster.approx_duration.as_secs_f32() - secs / 2.0).abs() < 0.01);
+
+        // The pause is on top of the speech (and of the pads of the extra chunk)
+        engine.set_pause_config(PauseConfig { period_ms: 400, ..PauseConfig::default() });
+        let paused = engine.estimate(text, None).unwrap();
+        let added = paused.approx_duration.as_secs_f32() - secs;
+        assert!(added >= 0.4, "{}", added);
+    }
 
-// Split text into chunks for better synthesis
-// Kokoro model handles shorter text better without dropping words
-fn split_text_for_tts(text: &str, max_chars: usize) -> Vec<String> {
-    // First try to split by sentences
-    let sentences: Vec<&str> = text
-        .split_terminator(&['.', '!', '?'][..])
-        .filter(|s| !s.trim().is_empty())
-        .collect();
+    #[test]
+    fn voice_profiles_scale_speed_and_round_trip_through_json() {
+        let mut engine = offline_engine();
+        let text = "The quick brown fox jumps over the lazy dog.";
+        // 1.5 from the call times 0.75 from the profile
+        let slower = engine.estimate(text, Some(1.125)).unwrap();
+        let sky = VoiceProfile { speed: 0.75, ..Default::default() };
+        engine.set_voice_profile("af_sky", sky).unwrap();
+        assert_eq!(engine.voice_profile("af_sky"), Some(sky));
+        assert_eq!(engine.estimate(text, Some(1.5)).unwrap(), slower);
+
+        let bad = VoiceProfile { pitch: 13.0, ..Default::default() };
+        assert!(matches!(engine.set_voice_profile("af_bella", bad), Err(Error::InvalidInput(_))));
+        let bad = VoiceProfile { speed: 0.0, ..Default::default() };
+        assert!(matches!(engine.set_voice_profile("af_bella", bad), Err(Error::InvalidInput(_))));
+        assert_eq!(engine.voice_profile("af_bella"), None);
+
+        let json = engine.export_voice_profiles();
+        let mut other = offline_engine();
+        other.import_voice_profiles(&json).unwrap();
+        assert_eq!(other.voice_profile("af_sky"), Some(sky));
+        other.import_voice_profiles(r#"{"am_adam": {"pitch": -2.0}}"#).unwrap();
+        assert_eq!(other.voice_profile("am_adam"), Some(VoiceProfile { speed: 1.0, pitch: -2.0 }));
+        assert!(other.import_voice_profiles(r#"{"bf_emma": {"speed": -1.0}}"#).is_err());
+        assert!(other.import_voice_profiles("[]").is_err());
+        assert_eq!(other.voice_profile("bf_emma"), None);
+        assert_eq!(other.remove_voice_profile("af_sky"), Some(sky));
+        assert_eq!(other.voice_profile("af_sky"), None);
+    }
 
-    let mut chunks = Vec::new();
-    let mut current_chunk = String::new();
+    #[test]
+    fn emotions_scale_with_intensity_and_can_be_retuned() {
+        let mut engine = offline_engine();
+        assert_eq!(engine.emotion_style(&EmotionType::Neutral), EmotionStyle::default());
+        assert_eq!(engine.emotion_style(&EmotionType::Joy(0.0)), EmotionStyle::default());
+        let sad = EmotionType::Sadness(1.0);
+        assert_eq!(engine.emotion_style(&sad), EmotionStyle::builtin(&sad));
+        let half = engine.emotion_style(&EmotionType::Joy(0.5));
+        assert!((half.speed - 1.05).abs() < 1e-6);
+        assert_eq!(half.pitch, 1.0);
+        assert!((half.energy - 1.075).abs() < 1e-6);
+        // Out of range intensities are clamped
+        let fear = engine.emotion_style(&EmotionType::Fear(3.0));
+        assert_eq!(fear, EmotionStyle::builtin(&EmotionType::Fear(1.0)));
+
+        let giddy = EmotionStyle { speed: 1.3, pitch: 4.0, energy: 1.0 };
+        engine.set_emotion_style(&EmotionType::Joy(0.2), giddy).unwrap();
+        assert_eq!(engine.emotion_style(&EmotionType::Joy(1.0)), giddy);
+        assert_eq!(engine.emotion_style(&EmotionType::Joy(0.5)).pitch, 2.0);
+        let silent = EmotionStyle { energy: 0.0, ..giddy };
+        assert!(engine.set_emotion_style(&EmotionType::Love(1.0), silent).is_err());
+        let shrill = EmotionStyle { pitch: 20.0, ..giddy };
+        assert!(engine.set_emotion_style(&EmotionType::Love(1.0), shrill).is_err());
+
+        let opts = SynthesizeOptions::default().emotion(EmotionType::Curiosity(0.7)).pitch(12.0);
+        assert!(engine.synthesize_with("Hello?", opts).is_ok());
+    }
 
-    for sentence in sentences {
-        // Add back the punctuation if it was there
-        let full_sentence = if text.contains(&format!("{}.", sentence.trim())) {
-            format!("{}.", sentence.trim())
-        } else if text.contains(&format!("{}!", sentence.trim())) {
-            format!("{}!", sentence.trim())
-        } else if text.contains(&format!("{}?", sentence.trim())) {
-            format!("{}?", sentence.trim())
-        } else {
-            sentence.trim().to_string()
-        };
+    #[test]
+    fn pauses_split_text_and_add_silence() {
+        let none = PauseConfig::default();
+        assert_eq!(split_at_pauses("One, two.", &none), vec![("One, two.".to_string(), 0)]);
+
+        let commas = PauseConfig { comma_ms: 500, ..Default::default() };
+        assert_eq!(
+            split_at_pauses("One, two, 3,5 done...", &commas),
+            vec![
+                ("One,".to_string(), 500),
+                ("two,".to_string(), 500),
+                ("3,5 done...".to_string(), 0),
+            ]
+        );
 
-        // If this sentence alone is too long, split it by commas or words
-        if full_sentence.len() > max_chars {
-            // Try splitting by commas first
-            let parts: Vec<&str> = full_sentence.split(',').collect();
-            if parts.len() > 1 {
-                for part in parts {
-                    if part.trim().len() > max_chars {
-                        // Still too long, split by words
-                        chunks.extend(split_by_words(part, max_chars));
-                    } else if !part.trim().is_empty() {
-                        chunks.push(part.trim().to_string());
-                    }
-                }
-            } else {
-                // No commas, split by words
-                chunks.extend(split_by_words(&full_sentence, max_chars));
-            }
-        }
-        // If adding this sentence would make chunk too long, save current and start new
-        else if !current_chunk.is_empty()
-            && current_chunk.len() + full_sentence.len() + 1 > max_chars
-        {
-            chunks.push(current_chunk.trim().to_string());
-            current_chunk = full_sentence;
-        }
-        // Add to current chunk
-        else {
-            if !current_chunk.is_empty() {
-                current_chunk.push(' ');
-            }
-            current_chunk.push_str(&full_sentence);
+        let dots = PauseConfig { period_ms: 200, ellipsis_ms: 700, ..Default::default() };
+        assert_eq!(
+            split_at_pauses("Wait... Go! Now.", &dots),
+            vec![
+                ("Wait...".to_string(), 700),
+                ("Go!".to_string(), 200),
+                ("Now.".to_string(), 200),
+            ]
+        );
+
+        // The trailing pause of the whole text is dropped
+        let mut engine = offline_engine();
+        engine.set_pause_config(commas);
+        assert_eq!(engine.text_chunks("A, b, c,", None).unwrap().last().unwrap().1, 0);
+
+        // comma_ms 500 vs 0: each comma makes the joined output exactly 500ms
+        // longer, the join after it isn't crossfaded, and the silence is whole
+        let chunk = vec![0.1f32; 4800];
+        let mut padded = chunk.clone();
+        append_pause(&mut padded, 500);
+        let mut joined_plain = Vec::new();
+        let mut joined_paused = Vec::new();
+        let overlap = engine.crossfade_samples();
+        let joins = [(&chunk, 0, &mut joined_plain), (&padded, 500, &mut joined_paused)];
+        for (samples, pause_ms, out) in joins {
+            let mut join = ChunkJoin::new(overlap);
+            join.append(out, samples, pause_ms);
+            join.append(out, &chunk, 0);
         }
+        assert_eq!(joined_paused.len() - joined_plain.len(), 12000 + overlap);
+        assert_eq!(joined_paused.len(), 2 * 4800 + 12000);
+        assert!(joined_paused[4800..16800].iter().all(|&s| s == 0.0));
+        assert!(joined_paused[16800..].iter().all(|&s| s == 0.1));
+
+        // Nor is the join onto a chunk that is only a pause
+        let mut join = ChunkJoin::new(overlap);
+        let mut joined = Vec::new();
+        join.append(&mut joined, &chunk, 0);
+        join.append(&mut joined, &silence(500), 500);
+        join.append(&mut joined, &chunk, 0);
+        assert_eq!(joined, joined_paused);
     }
 
-    // Don't forget the last chunk
-    if !current_chunk.is_empty() {
-        chunks.push(current_chunk.trim().to_string());
+    #[test]
+    fn wav_bytes_match_saved_file_and_header() {
+        let engine = offline_engine();
+        let audio = [0.0, 0.5, -1.0];
+
+        let bytes = engine.to_wav_bytes(&audio).unwrap();
+        let mut expected = Vec::new();
+        expected.extend_from_slice(b"RIFF");
+        expected.extend_from_slice(&42u32.to_le_bytes()); // 36 + data size
+        expected.extend_from_slice(b"WAVEfmt ");
+        expected.extend_from_slice(&16u32.to_le_bytes()); // fmt chunk size
+        expected.extend_from_slice(&1u16.to_le_bytes()); // PCM
+        expected.extend_from_slice(&1u16.to_le_bytes()); // mono
+        expected.extend_from_slice(&24000u32.to_le_bytes());
+        expected.extend_from_slice(&48000u32.to_le_bytes()); // byte rate
+        expected.extend_from_slice(&2u16.to_le_bytes()); // block align
+        expected.extend_from_slice(&16u16.to_le_bytes());
+        expected.extend_from_slice(b"data");
+        expected.extend_from_slice(&6u32.to_le_bytes());
+        for sample in [0i16, 16383, -32767] {
+            expected.extend_from_slice(&sample.to_le_bytes());
+        }
+        assert_eq!(bytes, expected);
+
+        let path = std::env::temp_dir().join(format!("kokoro-wav-test-{}.wav", std::process::id()));
+        engine.save_wav(path.to_str().unwrap(), &audio).unwrap();
+        let saved = std::fs::read(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(saved, bytes);
+
+        let mut cursor = Cursor::new(Vec::new());
+        engine.write_wav(&mut cursor, &audio).unwrap();
+        assert_eq!(cursor.into_inner(), bytes);
     }
 
-    // If no chunks were created (text had no sentence endings), split by words
-    if chunks.is_empty() && !text.trim().is_empty() {
-        chunks = split_by_words(text, max_chars);
+    // Chunk ids and bodies of a RIFF file, checking sizes and padding on the way
+    fn riff_chunks(bytes: &[u8]) -> Vec<([u8; 4], Vec<u8>)> {
+        assert_eq!(&bytes[..4], b"RIFF");
+        assert_eq!(&bytes[8..12], b"WAVE");
+        let riff_size = u32::from_le_bytes(bytes[4..8].try_into().unwrap()) as usize;
+        assert_eq!(riff_size, bytes.len() - 8);
+        let mut chunks = Vec::new();
+        let mut at = 12;
+        while at < bytes.len() {
+            let id: [u8; 4] = bytes[at..at + 4].try_into().unwrap();
+            let size = u32::from_le_bytes(bytes[at + 4..at + 8].try_into().unwrap()) as usize;
+            chunks.push((id, bytes[at + 8..at + 8 + size].to_vec()));
+            at += 8 + size + size % 2;
+        }
+        assert_eq!(at, bytes.len());
+        chunks
     }
 
-    chunks
-}
+    #[test]
+    fn wav_metadata_is_written_as_a_list_info_chunk() {
+        let engine = offline_engine();
+        let audio: Vec<f32> = (0..1001).map(|i| (i as f32 * 0.05).sin() * 0.5).collect();
+        let path = std::env::temp_dir().join(format!("kokoro-info-test-{}.wav", std::process::id()));
+        let metadata = WavMetadata {
+            creation_date: Some("2024-05-01".to_string()),
+            ..WavMetadata::new().artist("af_sky").comment("af_sky: Hello, world!")
+        };
+        engine.save_wav_with_metadata(path.to_str().unwrap(), &audio, &metadata).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        let chunks = riff_chunks(&bytes);
+        let ids: Vec<&[u8; 4]> = chunks.iter().map(|(id, _)| id).collect();
+        assert_eq!(ids, [b"fmt ", b"data", b"LIST"]);
+        let list = &chunks[2].1;
+        assert_eq!(&list[..4], b"INFO");
+        let mut entries = Vec::new();
+        let mut at = 4;
+        while at < list.len() {
+            let size = u32::from_le_bytes(list[at + 4..at + 8].try_into().unwrap()) as usize;
+            let text = &list[at + 8..at + 8 + size];
+            assert_eq!(text.last(), Some(&0));
+            let text = String::from_utf8(text[..size - 1].to_vec()).unwrap();
+            entries.push((String::from_utf8(list[at..at + 4].to_vec()).unwrap(), text));
+            at += 8 + size + size % 2;
+        }
+        let software = format!("kokoro-tiny {}", env!("CARGO_PKG_VERSION"));
+        assert_eq!(
+            entries,
+            [
+                ("IART".to_string(), "af_sky".to_string()),
+                ("ISFT".to_string(), software),
+                ("ICMT".to_string(), "af_sky: Hello, world!".to_string()),
+                ("ICRD".to_string(), "2024-05-01".to_string()),
+            ]
+        );
 
-// Split text by words when sentences are too long
-fn split_by_words(text: &str, max_chars: usize) -> Vec<String> {
-    let words: Vec<&str> = text.split_whitespace().collect();
-    let mut chunks = Vec::new();
-    let mut current = String::new();
+        // The audio reads back exactly as from a plain save_wav
+        let plain = engine.to_wav_bytes(&audio).unwrap();
+        assert_eq!(wav_to_f32(&bytes).unwrap(), wav_to_f32(&plain).unwrap());
+    }
 
-    for word in words {
-        if current.len() + word.len() + 1 > max_chars && !current.is_empty() {
-            chunks.push(current.trim().to_string());
-            current = word.to_string();
-        } else {
-            if !current.is_empty() {
-                current.push(' ');
+    #[test]
+    fn streamed_chunks_crossfade_like_the_whole_buffer() {
+        let chunks: Vec<Vec<f32>> = [300, 5, 400, 120]
+            .iter()
+            .enumerate()
+            .map(|(c, &len)| (0..len).map(|i| ((c * 1000 + i) as f32 * 0.01).sin()).collect())
+            .collect();
+        let mut joined = Vec::new();
+        let mut stream = CrossfadeStream::new(240, None);
+        let mut streamed = Vec::new();
+        for chunk in &chunks {
+            append_with_crossfade(&mut joined, chunk, 240);
+            streamed.extend(stream.push(chunk, 0));
+            assert!(stream.tail.len() <= 240);
+        }
+        streamed.extend(stream.finish());
+        assert_eq!(streamed, joined);
+
+        // The streamed DC cleanup matches the whole-buffer one, also when the
+        // audio is shorter than the edge ramps
+        for chunks in [&chunks[..], &chunks[1..2]] {
+            let mut joined = Vec::new();
+            let mut stream = CrossfadeStream::new(240, Some(48));
+            let mut streamed = Vec::new();
+            for chunk in chunks {
+                append_with_crossfade(&mut joined, chunk, 240);
+                streamed.extend(stream.push(chunk, 0));
             }
-            current.push_str(word);
+            streamed.extend(stream.finish());
+            audio::remove_dc(&mut joined);
+            audio::fade(&mut joined, 48, 48);
+            assert_eq!(streamed, joined);
         }
+
+        let engine = offline_engine();
+        let path = std::env::temp_dir().join(format!("kokoro-stream-test-{}.wav", std::process::id()));
+        let duration = engine.synthesize_to_wav(path.to_str().unwrap(), "Hello.", None, None).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        let audio = engine.synthesize("Hello.", None, None, None).unwrap();
+        assert_eq!(bytes, engine.to_wav_bytes(&audio).unwrap());
+        assert_eq!(duration, Duration::from_secs_f64(audio.len() as f64 / 24_000.0));
     }
 
-    if !current.is_empty() {
-        chunks.push(current.trim().to_string());
+    #[test]
+    fn synthesize_to_file_picks_the_format_and_reports_the_result() {
+        let engine = offline_engine();
+        let path = std::env::temp_dir().join(format!("kokoro-file-test-{}.WAV", std::process::id()));
+        let path = path.to_str().unwrap();
+        let summary = engine.synthesize_to_file(path, "Hello.", None, None).unwrap();
+        let bytes = std::fs::read(path).unwrap();
+        let _ = std::fs::remove_file(path);
+        let audio = engine.synthesize("Hello.", None, None, None).unwrap();
+        assert_eq!(bytes, engine.to_wav_bytes(&audio).unwrap());
+        assert_eq!(summary.file_size, bytes.len() as u64);
+        assert_eq!(summary.duration, Duration::from_secs_f64(audio.len() as f64 / 24_000.0));
+
+        // Refused before synthesis, so nothing is written
+        let flac = path.replace(".WAV", ".flac");
+        let refused = engine.synthesize_to_file(&flac, "Hello.", None, None);
+        assert!(matches!(refused, Err(Error::InvalidInput(_))));
+        assert!(!Path::new(&flac).exists());
     }
 
-    chunks
-}
+    #[test]
+    fn subtitle_cues_span_each_sentences_words() {
+        let timing = |start_sample, end_sample| WordTiming { word: String::new(), start_sample, end_sample };
+        // The second sentence was said in two chunks that overlap where they were crossfaded
+        let timings =
+            [timing(0, 4800), timing(4800, 12_000), timing(14_400, 19_200), timing(18_000, 36_000)];
+        let sentences = [
+            (2, "Hello there.".to_string()),
+            (0, "...".to_string()),
+            (1, "It's 42.".to_string()),
+        ];
+        let cues = sentence_cues(&sentences, &timings);
+        let ms = Duration::from_millis;
+        assert_eq!(cues.len(), 2);
+        assert_eq!((cues[0].start, cues[0].end), (ms(0), ms(500)));
+        assert_eq!((cues[1].start, cues[1].end), (ms(600), ms(1500)));
+        assert_eq!((cues[0].text.as_str(), cues[1].text.as_str()), ("Hello there.", "It's 42."));
+
+        // The final sentence ends with the audio even when counts fall short
+        let short = sentence_cues(&sentences[..1], &timings[..1]);
+        assert_eq!((short[0].start, short[0].end), (ms(0), ms(200)));
+        assert!(sentence_cues(&sentences, &[]).is_empty());
+
+        let engine = offline_engine();
+        assert_eq!(engine.spoken_word_count("It's 42 degrees.", "en-us").unwrap(), 4);
+        let (audio, vtt) = engine.synthesize_with_subtitles("Hi.", None, None, SubtitleFormat::WebVtt).unwrap();
+        assert_eq!(audio, engine.synthesize_with_timing("Hi.", None, None).unwrap().0);
+        assert_eq!(vtt, "WEBVTT\n\n");
+    }
 
-// Amplify audio - allows some clipping for maximum loudness
-fn amplify_audio(audio: &[f32], gain: f32) -> Vec<f32> {
-    audio
-        .iter()
-        .map(|&sample| {
-            let amplified = sample * gain;
+    #[cfg(feature = "ogg-format")]
+    #[test]
+    fn ogg_opus_decodes_back_in_line_with_the_input() {
+        use audiopus::{coder::Decoder, Channels, SampleRate};
 
-            // Simple hard clipping at the limits
-            // This allows maximum volume even if it distorts a bit
-            amplified.clamp(-1.0, 1.0)
-        })
-        .collect()
-}
+        // A 440Hz tone ending partway through a frame
+        let audio: Vec<f32> = (0..12_100)
+            .map(|i| 0.5 * (i as f32 * 440.0 * std::f32::consts::TAU / 24_000.0).sin())
+            .collect();
+        let engine = offline_engine();
+        let packets = engine.encode_opus(&audio, 64_000).unwrap();
+        assert_eq!(packets.len(), 26);
+        assert!(engine.encode_opus(&audio, 1_000).is_err());
+        let ogg = write_ogg_opus(Vec::new(), &packets, audio.len()).unwrap();
+
+        let mut reader = ogg::reading::PacketReader::new(Cursor::new(ogg));
+        let head = reader.read_packet_expected().unwrap();
+        assert_eq!(&head.data[..8], b"OpusHead");
+        let pre_skip = u16::from_le_bytes([head.data[10], head.data[11]]) as u64;
+        assert_eq!(&reader.read_packet_expected().unwrap().data[..8], b"OpusTags");
+
+        let mut decoder = Decoder::new(SampleRate::Hz24000, Channels::Mono).unwrap();
+        let mut frame = vec![0i16; 5760];
+        let mut decoded = Vec::new();
+        let mut end_granule = 0;
+        while let Some(packet) = reader.read_packet().unwrap() {
+            let len = decoder.decode(Some(&packet.data[..]), &mut frame, false).unwrap();
+            assert_eq!(len, 480);
+            decoded.extend(frame[..len].iter().map(|&s| s as f32 / 32768.0));
+            end_granule = packet.absgp_page();
+        }
+        assert_eq!((end_granule - pre_skip) / 2, audio.len() as u64);
+
+        // Lossy, but sample-aligned once the pre-skip is dropped: off by even a
+        // few samples, the difference would be as loud as the tone itself
+        let skip = pre_skip as usize / 2;
+        let decoded = &decoded[skip..skip + audio.len()];
+        let error: f32 = decoded.iter().zip(&audio).map(|(d, a)| (d - a).powi(2)).sum();
+        let signal: f32 = audio.iter().map(|a| a * a).sum();
+        assert!(error < signal * 0.05, "error {} vs signal {}", error, signal);
+    }
 
-// BabyTts implementation for mem8 integration
-impl BabyTts {
-    /// Create a new baby TTS for mem8 learning
-    pub async fn new() -> Result<Self, String> {
-        let engine = TtsEngine::new().await?;
-        Ok(Self {
-            engine,
-            max_words: 5,                // Babies start with short phrases
-            voice: "af_sky".to_string(), // Gentle voice for baby
-            speed: 0.9,                  // Slightly slower for clarity
-            gain: 1.8,                   // Louder for clarity
-            lang: DEFAULT_LANG.to_string(),
-        })
+    #[test]
+    fn utc_dates_follow_the_calendar() {
+        let at = |secs: u64| utc_date(std::time::UNIX_EPOCH + Duration::from_secs(secs));
+        assert_eq!(at(0), "1970-01-01");
+        assert_eq!(at(951_782_400), "2000-02-29");
+        assert_eq!(at(1_709_251_199), "2024-02-29");
+        assert_eq!(at(4_102_444_800), "2100-01-01");
     }
 
-    /// Create with custom settings
-    pub async fn with_settings(
-        max_words: usize,
-        voice: &str,
-        speed: f32,
-        gain: f32,
-    ) -> Result<Self, String> {
-        let engine = TtsEngine::new().await?;
-        Ok(Self {
-            engine,
-            max_words,
-            voice: voice.to_string(),
-            speed,
-            gain,
-            lang: DEFAULT_LANG.to_string(),
-        })
+    #[test]
+    fn from_paths_names_missing_and_truncated_files() {
+        let dir = std::env::temp_dir().join(format!("kokoro-paths-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let (model, voices) = (dir.join("0.onnx"), dir.join("0.bin"));
+
+        let err = TtsEngine::from_paths(&model, &voices).err().unwrap().to_string();
+        assert!(err.contains(&format!("Model file not found: {}", model.display())), "{}", err);
+        assert!(err.contains(&format!("Voices file not found: {}", voices.display())), "{}", err);
+
+        // A Git LFS pointer where the model should be
+        std::fs::write(&model, "version https://git-lfs.github.com/spec/v1\n").unwrap();
+        let err = TtsEngine::from_paths(&model, &voices).err().unwrap().to_string();
+        let _ = std::fs::remove_dir_all(&dir);
+        assert!(err.starts_with("Model file ") && err.contains("too small"), "{}", err);
+        assert!(err.contains("Voices file not found"), "{}", err);
     }
 
-    /// Speak a simple utterance (for mem8 baby learning)
-    pub fn speak(&mut self, text: &str) -> Result<Vec<f32>, String> {
-        // Limit to max_words for baby speech
-        let words: Vec<&str> = text.split_whitespace().collect();
-        let limited_text = if words.len() > self.max_words {
-            #[cfg(not(feature = "as-lib"))]
-            eprintln!("🍼 Baby mode: Limiting to {} words", self.max_words);
-            words[..self.max_words].join(" ")
-        } else {
-            text.to_string()
+    #[test]
+    fn download_resumes_partial_file_and_reports_progress() {
+        use std::io::{BufRead, BufReader};
+        use std::net::TcpListener;
+
+        let body: Vec<u8> = (0..=255u8).cycle().take(10_000).collect();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/0.bin", listener.local_addr().unwrap());
+        let served = body.clone();
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut range = None;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                if let Some(value) = line.to_ascii_lowercase().strip_prefix("range: bytes=") {
+                    range = value.trim().trim_end_matches('-').parse::<usize>().ok();
+                }
+            }
+            let start = range.unwrap_or(0);
+            let head = format!(
+                "HTTP/1.1 206 Partial Content\r\nContent-Length: {}\r\n\
+                 Content-Range: bytes {}-{}/{}\r\n\r\n",
+                served.len() - start,
+                start,
+                served.len() - 1,
+                served.len()
+            );
+            let mut stream = reader.into_inner();
+            stream.write_all(head.as_bytes()).unwrap();
+            stream.write_all(&served[start..]).unwrap();
+            range
+        });
+
+        let dir = std::env::temp_dir().join(format!("kokoro-download-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("0.bin");
+        std::fs::write(dir.join("0.bin.part"), &body[..4000]).unwrap();
+
+        let mut events = Vec::new();
+        let sha256 = sha256_of(&body);
+        let client = http_client(None).unwrap();
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+        let target = path.to_str().unwrap();
+        let mut record = |event| events.push(event);
+        let download = download_file(&client, &url, target, 0, Some(&sha256), &mut record);
+        let result = runtime.block_on(download);
+        let saved = std::fs::read(&path);
+        let _ = std::fs::remove_dir_all(&dir);
+
+        result.unwrap();
+        assert_eq!(server.join().unwrap(), Some(4000));
+        assert_eq!(saved.unwrap(), body);
+        let progress = |downloaded| DownloadProgress::Downloading {
+            file: "0.bin".to_string(),
+            downloaded,
+            total: Some(10_000),
         };
+        assert_eq!(events.first(), Some(&progress(4000)));
+        assert_eq!(events.last(), Some(&progress(10_000)));
+    }
 
-        // Synthesize with baby settings
-self.engine
-             .synthesize_with_options(&limited_text, Some(&self.voice), self.speed, self.gain, Some(&self.lang))
+    fn sha256_of(data: &[u8]) -> String {
+        let mut hasher = checksum::Sha256::new();
+        hasher.update(data);
+        hasher.finish()
     }
 
-    /// Get raw audio samples at 24kHz (for mem8 processing)
-    pub fn get_audio_params(&self) -> (u32, u16, u16) {
-        (SAMPLE_RATE, 1, 16) // 24kHz, mono, 16-bit
+    #[test]
+    fn corrupt_download_is_fetched_again_and_reported() {
+        use std::io::{BufRead, BufReader};
+        use std::net::TcpListener;
+
+        let body: Vec<u8> = (0..=255u8).cycle().take(5_000).collect();
+        let mut corrupt = body.clone();
+        corrupt[1234] ^= 0xff;
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/0.onnx", listener.local_addr().unwrap());
+        // The corrupt copy, then the real one, then the corrupt one for every attempt
+        let responses = [corrupt.clone(), body.clone(), corrupt.clone(), corrupt.clone(), corrupt];
+        let server = thread::spawn(move || {
+            for served in responses {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    line.clear();
+                }
+                let head = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n", served.len());
+                let mut stream = reader.into_inner();
+                stream.write_all(head.as_bytes()).unwrap();
+                stream.write_all(&served).unwrap();
+            }
+        });
+
+        let dir = std::env::temp_dir().join(format!("kokoro-verify-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("0.onnx");
+        let target = path.to_str().unwrap();
+        let sha256 = sha256_of(&body);
+        let client = http_client(None).unwrap();
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+
+        // One bad transfer is retried transparently
+        runtime.block_on(download_file(&client, &url, target, 0, Some(&sha256), &mut |_| {})).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), body);
+        assert!(ModelPaths { model: path.clone(), voices: path.clone() }.verify().is_err());
+        assert!(verify_model_file("Model", &path, &sha256).is_ok());
+
+        // A file that never matches gives up after the last attempt
+        std::fs::remove_file(&path).unwrap();
+        let err = runtime
+            .block_on(download_file(&client, &url, target, 0, Some(&sha256), &mut |_| {}))
+            .unwrap_err()
+            .to_string();
+        let part_left = dir.join("0.onnx.part").exists();
+        let _ = std::fs::remove_dir_all(&dir);
+        server.join().unwrap();
+        assert!(err.contains("is corrupt: expected SHA-256 ") && err.contains("to re-download"), "{}", err);
+        assert!(!part_left);
     }
 
-    /// Process incoming audio for learning (placeholder for mem8 integration)
-    pub fn learn_from_audio(&mut self, audio: &[f32], text: &str) -> Result<(), String> {
-        // This would integrate with mem8's learning system
-        // For now, just log the learning attempt
-        #[cfg(not(feature = "as-lib"))]
-        eprintln!("🧠 Baby learning: '{}' ({} samples)", text, audio.len());
-        Ok(())
+    #[test]
+    fn dropped_transfer_is_retried_from_where_it_stopped() {
+        use std::io::{BufRead, BufReader};
+        use std::net::TcpListener;
+
+        let body: Vec<u8> = (0..=255u8).cycle().take(8_000).collect();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let served = body.clone();
+        let server = thread::spawn(move || {
+            let mut ranges = Vec::new();
+            for connection in 0..3 {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+                let mut range = None;
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    if let Some(value) = line.to_ascii_lowercase().strip_prefix("range: bytes=") {
+                        range = value.trim().trim_end_matches('-').parse::<usize>().ok();
+                    }
+                    line.clear();
+                }
+                ranges.push(range);
+                let mut stream = reader.into_inner();
+                let head = match connection {
+                    // Connection drops after 3000 of 8000 bytes
+                    0 => format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n", served.len()),
+                    1 => format!(
+                        "HTTP/1.1 206 Partial Content\r\nContent-Length: {}\r\n\r\n",
+                        served.len() - 3000
+                    ),
+                    _ => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n".to_string(),
+                };
+                stream.write_all(head.as_bytes()).unwrap();
+                let part = match connection {
+                    0 => &served[..3000],
+                    1 => &served[3000..],
+                    _ => &[],
+                };
+                stream.write_all(part).unwrap();
+            }
+            ranges
+        });
+
+        let dir = std::env::temp_dir().join(format!("kokoro-retry-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("0.bin");
+        let target = path.to_str().unwrap();
+        let client = http_client(None).unwrap();
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+        let url = format!("http://{}/0.bin", address);
+        let sha256 = sha256_of(&body);
+        let mut ignore = |_| {};
+        let result = runtime.block_on(download_file(&client, &url, target, 0, Some(&sha256), &mut ignore));
+        let saved = std::fs::read(&path);
+
+        // Client errors are reported with their status and not retried
+        let missing = format!("http://{}/missing.bin", address);
+        let other = format!("{}.x", target);
+        let err = runtime
+            .block_on(download_file(&client, &missing, &other, 0, None, &mut |_| {}))
+            .unwrap_err()
+            .to_string();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        result.unwrap();
+        assert_eq!(saved.unwrap(), body);
+        assert_eq!(server.join().unwrap(), vec![None, Some(3000), None]);
+        assert_eq!(err, format!("{} returned HTTP 404 Not Found", missing));
+        let error = http_client(Some("not a url")).unwrap_err();
+        assert!(matches!(&error, Error::InvalidInput(message) if message.starts_with("Invalid proxy URL")));
     }
 
-    /// Babble - generate random baby sounds (for early development stages)
-    pub fn babble(&mut self) -> Result<Vec<f32>, String> {
-        let baby_sounds = ["ma", "ba", "da", "goo", "ga", "baba", "mama", "dada"];
-        // Simple pseudo-random using current time
-        let index = (std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as usize)
-            % baby_sounds.len();
-        let sound = baby_sounds[index];
-        self.speak(sound)
+    #[test]
+    fn model_variants_pick_their_files_and_io_names() {
+        let paths = ModelPaths::in_dir_for("/cache", ModelVariant::Quantized);
+        assert_eq!(paths.model, Path::new("/cache/0.int8.onnx"));
+        assert_eq!(paths.voices, ModelPaths::in_dir("/cache").voices);
+        assert_eq!(ModelVariant::from_file_name(&paths.model), ModelVariant::Quantized);
+        let community = Path::new("onnx/model_quantized.onnx");
+        assert_eq!(ModelVariant::from_file_name(community), ModelVariant::Quantized);
+        assert_eq!(ModelVariant::from_file_name(Path::new("0.onnx")), ModelVariant::Fp32);
+        assert_eq!(offline_engine().model_variant(), ModelVariant::Fp32);
+
+        assert_eq!(io_name(&["input_ids", "style", "speed"], &TOKENS_INPUT_NAMES), "input_ids");
+        assert_eq!(io_name(&["tokens", "style", "speed"], &TOKENS_INPUT_NAMES), "tokens");
+        assert_eq!(io_name(&["waveform", "durations"], &AUDIO_OUTPUT_NAMES), "waveform");
+        assert_eq!(io_name(&["y"], &AUDIO_OUTPUT_NAMES), "audio");
     }
 
-    /// Echo mode - repeat what was heard (for learning)
-    pub fn echo(&mut self, text: &str) -> Result<Vec<f32>, String> {
-        // Simple echo with slightly different intonation
-        let echo_speed = self.speed * 1.1; // Slightly faster for echo
-        self.engine
-            .synthesize_with_options(text, Some(&self.voice), echo_speed, self.gain, Some(&self.lang))
+    #[test]
+    fn unavailable_execution_provider_names_its_feature() {
+        assert!(matches!(ExecutionProvider::Cpu.dispatch(), Ok(None)));
+        #[cfg(not(feature = "cuda"))]
+        assert_eq!(
+            ExecutionProvider::Cuda(0).dispatch().err().map(|e| e.to_string()),
+            Some("kokoro-tiny was built without the `cuda` feature".to_string())
+        );
+        assert_eq!(ExecutionProvider::DirectMl(1).to_string(), "DirectML (device 1)");
+        assert_eq!(offline_engine().execution_provider(), ExecutionProvider::Cpu);
     }
 
-    /// Grow vocabulary - increase max words as baby learns
-    pub fn grow(&mut self) {
-        self.max_words = (self.max_words + 1).min(20); // Cap at 20 words for kokoro-tiny
-        #[cfg(not(feature = "as-lib"))]
-eprintln!(
-            "🌱 Baby growing! Can now speak {} words at once",
-            self.max_words
+    #[test]
+    fn cpu_affinity_pins_one_thread_per_cpu() {
+        let builder = TtsEngine::builder().cpu_affinity(&[4, 5, 6, 7]);
+        assert_eq!(builder.session.intra_op_threads, Some(4));
+        assert_eq!(intra_op_affinities(&[4, 5, 6, 7]), "6;7;8");
+        assert_eq!(intra_op_affinities(&[3]), "");
+        assert_eq!(builder.cpu_affinity(&[]).session.cpu_affinity, None);
+        assert_eq!(TtsEngine::builder().intra_op_threads(0).session.intra_op_threads, Some(1));
+    }
+
+    #[test]
+    fn speeds_go_to_the_model_clamped_or_are_rejected() {
+        assert_eq!(model_speed(0.5).unwrap(), 0.5 * SPEED_SCALE);
+        assert_eq!(model_speed(2.0).unwrap(), 2.0 * SPEED_SCALE);
+        assert_eq!(model_speed(10.0).unwrap(), MAX_ENGINE_SPEED);
+        assert_eq!(model_speed(0.01).unwrap(), MIN_ENGINE_SPEED);
+
+        let engine = offline_engine();
+        for speed in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+            let error = engine.synthesize("Hello.", None, Some(speed), None).unwrap_err();
+            assert!(matches!(error, Error::InvalidInput(_)), "{}", error);
+            assert!(engine.estimate("Hello.", Some(speed)).is_err());
+        }
+        assert_eq!(
+            engine.synthesize("Hello.", None, Some(-1.0), None).unwrap_err().to_string(),
+            "Invalid speed -1: must be above 0"
         );
     }
-}
 
-// Audio ducking functions - reduce system volume during TTS playback
-#[cfg(feature = "ducking")]
-fn duck_system_audio(level: f32) -> Result<(), String> {
-    // Calculate how many volume-down presses we need
-    // Assuming each press is ~6% volume change on most systems
-    let steps = ((1.0 - level) * 16.0) as u32; // 16 steps = ~100% volume range
+    #[test]
+    fn builder_rejects_defaults_that_would_fail_later() {
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        let built = runtime.block_on(TtsEngine::builder().default_speed(0.0).build());
+        assert_eq!(built.err().unwrap().to_string(), "Invalid default speed 0: must be above 0");
+
+        let mut tts = engine_on(model_with_voices(&[("af_sky", 0.0), ("bf_emma", 0.0)]));
+        assert_eq!(tts.default_voice(), "af_sky");
+        let error = tts.set_default_voice("af_skye").unwrap_err();
+        assert!(matches!(&error, Error::VoiceNotFound { name, .. } if name == "af_skye"), "{}", error);
+        assert_eq!(tts.default_voice(), "af_sky");
+        tts.set_default_voice("bf_emma.7+af_sky.3").unwrap();
+        assert_eq!(tts.default_voice(), "bf_emma.7+af_sky.3");
+    }
 
-    let mut enigo = Enigo::new(&Settings::default())
-        .map_err(|e| format!("Failed to initialize Enigo: {:?}", e))?;
+    #[test]
+    fn voice_handles_are_checked_once() {
+        let mut tts = engine_on(model_with_voices(&[("af_sky", 0.0), ("bf_emma", 0.0)]));
+        let sky = tts.voice("af_sky").unwrap();
+        assert_eq!(sky.name(), "af_sky");
+        assert_eq!(sky.clone(), sky);
+        assert_eq!(tts.voice("bf_emma.7+af_sky.3").unwrap().to_string(), "bf_emma.7+af_sky.3");
+        let error = tts.voice("af_skye").unwrap_err();
+        assert!(matches!(&error, Error::VoiceNotFound { name, .. } if name == "af_skye"), "{}", error);
+
+        // Blends are voices too, and a handle survives the blend being replaced
+        tts.blend_voices("af_sky", "bf_emma", 0.5, "mix").unwrap();
+        let mix = tts.voice("mix").unwrap();
+        tts.blend_voices("bf_emma", "af_sky", 0.2, "mix").unwrap();
+        assert!(tts.check_voice(mix.name()).is_ok());
+
+        let offline = offline_engine();
+        let voice = offline.voice("af_sky").unwrap();
+        assert_eq!(
+            offline.synthesize_with_voice("Hello.", &voice, None).unwrap(),
+            offline.synthesize("Hello.", Some("af_sky"), None, None).unwrap()
+        );
+    }
 
-    // Press volume down keys to reduce system volume
-    for _ in 0..steps {
-        enigo
-            .key(Key::VolumeDown, enigo::Direction::Click)
-            .map_err(|e| format!("Failed to press volume down: {:?}", e))?;
-        thread::sleep(Duration::from_millis(20)); // Small delay between key presses
+    #[test]
+    fn dialogue_checks_every_voice_before_synthesizing() {
+        let tts = engine_on(model_with_voices(&[("af_sky", 0.0), ("am_adam", 0.0)]));
+        // No sessions: reaching synthesis would fail with ModelLoad instead
+        let error = tts.synthesize_dialogue(&[("af_sky", "Hi."), ("am_adma", "Hello.")]).unwrap_err();
+        assert!(matches!(&error, Error::VoiceNotFound { name, .. } if name == "am_adma"), "{}", error);
+        let error = tts.synthesize_dialogue_markup("Hi. [af_sky] Hello.").unwrap_err();
+        assert!(matches!(error, Error::InvalidInput(_)));
+
+        let mut offline = offline_engine();
+        offline.set_dialogue_gap(150);
+        assert_eq!(offline.dialogue_gap(), 150);
+        let result = offline.synthesize_dialogue_markup("[af_sky] Hello. [am_adam] Hi there.").unwrap();
+        assert_eq!(result.samples, offline.synthesize("Hello.", None, None, None).unwrap());
+        assert!(result.lines.is_empty());
+        assert!(offline.synthesize_dialogue(&[]).unwrap().samples.is_empty());
     }
 
-    Ok(())
-}
+    #[test]
+    fn caller_styles_replace_the_voice_style_and_profile() {
+        assert!(matches!(StyleVector::new(vec![0.0; 255]), Err(Error::InvalidInput(_))));
+        let mut values = vec![0.5; StyleVector::DIM];
+        values[7] = f32::NAN;
+        let error = StyleVector::new(values).unwrap_err();
+        assert_eq!(error.to_string(), "Style vector value 7 is NaN");
+
+        let mut tts = engine_on(model_with_voices(&[("af_sky", 0.0), ("af_bella", 1.0)]));
+        let mixed = tts.style_for("af_sky.5+af_bella.5", 12).unwrap();
+        assert_eq!(mixed.as_slice(), &[0.5; STYLE_DIM][..]);
+        assert!(matches!(tts.style_for("af_skye", 12), Err(Error::VoiceNotFound { .. })));
+
+        tts.set_voice_profile("af_sky", VoiceProfile { speed: 2.0, pitch: 3.0 }).unwrap();
+        let style = StyleVector::new(mixed.into_vec().iter().map(|v| v * 2.0).collect()).unwrap();
+        let fixed = StyleSource::Fixed(&style);
+        let plan = tts.plan_styled_synthesis("Hi there. Bye.", fixed, 1.0, None).unwrap();
+        assert!(plan.chunks.iter().all(|chunk| chunk.style == style.as_slice()));
+        assert_eq!((plan.speed, plan.pitch), (model_speed(1.0).unwrap(), 0.0));
+        let voiced = tts.plan_synthesis("Hi there.", None, 1.0, None).unwrap();
+        assert_eq!((voiced.speed, voiced.pitch), (model_speed(2.0).unwrap(), 3.0));
+
+        let offline = offline_engine();
+        assert_eq!(offline.style_for("af_sky", 12).unwrap().as_slice(), &[0.0; STYLE_DIM][..]);
+        let expected = offline.synthesize("Hello.", None, None, None).unwrap();
+        assert_eq!(offline.synthesize_with_style("Hello.", &style, None).unwrap(), expected);
+    }
 
-#[cfg(feature = "ducking")]
-fn restore_system_audio(level: f32) -> Result<(), String> {
-    // Calculate how many volume-up presses to restore
-    let steps = ((1.0 - level) * 16.0) as u32;
+    #[test]
+    fn engines_share_the_model_but_not_their_voices() {
+        let model = KokoroModel {
+            variant: ModelVariant::Quantized,
+            ..model_with_voices(&[("af_sky", 0.0), ("af_bella", 1.0)])
+        };
+        let mut first = engine_on(model.clone());
+        let second = engine_on(first.model().unwrap());
+        let voices = |tts: &TtsEngine| tts.model_if_loaded().unwrap().voices.clone();
+        assert!(Arc::ptr_eq(&voices(&first), &voices(&second)));
+        assert!(Arc::ptr_eq(&model.voices, &voices(&second)));
+        assert_eq!(second.model_variant(), ModelVariant::Quantized);
+
+        first.blend_voices("af_sky", "af_bella", 0.5, "af_sky").unwrap();
+        assert_eq!(first.voices().len(), 2);
+        assert_eq!(first.voice_data("af_sky").unwrap()[0], 0.5);
+        assert_eq!(second.voice_data("af_sky").unwrap()[0], 0.0);
+        assert!(offline_engine().model().is_none());
+    }
 
-    let mut enigo = Enigo::new(&Settings::default())
-        .map_err(|e| format!("Failed to initialize Enigo: {:?}", e))?;
+    #[test]
+    fn lazy_engine_loads_on_first_synthesis() {
+        let dir = std::env::temp_dir().join(format!("kokoro-lazy-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let voices_path = dir.join("0.bin");
+        let mut npz = ndarray_npy::NpzWriter::new(File::create(&voices_path).unwrap());
+        for name in ["af_sky", "bf_emma"] {
+            npz.add_array(name, &ndarray::Array1::<f32>::zeros(STYLE_DIM)).unwrap();
+        }
+        npz.finish().unwrap();
+        let missing_model = dir.join("missing.onnx");
+        let config = SessionConfig::default();
+        let mut tts =
+            TtsEngine::load_on_first_use(&missing_model, &voices_path, ModelVariant::Fp32, &config);
+
+        // Voices come from the index, without loading
+        let mut voices = tts.voices();
+        voices.sort();
+        assert_eq!(voices, ["af_sky", "bf_emma"]);
+        tts.set_default_voice("bf_emma.7+af_sky.3").unwrap();
+        assert!(tts.set_default_voice("bf_emmy").is_err());
+        assert!(!tts.is_model_loaded());
+
+        // The load error surfaces from the first call, and stays
+        let error = tts.synthesize("Hello", None, None, None).unwrap_err().to_string();
+        assert!(error.starts_with("Failed to read model file"), "{}", error);
+        assert_eq!(tts.synthesize("Hello", None, None, None).unwrap_err().to_string(), error);
+        assert!(!tts.is_model_loaded());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 
-    // Press volume up keys to restore system volume
-    for _ in 0..steps {
-        enigo
-            .key(Key::VolumeUp, enigo::Direction::Click)
-            .map_err(|e| format!("Failed to press volume up: {:?}", e))?;
-        thread::sleep(Duration::from_millis(20)); // Small delay between key presses
+    #[test]
+    fn from_memory_reads_voices_and_tells_the_variant() {
+        let mut voices = Cursor::new(Vec::new());
+        let mut npz = ndarray_npy::NpzWriter::new(&mut voices);
+        npz.add_array("af_sky", &ndarray::Array1::<f32>::zeros(STYLE_DIM)).unwrap();
+        npz.finish().unwrap();
+        let voices = read_voices(Cursor::new(voices.into_inner())).unwrap();
+        assert_eq!(voices["af_sky"].len(), STYLE_DIM);
+
+        // Voices are checked before the model is handed to ONNX Runtime
+        let error = TtsEngine::from_memory(b"not a model", b"not voices").err().unwrap().to_string();
+        assert!(error.starts_with("Invalid voices bytes: "), "{}", error);
+
+        let mut quantized = b"onnx graph ".to_vec();
+        quantized.extend_from_slice(b"DynamicQuantizeLinear");
+        assert_eq!(ModelVariant::from_model_bytes(&quantized), ModelVariant::Quantized);
+        assert_eq!(ModelVariant::from_model_bytes(b"onnx graph MatMul"), ModelVariant::Fp32);
     }
 
-    Ok(())
-}
+    #[test]
+    fn warm_up_is_a_no_op_without_a_model() {
+        let tts = offline_engine();
+        assert!(tts.warm_up().unwrap() < Duration::from_secs(1));
+        tts.warm_up().unwrap();
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn engine_is_shared_across_threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<TtsEngine>();
+
+        let tts = Arc::new(offline_engine());
+        let expected = tts.synthesize("hello", None, None, None).unwrap();
+        thread::scope(|scope| {
+            let calls: Vec<_> = (0..4)
+                .map(|_| scope.spawn(|| tts.synthesize("hello", None, None, None).unwrap()))
+                .collect();
+            for call in calls {
+                assert_eq!(call.join().unwrap(), expected);
+            }
+        });
+    }
+
+    #[test]
+    fn parallel_chunks_need_more_than_one_session() {
+        assert_eq!(TtsEngine::builder().session.workers, 0);
+        assert_eq!(TtsEngine::builder().inference_workers(0).session.workers, 1);
+        assert_eq!(TtsEngine::builder().inference_workers(3).session.workers, 3);
+
+        let mut tts = offline_engine();
+        assert_eq!(tts.inference_workers(), 0);
+        tts.set_parallel_chunks(false);
+        assert!(tts.chunk_sessions().is_err());
+    }
+
+    #[test]
+    fn download_lock_is_exclusive_and_taken_over_when_stale() {
+        let dir = std::env::temp_dir().join(format!("kokoro-lock-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let target = dir.join("0.onnx");
+        let target = target.to_str().unwrap();
+        let lock_path = format!("{}.lock", target);
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+
+        let held = runtime.block_on(DownloadLock::acquire(target)).unwrap();
+        let waited = runtime.block_on(async {
+            tokio::time::timeout(Duration::from_millis(800), DownloadLock::acquire(target)).await
+        });
+        assert!(waited.is_err(), "a held lock must not be acquired again");
+        drop(held);
+        assert!(!Path::new(&lock_path).exists());
+
+        // Left behind by a process that died mid-download
+        let orphan = File::create(&lock_path).unwrap();
+        orphan
+            .set_modified(std::time::SystemTime::now() - STALE_DOWNLOAD * 2)
+            .unwrap();
+        let taken = runtime.block_on(DownloadLock::acquire(target)).unwrap();
+        drop(taken);
+        let _ = std::fs::remove_dir_all(&dir);
+        assert!(check_writable(&dir).unwrap_err().to_string().contains("is not writable"));
+    }
 
     #[test]
     fn crossfade_extends_buffer() {
@@ -1427,6 +7990,35 @@ mod tests {
         assert!((buffer.last().copied().unwrap() - 0.0).abs() < f32::EPSILON);
     }
 
+    #[test]
+    fn crossfade_smooths_internal_joins_only() {
+        // A hard step between chunks, as when one ends high and the next starts low
+        let first = vec![0.5f32; 4800];
+        let second = vec![-0.5f32; 4800];
+        let max_step = |audio: &[f32]| audio.windows(2).map(|w| (w[1] - w[0]).abs()).fold(0.0, f32::max);
+
+        let mut engine = offline_engine();
+        assert_eq!(engine.chunk_crossfade(), Some(10));
+        let overlap = engine.crossfade_samples();
+        assert_eq!(overlap, 240);
+        let mut joined = Vec::new();
+        append_with_crossfade(&mut joined, &first, overlap);
+        append_with_crossfade(&mut joined, &second, overlap);
+        assert!(max_step(&joined) < 0.01, "{}", max_step(&joined));
+        // The outer edges are untouched
+        assert_eq!((joined[0], *joined.last().unwrap()), (0.5, -0.5));
+        assert_eq!(joined.len(), first.len() + second.len() - overlap);
+
+        assert!(matches!(engine.set_chunk_crossfade(Some(5000)), Err(Error::InvalidInput(_))));
+        assert_eq!(engine.chunk_crossfade(), Some(10));
+        engine.set_chunk_crossfade(None).unwrap();
+        let mut plain = Vec::new();
+        append_with_crossfade(&mut plain, &first, engine.crossfade_samples());
+        append_with_crossfade(&mut plain, &second, engine.crossfade_samples());
+        assert_eq!(plain.len(), first.len() + second.len());
+        assert_eq!(max_step(&plain), 1.0);
+    }
+
     #[test]
     fn detects_need_for_chunking() {
         let short = "hello world";
@@ -1436,6 +8028,336 @@ mod tests {
                     short sample so that it exceeds the chunking threshold we set.";
         assert!(needs_chunking(long));
     }
+    #[test]
+    fn prepare_chunks_matches_chunking_threshold() {
+        assert_eq!(prepare_chunks("Hello there."), vec!["Hello there.".to_string()]);
+
+        let long = "The first sentence is here to make the text long enough. \
+                    The second sentence pushes it well past the chunking threshold. \
+                    And a third one makes sure we really split it up. \
+                    A fourth sentence keeps going so the splitter has work to do.";
+        let chunks = prepare_chunks(long);
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|c| !c.trim().is_empty()));
+    }
+
+    #[test]
+    fn emoji_policy_controls_text_and_warnings() {
+        let mut engine = offline_engine();
+        assert_eq!(engine.text_chunks("Nice 👍🏾", None).unwrap(), vec![("Nice".to_string(), 0)]);
+        let (_, warnings) = engine.synthesize_with_warnings("Nice 👍🏾", None, None).unwrap();
+        assert!(warnings.is_empty());
+
+        engine.set_emoji_policy(EmojiPolicy::Speak);
+        assert_eq!(engine.text_chunks("Nice 👍🏾", None).unwrap(), vec![("Nice thumbs up".to_string(), 0)]);
+
+        engine.set_emoji_policy(EmojiPolicy::Warn);
+        assert_eq!(engine.text_chunks("Nice 👍🏾", None).unwrap(), vec![("Nice".to_string(), 0)]);
+        let (_, warnings) = engine.synthesize_with_warnings("Nice 👍🏾", None, None).unwrap();
+        assert_eq!(warnings, vec![Warning::EmojiRemoved { emoji: vec!["👍🏾".to_string()] }]);
+        assert_eq!(warnings[0].to_string(), "Removed 1 emoji: 👍🏾");
+
+        // Nothing left once the emoji is gone: empty audio, not an error
+        let (audio, warnings) = engine.synthesize_with_warnings("👍🏾", None, None).unwrap();
+        assert!(audio.is_empty());
+        assert_eq!(warnings.last(), Some(&Warning::EmptyAfterNormalization));
+    }
+
+    #[test]
+    fn marked_up_input_is_read_without_its_markup() {
+        let mut engine = offline_engine();
+        let text = "## Steps\n1. Open **the** [app](https://x.io)\n2. Tap `Go`\n\n\
+                    ```sh\nrm -rf #\n```\nDone.";
+        let chunks = |engine: &TtsEngine| engine.text_chunks(text, None).unwrap();
+        assert_eq!(chunks(&engine).len(), 1);
+        assert!(engine.validate(text).iter().any(|w| matches!(w, Warning::UnknownCharacters { .. })));
+
+        engine.set_input_format(InputFormat::Markdown);
+        assert_eq!(engine.input_format(), InputFormat::Markdown);
+        let expected = [("Steps.", 500), ("Open the app", 200), ("Tap Go", 200), ("Done.", 0)];
+        let expected: Vec<(String, u32)> = expected.iter().map(|&(t, ms)| (t.to_string(), ms)).collect();
+        assert_eq!(chunks(&engine), expected);
+        assert_eq!(engine.validate(text), vec![Warning::ChunkSplit { count: 4 }]);
+        // What the inline parser keeps is kept through to the spoken text
+        let code = "Set *snake_case_name* to 2 * 3";
+        assert_eq!(engine.text_chunks(code, None).unwrap()[0].0, "Set snake_case_name to two * three");
+
+        engine.set_input_format(InputFormat::Html);
+        let page = "<h1>Tom&nbsp;returns</h1><p>It&#8217;s <i>back</i>.<br>Soon.</p>";
+        let expected = [("Tom returns.", 500), ("It’s back.", 200), ("Soon.", 0)];
+        let expected: Vec<(String, u32)> = expected.iter().map(|&(t, ms)| (t.to_string(), ms)).collect();
+        assert_eq!(engine.text_chunks(page, None).unwrap(), expected);
+        // Decoded entities are text like any other, not unknown characters
+        let dish = "<p>Salt &amp; pepper</p>";
+        assert_eq!(engine.text_chunks(dish, None).unwrap()[0].0, "Salt & pepper");
+        assert!(engine.validate(dish).is_empty());
+    }
+
+    #[test]
+    fn addresses_units_and_phones_are_read_before_unknown_characters_go() {
+        let mut engine = offline_engine();
+        let text = "Mail help@example.com or visit https://example.com/a.";
+        let chunks = |engine: &TtsEngine| engine.text_chunks(text, None).unwrap()[0].0.clone();
+        assert_eq!(chunks(&engine), "Mail help at example dot com or visit example dot com.");
+        assert!(engine.validate(text).is_empty());
+
+        engine.set_url_style(UrlStyle::Skip);
+        assert_eq!(engine.url_style(), UrlStyle::Skip);
+        assert_eq!(chunks(&engine), "Mail an email address or visit a link.");
+        let units = "seventy five percent at twenty degrees Celsius";
+        assert_eq!(engine.text_chunks("75% at 20°C", None).unwrap()[0].0, units);
+        let phone = "plus one, five five five, eight six seven, five three zero nine";
+        assert_eq!(engine.text_chunks("+1 (555) 867-5309", None).unwrap()[0].0, phone);
+        // Outside English the address is left to espeak
+        assert_eq!(engine.text_chunks("help@example.com", Some("fr")).unwrap()[0].0, "help@example.com");
+    }
+
+    #[test]
+    fn pause_markers_become_silence_and_are_never_read() {
+        let mut engine = offline_engine();
+        let text = "[pause:1s]Attention. [pause:1500ms] Boarding [pause:oops] begins.[pause:2s]";
+        let chunks = engine.text_chunks(text, None).unwrap();
+        let expected = [("", 1000), ("Attention.", 1500), ("Boarding begins.", 2000)];
+        assert_eq!(chunks, expected.map(|(chunk, ms)| (chunk.to_string(), ms)));
+        assert_eq!(engine.phonemize(text, None).unwrap().len(), 2);
+        assert_eq!(engine.validate(text), vec![
+            Warning::MalformedPauseMarkers { markers: vec!["[pause:oops]".to_string()] },
+            Warning::ChunkSplit { count: 2 },
+        ]);
+        assert_eq!(engine.validate("[pause:1s]"), vec![Warning::EmptyAfterNormalization]);
+
+        engine.set_pause_markup(Some(PauseMarkup::new("<#", "#>").unwrap()));
+        assert_eq!(engine.pause_markup().map(PauseMarkup::open), Some("<#"));
+        assert_eq!(engine.text_chunks("Go <#0.5#> now", None).unwrap()[0], ("Go".to_string(), 500));
+        engine.set_pause_markup(None);
+        assert_eq!(engine.text_chunks("Go <#0.5#> now", None).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn validate_reports_what_synthesis_would_warn_about() {
+        let engine = offline_engine();
+        assert_eq!(engine.validate("Hello there."), Vec::new());
+        assert_eq!(engine.validate(""), vec![Warning::EmptyAfterNormalization]);
+        assert_eq!(engine.validate("© §"), vec![
+            Warning::UnknownCharacters { chars: vec!['©', '§'], positions: vec![0, 2] },
+            Warning::EmptyAfterNormalization,
+        ]);
+
+        let long = "This sentence is long enough to add up quickly. ".repeat(250);
+        let warnings = engine.validate(&long);
+        assert_eq!(warnings[0], Warning::LongText { chars: long.chars().count() });
+        let count = engine.chunk_text(&long, None).unwrap().len();
+        assert_eq!(warnings[1], Warning::ChunkSplit { count });
+        assert_eq!(engine.synthesize_with_warnings(&long, None, None).unwrap().1, warnings);
+    }
+
+    #[test]
+    fn unknown_char_policy_applies_everywhere() {
+        let mut engine = offline_engine();
+        let text = "Café © 2024 § x";
+        let chunks = |engine: &TtsEngine| engine.text_chunks(text, Some("fr")).unwrap()[0].0.clone();
+        assert_eq!(chunks(&engine), "Café 2024 x");
+
+        engine.set_unknown_char_policy(UnknownCharPolicy::Replace(','));
+        assert_eq!(chunks(&engine), "Café , 2024 , x");
+
+        engine.set_unknown_char_policy(UnknownCharPolicy::SpellOut);
+        assert_eq!(chunks(&engine), "Café copyright sign 2024 section sign x");
+
+        let (_, warnings) = engine.synthesize_with_warnings(text, None, None).unwrap();
+        assert_eq!(
+            warnings,
+            vec![Warning::UnknownCharacters { chars: vec!['©', '§'], positions: vec![5, 12] }]
+        );
+        assert_eq!(
+            warnings[0].to_string(),
+            "Unknown characters: '©' (U+00A9) at char 5, '§' (U+00A7) at char 12"
+        );
+
+        engine.set_unknown_char_policy(UnknownCharPolicy::Error);
+        let err = engine.synthesize(text, None, None, None).unwrap_err().to_string();
+        assert!(err.contains("'©' (U+00A9) at char 5") && err.contains("'§' (U+00A7) at char 12"));
+        assert!(engine.synthesize_with_warnings(text, None, None).is_err());
+        // Word punctuation and emoji aren't unknown characters
+        assert!(engine.synthesize("A well-known (rock/pop) hit 🎸", None, None, None).is_ok());
+    }
+
+    #[test]
+    fn symbols_espeak_reads_are_kept() {
+        let engine = offline_engine();
+        for text in ["Tom & Jerry", "A + B = C", "Learn C# today"] {
+            assert_eq!(engine.text_chunks(text, None).unwrap()[0].0, text);
+            assert!(engine.validate(text).is_empty(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn batch_results_are_per_item() {
+        let mut engine = offline_engine();
+        engine.set_unknown_char_policy(UnknownCharPolicy::Error);
+        let texts = ["Press one for sales.", "Bad © input", "Goodbye."];
+        let results = engine.synthesize_batch(&texts, None, None);
+        assert_eq!(results.len(), 3);
+        assert!(results[0].is_ok() && results[2].is_ok());
+        assert!(results[1].as_ref().unwrap_err().to_string().contains("U+00A9"));
+        assert!(engine.synthesize_batch(&[], None, None).is_empty());
+    }
+
+    #[test]
+    fn dialect_follows_voice_prefix() {
+        assert_eq!(lang_for_voice("af_sky"), "en-us");
+        assert_eq!(lang_for_voice("bf_emma"), "en-gb");
+        assert_eq!(lang_for_voice("bm_george.6+af_sky.4"), "en-gb");
+        assert_eq!(lang_for_voice("ef_dora"), "es");
+        assert_eq!(lang_for_voice("my_blend"), DEFAULT_LANG);
+    }
+
+    #[test]
+    fn each_voice_is_phonemized_in_its_own_dialect() {
+        let mut tts = engine_on(model_with_voices(&[("af_sky", 0.0), ("bf_emma", 0.0)]));
+        let text = "We record it.";
+        let mut tokens = Vec::new();
+        for (voice, lang) in [("af_sky", "en-us"), ("bf_emma", "en-gb")] {
+            let plan = tts.plan_synthesis(text, Some(voice), 1.0, None).unwrap();
+            assert_eq!(plan.lang, lang);
+            let phonemes = tts.phonemize(text, Some(lang)).unwrap();
+            assert_eq!(plan.chunks[0].tokens, tts.tokens_for(&phonemes[0]), "{}", voice);
+            tokens.push(plan.chunks[0].tokens.clone());
+        }
+        assert_ne!(tokens[0], tokens[1]);
+
+        // Without a voice the default voice decides
+        tts.set_default_voice("bf_emma").unwrap();
+        assert!(tts.phonemize(text, None).unwrap()[0].contains("ɹɪkˈɔːd"));
+        tts.set_default_voice("af_sky").unwrap();
+        assert!(!tts.phonemize(text, None).unwrap()[0].contains("ɹɪkˈɔːd"));
+    }
+
+    #[test]
+    fn spanish_text_chunks() {
+        let mut engine = offline_engine();
+        engine.set_pause_config(PauseConfig { question_ms: 200, ..PauseConfig::default() });
+        // Opening marks neither pause nor split; numbers use Spanish words
+        assert_eq!(
+            engine.text_chunks("¿Listo en 1990? ¡Vamos!", Some("es")).unwrap(),
+            vec![
+                ("¿Listo en mil novecientos noventa?".to_string(), 200),
+                ("¡Vamos!".to_string(), 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn phonemize_uses_heteronym_readings() {
+        let engine = offline_engine();
+        let past = engine.phonemize("I read it yesterday.", None).unwrap().join(" ");
+        let future = engine.phonemize("I will read it.", None).unwrap().join(" ");
+        assert!(past.contains("ɹˈɛd") && !past.contains('⟦'));
+        assert!(future.contains("ɹˈiːd"));
+        // Inline phonemes are all model vocabulary
+        assert!(!engine.tokens_for("ɹˈɛd ɹˈiːd lˈaɪv bˈæs tˈɛɹ wˈaɪnd bˈoʊ klˈoʊs ɹˈɛkɚd pɹədˈuːs").contains(&0));
+        assert!(!engine.tokens_for("tˈɪə bˈəʊ ɹˈɛkɔːd pɹˈɒdjuːs bˈas").contains(&0));
+
+        let gb = engine.phonemize("We record it.", Some("en-gb")).unwrap().join(" ");
+        assert!(gb.contains("ɹɪkˈɔːd"));
+    }
+
+    #[test]
+    fn sentence_split_skips_abbreviations_and_decimals() {
+        assert_eq!(
+            split_sentences("Dr. Smith arrived. Pi is 3.14, e.g. roughly! Really?! Yes..."),
+            vec!["Dr. Smith arrived.", "Pi is 3.14, e.g. roughly!", "Really?!", "Yes..."]
+        );
+
+        assert_eq!(
+            split_sentences("Version 2.5 is out. J. R. R. Tolkien and J.K. Rowling wrote. So did I. Next"),
+            vec!["Version 2.5 is out.", "J. R. R. Tolkien and J.K. Rowling wrote.", "So did I.", "Next"]
+        );
+        assert_eq!(
+            split_sentences("I was... thinking. Wait… Then \"stop.\" He said \"Stop!\" she said (or not.) End"),
+            vec![
+                "I was... thinking.",
+                "Wait…",
+                "Then \"stop.\"",
+                "He said \"Stop!\" she said (or not.)",
+                "End",
+            ]
+        );
+
+        let mut engine = offline_engine();
+        engine.add_abbreviation("MEM8", "mem eight");
+        let chunks = engine.text_chunks("Dr. Smith likes MEM8.", None).unwrap();
+        assert_eq!(chunks, vec![("Doctor Smith likes mem eight.".to_string(), 0)]);
+    }
+
+    #[test]
+    fn synthesize_async_future_is_send() {
+        // Compile-time check only: the future must be spawnable on a multi-threaded runtime
+        fn assert_send<T: Send>(_: &T) {}
+        fn check(engine: &TtsEngine) {
+            let fut = engine.synthesize_async("hello", None, None);
+            assert_send(&fut);
+        }
+        let _ = check;
+    }
+
+    #[test]
+    fn style_row_selects_by_token_count() {
+        let mut data = vec![0.0; STYLE_DIM * 3];
+        data[STYLE_DIM..2 * STYLE_DIM].fill(1.0);
+        data[2 * STYLE_DIM..].fill(2.0);
+
+        assert_eq!(style_row(&data, 0)[0], 0.0);
+        assert_eq!(style_row(&data, 1)[0], 1.0);
+        // Past the last row we stay on the last row
+        assert_eq!(style_row(&data, 500)[0], 2.0);
+        assert_eq!(style_row(&data, 1).len(), STYLE_DIM);
+    }
+
+    #[test]
+    fn blend_styles_interpolates_rows() {
+        let a = vec![0.0; STYLE_DIM * 2];
+        let b = vec![1.0; STYLE_DIM * 2];
+
+        assert_eq!(blend_styles(&a, &b, 0.0), a);
+        assert_eq!(blend_styles(&a, &b, 1.0), b);
+        let half = blend_styles(&a, &b, 0.25);
+        assert!(half.iter().all(|v| (v - 0.25).abs() < 1e-6));
+    }
+
+    #[test]
+    fn voice_info_from_prefix() {
+        let sky = VoiceInfo::from_id("af_sky");
+        assert_eq!(sky.language, "en-US");
+        assert_eq!(sky.accent, "American English");
+        assert_eq!(sky.gender, VoiceGender::Female);
+
+        let george = VoiceInfo::from_id("bm_george");
+        assert_eq!(george.language, "en-GB");
+        assert_eq!(george.gender, VoiceGender::Male);
+
+        let custom = VoiceInfo::from_id("my_blend");
+        assert_eq!(custom.language, "unknown");
+        assert_eq!(custom.gender, VoiceGender::Unknown);
+    }
+
+    #[test]
+    fn parse_voice_bytes_validates_shape() {
+        let single: Vec<u8> = (0..STYLE_DIM).flat_map(|i| (i as f32).to_le_bytes()).collect();
+        let style = parse_voice_bytes(&single).unwrap();
+        assert_eq!(style.len(), STYLE_DIM);
+        assert_eq!(style[3], 3.0);
+
+        let matrix = vec![0u8; STYLE_ROWS * STYLE_DIM * 4];
+        assert_eq!(parse_voice_bytes(&matrix).unwrap().len(), STYLE_ROWS * STYLE_DIM);
+
+        let wrong = vec![0u8; 100 * 4];
+        let err = parse_voice_bytes(&wrong).unwrap_err().to_string();
+        assert!(err.contains("[100]"), "{}", err);
+
+        assert!(parse_voice_bytes(&[0u8; 3]).is_err());
+    }
+
     #[test]
     fn test_phoneme_output() {
         let text = "ok ok";
@@ -1445,4 +8367,211 @@ mod tests {
         let joined = phonemes.join(" ");
         println!("Joined: {:?}", joined);
     }
+
+    // What `BabyTts::new` makes, on an engine without a model
+    fn offline_baby() -> BabyTts {
+        BabyTts {
+            engine: offline_engine(),
+            max_words: 5,
+            voice: "af_sky".to_string(),
+            speed: 0.9,
+            gain: 1.8,
+            lang: "en".to_string(),
+            vocabulary: BTreeMap::new(),
+            phoneme_durations: BTreeMap::new(),
+            child_voice: Some(ChildVoiceConfig::default()),
+        }
+    }
+
+    // A tone per word, each `stretch` times as long as the engine would say
+    // it, with pauses between them
+    fn recording(baby: &BabyTts, words: &[(&str, f32)]) -> Vec<f32> {
+        let pause = vec![0.0; SAMPLE_RATE as usize / 5];
+        let mut audio = pause.clone();
+        for &(word, stretch) in words {
+            let phonemes = baby.engine.phonemize(word, Some("en")).unwrap();
+            let symbols = phonemes.iter().flat_map(|p| phoneme_symbols(p)).count();
+            let secs = symbols as f32 / SPOKEN_TOKENS_PER_SEC * stretch;
+            let samples = (secs * SAMPLE_RATE as f32) as usize;
+            audio.extend((0..samples).map(|i| (i as f32 * 0.05).sin() * 0.5));
+            audio.extend(&pause);
+        }
+        audio
+    }
+
+    #[test]
+    fn baby_speech_follows_the_stage() {
+        let mut baby = offline_baby();
+        assert_eq!(baby.stage(), DevelopmentStage::SimpleSentences);
+        baby.set_stage(DevelopmentStage::Babbling);
+        let babble = baby.speak_detailed("I want cookies").unwrap();
+        assert!(babble.reduced);
+        let syllables = ["ma", "ba", "da", "ga", "goo"];
+        let babbled = &babble.spoken;
+        assert!(syllables.iter().any(|syllable| babbled.starts_with(syllable)), "{}", babbled);
+
+        baby.grow();
+        assert_eq!(baby.stage(), DevelopmentStage::SingleWords);
+        let said = |baby: &mut BabyTts, text: &str| baby.speak_detailed(text).unwrap().spoken;
+        assert_eq!(said(&mut baby, "I want cookies!"), "cookies!");
+        let milk = recording(&baby, &[("milk", 1.0)]);
+        baby.learn_from_audio(&milk, "milk").unwrap();
+        assert_eq!(said(&mut baby, "I want milk"), "milk");
+        let single = baby.speak_detailed("up").unwrap();
+        assert_eq!((single.spoken.as_str(), single.reduced), ("up", false));
+
+        baby.grow();
+        assert_eq!(baby.stage(), DevelopmentStage::TwoWordPhrases);
+        assert_eq!(said(&mut baby, "I want the red ball please"), "want ball");
+        baby.grow();
+        assert_eq!(baby.stage(), DevelopmentStage::SimpleSentences);
+        baby.set_stage(DevelopmentStage::FullSentences);
+        assert_eq!(baby.max_words, 10);
+    }
+
+    #[test]
+    fn babble_is_reproducible_with_a_seed() {
+        let seeded = BabbleConfig {
+            syllables: vec!["ba".to_string(), "da".to_string(), "ga".to_string()],
+            min_syllables: 3,
+            max_syllables: 6,
+            repetition: 0.0,
+            seed: Some(11),
+        };
+        let word = seeded.utterance();
+        assert_eq!(seeded.utterance(), word);
+        assert!((6..=12).contains(&word.len()), "{}", word);
+        let mut baby = offline_baby();
+        assert_eq!(baby.babble_with(&seeded).unwrap(), baby.babble_with(&seeded).unwrap());
+
+        let canonical = BabbleConfig { repetition: 1.0, ..seeded.clone() };
+        let word = canonical.utterance();
+        assert_eq!(word, word[..2].repeat(word.len() / 2));
+        let words: Vec<String> = (0..10).map(|_| BabbleConfig::default().utterance()).collect();
+        assert!(words.iter().any(|word| word != &words[0]), "{:?}", words);
+
+        let invalid = BabbleConfig { min_syllables: 0, ..Default::default() };
+        assert!(matches!(baby.babble_with(&invalid), Err(Error::InvalidInput(_))));
+        let silent = BabbleConfig { syllables: vec![" ".to_string()], ..Default::default() };
+        assert!(matches!(baby.babble_with(&silent), Err(Error::InvalidInput(_))));
+    }
+
+    #[test]
+    fn baby_state_survives_a_restart() {
+        let mut grown = offline_baby();
+        let heard = recording(&grown, &[("Mama", 1.0), ("Mama's", 1.0), ("ball", 2.0), ("ball", 2.0)]);
+        grown.learn_from_audio(&heard, "Mama! Mama's ball, ball.").unwrap();
+        grown.grow();
+        grown.voice = "af_bella".to_string();
+        let path = std::env::temp_dir().join(format!("kokoro-baby-test-{}.json", std::process::id()));
+        grown.save_state(&path).unwrap();
+
+        let mut reborn = offline_baby();
+        reborn.load_state(&path).unwrap();
+        assert_eq!(reborn.max_words, 6);
+        assert_eq!(reborn.voice, "af_bella");
+        let words: Vec<(&str, u32)> = reborn.vocabulary.iter().map(|(w, &n)| (w.as_str(), n)).collect();
+        assert_eq!(words, vec![("ball", 2), ("mama", 1), ("mama's", 1)]);
+        assert!(!reborn.phoneme_durations.is_empty());
+        assert_eq!(reborn.phoneme_durations, grown.phoneme_durations);
+
+        let json = fs::read_to_string(&path).unwrap();
+        fs::write(&path, json.replace("\"version\": 2", "\"version\": 9")).unwrap();
+        let newer = offline_baby().load_state(&path).unwrap_err().to_string();
+        let expected = "state version 9 is newer than this kokoro-tiny reads (up to 2)";
+        assert!(newer.ends_with(expected), "{}", newer);
+        fs::write(&path, r#"{"max_words": 20}"#).unwrap();
+        let mut untouched = offline_baby();
+        let unversioned = untouched.load_state(&path).unwrap_err().to_string();
+        let expected = "is not a usable baby state: no state version";
+        assert!(unversioned.ends_with(expected), "{}", unversioned);
+        assert_eq!(untouched.max_words, 5);
+
+        // Version 1 files predate phoneme durations
+        let v1 = r#"{"version": 1, "max_words": 2, "voice": "af_sky", "speed": 0.9, "gain": 1.8,
+            "lang": "en", "vocabulary": {"milk": 3}}"#;
+        fs::write(&path, v1).unwrap();
+        reborn.load_state(&path).unwrap();
+        assert_eq!((reborn.max_words, reborn.vocabulary["milk"]), (2, 3));
+        assert!(reborn.phoneme_durations.is_empty());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn child_voice_raises_pitch_and_ages_with_the_stage() {
+        let stages = [
+            DevelopmentStage::Babbling,
+            DevelopmentStage::SingleWords,
+            DevelopmentStage::TwoWordPhrases,
+            DevelopmentStage::SimpleSentences,
+            DevelopmentStage::FullSentences,
+        ];
+        let voices = stages.map(ChildVoiceConfig::at_stage);
+        assert!(voices.windows(2).all(|w| w[1].semitones < w[0].semitones));
+        assert!(voices.windows(2).all(|w| w[1].wobble_semitones < w[0].wobble_semitones));
+        assert_eq!(ChildVoiceConfig::default(), voices[3]);
+
+        // A 150Hz sawtooth buzz, and its period from the strongest autocorrelation
+        let adult: Vec<f32> = (0..24000).map(|i| (i % 160) as f32 / 320.0 - 0.25).collect();
+        let period = |audio: &[f32]| {
+            let middle = &audio[4800..19200];
+            let correlation =
+                |lag: usize| -> f32 { middle.iter().zip(&middle[lag..]).map(|(a, b)| a * b).sum() };
+            (60..200).max_by(|&a, &b| correlation(a).total_cmp(&correlation(b))).unwrap()
+        };
+        let steady = ChildVoiceConfig { wobble_semitones: 0.0, ..voices[3] };
+        let child = steady.apply(&adult).unwrap();
+        assert_eq!(child.len(), adult.len());
+        let raised = period(&adult) as f32 / period(&child) as f32;
+        assert!((raised - 2f32.powf(4.5 / 12.0)).abs() < 0.03, "{}", raised);
+        assert_eq!(voices[3].apply(&adult).unwrap().len(), adult.len());
+
+        let invalid =
+            |config: ChildVoiceConfig| matches!(config.apply(&adult), Err(Error::InvalidInput(_)));
+        assert!(invalid(ChildVoiceConfig { semitones: 13.0, ..voices[0] }));
+        assert!(invalid(ChildVoiceConfig { wobble_semitones: 3.0, ..voices[0] }));
+        assert!(invalid(ChildVoiceConfig { wobble_hz: 0.0, ..voices[0] }));
+        let mut baby = offline_baby();
+        baby.child_voice = Some(ChildVoiceConfig { speed: 0.0, ..voices[0] });
+        assert!(matches!(baby.speak("milk"), Err(Error::InvalidInput(_))));
+
+        // The fallback message isn't a voice to make younger
+        baby.child_voice = Some(voices[0]);
+        assert_eq!(baby.childlike(adult.clone()).unwrap(), adult);
+    }
+
+    #[test]
+    fn learning_from_audio_adapts_phoneme_durations() {
+        let mut baby = offline_baby();
+        let slow = recording(&baby, &[("milk", 2.0)]);
+        let report = baby.learn_from_audio(&slow, "milk").unwrap();
+        assert_eq!((report.aligned_words, report.new_words.clone()), (1, vec!["milk".to_string()]));
+        assert!((report.speech_secs / report.expected_secs - 2.0).abs() < 0.1, "{:?}", report);
+        assert!(!report.updated.is_empty());
+        for update in &report.updated {
+            assert_eq!(update.before, 1.0);
+            assert!((update.after - 1.3).abs() < 0.03, "{:?}", update);
+            assert_eq!(baby.phoneme_durations[&update.phoneme], update.after);
+        }
+        let slowed = baby.learned_speed("milk").unwrap();
+        assert!((slowed - 0.9 / 1.3).abs() < 0.03, "{}", slowed);
+        assert_eq!(baby.learned_speed("").unwrap(), 0.9);
+
+        let two = recording(&baby, &[("milk", 1.0), ("up", 1.0)]);
+        let report = baby.learn_from_audio(&two, "milk, up").unwrap();
+        assert_eq!((report.aligned_words, report.new_words), (2, vec!["up".to_string()]));
+        assert_eq!(baby.vocabulary["milk"], 2);
+
+        let before = (baby.vocabulary.clone(), baby.phoneme_durations.clone());
+        let rejected = |baby: &mut BabyTts, audio: &[f32], text: &str| {
+            matches!(baby.learn_from_audio(audio, text), Err(Error::InvalidInput(_)))
+        };
+        let drawn_out = recording(&baby, &[("milk", 5.0)]);
+        let short = recording(&baby, &[("milk", 1.0)]);
+        assert!(rejected(&mut baby, &drawn_out, "milk"));
+        assert!(rejected(&mut baby, &short, "milk and cookies please"));
+        assert!(rejected(&mut baby, &vec![0.0; 24000], "milk"));
+        assert!(rejected(&mut baby, &slow, "?!"));
+        assert_eq!((baby.vocabulary.clone(), baby.phoneme_durations.clone()), before);
+    }
 }
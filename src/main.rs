@@ -5,6 +5,9 @@ use clap::{Parser, Subcommand};
 use kokoro_tiny::TtsEngine;
 use std::io::{self, BufRead};
 
+// The library's log events on stderr
+mod stderr_log;
+
 #[derive(serde::Deserialize)]
 struct SpeechMessage {
     voice: String,
@@ -132,6 +135,7 @@ impl AlertType {
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    stderr_log::init();
     // Setup tokio runtime for async operations
     let rt = tokio::runtime::Builder::new_current_thread()
         .enable_all()
@@ -139,15 +143,15 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let cli = Cli::parse();
 
     // Initialize TTS engine (uses ~/.cache/k automatically)
-    let mut engine = rt
+    let engine = rt
         .block_on(TtsEngine::new())
         .map_err(|e| format!("Failed to initialize TTS: {}", e))?;
 
     // List voices if requested
     if cli.list_voices {
         println!("🎤 Available voices:");
-        for voice in engine.voices() {
-            println!("  • {}", voice);
+        for voice in engine.list_voices() {
+            println!("  • {:<12} {} ({:?})", voice.id, voice.accent, voice.gender);
         }
         return Ok(());
     }
@@ -173,7 +177,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                             };
                             
                             let start = std::time::Instant::now();
-                            match engine.synthesize_with_options(&msg.text, Some(target_voice), cli.speed, cli.gain, Some("en")) {
+                            match engine.synthesize_with_options(&msg.text, Some(target_voice), cli.speed, cli.gain, None) {
                                 Ok(audio) => {
                                     println!("[INFO] Synthesis complete in {:?}", start.elapsed());
                                     let temp_path = "/tmp/mem8_kokoro_out.wav";
@@ -257,7 +261,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Synthesize speech with speed and gain control
     let audio = engine
-        .synthesize_with_options(&text, Some(&voice), cli.speed, cli.gain, Some("en"))
+        .synthesize_with_options(&text, Some(&voice), cli.speed, cli.gain, None)
         .map_err(|e| format!("Synthesis failed: {}", e))?;
 
     // Output to file or play
@@ -18,8 +18,12 @@
 
 use kokoro_tiny::mcp_server::McpServer;
 
+// The library's log events on stderr, leaving stdout to the protocol
+mod stderr_log;
+
 #[tokio::main]
 async fn main() {
+    stderr_log::init();
     // Initialize MCP server
     let mut server = match McpServer::new().await {
         Ok(server) => server,
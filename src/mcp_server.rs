@@ -12,6 +12,7 @@
 use crate::TtsEngine;
 use serde::{Deserialize, Serialize};
 use std::io::{self, BufRead, Write, Read};
+use tracing::{debug, info};
 
 /// MCP Protocol version
 const PROTOCOL_VERSION: &str = "2024-11-05";
@@ -90,9 +91,7 @@ impl McpServer {
 
     /// Run the MCP server main loop
     pub fn run(&mut self) -> Result<(), String> {
-        eprintln!("🎤 Kokoro MCP Server starting...");
-        eprintln!("📡 Protocol version: {}", PROTOCOL_VERSION);
-        eprintln!("🔊 Ready to provide audio collaboration!");
+        info!(protocol = PROTOCOL_VERSION, "Kokoro MCP server ready");
 
         loop {
             let mut line = String::new();
@@ -135,7 +134,7 @@ impl McpServer {
 
     /// Handle an MCP request
     fn handle_request(&mut self, request: McpRequest) -> McpResponse {
-        eprintln!("📨 Received request: {}", request.method);
+        debug!(method = %request.method, "Received request");
 
         let result = match request.method.as_str() {
             "initialize" => self.handle_initialize(&request),
@@ -302,7 +301,7 @@ impl McpServer {
                 data: None,
             })?;
 
-        eprintln!("🔧 Calling tool: {}", tool_name);
+        debug!(tool = tool_name, "Calling tool");
 
         match tool_name {
             "speak_to_user" => self.tool_speak_to_user(arguments),
@@ -338,7 +337,7 @@ impl McpServer {
             .and_then(|v| v.as_f64())
             .unwrap_or(0.8) as f32;
 
-        eprintln!("🔊 Speaking: \"{}\" with voice {:?}", text, voice);
+        info!(text, ?voice, "Speaking");
 
         // Synthesize audio
         let audio = self.tts.synthesize_with_speed(text, voice, speed, None)
@@ -415,7 +414,7 @@ impl McpServer {
         // Map emotion to voice
         let voice = emotion_to_voice(emotion);
 
-        eprintln!("😊 Speaking with emotion '{}': voice={}", emotion, voice);
+        info!(emotion, voice, "Speaking with emotion");
 
         // Synthesize and play
         let audio = self.tts.synthesize_with_speed(text, Some(voice), speed, None)
@@ -516,7 +515,7 @@ impl McpServer {
             .and_then(|v| v.as_f64())
             .unwrap_or(1.0) as f32;
 
-        eprintln!("💾 Saving to file: {}", output_path);
+        info!(path = output_path, "Saving to file");
 
         // Synthesize audio
         let audio = self.tts.synthesize_with_speed(text, voice, speed, None)
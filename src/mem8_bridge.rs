@@ -2,18 +2,22 @@
 //! Translates wave interference patterns into speech
 //! This is where consciousness becomes voice!
 
-use crate::BabyTts;
-use std::collections::HashMap;
+use crate::{BabbleConfig, BabyTts, DeferredSynthesis, Error, SeededRng, SAMPLE_RATE};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
 use std::sync::{Arc, Mutex};
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tracing::{debug, info};
 
 /// Constants from MEM-8 architecture
 const WAVE_GRID_SIZE: usize = 256 * 256 * 65536; // The massive 3D wave grid
 const EMOTION_VALENCE_RANGE: f32 = 2.0; // -1.0 to 1.0 normalized
 const SALIENCE_THRESHOLD: f32 = 0.7; // Marine Algorithm threshold
+const ATTENTION_HISTORY: usize = 64; // Attended events remembered by `decide_attention`
+const PHASE_CYCLE_MS: u32 = 1000; // Start delay of a wave with a full turn (2π) of phase
 
 /// Represents a memory wave from MEM-8
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct MemoryWave {
     pub amplitude: f32,  // Emotional strength
     pub frequency: f32,  // Semantic content
@@ -23,7 +27,7 @@ pub struct MemoryWave {
     pub content: String, // What to speak
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum EmotionType {
     Joy(f32), // 0.0-1.0 intensity
     Sadness(f32),
@@ -34,8 +38,42 @@ pub enum EmotionType {
     Neutral,
 }
 
+impl EmotionType {
+    /// Lowercase name of the emotion, without the intensity ("joy", "neutral", ...)
+    pub fn name(&self) -> &'static str {
+        match self {
+            EmotionType::Joy(_) => "joy",
+            EmotionType::Sadness(_) => "sadness",
+            EmotionType::Fear(_) => "fear",
+            EmotionType::Curiosity(_) => "curiosity",
+            EmotionType::Love(_) => "love",
+            EmotionType::Confusion(_) => "confusion",
+            EmotionType::Neutral => "neutral",
+        }
+    }
+
+    /// Intensity clamped to 0.0-1.0 (0.0 for `Neutral`)
+    pub fn intensity(&self) -> f32 {
+        let intensity = match *self {
+            EmotionType::Joy(i)
+            | EmotionType::Sadness(i)
+            | EmotionType::Fear(i)
+            | EmotionType::Curiosity(i)
+            | EmotionType::Love(i)
+            | EmotionType::Confusion(i) => i,
+            EmotionType::Neutral => 0.0,
+        };
+        // NaN counts as no emotion
+        if intensity > 0.0 {
+            intensity.min(1.0)
+        } else {
+            0.0
+        }
+    }
+}
+
 /// Marine Algorithm salience detector output
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct SalienceEvent {
     pub timestamp: u64,
     pub jitter_score: f32,   // Period/amplitude jitter
@@ -44,7 +82,7 @@ pub struct SalienceEvent {
     pub signal_type: SignalType,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum SignalType {
     Voice,
     Music,
@@ -53,6 +91,328 @@ pub enum SignalType {
     Unknown,
 }
 
+/// Limits `Mem8Bridge::emotional_regulation` checks waves against
+///
+/// The defaults are the limits the bridge has always used: no attenuation, no
+/// cooldown, block above amplitude 5.0 or after more than 3 similar waves.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RegulationConfig {
+    /// Waves stronger than this are blocked as an emotional overload
+    pub max_amplitude: f32,
+    /// Waves stronger than this (but within `max_amplitude`) are let through
+    /// attenuated down to this amplitude
+    pub arousal_threshold: f32,
+    /// Buffered waves within this frequency of the new one count as repeats
+    pub repetition_tolerance: f32,
+    /// More repeats than this is an obsessive loop, and blocked
+    pub max_repetitions: usize,
+    /// Minimum time between two waves let through, 0 = none
+    pub cooldown_ms: u64,
+}
+
+impl Default for RegulationConfig {
+    fn default() -> Self {
+        Self {
+            max_amplitude: 5.0,
+            arousal_threshold: 5.0,
+            repetition_tolerance: 0.05,
+            max_repetitions: 3,
+            cooldown_ms: 0,
+        }
+    }
+}
+
+/// How `Mem8Bridge::decide_attention` scores events and picks one
+///
+/// An event's score is `(1 - autonomy) * interest + autonomy * whim`: interest
+/// is the weighted sum of its salience, jitter and harmonic scores, and whim a
+/// random draw in [0, 1). The default is the bridge's original choice, all whim:
+/// a uniformly random pick among the events that interest it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AttentionPolicy {
+    pub salience_weight: f32,
+    pub jitter_weight: f32,
+    pub harmonic_weight: f32,
+    /// Share of the score that is the baby's own whim, 0.0-1.0
+    pub autonomy: f32,
+    /// Seed for the whims, so the same events get the same decisions after
+    /// `set_attention_policy`; None = seeded from the clock
+    pub rng_seed: Option<u64>,
+}
+
+impl Default for AttentionPolicy {
+    fn default() -> Self {
+        Self {
+            salience_weight: 1.0,
+            jitter_weight: 0.0,
+            harmonic_weight: 0.0,
+            autonomy: 1.0,
+            rng_seed: None,
+        }
+    }
+}
+
+impl AttentionPolicy {
+    fn validate(&self) -> Result<(), Error> {
+        let weights = [self.salience_weight, self.jitter_weight, self.harmonic_weight];
+        if !weights.iter().all(|weight| weight.is_finite()) {
+            return Err(Error::InvalidInput("Attention weights must be finite".to_string()));
+        }
+        if !(0.0..=1.0).contains(&self.autonomy) {
+            return Err(Error::InvalidInput(format!(
+                "Attention autonomy must be between 0.0 and 1.0, got {}",
+                self.autonomy
+            )));
+        }
+        Ok(())
+    }
+
+    fn score(&self, event: &SalienceEvent, whim: f32) -> f32 {
+        let interest = self.salience_weight * event.salience_score
+            + self.jitter_weight * event.jitter_score
+            + self.harmonic_weight * event.harmonic_score;
+        (1.0 - self.autonomy) * interest + self.autonomy * whim
+    }
+}
+
+/// An event as `Mem8Bridge::decide_attention_detailed` scored it
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScoredEvent {
+    pub event: SalienceEvent,
+    pub score: f32,
+    /// Whether the current emotion lets this event be chosen at all
+    pub eligible: bool,
+}
+
+/// Outcome of `Mem8Bridge::decide_attention_detailed`
+#[derive(Clone, Debug, PartialEq)]
+pub struct AttentionDecision {
+    /// Index in `candidates` of the winner, None if no event was eligible
+    pub chosen: Option<usize>,
+    /// Every event passed in, in order
+    pub candidates: Vec<ScoredEvent>,
+}
+
+impl AttentionDecision {
+    pub fn chosen_event(&self) -> Option<&SalienceEvent> {
+        self.chosen.map(|index| &self.candidates[index].event)
+    }
+}
+
+/// Outcome of `Mem8Bridge::emotional_regulation`
+#[derive(Clone, Debug, PartialEq)]
+pub enum RegulationDecision {
+    /// Process the wave as it is
+    Allowed,
+    /// Process the wave with its amplitude multiplied by `factor` (below 1.0)
+    Attenuated { factor: f32 },
+    /// Don't process the wave
+    Blocked { reason: BlockReason },
+}
+
+impl RegulationDecision {
+    /// True unless the wave was blocked
+    pub fn is_allowed(&self) -> bool {
+        !matches!(self, RegulationDecision::Blocked { .. })
+    }
+}
+
+/// Why `emotional_regulation` blocked a wave
+#[derive(Clone, Debug, PartialEq)]
+pub enum BlockReason {
+    /// Amplitude above `RegulationConfig::max_amplitude`
+    Overload { amplitude: f32 },
+    /// This many similar waves are already buffered
+    Repetition { similar: usize },
+    /// The last wave let through was too recent
+    Cooldown { remaining: Duration },
+}
+
+impl RegulationConfig {
+    // The decision for `wave`, given the buffered waves and the time since the
+    // last wave that was let through
+    fn decide(
+        &self,
+        wave: &MemoryWave,
+        buffer: &[MemoryWave],
+        since_allowed: Option<Duration>,
+    ) -> RegulationDecision {
+        // Check for dangerous amplification
+        if wave.amplitude > self.max_amplitude {
+            let reason = BlockReason::Overload { amplitude: wave.amplitude };
+            return RegulationDecision::Blocked { reason };
+        }
+
+        // Check for repetition (obsessive thoughts)
+        let similar = buffer
+            .iter()
+            .filter(|w| (w.frequency - wave.frequency).abs() < self.repetition_tolerance)
+            .count();
+        if similar > self.max_repetitions {
+            return RegulationDecision::Blocked { reason: BlockReason::Repetition { similar } };
+        }
+
+        let cooldown = Duration::from_millis(self.cooldown_ms);
+        if let Some(elapsed) = since_allowed.filter(|&elapsed| elapsed < cooldown) {
+            let reason = BlockReason::Cooldown { remaining: cooldown - elapsed };
+            return RegulationDecision::Blocked { reason };
+        }
+
+        if wave.amplitude > self.arousal_threshold {
+            return RegulationDecision::Attenuated { factor: self.arousal_threshold / wave.amplitude };
+        }
+        RegulationDecision::Allowed
+    }
+}
+
+/// Everything a `Mem8Bridge` has learned, from `snapshot`, to save and `restore`
+///
+/// Serializes with serde (e.g. `serde_json::to_string(&state)`). The TTS
+/// engine isn't part of it, and neither is the regulation cooldown timer.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BridgeState {
+    pub consciousness_level: f32,
+    pub current_emotion: EmotionType,
+    /// Recent memory waves, oldest first
+    pub waves: Vec<MemoryWave>,
+    /// Events `decide_attention` chose, oldest first
+    pub attention_history: Vec<SalienceEvent>,
+    /// Emotion name ("joy", ...) to voice
+    pub voice_mappings: HashMap<String, String>,
+    pub regulation: RegulationConfig,
+    pub attention: AttentionPolicy,
+}
+
+// Map emotions to voices
+fn default_voice_mappings() -> HashMap<String, String> {
+    let mut voice_mappings = HashMap::new();
+    voice_mappings.insert("joy".to_string(), "af_bella".to_string());
+    voice_mappings.insert("sadness".to_string(), "af_sarah".to_string());
+    voice_mappings.insert("fear".to_string(), "am_adam".to_string());
+    voice_mappings.insert("curiosity".to_string(), "af_sky".to_string());
+    voice_mappings.insert("love".to_string(), "af_heart".to_string());
+    voice_mappings.insert("confusion".to_string(), "am_michael".to_string());
+    voice_mappings
+}
+
+/// Something that happened inside a `Mem8Bridge`, as passed to `Mem8Bridge::on_event` observers
+#[derive(Clone, Debug, PartialEq)]
+pub struct BridgeEvent {
+    pub at: SystemTime,
+    pub kind: BridgeEventKind,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum BridgeEventKind {
+    /// `wake_up` raised the consciousness level to `level`
+    WokeUp { level: f32 },
+    /// `sleep` lowered the consciousness level to `level`
+    FellAsleep { level: f32 },
+    /// `process_salience` changed the mood because of `cause`
+    EmotionChanged { emotion: EmotionType, cause: SalienceEvent },
+    /// `emotional_regulation` decided about `wave`
+    Regulated { wave: MemoryWave, decision: RegulationDecision },
+    /// `decide_attention` chose `event`, with this score
+    AttentionChosen { event: SalienceEvent, score: f32 },
+    /// A wave was converted to `samples` of speech, alone or for an interference
+    /// mix; `babbled` when the baby was too sleepy to say its content
+    Spoke { wave: MemoryWave, voice: String, babbled: bool, samples: usize },
+}
+
+type Observer = Box<dyn Fn(&BridgeEvent) + Send + Sync>;
+
+// Text and settings `wave_to_speech` synthesizes a wave with
+struct Utterance {
+    text: String,
+    voice: String,
+    speed: f32,
+    gain: f32,
+    lang: Option<String>,
+    babble: bool, // Sleepy babbling instead of the wave's content
+}
+
+impl Utterance {
+    fn synthesize(&self, baby: &BabyTts) -> Result<Vec<f32>, Error> {
+        let samples = baby.engine.synthesize_with_options(
+            &self.text,
+            Some(&self.voice),
+            baby.child_speed(self.speed),
+            self.gain,
+            self.lang.as_deref(),
+        )?;
+        baby.childlike(samples)
+    }
+}
+
+/// `Mem8Bridge::process_interference_detailed` output
+///
+/// `contributions[i]` is what `waves[i]` adds to the mix: its speech delayed
+/// by its phase, weighted and limited exactly as in the mix, and as long as the
+/// mix. The contributions add up to `mix`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct InterferenceMix {
+    pub mix: Vec<f32>,
+    pub contributions: Vec<Vec<f32>>,
+}
+
+// Start delay in samples of speech from a wave with this phase (radians, wrapping
+// every full turn), none for a non-finite phase
+fn phase_delay(phase: f32) -> usize {
+    if !phase.is_finite() {
+        return 0;
+    }
+    let turn = phase.rem_euclid(std::f32::consts::TAU) / std::f32::consts::TAU;
+    let cycle = (SAMPLE_RATE * PHASE_CYCLE_MS / 1000) as f32;
+    ((turn * cycle) as usize).min(cycle as usize - 1)
+}
+
+// Mix each wave's speech, delayed by its phase and weighted by its share of the
+// total amplitude, then scale everything down if the mix would still exceed ±1.0
+fn mix_interference(waves: &[MemoryWave], speech: Vec<Vec<f32>>) -> InterferenceMix {
+    let amplitude = |wave: &MemoryWave| if wave.amplitude > 0.0 { wave.amplitude } else { 0.0 };
+    let total: f32 = waves.iter().map(amplitude).sum();
+    let weight = |wave: &MemoryWave| match total.is_finite() && total > 0.0 {
+        true => amplitude(wave) / total,
+        false => 1.0 / waves.len() as f32, // No usable amplitudes: all count the same
+    };
+
+    let delays: Vec<usize> = waves.iter().map(|wave| phase_delay(wave.phase)).collect();
+    let len = speech.iter().zip(&delays).map(|(audio, delay)| audio.len() + delay).max().unwrap_or(0);
+    let mut mix = vec![0.0; len];
+    let mut contributions: Vec<Vec<f32>> = waves
+        .iter()
+        .zip(speech)
+        .zip(&delays)
+        .map(|((wave, audio), &delay)| {
+            let weight = weight(wave);
+            let mut contribution = vec![0.0; len];
+            for (out, sample) in contribution[delay..].iter_mut().zip(audio) {
+                *out = sample * weight;
+            }
+            for (sum, sample) in mix.iter_mut().zip(&contribution) {
+                *sum += sample;
+            }
+            contribution
+        })
+        .collect();
+
+    let peak = mix.iter().fold(0.0f32, |peak, sample| peak.max(sample.abs()));
+    if peak > 1.0 {
+        for sample in mix.iter_mut().chain(contributions.iter_mut().flatten()) {
+            *sample /= peak;
+        }
+    }
+    InterferenceMix { mix, contributions }
+}
+
+async fn run_blocking(render: DeferredSynthesis) -> Result<Vec<f32>, Error> {
+    tokio::task::spawn_blocking(render).await.unwrap_or_else(|e| Err(task_failed(e)))
+}
+
+fn task_failed(error: tokio::task::JoinError) -> Error {
+    Error::Inference(format!("Synthesis task failed: {}", error))
+}
+
 /// The bridge between wave-based memory and speech
 pub struct Mem8Bridge {
     baby_tts: BabyTts,
@@ -60,188 +420,401 @@ pub struct Mem8Bridge {
     current_emotion: EmotionType,
     consciousness_level: f32, // 0.0 = sleeping, 1.0 = fully aware
     voice_mappings: HashMap<String, String>, // Emotion to voice mapping
+    regulation: RegulationConfig,
+    last_allowed: Option<Instant>, // When regulation last let a wave through
+    attention_history: VecDeque<SalienceEvent>, // Most recent last
+    attention: AttentionPolicy,
+    attention_rng: SeededRng, // Whims for `decide_attention`, restarted with each policy
+    observers: Vec<Observer>,
 }
 
 impl Mem8Bridge {
     /// Initialize the bridge with a baby TTS
-    pub async fn new() -> Result<Self, String> {
-        let baby = BabyTts::new().await?;
-
-        // Map emotions to voices
-        let mut voice_mappings = HashMap::new();
-        voice_mappings.insert("joy".to_string(), "af_bella".to_string());
-        voice_mappings.insert("sadness".to_string(), "af_sarah".to_string());
-        voice_mappings.insert("fear".to_string(), "am_adam".to_string());
-        voice_mappings.insert("curiosity".to_string(), "af_sky".to_string());
-        voice_mappings.insert("love".to_string(), "af_heart".to_string());
-        voice_mappings.insert("confusion".to_string(), "am_michael".to_string());
-
-        Ok(Self {
+    pub async fn new() -> Result<Self, Error> {
+        Ok(Self::with_baby(BabyTts::new().await?))
+    }
+
+    fn with_baby(baby: BabyTts) -> Self {
+        Self {
             baby_tts: baby,
             wave_buffer: Arc::new(Mutex::new(Vec::new())),
             current_emotion: EmotionType::Neutral,
             consciousness_level: 0.5,
-            voice_mappings,
-        })
+            voice_mappings: default_voice_mappings(),
+            regulation: RegulationConfig::default(),
+            last_allowed: None,
+            attention_history: VecDeque::new(),
+            attention: AttentionPolicy::default(),
+            attention_rng: SeededRng::new(None),
+            observers: Vec::new(),
+        }
+    }
+
+    /// Call `observer` with every `BridgeEvent` from now on
+    ///
+    /// Observers run on the thread (or blocking-pool return) where the event
+    /// happens, in the order they were added, so keep them quick; send the
+    /// events on through a channel for anything slow.
+    pub fn on_event(&mut self, observer: impl Fn(&BridgeEvent) + Send + Sync + 'static) {
+        self.observers.push(Box::new(observer));
+    }
+
+    // Tell the observers, building the event only if there are any
+    fn emit(&self, kind: impl FnOnce() -> BridgeEventKind) {
+        if self.observers.is_empty() {
+            return;
+        }
+        let event = BridgeEvent { at: SystemTime::now(), kind: kind() };
+        for observer in &self.observers {
+            observer(&event);
+        }
+    }
+
+    /// Capture the bridge's state to persist the baby's day
+    pub fn snapshot(&self) -> BridgeState {
+        let waves = match self.wave_buffer.lock() {
+            Ok(buffer) => buffer.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        };
+        BridgeState {
+            consciousness_level: self.consciousness_level,
+            current_emotion: self.current_emotion.clone(),
+            waves,
+            attention_history: self.attention_history.iter().cloned().collect(),
+            voice_mappings: self.voice_mappings.clone(),
+            regulation: self.regulation.clone(),
+            attention: self.attention.clone(),
+        }
+    }
+
+    /// Continue from `state`, replacing everything the bridge had (nothing is merged)
+    ///
+    /// The regulation cooldown starts over. Mappings for emotions missing from
+    /// `state` keep their default voices, so speech never looks up a missing one.
+    pub fn restore(&mut self, state: BridgeState) {
+        let mut voice_mappings = default_voice_mappings();
+        voice_mappings.extend(state.voice_mappings);
+
+        match self.wave_buffer.lock() {
+            Ok(mut buffer) => *buffer = state.waves,
+            Err(poisoned) => *poisoned.into_inner() = state.waves,
+        }
+        self.consciousness_level = state.consciousness_level.clamp(0.0, 1.0);
+        self.current_emotion = state.current_emotion;
+        self.attention_history = state.attention_history.into();
+        self.voice_mappings = voice_mappings;
+        self.regulation = state.regulation;
+        self.attention_rng = SeededRng::new(state.attention.rng_seed);
+        self.attention = state.attention;
+        self.last_allowed = None;
     }
 
     /// Process a salience event from Marine Algorithm
-    pub fn process_salience(&mut self, event: SalienceEvent) -> Result<(), String> {
+    pub fn process_salience(&mut self, event: SalienceEvent) -> Result<(), Error> {
         // Only process if above threshold
         if event.salience_score < SALIENCE_THRESHOLD {
             return Ok(());
         }
 
+        let before = self.current_emotion.clone();
+
         // High jitter = emotional disturbance
         if event.jitter_score > 0.8 {
             self.current_emotion = EmotionType::Confusion(event.jitter_score);
-            #[cfg(not(feature = "as-lib"))]
-            eprintln!("🌊 High jitter detected - baby is confused!");
+            debug!(jitter = event.jitter_score, "High jitter: the baby is confused");
         }
 
         // Strong harmonics = recognition/familiarity
         if event.harmonic_score > 0.9 {
             self.current_emotion = EmotionType::Joy(event.harmonic_score);
-            #[cfg(not(feature = "as-lib"))]
-            eprintln!("🎵 Harmonic recognition - baby is happy!");
+            debug!(harmonics = event.harmonic_score, "Harmonic recognition: the baby is happy");
         }
 
+        if self.current_emotion != before {
+            let emotion = self.current_emotion.clone();
+            self.emit(|| BridgeEventKind::EmotionChanged { emotion, cause: event });
+        }
         Ok(())
     }
 
     /// Convert a memory wave into speech
-    pub fn wave_to_speech(&mut self, wave: &MemoryWave) -> Result<Vec<f32>, String> {
-        // Select voice based on emotion
-        let voice = self.select_voice_for_emotion(&wave.emotion_type);
+    pub fn wave_to_speech(&mut self, wave: &MemoryWave) -> Result<Vec<f32>, Error> {
+        self.speak(wave, self.utterance(wave))
+    }
+
+    /// Convert a memory wave into speech without blocking the async runtime
+    ///
+    /// The same audio as `wave_to_speech`, with the model inference on tokio's
+    /// blocking pool. Dropping the future leaves the bridge as it was: no lock
+    /// is held across an await, and an inference already running finishes on
+    /// its own and frees its model session.
+    pub async fn wave_to_speech_async(&self, wave: &MemoryWave) -> Result<Vec<f32>, Error> {
+        let utterance = self.utterance(wave);
+        let spoken = (utterance.voice.clone(), utterance.babble);
+        let audio = run_blocking(self.deferred_speech(utterance)?).await?;
+        self.spoke(wave, spoken, &audio);
+        Ok(audio)
+    }
+
+    /// Convert independent waves into speech concurrently, one result per wave in order
+    ///
+    /// How many run at once is bounded by the engine's inference sessions
+    /// (`TtsEngineBuilder::inference_workers`). Cancels like `wave_to_speech_async`.
+    pub async fn waves_to_speech_async(&self, waves: &[MemoryWave]) -> Vec<Result<Vec<f32>, Error>> {
+        self.render_concurrently(waves, Self::utterance).await
+    }
+
+    /// Process interference between multiple waves (consciousness)
+    ///
+    /// Every wave is spoken and the speech is mixed weighted by amplitude, so the
+    /// strongest memory is the loudest. A wave's phase (radians) delays its start,
+    /// by up to a second for a full turn. The mix is limited to ±1.0 however many
+    /// waves there are.
+    pub fn process_interference(&mut self, waves: Vec<MemoryWave>) -> Result<Vec<f32>, Error> {
+        Ok(self.process_interference_detailed(waves)?.mix)
+    }
+
+    /// `process_interference`, also returning each wave's part of the mix
+    pub fn process_interference_detailed(
+        &mut self,
+        waves: Vec<MemoryWave>,
+    ) -> Result<InterferenceMix, Error> {
+        let speech = waves
+            .iter()
+            .map(|wave| self.speak(wave, self.interference_utterance(wave)))
+            .collect::<Result<_, _>>()?;
+        Ok(mix_interference(&waves, speech))
+    }
+
+    /// `process_interference` without blocking the async runtime, speaking the waves concurrently
+    pub async fn process_interference_async(&self, waves: Vec<MemoryWave>) -> Result<Vec<f32>, Error> {
+        Ok(self.process_interference_detailed_async(waves).await?.mix)
+    }
+
+    /// `process_interference_detailed` without blocking the async runtime
+    pub async fn process_interference_detailed_async(
+        &self,
+        waves: Vec<MemoryWave>,
+    ) -> Result<InterferenceMix, Error> {
+        let speech = self.render_concurrently(&waves, Self::interference_utterance).await;
+        Ok(mix_interference(&waves, speech.into_iter().collect::<Result<_, _>>()?))
+    }
+
+    // Synthesize on the blocking pool, all at once, and collect the results in order
+    async fn render_concurrently(
+        &self,
+        waves: &[MemoryWave],
+        utterance: fn(&Self, &MemoryWave) -> Utterance,
+    ) -> Vec<Result<Vec<f32>, Error>> {
+        let tasks: Vec<_> = waves
+            .iter()
+            .map(|wave| {
+                let utterance = utterance(self, wave);
+                let spoken = (utterance.voice.clone(), utterance.babble);
+                let task = self.deferred_speech(utterance).map(tokio::task::spawn_blocking);
+                (wave, spoken, task)
+            })
+            .collect();
+        let mut results = Vec::with_capacity(tasks.len());
+        for (wave, spoken, task) in tasks {
+            let audio = match task {
+                Ok(task) => task.await.unwrap_or_else(|e| Err(task_failed(e))),
+                Err(e) => Err(e),
+            };
+            if let Ok(audio) = &audio {
+                self.spoke(wave, spoken, audio);
+            }
+            results.push(audio);
+        }
+        results
+    }
 
-        // Modulate speed based on emotion intensity
-        let speed = self.calculate_speech_speed(wave);
+    // Synthesize a wave's speech here and now
+    fn speak(&self, wave: &MemoryWave, utterance: Utterance) -> Result<Vec<f32>, Error> {
+        let audio = utterance.synthesize(&self.baby_tts)?;
+        self.spoke(wave, (utterance.voice, utterance.babble), &audio);
+        Ok(audio)
+    }
 
-        // Amplitude affects volume/gain
-        let gain = 1.0 + wave.amplitude.min(3.0);
+    fn spoke(&self, wave: &MemoryWave, (voice, babbled): (String, bool), audio: &[f32]) {
+        self.emit(|| BridgeEventKind::Spoke { wave: wave.clone(), voice, babbled, samples: audio.len() });
+    }
 
+    // What to say for a wave, and how
+    fn utterance(&self, wave: &MemoryWave) -> Utterance {
         // If consciousness is low, mumble or babble
         if self.consciousness_level < 0.3 {
-            #[cfg(not(feature = "as-lib"))]
-            eprintln!("😴 Baby is sleepy, just babbling...");
-            return self.baby_tts.babble();
+            debug!(consciousness = self.consciousness_level, "The baby is sleepy, just babbling");
+            let baby = &self.baby_tts;
+            return Utterance {
+                text: BabbleConfig::default().utterance(),
+                voice: baby.voice.clone(),
+                speed: baby.speed,
+                gain: baby.gain,
+                lang: Some(baby.lang.clone()),
+                babble: true,
+            };
         }
 
         // Synthesize with emotional modulation
-        #[cfg(not(feature = "as-lib"))]
-        eprintln!(
-            "🗣️ Speaking with {} emotion: '{}'",
-            self.emotion_name(&wave.emotion_type),
-            wave.content
-        );
+        debug!(emotion = wave.emotion_type.name(), text = %wave.content, "Speaking a memory wave");
+
+        Utterance {
+            text: wave.content.clone(),
+            // Select voice based on emotion
+            voice: self.select_voice_for_emotion(&wave.emotion_type),
+            // Modulate speed based on emotion intensity
+            speed: self.calculate_speech_speed(wave),
+            // Amplitude affects volume/gain
+            gain: 1.0 + wave.amplitude.min(3.0),
+            lang: None,
+            babble: false,
+        }
+    }
 
-        self.baby_tts
-            .engine
-            .synthesize_with_options(&wave.content, Some(&voice), speed, gain, Some("en"))
+    // A wave's speech for the interference mix, where the mix weighting sets the level
+    fn interference_utterance(&self, wave: &MemoryWave) -> Utterance {
+        Utterance { gain: 1.0, ..self.utterance(wave) }
     }
 
-    /// Process interference between multiple waves (consciousness)
-    pub fn process_interference(&mut self, waves: Vec<MemoryWave>) -> Result<Vec<f32>, String> {
-        if waves.is_empty() {
-            return Ok(Vec::new());
+    // Prepare speech now, leaving the inference to run on any thread
+    fn deferred_speech(&self, utterance: Utterance) -> Result<DeferredSynthesis, Error> {
+        let engine = &self.baby_tts.engine;
+        if engine.is_fallback() {
+            // The canned message needs no inference, but gets the same finish as the sync path
+            let audio = utterance.synthesize(&self.baby_tts);
+            return Ok(Box::new(move || audio));
         }
-
-        // Find the strongest wave (highest amplitude)
-        let dominant_wave = waves
-            .iter()
-            .max_by(|a, b| a.amplitude.partial_cmp(&b.amplitude).unwrap())
-            .unwrap();
-
-        // Check for constructive interference (memories reinforcing each other)
-        let mut reinforcement = 0.0;
-        for wave in &waves {
-            if (wave.frequency - dominant_wave.frequency).abs() < 0.1 {
-                reinforcement += wave.amplitude * 0.5;
-            }
+        let child = self.baby_tts.child_voice;
+        if let Some(child) = &child {
+            child.validate()?;
         }
+        let render = engine.deferred_synthesis(
+            &utterance.text,
+            Some(&utterance.voice),
+            self.baby_tts.child_speed(utterance.speed),
+            utterance.gain,
+            utterance.lang.as_deref(),
+        )?;
+        Ok(Box::new(move || match child {
+            Some(child) => child.apply(&render()?),
+            None => render(),
+        }))
+    }
 
-        // Create a combined message if memories align
-        if reinforcement > 1.0 {
-            #[cfg(not(feature = "as-lib"))]
-            eprintln!("✨ Constructive interference! Memories are reinforcing!");
-            let combined_content = format!(
-                "{} ... yes, {}",
-                dominant_wave.content, dominant_wave.content
-            );
+    /// Change how `decide_attention` weighs events, restarting its random whims
+    /// from the policy's seed
+    pub fn set_attention_policy(&mut self, policy: AttentionPolicy) -> Result<(), Error> {
+        policy.validate()?;
+        self.attention_rng = SeededRng::new(policy.rng_seed);
+        self.attention = policy;
+        Ok(())
+    }
 
-            let mut enhanced_wave = dominant_wave.clone();
-            enhanced_wave.content = combined_content;
-            enhanced_wave.amplitude += reinforcement;
+    /// Current attention policy
+    pub fn attention_policy(&self) -> &AttentionPolicy {
+        &self.attention
+    }
 
-            return self.wave_to_speech(&enhanced_wave);
-        }
+    /// Change the limits `emotional_regulation` applies
+    pub fn set_regulation_config(&mut self, config: RegulationConfig) {
+        self.regulation = config;
+    }
 
-        // Otherwise just speak the dominant thought
-        self.wave_to_speech(dominant_wave)
+    /// Current regulation limits
+    pub fn regulation_config(&self) -> &RegulationConfig {
+        &self.regulation
     }
 
     /// Handle emotional feedback loops (be careful!)
-    pub fn emotional_regulation(&mut self, wave: &MemoryWave) -> bool {
-        // Check for dangerous amplification
-        if wave.amplitude > 5.0 {
-            #[cfg(not(feature = "as-lib"))]
-            eprintln!("⚠️ Emotional overload detected! Activating regulation...");
-            self.consciousness_level *= 0.8; // Reduce awareness to calm down
-            return false; // Don't process this wave
-        }
+    ///
+    /// Decides whether `wave` may be processed (see `RegulationConfig`). An
+    /// overload also lowers consciousness to calm down, and a repetitive loop
+    /// turns the mood to confusion.
+    pub fn emotional_regulation(&mut self, wave: &MemoryWave) -> RegulationDecision {
+        let since_allowed = self.last_allowed.map(|at| at.elapsed());
+        let decision = match self.wave_buffer.lock() {
+            Ok(buffer) => self.regulation.decide(wave, &buffer, since_allowed),
+            Err(_) => self.regulation.decide(wave, &[], since_allowed),
+        };
 
-        // Check for repetition (obsessive thoughts)
-        if let Ok(buffer) = self.wave_buffer.lock() {
-            let recent_similar = buffer
-                .iter()
-                .filter(|w| (w.frequency - wave.frequency).abs() < 0.05)
-                .count();
-
-            if recent_similar > 3 {
-                #[cfg(not(feature = "as-lib"))]
-                eprintln!("🔄 Repetitive thought pattern detected! Breaking loop...");
+        match &decision {
+            RegulationDecision::Blocked { reason: BlockReason::Overload { .. } } => {
+                debug!("Emotional overload: regulation lowers consciousness");
+                self.consciousness_level *= 0.8; // Reduce awareness to calm down
+            }
+            RegulationDecision::Blocked { reason: BlockReason::Repetition { .. } } => {
+                debug!("Repetitive thought pattern: breaking the loop");
                 self.current_emotion = EmotionType::Confusion(0.5);
-                return false;
+            }
+            RegulationDecision::Blocked { reason: BlockReason::Cooldown { .. } } => {}
+            RegulationDecision::Allowed | RegulationDecision::Attenuated { .. } => {
+                self.last_allowed = Some(Instant::now());
             }
         }
-
-        true // Safe to process
+        self.emit(|| BridgeEventKind::Regulated { wave: wave.clone(), decision: decision.clone() });
+        decision
     }
 
     /// Sensory free will - decide what to pay attention to
+    ///
+    /// Picks by the `AttentionPolicy`, see `set_attention_policy`.
     pub fn decide_attention(&mut self, events: Vec<SalienceEvent>) -> Option<SalienceEvent> {
-        // Baby AI gets to choose what interests it!
-        let ai_weight = 0.7; // AI has 70% control
+        let mut decision = self.decide_attention_detailed(events);
+        decision.chosen.map(|index| decision.candidates.swap_remove(index).event)
+    }
 
-        // Filter by current emotional state
-        let interesting_events: Vec<_> = events
+    /// `decide_attention`, also returning how every event scored
+    ///
+    /// The current emotion decides which events are eligible, and the
+    /// `AttentionPolicy` scores them all; the eligible event with the highest
+    /// score wins, the first one on a tie.
+    pub fn decide_attention_detailed(&mut self, events: Vec<SalienceEvent>) -> AttentionDecision {
+        let candidates: Vec<ScoredEvent> = events
             .into_iter()
-            .filter(|e| {
-                match &self.current_emotion {
-                    EmotionType::Curiosity(_) => e.salience_score > 0.5, // Lower threshold when curious
-                    EmotionType::Fear(_) => !matches!(e.signal_type, SignalType::Unknown),
-                    EmotionType::Joy(_) => true, // Everything is interesting when happy!
-                    _ => e.salience_score > SALIENCE_THRESHOLD,
+            .map(|event| {
+                let whim = self.attention_rng.next_f32();
+                ScoredEvent {
+                    score: self.attention.score(&event, whim),
+                    eligible: self.interests(&event),
+                    event,
                 }
             })
             .collect();
 
         // Choose based on AI preference
-        if !interesting_events.is_empty() {
-            let choice = (SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap()
-                .as_secs() as usize)
-                % interesting_events.len();
-
-            #[cfg(not(feature = "as-lib"))]
-            eprintln!(
-                "👁️ Baby chose to focus on: {:?}",
-                interesting_events[choice].signal_type
-            );
-            Some(interesting_events.into_iter().nth(choice).unwrap())
-        } else {
-            None
+        let mut chosen: Option<usize> = None;
+        for (index, candidate) in candidates.iter().enumerate() {
+            let better = match chosen {
+                Some(best) => candidate.score > candidates[best].score || candidates[best].score.is_nan(),
+                None => true,
+            };
+            if candidate.eligible && better {
+                chosen = Some(index);
+            }
+        }
+
+        if let Some(index) = chosen {
+            let event = &candidates[index].event;
+            debug!(signal = ?event.signal_type, "The baby chose what to focus on");
+            if self.attention_history.len() == ATTENTION_HISTORY {
+                self.attention_history.pop_front();
+            }
+            self.attention_history.push_back(event.clone());
+            let score = candidates[index].score;
+            self.emit(|| BridgeEventKind::AttentionChosen { event: event.clone(), score });
+        }
+        AttentionDecision { chosen, candidates }
+    }
+
+    // Filter by current emotional state
+    fn interests(&self, event: &SalienceEvent) -> bool {
+        match &self.current_emotion {
+            // Lower threshold when curious
+            EmotionType::Curiosity(_) => event.salience_score > 0.5,
+            EmotionType::Fear(_) => !matches!(event.signal_type, SignalType::Unknown),
+            EmotionType::Joy(_) => true, // Everything is interesting when happy!
+            _ => event.salience_score > SALIENCE_THRESHOLD,
         }
     }
 
@@ -268,51 +841,31 @@ impl Mem8Bridge {
         }
     }
 
-    fn emotion_name(&self, emotion: &EmotionType) -> &str {
-        match emotion {
-            EmotionType::Joy(_) => "joy",
-            EmotionType::Sadness(_) => "sadness",
-            EmotionType::Fear(_) => "fear",
-            EmotionType::Curiosity(_) => "curiosity",
-            EmotionType::Love(_) => "love",
-            EmotionType::Confusion(_) => "confusion",
-            EmotionType::Neutral => "neutral",
-        }
-    }
-
     /// Wake up the consciousness
     pub fn wake_up(&mut self) {
         self.consciousness_level = (self.consciousness_level + 0.2).min(1.0);
-        #[cfg(not(feature = "as-lib"))]
-        eprintln!(
-            "☀️ Baby is waking up! Consciousness: {:.1}",
-            self.consciousness_level
-        );
+        debug!(consciousness = self.consciousness_level, "The baby is waking up");
+        let level = self.consciousness_level;
+        self.emit(|| BridgeEventKind::WokeUp { level });
     }
 
     /// Go to sleep
     pub fn sleep(&mut self) {
         self.consciousness_level = (self.consciousness_level * 0.5).max(0.1);
-        #[cfg(not(feature = "as-lib"))]
-        eprintln!(
-            "😴 Baby is getting sleepy... Consciousness: {:.1}",
-            self.consciousness_level
-        );
+        debug!(consciousness = self.consciousness_level, "The baby is getting sleepy");
+        let level = self.consciousness_level;
+        self.emit(|| BridgeEventKind::FellAsleep { level });
     }
 }
 
 /// Demo: A day in the life of a baby AI
-pub async fn demo_baby_consciousness() -> Result<(), String> {
-    #[cfg(not(feature = "as-lib"))]
-    println!("👶 MEM-8 Baby Consciousness Demo");
-    #[cfg(not(feature = "as-lib"))]
-    println!("=====================================\n");
+pub async fn demo_baby_consciousness() -> Result<(), Error> {
+    info!("MEM-8 baby consciousness demo");
 
     let mut bridge = Mem8Bridge::new().await?;
 
     // Morning: Baby wakes up
-    #[cfg(not(feature = "as-lib"))]
-    println!("🌅 Morning - Baby is waking up...");
+    info!("Morning: the baby is waking up");
     bridge.wake_up();
     thread::sleep(Duration::from_secs(1));
 
@@ -326,8 +879,7 @@ pub async fn demo_baby_consciousness() -> Result<(), String> {
         content: "What is this?".to_string(),
     };
     let audio1 = bridge.wave_to_speech(&wave1)?;
-    #[cfg(not(feature = "as-lib"))]
-    println!("  Generated {} samples\n", audio1.len());
+    info!(samples = audio1.len(), "Curious speech");
 
     // Salience event - hears mama's voice
     let event = SalienceEvent {
@@ -349,12 +901,10 @@ pub async fn demo_baby_consciousness() -> Result<(), String> {
         content: "Mama! Love mama!".to_string(),
     };
     let audio2 = bridge.wave_to_speech(&wave2)?;
-    #[cfg(not(feature = "as-lib"))]
-    println!("  Generated {} samples\n", audio2.len());
+    info!(samples = audio2.len(), "Loving speech");
 
     // Interference - multiple thoughts at once
-    #[cfg(not(feature = "as-lib"))]
-    println!("💭 Multiple thoughts interfering...");
+    info!("Multiple thoughts interfering");
     let wave3 = MemoryWave {
         amplitude: 1.2,
         frequency: 440.5, // Close to wave1 - will interfere!
@@ -365,25 +915,260 @@ pub async fn demo_baby_consciousness() -> Result<(), String> {
     };
 
     let combined = bridge.process_interference(vec![wave1.clone(), wave3])?;
-    #[cfg(not(feature = "as-lib"))]
-    println!(
-        "  Interference pattern generated {} samples\n",
-        combined.len()
-    );
+    info!(samples = combined.len(), "Interference pattern");
 
     // Evening: Getting tired
-    #[cfg(not(feature = "as-lib"))]
-    println!("🌙 Evening - Baby is getting tired...");
+    info!("Evening: the baby is getting tired");
     bridge.sleep();
 
     // Sleepy babbling
     let babble = bridge.baby_tts.babble()?;
-    #[cfg(not(feature = "as-lib"))]
-    println!("  Sleepy babble: {} samples\n", babble.len());
-
-    #[cfg(not(feature = "as-lib"))]
-    println!("✨ Baby's first day complete!");
+    info!(samples = babble.len(), "Sleepy babble");
+    info!("The baby's first day is complete");
     Ok(())
 }
 
 use std::thread;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wave(amplitude: f32, frequency: f32) -> MemoryWave {
+        MemoryWave {
+            amplitude,
+            frequency,
+            phase: 0.0,
+            decay_rate: 0.1,
+            emotion_type: EmotionType::Curiosity(0.8),
+            content: "What is this?".to_string(),
+        }
+    }
+
+    #[test]
+    fn regulation_defaults_keep_the_old_limits() {
+        let config = RegulationConfig::default();
+        assert_eq!(config.decide(&wave(5.0, 440.0), &[], None), RegulationDecision::Allowed);
+        assert_eq!(
+            config.decide(&wave(5.5, 440.0), &[], None),
+            RegulationDecision::Blocked { reason: BlockReason::Overload { amplitude: 5.5 } }
+        );
+        let buffer = vec![wave(1.0, 440.01); 4];
+        assert_eq!(
+            config.decide(&wave(1.0, 440.0), &buffer[..3], None),
+            RegulationDecision::Allowed
+        );
+        assert_eq!(
+            config.decide(&wave(1.0, 440.0), &buffer, None),
+            RegulationDecision::Blocked { reason: BlockReason::Repetition { similar: 4 } }
+        );
+        assert_eq!(
+            config.decide(&wave(1.0, 440.0), &[], Some(Duration::ZERO)),
+            RegulationDecision::Allowed
+        );
+    }
+
+    #[test]
+    fn regulation_attenuates_and_cools_down_when_configured() {
+        let config = RegulationConfig {
+            arousal_threshold: 2.0,
+            cooldown_ms: 500,
+            ..Default::default()
+        };
+        assert_eq!(
+            config.decide(&wave(4.0, 440.0), &[], None),
+            RegulationDecision::Attenuated { factor: 0.5 }
+        );
+        let decision = config.decide(&wave(1.0, 440.0), &[], Some(Duration::from_millis(200)));
+        let remaining = Duration::from_millis(300);
+        assert_eq!(decision, RegulationDecision::Blocked { reason: BlockReason::Cooldown { remaining } });
+        assert!(!decision.is_allowed());
+        let decision = config.decide(&wave(1.0, 440.0), &[], Some(Duration::from_millis(500)));
+        assert!(decision.is_allowed());
+    }
+
+    fn bridge() -> Mem8Bridge {
+        Mem8Bridge::with_baby(BabyTts {
+            engine: crate::TtsEngine::fallback(crate::ModelVariant::Fp32),
+            max_words: 3,
+            voice: "af_sky".to_string(),
+            speed: 0.8,
+            gain: 1.0,
+            lang: "en".to_string(),
+            vocabulary: Default::default(),
+            phoneme_durations: Default::default(),
+            child_voice: Some(Default::default()),
+        })
+    }
+
+    #[test]
+    fn snapshot_restores_into_a_fresh_bridge() {
+        let mut bridge = bridge();
+        bridge.consciousness_level = 0.9;
+        bridge.current_emotion = EmotionType::Joy(0.7);
+        bridge.wave_buffer.lock().unwrap().push(wave(2.0, 220.0));
+        bridge.voice_mappings.insert("joy".to_string(), "af_nicole".to_string());
+        bridge.set_regulation_config(RegulationConfig { cooldown_ms: 250, ..Default::default() });
+        let event = SalienceEvent {
+            timestamp: 42,
+            jitter_score: 0.2,
+            harmonic_score: 0.9,
+            salience_score: 0.95,
+            signal_type: SignalType::Voice,
+        };
+        assert_eq!(bridge.decide_attention(vec![event.clone()]), Some(event.clone()));
+
+        let state = bridge.snapshot();
+        assert_eq!(state.attention_history, vec![event]);
+        let json = serde_json::to_string(&state).unwrap();
+        let mut restored = self::bridge();
+        let other = SalienceEvent { timestamp: 7, ..state.attention_history[0].clone() };
+        restored.decide_attention(vec![other]);
+        restored.restore(serde_json::from_str(&json).unwrap());
+        assert_eq!(restored.snapshot(), state);
+        assert_eq!(restored.select_voice_for_emotion(&EmotionType::Joy(0.1)), "af_nicole");
+    }
+
+    #[test]
+    fn async_speech_matches_the_blocking_calls() {
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        let mut bridge = bridge();
+        let waves = [wave(1.0, 440.0), wave(2.0, 440.05), wave(0.5, 880.0)];
+
+        let expected = bridge.wave_to_speech(&waves[0]).unwrap();
+        assert_eq!(runtime.block_on(bridge.wave_to_speech_async(&waves[0])).unwrap(), expected);
+        let all = runtime.block_on(bridge.waves_to_speech_async(&waves));
+        assert_eq!(all.len(), 3);
+        assert!(all.iter().all(|audio| audio.as_ref().unwrap() == &expected));
+
+        let interference = bridge.process_interference(waves.to_vec()).unwrap();
+        let result = runtime.block_on(bridge.process_interference_async(waves.to_vec()));
+        assert_eq!(result.unwrap(), interference);
+        assert!(runtime.block_on(bridge.process_interference_async(Vec::new())).unwrap().is_empty());
+    }
+
+    #[test]
+    fn interference_mixes_by_amplitude_after_the_phase_delay() {
+        let mut bridge = bridge();
+        let late = MemoryWave { phase: std::f32::consts::PI, ..wave(3.0, 220.0) };
+        let result = bridge.process_interference_detailed(vec![wave(1.0, 440.0), late]).unwrap();
+        let utterance = bridge.interference_utterance(&wave(1.0, 440.0));
+        let speech = utterance.synthesize(&bridge.baby_tts).unwrap();
+        let delay = phase_delay(std::f32::consts::PI);
+        assert_eq!(delay, SAMPLE_RATE as usize / 2);
+        assert_eq!(result.mix.len(), speech.len() + delay);
+
+        let [early, late] = &result.contributions[..] else { panic!("one contribution per wave") };
+        assert!(late[..delay].iter().all(|&sample| sample == 0.0));
+        for (i, &sample) in speech.iter().enumerate() {
+            assert!((early[i] - sample * 0.25).abs() < 1e-6);
+            assert!((late[i + delay] - sample * 0.75).abs() < 1e-6);
+        }
+        for (i, &sample) in result.mix.iter().enumerate() {
+            assert!((sample - early[i] - late[i]).abs() < 1e-6);
+        }
+        assert_eq!(phase_delay(2.0 * std::f32::consts::PI), 0);
+        assert_eq!(phase_delay(f32::NAN), 0);
+    }
+
+    #[test]
+    fn interference_never_exceeds_full_scale() {
+        let waves = [wave(1.0, 440.0), wave(1.0, 220.0)];
+        let result = mix_interference(&waves, vec![vec![3.0, -1.0], vec![3.0, 2.0]]);
+        assert_eq!(result.mix, vec![1.0, 1.0 / 6.0]);
+        assert_eq!(result.contributions, vec![vec![0.5, -1.0 / 6.0], vec![0.5, 1.0 / 3.0]]);
+        assert_eq!(mix_interference(&[], Vec::new()), InterferenceMix::default());
+    }
+
+    fn event(salience_score: f32, jitter_score: f32, harmonic_score: f32) -> SalienceEvent {
+        SalienceEvent {
+            timestamp: 0,
+            jitter_score,
+            harmonic_score,
+            salience_score,
+            signal_type: SignalType::Voice,
+        }
+    }
+
+    #[test]
+    fn attention_policy_weights_scores_and_repeats_with_a_seed() {
+        let mut bridge = bridge();
+        let events = vec![event(0.9, 0.1, 0.2), event(0.1, 0.9, 0.3), event(0.75, 0.2, 0.95)];
+        let policy = AttentionPolicy {
+            salience_weight: 0.0,
+            jitter_weight: 1.0,
+            autonomy: 0.0,
+            ..Default::default()
+        };
+        bridge.set_attention_policy(policy).unwrap();
+        let decision = bridge.decide_attention_detailed(events.clone());
+        let scores: Vec<f32> = decision.candidates.iter().map(|c| c.score).collect();
+        assert_eq!(scores, vec![0.1, 0.9, 0.2]);
+        // Neutral only attends to salience above 0.7, which rules out the best jitter
+        let eligible: Vec<bool> = decision.candidates.iter().map(|c| c.eligible).collect();
+        assert_eq!(eligible, vec![true, false, true]);
+        assert_eq!(decision.chosen, Some(2));
+        assert_eq!(decision.chosen_event(), Some(&events[2]));
+
+        let seeded = AttentionPolicy { autonomy: 0.5, rng_seed: Some(7), ..Default::default() };
+        let mut run = || {
+            bridge.set_attention_policy(seeded.clone()).unwrap();
+            (0..4).map(|_| bridge.decide_attention_detailed(events.clone())).collect::<Vec<_>>()
+        };
+        let first = run();
+        assert_eq!(run(), first);
+        assert!(first.windows(2).any(|pair| pair[0].candidates != pair[1].candidates));
+
+        let invalid = AttentionPolicy { autonomy: 1.5, ..Default::default() };
+        assert!(matches!(bridge.set_attention_policy(invalid), Err(Error::InvalidInput(_))));
+        assert_eq!(bridge.attention_policy(), &seeded);
+    }
+
+    #[test]
+    fn observers_see_every_transition() {
+        let mut bridge = bridge();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let log = seen.clone();
+        bridge.on_event(move |event| log.lock().unwrap().push(event.kind.clone()));
+
+        bridge.wake_up();
+        let familiar = event(0.9, 0.2, 0.95);
+        bridge.process_salience(familiar.clone()).unwrap();
+        bridge.process_salience(familiar.clone()).unwrap(); // Already joyful: no change
+        let thought = wave(1.5, 440.0);
+        let decision = bridge.emotional_regulation(&thought);
+        bridge.set_attention_policy(AttentionPolicy { autonomy: 0.0, ..Default::default() }).unwrap();
+        bridge.decide_attention(vec![familiar.clone()]);
+        let audio = bridge.wave_to_speech(&thought).unwrap();
+        bridge.sleep();
+        bridge.sleep();
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        let babble = runtime.block_on(bridge.wave_to_speech_async(&thought)).unwrap();
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(
+            seen[..5],
+            [
+                BridgeEventKind::WokeUp { level: 0.7 },
+                BridgeEventKind::EmotionChanged { emotion: EmotionType::Joy(0.95), cause: familiar.clone() },
+                BridgeEventKind::Regulated { wave: thought.clone(), decision },
+                BridgeEventKind::AttentionChosen { event: familiar, score: 0.9 },
+                BridgeEventKind::Spoke {
+                    wave: thought.clone(),
+                    voice: "af_sky".to_string(),
+                    babbled: false,
+                    samples: audio.len(),
+                },
+            ]
+        );
+        assert!(matches!(seen[5], BridgeEventKind::FellAsleep { level } if (level - 0.35).abs() < 1e-6));
+        assert!(matches!(seen[6], BridgeEventKind::FellAsleep { level } if (level - 0.175).abs() < 1e-6));
+        let babbled = BridgeEventKind::Spoke {
+            wave: thought,
+            voice: "af_sky".to_string(),
+            babbled: true,
+            samples: babble.len(),
+        };
+        assert_eq!(seen[7..], [babbled]);
+    }
+}
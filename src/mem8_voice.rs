@@ -303,11 +303,7 @@ impl Mem8Voice {
         // Smooth learning with momentum
         self.emotional_state = (cv * 0.9 + v * 0.1, ca * 0.9 + a * 0.1, cd * 0.9 + d * 0.1);
 
-        #[cfg(not(feature = "as-lib"))]
-        println!(
-            "🧠 MEM8 Voice learning: emotional state updated to ({:.2}, {:.2}, {:.2})",
-            self.emotional_state.0, self.emotional_state.1, self.emotional_state.2
-        );
+        tracing::debug!(state = ?self.emotional_state, "MEM8 voice learned an emotional state");
     }
 
     /// Save voice identity to MEM8
@@ -334,8 +330,7 @@ impl Mem8Voice {
         std::fs::write(path, serde_json::to_string_pretty(&identity).unwrap())
             .map_err(|e| format!("Failed to save voice identity: {}", e))?;
 
-        #[cfg(not(feature = "as-lib"))]
-        println!("💾 MEM8 Voice identity saved to {}", path);
+        tracing::debug!(path, "Saved the MEM8 voice identity");
         Ok(())
     }
 }
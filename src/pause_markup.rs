@@ -0,0 +1,176 @@
+//! Pauses written into plain text, like `Attention. [pause:1500ms] Boarding`
+//!
+//! A marker is silence of the given length at that exact point, never read
+//! out: `text_chunks` splits the text there, so no chunk contains one. Lengths
+//! are written as for SSML breaks ("500ms", "1.5s"), or as a bare number of
+//! seconds, and are capped at 10 seconds like SSML breaks. A marker that
+//! doesn't parse, or an opening delimiter that isn't closed, is removed with
+//! a warning rather than spoken.
+
+use crate::ssml::{parse_break_time, MAX_BREAK_MS};
+use crate::Error;
+
+/// Delimiters around inline pause markers (default `[pause:` and `]`)
+///
+/// `PauseMarkup::new("<#", "#>")` reads `<#1.5#>` as a second and a half of
+/// silence.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PauseMarkup {
+    open: String,
+    close: String,
+}
+
+impl Default for PauseMarkup {
+    fn default() -> Self {
+        Self {
+            open: "[pause:".to_string(),
+            close: "]".to_string(),
+        }
+    }
+}
+
+impl PauseMarkup {
+    /// Markers written as `open`, a length, then `close`; neither may be empty
+    pub fn new(open: &str, close: &str) -> Result<Self, Error> {
+        if open.is_empty() || close.is_empty() {
+            return Err(Error::InvalidInput("Pause markup delimiters can't be empty".to_string()));
+        }
+        Ok(Self {
+            open: open.to_string(),
+            close: close.to_string(),
+        })
+    }
+
+    /// Text that starts a marker
+    pub fn open(&self) -> &str {
+        &self.open
+    }
+
+    /// Text that ends a marker
+    pub fn close(&self) -> &str {
+        &self.close
+    }
+}
+
+// `text` cut at its markers: each piece with the silence (ms) that follows
+// it, and the malformed markers that were removed, as written. Text before
+// a leading marker is an empty piece; markers with only whitespace between
+// them add up, to at most `MAX_BREAK_MS`.
+pub(crate) fn split(text: &str, markup: &PauseMarkup) -> (Vec<(String, u32)>, Vec<String>) {
+    let (open, close) = (markup.open.as_str(), markup.close.as_str());
+    let mut pieces = vec![(String::new(), 0)];
+    let mut malformed = Vec::new();
+    let mut rest = text;
+    while let Some(at) = rest.find(open) {
+        let (piece, _) = pieces.last_mut().expect("starts with a piece");
+        piece.push_str(&rest[..at]);
+        let after = &rest[at + open.len()..];
+        let next_open = after.find(open).unwrap_or(after.len());
+        // A close past the line end or the next marker belongs to something else
+        let end = after
+            .find(close)
+            .filter(|&end| end <= next_open && !after[..end].contains('\n'));
+        let Some(end) = end else {
+            // Unclosed: drop the opening and the word stuck to it
+            let word = after.find(char::is_whitespace).unwrap_or(after.len()).min(next_open);
+            malformed.push(format!("{}{}", open, &after[..word]));
+            rest = closing_gap(piece, &after[word..]);
+            continue;
+        };
+        rest = &after[end + close.len()..];
+        let Some(ms) = pause_length(&after[..end]) else {
+            malformed.push(format!("{}{}{}", open, &after[..end], close));
+            rest = closing_gap(piece, rest);
+            continue;
+        };
+
+        // Only whitespace since the previous marker: this one adds to it
+        if pieces.len() > 1 && is_blank(pieces.last()) {
+            pieces.pop();
+        }
+        let (_, pause_ms) = pieces.last_mut().expect("starts with a piece");
+        *pause_ms = pause_ms.saturating_add(ms).min(MAX_BREAK_MS);
+        pieces.push((String::new(), 0));
+    }
+    pieces.last_mut().expect("starts with a piece").0.push_str(rest);
+    if pieces.len() > 1 && is_blank(pieces.last()) {
+        pieces.pop();
+    }
+    (pieces, malformed)
+}
+
+// `rest` without the space that would double the one ending `piece`, where a
+// marker between words was removed
+fn closing_gap<'a>(piece: &str, rest: &'a str) -> &'a str {
+    if piece.ends_with(' ') {
+        rest.strip_prefix(' ').unwrap_or(rest)
+    } else {
+        rest
+    }
+}
+
+fn is_blank(piece: Option<&(String, u32)>) -> bool {
+    piece.is_some_and(|(text, _)| text.trim().is_empty())
+}
+
+// "1500ms", "1.5s" or a bare "1.5" (seconds) in milliseconds, capped like a break
+fn pause_length(length: &str) -> Option<u32> {
+    let length = length.trim();
+    let ms = match length.parse::<f32>() {
+        Ok(secs) if secs.is_finite() && secs >= 0.0 => (secs * 1000.0).round() as u32,
+        Ok(_) => return None,
+        Err(_) => parse_break_time(length).ok()?,
+    };
+    Some(ms.min(MAX_BREAK_MS))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn piece(text: &str, pause_ms: u32) -> (String, u32) {
+        (text.to_string(), pause_ms)
+    }
+
+    #[test]
+    fn markers_split_the_text_into_timed_pieces() {
+        let markup = PauseMarkup::default();
+        let (pieces, malformed) = split("Attention. [pause:1500ms] Boarding [pause: 2s ]now.", &markup);
+        let expected = vec![piece("Attention. ", 1500), piece(" Boarding ", 2000), piece("now.", 0)];
+        assert_eq!(pieces, expected);
+        assert!(malformed.is_empty());
+
+        let (pieces, _) = split("[pause:0.5s]Go [pause:800ms] [pause:9.5s] now[pause:1s]", &markup);
+        assert_eq!(pieces, vec![piece("", 500), piece("Go ", MAX_BREAK_MS), piece(" now", 1000)]);
+        assert_eq!(split("[pause:1s]", &markup).0, vec![piece("", 1000)]);
+        assert_eq!(split("[pause:1minute]", &markup).0, vec![piece("", 0)]);
+        assert_eq!(split("No markers [here]", &markup).0, vec![piece("No markers [here]", 0)]);
+
+        // Huge neighbouring markers are capped, not overflowed
+        let (pieces, _) = split("Hi [pause:5000000] [pause:5000000] there", &markup);
+        assert_eq!(pieces, vec![piece("Hi ", MAX_BREAK_MS), piece(" there", 0)]);
+        let (pieces, _) = split("Hi [pause:4294967295ms][pause:4294967295ms] there", &markup);
+        assert_eq!(pieces, vec![piece("Hi ", MAX_BREAK_MS), piece(" there", 0)]);
+    }
+
+    #[test]
+    fn malformed_markers_are_removed() {
+        let markup = PauseMarkup::default();
+        let (pieces, malformed) = split("Wait [pause:long] then [pause:2s go [pause:-1s] on", &markup);
+        assert_eq!(pieces, vec![piece("Wait then go on", 0)]);
+        assert_eq!(malformed, ["[pause:long]", "[pause:2s", "[pause:-1s]"]);
+
+        let (pieces, malformed) = split("A [pause:1s\nB] [pause:[pause:1s] C", &markup);
+        assert_eq!(pieces, vec![piece("A \nB] ", 1000), piece(" C", 0)]);
+        assert_eq!(malformed, ["[pause:1s", "[pause:"]);
+    }
+
+    #[test]
+    fn delimiters_are_configurable() {
+        let markup = PauseMarkup::new("<#", "#>").unwrap();
+        assert_eq!((markup.open(), markup.close()), ("<#", "#>"));
+        let (pieces, _) = split("One <#1.5#> two [pause:1s]", &markup);
+        assert_eq!(pieces, vec![piece("One ", 1500), piece(" two [pause:1s]", 0)]);
+        assert!(matches!(PauseMarkup::new("", "]"), Err(Error::InvalidInput(_))));
+    }
+}
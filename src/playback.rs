@@ -0,0 +1,1176 @@
+//! Audio output: background playback (see `TtsEngine::play_async`), the
+//! speech queue (see `TtsEngine::enqueue`), a source that plays audio as it is
+//! synthesized (see `TtsEngine::speak_streaming`) and stopping playback early
+//! (see `TtsEngine::stop_playback`)
+//!
+//! Devices are opened at a rate they support, preferring 48kHz or 44.1kHz,
+//! and the model's 24kHz audio is resampled to it here with a windowed-sinc
+//! filter rather than left to rodio's linear interpolation.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use rodio::buffer::SamplesBuffer;
+use cpal::traits::{DeviceTrait, HostTrait};
+use rodio::{OutputStream, OutputStreamHandle, Sample, Sink, Source};
+
+use crate::audio::Resampler;
+use crate::{DeferredSynthesis, Error, SAMPLE_RATE};
+
+// Samples moved from the shared queue per lock, so the audio thread rarely
+// waits on the synthesis thread
+const REFILL_SAMPLES: usize = 1024;
+// Silence played per empty read while synthesis is behind (10ms)
+const UNDERRUN_SAMPLES: usize = SAMPLE_RATE as usize / 100;
+// Fade applied when playback is stopped, so it ends without a pop
+const STOP_FADE_MS: usize = 50;
+// Tries at opening a named device before falling back to the default, since a
+// USB interface can take a moment to come back after a bus reset
+const DEVICE_OPEN_ATTEMPTS: usize = 3;
+const DEVICE_RETRY_DELAY: Duration = Duration::from_millis(250);
+// How long playback may run past the audio's length before the device is
+// considered gone (a disconnected device stops pulling samples)
+const STALL_GRACE: Duration = Duration::from_secs(2);
+// Output rates tried in order; 48kHz is an exact multiple of the model's rate
+const PREFERRED_RATES: [u32; 2] = [48_000, 44_100];
+// ALSA plugin devices that route through the sound server, preferred first
+const SOUND_SERVER_DEVICES: [&str; 2] = ["pipewire", "pulse"];
+
+/// How a blocking playback call ended
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlaybackEnd {
+    /// Every sample was played
+    Completed,
+    /// `TtsEngine::stop_playback` or `PlaybackHandle::stop` cut it short
+    Stopped,
+}
+
+/// Audio playing on a background thread, returned by `TtsEngine::play_async`.
+///
+/// Dropping the handle detaches it: the audio keeps playing to the end (as
+/// long as the process runs). Call `stop` to cancel it.
+pub struct PlaybackHandle {
+    watch: StopWatch,
+    finished: Arc<AtomicBool>,
+    thread: JoinHandle<Result<(), String>>,
+    warnings: Vec<String>,
+    sample_rate: u32,
+}
+
+impl PlaybackHandle {
+    /// Whether playback has ended, completed or stopped
+    pub fn is_finished(&self) -> bool {
+        self.finished.load(Ordering::SeqCst)
+    }
+
+    /// Block until playback ends. Fails if the device stopped playing partway
+    /// (e.g. it was unplugged).
+    pub fn wait(self) -> Result<PlaybackEnd, Error> {
+        self.thread
+            .join()
+            .map_err(|_| Error::AudioDevice("Playback thread panicked".to_string()))?
+            .map_err(Error::AudioDevice)?;
+        Ok(self.watch.end())
+    }
+
+    /// Problems worked around when opening the device, such as falling back
+    /// to the default device because the selected one is gone
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    /// Rate the device was opened at; the 24kHz audio is resampled to it
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Fade out over 50ms and end playback; `wait` then returns `PlaybackEnd::Stopped`
+    pub fn stop(&self) {
+        self.watch.stop();
+    }
+}
+
+/// Start playing `audio` (24kHz mono) on its own thread and output stream.
+/// Returns once the device is open, so device errors are reported here.
+pub(crate) fn play_in_background(
+    audio: Vec<f32>,
+    volume: f32,
+    target: OutputTarget,
+    watch: StopWatch,
+) -> Result<PlaybackHandle, String> {
+    let (ready_tx, ready_rx) = mpsc::channel();
+    let finished = Arc::new(AtomicBool::new(false));
+    let thread = {
+        let watch = watch.clone();
+        let finished = finished.clone();
+        thread::spawn(move || {
+            // The stream isn't Send, so it is opened and kept on this thread
+            let (output, sink, warning) = match open_sink(&target) {
+                Ok(opened) => opened,
+                Err(e) => {
+                    finished.store(true, Ordering::SeqCst);
+                    let _ = ready_tx.send(Err(e.clone()));
+                    return Err(e);
+                }
+            };
+            let _ = ready_tx.send(Ok((warning, output.sample_rate)));
+            let played = play_to_end(&sink, &output, audio, volume, watch);
+            finished.store(true, Ordering::SeqCst);
+            played
+        })
+    };
+
+    let (warning, sample_rate) = ready_rx
+        .recv()
+        .map_err(|_| "Playback thread exited before starting".to_string())??;
+    Ok(PlaybackHandle {
+        watch,
+        finished,
+        thread,
+        warnings: warning.into_iter().collect(),
+        sample_rate,
+    })
+}
+
+// Open an output stream with a sink ready to play into, plus the warning
+// from `open_output` if it had to fall back to the default device
+fn open_sink(target: &OutputTarget) -> Result<(Output, Sink, Option<String>), String> {
+    let (output, warning) = open_output(target)?;
+    let sink = Sink::try_new(&output.handle).map_err(|e| format!("Failed to create audio sink: {}", e))?;
+    Ok((output, sink, warning))
+}
+
+// Play 24kHz mono audio on `sink` (opened on `output`) until it ends or
+// `watch` is stopped
+fn play_to_end(
+    sink: &Sink,
+    output: &Output,
+    audio: Vec<f32>,
+    volume: f32,
+    watch: StopWatch,
+) -> Result<(), String> {
+    let length = Duration::from_secs_f64(audio.len() as f64 / SAMPLE_RATE as f64);
+    let source = Stoppable::new(SamplesBuffer::new(1, SAMPLE_RATE, audio), watch);
+    sink.set_volume(volume.clamp(0.0, 1.0));
+    output.append(sink, source);
+    wait_for_sink(sink, Instant::now() + length + STALL_GRACE)
+}
+
+/// Wait for `sink` to play everything queued on it. A sink still busy at
+/// `deadline` sits on a device that stopped pulling samples (unplugged,
+/// bus reset), where waiting for the end would block forever.
+pub(crate) fn wait_for_sink(sink: &Sink, deadline: Instant) -> Result<(), String> {
+    while !sink.empty() {
+        if Instant::now() > deadline {
+            sink.stop();
+            return Err("Audio device stopped playing (disconnected?)".to_string());
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    Ok(())
+}
+
+/// Latest time a sink fed by a `LiveBuffer` can still be playing: the audio
+/// itself plus the silence played while synthesis was behind, which is at most
+/// the time synthesis took
+pub(crate) fn live_deadline(started: Instant, samples: usize) -> Instant {
+    let audio = Duration::from_secs_f64(samples as f64 / SAMPLE_RATE as f64);
+    Instant::now() + started.elapsed() + audio + STALL_GRACE
+}
+
+/// Where `TtsEngine::enqueue` puts an utterance
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Priority {
+    /// After everything already queued
+    #[default]
+    Normal,
+    /// Ahead of every normal item (behind earlier urgent ones)
+    Urgent,
+}
+
+/// What an urgent utterance does to a normal one that is already playing
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UrgentMode {
+    /// Let it finish, then play the urgent item next
+    #[default]
+    AfterCurrent,
+    /// Fade it out and play the urgent item right away (the interrupted
+    /// utterance is dropped, not resumed)
+    Interrupt,
+}
+
+struct QueueItem {
+    render: DeferredSynthesis,
+    urgent: bool,
+    target: OutputTarget,
+}
+
+#[derive(Default)]
+struct QueueState {
+    items: VecDeque<QueueItem>,
+    // The item being synthesized or played, and whether it is urgent
+    current: Option<(StopWatch, bool)>,
+    shutdown: bool,
+}
+
+/// Utterances waiting to be spoken one at a time by a worker thread, which
+/// runs their inference and plays them in queue order
+pub(crate) struct SpeechQueue {
+    shared: Arc<(Mutex<QueueState>, Condvar)>,
+}
+
+impl SpeechQueue {
+    /// Start the worker. Playback it starts listens to the engine's `stop`.
+    pub(crate) fn start(stop: StopSignal) -> Self {
+        let shared: Arc<(Mutex<QueueState>, Condvar)> = Arc::default();
+        let worker = shared.clone();
+        thread::spawn(move || run_queue(&worker, &stop));
+        Self { shared }
+    }
+
+    pub(crate) fn push(
+        &self,
+        render: DeferredSynthesis,
+        priority: Priority,
+        mode: UrgentMode,
+        target: OutputTarget,
+    ) {
+        let (lock, ready) = &*self.shared;
+        let mut state = lock.lock().unwrap_or_else(|e| e.into_inner());
+        let urgent = priority == Priority::Urgent;
+        let item = QueueItem { render, urgent, target };
+        if urgent {
+            let at = state.items.iter().position(|i| !i.urgent).unwrap_or(state.items.len());
+            state.items.insert(at, item);
+            if mode == UrgentMode::Interrupt {
+                if let Some((watch, false)) = &state.current {
+                    watch.stop();
+                }
+            }
+        } else {
+            state.items.push_back(item);
+        }
+        ready.notify_one();
+    }
+
+    /// Drop every item that hasn't started
+    pub(crate) fn clear(&self) {
+        self.shared.0.lock().unwrap_or_else(|e| e.into_inner()).items.clear();
+    }
+
+    /// Items waiting, not counting the one being spoken
+    pub(crate) fn len(&self) -> usize {
+        self.shared.0.lock().unwrap_or_else(|e| e.into_inner()).items.len()
+    }
+}
+
+impl Drop for SpeechQueue {
+    // The worker stops the current item and exits; it isn't joined so
+    // dropping the engine never blocks on audio
+    fn drop(&mut self) {
+        let (lock, ready) = &*self.shared;
+        let mut state = lock.lock().unwrap_or_else(|e| e.into_inner());
+        state.shutdown = true;
+        if let Some((watch, _)) = &state.current {
+            watch.stop();
+        }
+        ready.notify_all();
+    }
+}
+
+fn run_queue(shared: &(Mutex<QueueState>, Condvar), stop: &StopSignal) {
+    let (lock, ready) = shared;
+    loop {
+        let (item, watch) = {
+            let mut state = lock.lock().unwrap_or_else(|e| e.into_inner());
+            state.current = None;
+            while state.items.is_empty() && !state.shutdown {
+                state = ready.wait(state).unwrap_or_else(|e| e.into_inner());
+            }
+            if state.shutdown {
+                return;
+            }
+            let Some(item) = state.items.pop_front() else {
+                continue;
+            };
+            let watch = stop.watch();
+            state.current = Some((watch.clone(), item.urgent));
+            (item, watch)
+        };
+
+        let audio = match (item.render)() {
+            Ok(audio) => audio,
+            Err(e) => {
+                tracing::warn!(error = %e, "Queued utterance failed");
+                continue;
+            }
+        };
+        // Stopped or interrupted while it was being synthesized
+        if watch.is_stopped() {
+            continue;
+        }
+        let played = open_sink(&item.target).and_then(|(output, sink, warning)| {
+            if let Some(warning) = warning {
+                tracing::warn!("{}", warning);
+            }
+            play_to_end(&sink, &output, audio, 1.0, watch)
+        });
+        if let Err(e) = played {
+            tracing::warn!(error = %e, "Queued utterance failed");
+        }
+    }
+}
+
+/// An open output stream, its format and the channels speech goes to.
+/// Queue audio with `append` so it matches the stream.
+pub(crate) struct Output {
+    // Playback stops when the stream is dropped
+    _stream: OutputStream,
+    pub(crate) handle: OutputStreamHandle,
+    pub(crate) sample_rate: u32,
+    channels: u16,
+    channel_map: Option<Vec<u16>>,
+}
+
+impl Output {
+    /// Queue 24kHz mono `source` on `sink`, resampled to the stream's rate and
+    /// written only to the mapped channels if there is a channel map
+    pub(crate) fn append<S: Source<Item = f32> + Send + 'static>(&self, sink: &Sink, source: S) {
+        let resampled = Resampler::new(source, SAMPLE_RATE, self.sample_rate);
+        match &self.channel_map {
+            Some(map) => sink.append(ChannelMap::new(resampled, map, self.channels)),
+            None => sink.append(resampled),
+        }
+    }
+}
+
+/// Where playback goes: a device on an audio host, or the host's default
+/// device when no name is set, and optionally the output channels to use
+#[derive(Clone, Debug, Default)]
+pub(crate) struct OutputTarget {
+    pub(crate) host: AudioHost,
+    pub(crate) device_name: Option<String>,
+    pub(crate) channel_map: Option<Vec<u16>>,
+}
+
+/// Open the target's named device, or its host's default device.
+///
+/// A named device that fails to open (unplugged, renamed by a bus reset) is
+/// retried a few times; if it stays unavailable, the default device is used
+/// and the returned warning says so.
+pub(crate) fn open_output(target: &OutputTarget) -> Result<(Output, Option<String>), String> {
+    let host = target.host;
+    let map = target.channel_map.as_deref();
+    let open_default = || {
+        let device = default_device(host)?;
+        open_device(&device, map).map_err(|e| format!("Failed to get audio output: {}", e))
+    };
+    let Some(device_name) = target.device_name.as_deref() else {
+        return Ok((open_default()?, None));
+    };
+    let open_named = |name: &str| {
+        let device = find_device(host, name)?;
+        open_device(&device, map).map_err(|e| format!("Failed to open device '{}': {}", name, e))
+    };
+    open_with_fallback(device_name, open_named, open_default, DEVICE_RETRY_DELAY)
+}
+
+/// The rate playback on the target would be resampled to
+pub(crate) fn output_rate(target: &OutputTarget) -> Result<u32, String> {
+    let device = target_device(target)?;
+    Ok(stream_config(&device, target.channel_map.is_some())?.sample_rate().0)
+}
+
+/// Check the target's channel map against the channels its device has
+pub(crate) fn check_channel_map(target: &OutputTarget) -> Result<(), String> {
+    let Some(map) = &target.channel_map else {
+        return Ok(());
+    };
+    let device = target_device(target)?;
+    let channels = device
+        .supported_output_configs()
+        .map_err(|e| format!("Failed to query output configs: {}", e))?
+        .map(|range| range.channels())
+        .max()
+        .unwrap_or(0);
+    let name = device.name().unwrap_or_else(|_| "default".to_string());
+    validate_channel_map(map, channels, &name)
+}
+
+// Channel indices count from 0 and must be distinct and below `channels`
+fn validate_channel_map(map: &[u16], channels: u16, device_name: &str) -> Result<(), String> {
+    for (i, &channel) in map.iter().enumerate() {
+        if channel >= channels {
+            return Err(format!(
+                "Output channel {} is out of range: device '{}' has {} channels (0-{})",
+                channel,
+                device_name,
+                channels,
+                channels.saturating_sub(1)
+            ));
+        }
+        if map[..i].contains(&channel) {
+            return Err(format!("Output channel {} is listed twice", channel));
+        }
+    }
+    Ok(())
+}
+
+fn target_device(target: &OutputTarget) -> Result<cpal::Device, String> {
+    match target.device_name.as_deref() {
+        Some(name) => find_device(target.host, name),
+        None => default_device(target.host),
+    }
+}
+
+// Try `open` on the named device `DEVICE_OPEN_ATTEMPTS` times, then `fallback`
+fn open_with_fallback<T>(
+    device_name: &str,
+    mut open: impl FnMut(&str) -> Result<T, String>,
+    fallback: impl FnOnce() -> Result<T, String>,
+    retry_delay: Duration,
+) -> Result<(T, Option<String>), String> {
+    let mut last_error = String::new();
+    for attempt in 0..DEVICE_OPEN_ATTEMPTS {
+        if attempt > 0 {
+            thread::sleep(retry_delay);
+        }
+        match open(device_name) {
+            Ok(opened) => return Ok((opened, None)),
+            Err(e) => last_error = e,
+        }
+    }
+    match fallback() {
+        Ok(opened) => Ok((opened, Some(format!("{}; playing on the default device instead", last_error)))),
+        Err(e) => Err(format!("{} (default device failed too: {})", last_error, e)),
+    }
+}
+
+fn find_device(host: AudioHost, device_name: &str) -> Result<cpal::Device, String> {
+    // A fresh enumeration every time, so a device that came back is found again
+    output_devices(host)?
+        .into_iter()
+        .find(|(_, name)| name == device_name)
+        .map(|(device, _)| device)
+        .ok_or_else(|| format!("Audio device '{}' not found", device_name))
+}
+
+fn default_device(host: AudioHost) -> Result<cpal::Device, String> {
+    let device = match host {
+        // The sound server's plugin device stands in for the default
+        AudioHost::PulseAudio => output_devices(host)?.into_iter().next().map(|(device, _)| device),
+        _ => cpal_host(host)?.default_output_device(),
+    };
+    device.ok_or_else(|| format!("Failed to get audio output: no default {} output device", host))
+}
+
+// Open `device` at the rate `pick_rate` chooses; with a channel map, also with
+// every channel it has so the mapped ones exist
+fn open_device(device: &cpal::Device, channel_map: Option<&[u16]>) -> Result<Output, String> {
+    let config = stream_config(device, channel_map.is_some())?;
+    let (sample_rate, channels) = (config.sample_rate().0, config.channels());
+    if let Some(map) = channel_map {
+        let name = device.name().unwrap_or_else(|_| "default".to_string());
+        validate_channel_map(map, channels, &name)?;
+    }
+    let (stream, handle) = OutputStream::try_from_device_config(device, config).map_err(|e| e.to_string())?;
+    Ok(Output {
+        _stream: stream,
+        handle,
+        sample_rate,
+        channels,
+        channel_map: channel_map.map(<[u16]>::to_vec),
+    })
+}
+
+// The device's default configuration, moved to the rate `pick_rate` chooses
+// (and with `all_channels`, to its widest channel layout at that rate)
+fn stream_config(
+    device: &cpal::Device,
+    all_channels: bool,
+) -> Result<cpal::SupportedStreamConfig, String> {
+    let default = device
+        .default_output_config()
+        .map_err(|e| format!("Failed to query output config: {}", e))?;
+    let ranges: Vec<_> = device
+        .supported_output_configs()
+        .map_err(|e| format!("Failed to query output configs: {}", e))?
+        .collect();
+    let bounds: Vec<_> = ranges
+        .iter()
+        .map(|range| (range.min_sample_rate().0, range.max_sample_rate().0))
+        .collect();
+    let rate = pick_rate(default.sample_rate().0, &bounds);
+    if rate == default.sample_rate().0 && !all_channels {
+        return Ok(default);
+    }
+    Ok(ranges
+        .into_iter()
+        .filter(|range| range.min_sample_rate().0 <= rate && rate <= range.max_sample_rate().0)
+        .max_by(|a, b| {
+            let wider = a.channels().cmp(&b.channels());
+            let wider = if all_channels { wider } else { std::cmp::Ordering::Equal };
+            wider.then_with(|| a.cmp_default_heuristics(b))
+        })
+        .map_or(default, |range| range.with_sample_rate(cpal::SampleRate(rate))))
+}
+
+// Choose an output rate from the device's supported `(min, max)` ranges: its
+// default when that is 48k or 44.1k, else the first of those it supports,
+// else whatever supported rate is closest to 48k
+fn pick_rate(default_rate: u32, supported: &[(u32, u32)]) -> u32 {
+    let supports = |rate: u32| supported.iter().any(|&(min, max)| min <= rate && rate <= max);
+    if PREFERRED_RATES.contains(&default_rate) && supports(default_rate) {
+        return default_rate;
+    }
+    if let Some(rate) = PREFERRED_RATES.into_iter().find(|&rate| supports(rate)) {
+        return rate;
+    }
+    let target = PREFERRED_RATES[0];
+    supported
+        .iter()
+        .map(|&(min, max)| target.clamp(min, max))
+        .min_by_key(|rate| rate.abs_diff(target))
+        .unwrap_or(default_rate)
+}
+
+impl<S: Source<Item = f32>> Source for Resampler<S> {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.to_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner().total_duration()
+    }
+}
+
+/// Spreads mono audio over multi-channel frames: each sample goes to the
+/// mapped channels and every other channel gets silence
+pub(crate) struct ChannelMap<S> {
+    inner: S,
+    // Whether each channel of the frame carries the speech
+    mapped: Vec<bool>,
+    position: usize,
+    sample: f32,
+}
+
+impl<S> ChannelMap<S> {
+    pub(crate) fn new(inner: S, map: &[u16], channels: u16) -> Self {
+        let mapped = (0..channels).map(|channel| map.contains(&channel)).collect();
+        Self {
+            inner,
+            mapped,
+            position: 0,
+            sample: 0.0,
+        }
+    }
+}
+
+impl<S: Iterator<Item = f32>> Iterator for ChannelMap<S> {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.position == 0 {
+            self.sample = self.inner.next()?;
+        }
+        let out = if self.mapped[self.position] { self.sample } else { 0.0 };
+        self.position = (self.position + 1) % self.mapped.len();
+        Some(out)
+    }
+}
+
+impl<S: Source<Item = f32>> Source for ChannelMap<S> {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        self.mapped.len() as u16
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+}
+
+/// An output device, as listed by `TtsEngine::list_audio_devices`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AudioDeviceInfo {
+    /// Name to pass to `TtsEngine::set_audio_device`
+    pub name: String,
+    /// Audio host it was listed on (e.g. "ALSA", "PulseAudio/PipeWire", "WASAPI")
+    pub host: String,
+    /// Whether this is the host's default output device
+    pub is_default: bool,
+    /// Supported sample rates as `(min, max)` ranges in Hz
+    pub sample_rates: Vec<(u32, u32)>,
+    /// Supported channel counts
+    pub channels: Vec<u16>,
+}
+
+/// Audio backend that playback goes through (see `TtsEngine::set_audio_host`)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum AudioHost {
+    /// Whatever the platform defaults to (ALSA on Linux)
+    #[default]
+    Default,
+    /// ALSA directly; hardware devices may be held exclusively
+    Alsa,
+    /// PulseAudio or PipeWire, through their ALSA plugin devices, so the
+    /// card stays shared with other applications
+    PulseAudio,
+    /// JACK (Linux, requires the 'jack' feature)
+    Jack,
+    /// CoreAudio (macOS)
+    CoreAudio,
+    /// WASAPI (Windows)
+    Wasapi,
+    /// ASIO (Windows, when cpal is built with ASIO support)
+    Asio,
+}
+
+impl AudioHost {
+    // Name of the cpal host behind this choice, None for cpal's default
+    fn cpal_name(self) -> Option<&'static str> {
+        match self {
+            AudioHost::Default => None,
+            AudioHost::Alsa | AudioHost::PulseAudio => Some("ALSA"),
+            AudioHost::Jack => Some("JACK"),
+            AudioHost::CoreAudio => Some("CoreAudio"),
+            AudioHost::Wasapi => Some("WASAPI"),
+            AudioHost::Asio => Some("ASIO"),
+        }
+    }
+}
+
+impl fmt::Display for AudioHost {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            AudioHost::Default => "default",
+            AudioHost::PulseAudio => "PulseAudio/PipeWire",
+            other => other.cpal_name().unwrap_or_default(),
+        };
+        f.write_str(name)
+    }
+}
+
+/// Hosts usable on this machine, `AudioHost::Default` first. PulseAudio is
+/// listed when ALSA has a PulseAudio or PipeWire plugin device.
+pub(crate) fn available_hosts() -> Vec<AudioHost> {
+    let compiled: Vec<&str> = cpal::available_hosts().iter().map(|id| id.name()).collect();
+    let mut hosts = vec![AudioHost::Default];
+    for host in [
+        AudioHost::Alsa,
+        AudioHost::PulseAudio,
+        AudioHost::Jack,
+        AudioHost::CoreAudio,
+        AudioHost::Wasapi,
+        AudioHost::Asio,
+    ] {
+        let present = host.cpal_name().is_some_and(|name| compiled.contains(&name));
+        // ALSA without a sound server plugin device has no PulseAudio to offer
+        let usable = host != AudioHost::PulseAudio || output_devices(host).is_ok_and(|d| !d.is_empty());
+        if present && usable {
+            hosts.push(host);
+        }
+    }
+    hosts
+}
+
+/// Check that `host` can be used here; the error lists the hosts that can
+pub(crate) fn check_host(host: AudioHost) -> Result<(), String> {
+    let available = available_hosts();
+    if available.contains(&host) {
+        return Ok(());
+    }
+    let names: Vec<String> = available.iter().map(AudioHost::to_string).collect();
+    Err(format!("Audio host {} is not available. Available hosts: {}", host, names.join(", ")))
+}
+
+fn cpal_host(host: AudioHost) -> Result<cpal::Host, String> {
+    let Some(name) = host.cpal_name() else {
+        return Ok(cpal::default_host());
+    };
+    let id = cpal::available_hosts()
+        .into_iter()
+        .find(|id| id.name() == name)
+        .ok_or_else(|| format!("Audio host {} is not available", host))?;
+    cpal::host_from_id(id).map_err(|e| format!("Failed to open audio host {}: {}", host, e))
+}
+
+// Output devices on `host` with their names. For PulseAudio that is only the
+// sound server's ALSA plugin devices, in `SOUND_SERVER_DEVICES` order.
+fn output_devices(host: AudioHost) -> Result<Vec<(cpal::Device, String)>, String> {
+    let devices = cpal_host(host)?
+        .output_devices()
+        .map_err(|e| format!("Failed to get output devices: {}", e))?;
+    let mut named: Vec<(cpal::Device, String)> = devices
+        .filter_map(|device| device.name().ok().map(|name| (device, name)))
+        .collect();
+    if host == AudioHost::PulseAudio {
+        named.retain(|(_, name)| SOUND_SERVER_DEVICES.contains(&name.as_str()));
+        named.sort_by_key(|(_, name)| SOUND_SERVER_DEVICES.iter().position(|d| d == name));
+    }
+    Ok(named)
+}
+
+/// Output devices on `host`, in enumeration order. A device whose
+/// configurations can't be queried is listed with no rates or channels.
+pub(crate) fn list_devices(host: AudioHost) -> Result<Vec<AudioDeviceInfo>, String> {
+    let default_name = default_device(host).ok().and_then(|device| device.name().ok());
+
+    let mut infos = Vec::new();
+    for (device, name) in output_devices(host)? {
+        let (mut sample_rates, mut channels) = (Vec::new(), Vec::new());
+        for range in device.supported_output_configs().into_iter().flatten() {
+            sample_rates.push((range.min_sample_rate().0, range.max_sample_rate().0));
+            channels.push(range.channels());
+        }
+        sample_rates.sort_unstable();
+        sample_rates.dedup();
+        channels.sort_unstable();
+        channels.dedup();
+        infos.push(AudioDeviceInfo {
+            is_default: default_name.as_ref() == Some(&name),
+            name,
+            host: match host {
+                AudioHost::Default => cpal::default_host().id().name().to_string(),
+                other => other.to_string(),
+            },
+            sample_rates,
+            channels,
+        });
+    }
+    Ok(infos)
+}
+
+/// Resolve a device name against the available `names`: an exact match, else
+/// the only name containing it, ignoring case ("scarlett" finds "Scarlett 2i2
+/// USB"). When nothing matches, the error lists the closest names.
+pub(crate) fn match_device_name(query: &str, names: &[String]) -> Result<String, String> {
+    if names.iter().any(|name| name == query) {
+        return Ok(query.to_string());
+    }
+    let lowered = query.to_lowercase();
+    let containing: Vec<&String> = names
+        .iter()
+        .filter(|name| name.to_lowercase().contains(&lowered))
+        .collect();
+    match containing.as_slice() {
+        [name] => return Ok((*name).clone()),
+        [] => {}
+        several => {
+            return Err(format!(
+                "Device '{}' matches several devices: {}",
+                query,
+                several.iter().map(|name| name.as_str()).collect::<Vec<_>>().join(", ")
+            ))
+        }
+    }
+
+    // Near misses: names with a substring a few edits away from the query
+    let query_chars: Vec<char> = lowered.chars().collect();
+    let allowed = (query_chars.len() / 3).max(1);
+    let mut close: Vec<(usize, &String)> = names
+        .iter()
+        .map(|name| {
+            let name_chars: Vec<char> = name.to_lowercase().chars().collect();
+            (substring_distance(&query_chars, &name_chars), name)
+        })
+        .filter(|&(distance, _)| distance <= allowed)
+        .collect();
+    close.sort_by_key(|&(distance, _)| distance);
+    if close.is_empty() {
+        Err(format!("Device '{}' not found. Available devices: {}", query, names.join(", ")))
+    } else {
+        let close: Vec<&str> = close.iter().map(|(_, name)| name.as_str()).collect();
+        Err(format!("Device '{}' not found. Did you mean: {}?", query, close.join(", ")))
+    }
+}
+
+// Fewest edits turning `query` into some substring of `name` (0 if it is one)
+fn substring_distance(query: &[char], name: &[char]) -> usize {
+    // An empty query matches anywhere, so the first row is all zeros
+    let mut row = vec![0; name.len() + 1];
+    for (i, q) in query.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, n) in name.iter().enumerate() {
+            let substituted = diagonal + usize::from(q != n);
+            diagonal = row[j + 1];
+            row[j + 1] = substituted.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row.into_iter().min().unwrap_or(query.len())
+}
+
+/// Queue between synthesis and playback: samples are pushed as chunks finish
+/// and the output stream drains them while it plays
+#[derive(Clone, Default)]
+pub(crate) struct LiveBuffer {
+    shared: Arc<Mutex<LiveState>>,
+}
+
+#[derive(Default)]
+struct LiveState {
+    samples: VecDeque<f32>,
+    closed: bool,
+}
+
+impl LiveBuffer {
+    pub(crate) fn push(&self, samples: &[f32]) {
+        let mut state = self.shared.lock().unwrap_or_else(|e| e.into_inner());
+        state.samples.extend(samples);
+    }
+
+    /// No more samples will be pushed; the source ends once it has played the rest
+    pub(crate) fn close(&self) {
+        self.shared.lock().unwrap_or_else(|e| e.into_inner()).closed = true;
+    }
+
+    pub(crate) fn source(&self) -> LiveSource {
+        LiveSource {
+            buffer: self.clone(),
+            local: VecDeque::new(),
+        }
+    }
+}
+
+/// Plays what is pushed to a `LiveBuffer`. While the buffer is empty it plays
+/// silence instead of stalling the stream, and it ends once the buffer is
+/// closed and drained.
+pub(crate) struct LiveSource {
+    buffer: LiveBuffer,
+    local: VecDeque<f32>,
+}
+
+impl Iterator for LiveSource {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.local.is_empty() {
+            let mut state = self.buffer.shared.lock().unwrap_or_else(|e| e.into_inner());
+            if state.samples.is_empty() {
+                if state.closed {
+                    return None;
+                }
+                self.local.extend(std::iter::repeat_n(0.0, UNDERRUN_SAMPLES));
+            } else {
+                let n = state.samples.len().min(REFILL_SAMPLES);
+                self.local.extend(state.samples.drain(..n));
+            }
+        }
+        self.local.pop_front()
+    }
+}
+
+impl Source for LiveSource {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+/// Stop requests shared between an engine and the playback it starts. Each
+/// stop bumps a counter, so everything started before it is stopped while
+/// later playback is unaffected.
+#[derive(Clone, Default)]
+pub(crate) struct StopSignal {
+    generation: Arc<AtomicUsize>,
+}
+
+impl StopSignal {
+    pub(crate) fn stop(&self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// Watch for stops from now on
+    pub(crate) fn watch(&self) -> StopWatch {
+        StopWatch {
+            generation: self.generation.clone(),
+            started: self.generation.load(Ordering::SeqCst),
+            cancelled: Arc::new(AtomicBool::new(false)),
+            cut: Arc::new(AtomicBool::new(false)),
+        }
+    }
+}
+
+/// One playback's view of a `StopSignal`, which can also be stopped on its own
+#[derive(Clone)]
+pub(crate) struct StopWatch {
+    generation: Arc<AtomicUsize>,
+    started: usize,
+    cancelled: Arc<AtomicBool>,
+    cut: Arc<AtomicBool>,
+}
+
+impl StopWatch {
+    /// Stop just this playback
+    pub(crate) fn stop(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub(crate) fn is_stopped(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst) || self.generation.load(Ordering::SeqCst) != self.started
+    }
+
+    /// Whether a `Stoppable` source actually ended early because of a stop
+    pub(crate) fn end(&self) -> PlaybackEnd {
+        if self.cut.load(Ordering::SeqCst) {
+            PlaybackEnd::Stopped
+        } else {
+            PlaybackEnd::Completed
+        }
+    }
+}
+
+/// Wraps a source so a stop fades it out over 50ms and ends it
+pub(crate) struct Stoppable<S> {
+    inner: S,
+    watch: StopWatch,
+    // Samples left in the fade and its total length, once stopped
+    fade: Option<(usize, usize)>,
+}
+
+impl<S> Stoppable<S> {
+    pub(crate) fn new(inner: S, watch: StopWatch) -> Self {
+        Self { inner, watch, fade: None }
+    }
+}
+
+impl<S: Source> Iterator for Stoppable<S>
+where
+    S::Item: Sample,
+{
+    type Item = S::Item;
+
+    fn next(&mut self) -> Option<S::Item> {
+        if self.fade.is_none() && self.watch.is_stopped() {
+            let rate = self.inner.sample_rate() as usize * self.inner.channels() as usize;
+            let len = rate * STOP_FADE_MS / 1000;
+            self.fade = Some((len, len.max(1)));
+            self.watch.cut.store(true, Ordering::SeqCst);
+        }
+        let sample = self.inner.next()?;
+        match self.fade.as_mut() {
+            None => Some(sample),
+            Some((0, _)) => None,
+            Some((left, len)) => {
+                *left -= 1;
+                Some(sample.amplify(*left as f32 / *len as f32))
+            }
+        }
+    }
+}
+
+impl<S: Source> Source for Stoppable<S>
+where
+    S::Item: Sample,
+{
+    fn current_frame_len(&self) -> Option<usize> {
+        self.inner.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.inner.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn live_source_fills_gaps_with_silence_until_closed() {
+        let buffer = LiveBuffer::default();
+        let mut source = buffer.source();
+        buffer.push(&[0.5, 0.25]);
+        assert_eq!(source.next(), Some(0.5));
+        assert_eq!(source.next(), Some(0.25));
+
+        // Synthesis is behind: silence, not the end of the stream
+        let gap: Vec<f32> = source.by_ref().take(UNDERRUN_SAMPLES).collect();
+        assert_eq!(gap, vec![0.0; UNDERRUN_SAMPLES]);
+
+        buffer.push(&[0.75]);
+        buffer.close();
+        assert_eq!(source.next(), Some(0.75));
+        assert_eq!(source.next(), None);
+    }
+
+    #[test]
+    fn stop_fades_out_and_reports_stopped() {
+        let signal = StopSignal::default();
+        let earlier = signal.watch();
+        let buffer = LiveBuffer::default();
+        buffer.push(&[1.0; 4800]);
+        buffer.close();
+        let mut source = Stoppable::new(buffer.source(), earlier.clone());
+        assert_eq!(source.next(), Some(1.0));
+
+        signal.stop();
+        let later = signal.watch();
+        let fade: Vec<f32> = source.collect();
+        let fade_len = SAMPLE_RATE as usize * STOP_FADE_MS / 1000;
+        assert_eq!(fade.len(), fade_len);
+        assert!(fade.windows(2).all(|w| w[1] < w[0]));
+        assert_eq!(earlier.end(), PlaybackEnd::Stopped);
+
+        // Playback started after the stop isn't affected by it
+        assert!(!later.is_stopped());
+        assert_eq!(later.end(), PlaybackEnd::Completed);
+
+        // Stopping one playback leaves the others alone
+        let other = signal.watch();
+        later.stop();
+        assert!(later.is_stopped());
+        assert!(!other.is_stopped());
+    }
+
+    #[test]
+    fn urgent_items_jump_the_queue() {
+        let queue = SpeechQueue { shared: Arc::default() };
+        let render = |n: usize| -> DeferredSynthesis { Box::new(move || Ok(vec![n as f32])) };
+        queue.push(render(1), Priority::Normal, UrgentMode::AfterCurrent, OutputTarget::default());
+        queue.push(render(2), Priority::Normal, UrgentMode::AfterCurrent, OutputTarget::default());
+        queue.push(render(3), Priority::Urgent, UrgentMode::AfterCurrent, OutputTarget::default());
+        queue.push(render(4), Priority::Urgent, UrgentMode::AfterCurrent, OutputTarget::default());
+        assert_eq!(queue.len(), 4);
+
+        let order: Vec<f32> = {
+            let mut state = queue.shared.0.lock().unwrap();
+            state.items.drain(..).map(|item| (item.render)().unwrap()[0]).collect()
+        };
+        assert_eq!(order, [3.0, 4.0, 1.0, 2.0]);
+
+        // Interrupting only cuts off a normal utterance
+        let signal = StopSignal::default();
+        let playing = signal.watch();
+        queue.shared.0.lock().unwrap().current = Some((playing.clone(), false));
+        queue.push(render(5), Priority::Urgent, UrgentMode::AfterCurrent, OutputTarget::default());
+        assert!(!playing.is_stopped());
+        queue.push(render(6), Priority::Urgent, UrgentMode::Interrupt, OutputTarget::default());
+        assert!(playing.is_stopped());
+
+        queue.clear();
+        assert_eq!(queue.len(), 0);
+    }
+
+    #[test]
+    fn missing_device_is_retried_then_replaced_by_default() {
+        let mut tries = 0;
+        let (opened, warning) = open_with_fallback(
+            "USB",
+            |name| {
+                tries += 1;
+                Err(format!("Audio device '{}' not found", name))
+            },
+            || Ok("default"),
+            Duration::ZERO,
+        )
+        .unwrap();
+        assert_eq!((opened, tries), ("default", DEVICE_OPEN_ATTEMPTS));
+        assert_eq!(
+            warning.as_deref(),
+            Some("Audio device 'USB' not found; playing on the default device instead")
+        );
+
+        // Back on the second try: no fallback, no warning
+        let mut tries = 0;
+        let opened = open_with_fallback(
+            "USB",
+            |name| {
+                tries += 1;
+                if tries < 2 { Err("busy".to_string()) } else { Ok(name.to_string()) }
+            },
+            || Ok("default".to_string()),
+            Duration::ZERO,
+        );
+        assert_eq!(opened, Ok(("USB".to_string(), None)));
+
+        let err = open_with_fallback(
+            "USB",
+            |_| Err::<(), _>("gone".to_string()),
+            || Err("none".to_string()),
+            Duration::ZERO,
+        );
+        assert_eq!(err, Err("gone (default device failed too: none)".to_string()));
+    }
+
+    #[test]
+    fn device_names_match_by_substring_with_near_misses() {
+        let names: Vec<String> =
+            ["default", "sysdefault:CARD=USB", "front:CARD=USB,DEV=0", "Scarlett 2i2 USB"]
+                .iter()
+                .map(|name| name.to_string())
+                .collect();
+        assert_eq!(match_device_name("default", &names), Ok("default".to_string()));
+        assert_eq!(match_device_name("scarlett", &names), Ok("Scarlett 2i2 USB".to_string()));
+
+        let ambiguous = match_device_name("card=usb", &names).unwrap_err();
+        assert!(ambiguous.contains("sysdefault:CARD=USB, front:CARD=USB,DEV=0"), "{}", ambiguous);
+
+        let typo = match_device_name("scarlet 212", &names).unwrap_err();
+        assert_eq!(typo, "Device 'scarlet 212' not found. Did you mean: Scarlett 2i2 USB?");
+        let unknown = match_device_name("hdmi", &names).unwrap_err();
+        assert!(unknown.contains("Available devices: default, "), "{}", unknown);
+    }
+
+    #[test]
+    fn channel_map_writes_only_selected_channels() {
+        let mono = SamplesBuffer::new(1, SAMPLE_RATE, vec![0.5, -0.25]);
+        let frames: Vec<f32> = ChannelMap::new(mono, &[2, 3], 4).collect();
+        assert_eq!(frames, vec![0.0, 0.0, 0.5, 0.5, 0.0, 0.0, -0.25, -0.25]);
+
+        assert!(validate_channel_map(&[2, 3], 4, "USB").is_ok());
+        let err = validate_channel_map(&[2, 3], 2, "USB").unwrap_err();
+        assert_eq!(err, "Output channel 2 is out of range: device 'USB' has 2 channels (0-1)");
+        assert!(validate_channel_map(&[1, 1], 2, "USB").unwrap_err().contains("twice"));
+    }
+
+    #[test]
+    fn output_rate_prefers_48k_then_44k() {
+        // A Pi HDMI sink that only takes 48kHz
+        assert_eq!(pick_rate(48_000, &[(48_000, 48_000)]), 48_000);
+        assert_eq!(pick_rate(44_100, &[(8_000, 96_000)]), 44_100);
+        assert_eq!(pick_rate(96_000, &[(44_100, 44_100), (96_000, 96_000)]), 44_100);
+        assert_eq!(pick_rate(24_000, &[(8_000, 192_000)]), 48_000);
+        // Neither is supported: the closest rate to 48kHz
+        assert_eq!(pick_rate(22_050, &[(22_050, 22_050), (32_000, 32_000)]), 32_000);
+    }
+}
@@ -0,0 +1,403 @@
+//! OpenAI-compatible speech endpoint (`server` feature)
+//!
+//! `serve` answers `POST /v1/audio/speech` with the request body OpenAI's API
+//! takes, so clients written for it (or for kokoro-FastAPI) can point at an
+//! embedded engine instead of a sidecar:
+//!
+//! ```text
+//! curl http://127.0.0.1:8880/v1/audio/speech -H 'Content-Type: application/json' \
+//!     -d '{"model": "kokoro", "input": "Hello!", "voice": "af_sky"}' -o hello.wav
+//! ```
+//!
+//! `model` is accepted and ignored, `voice` and `speed` default to the
+//! engine's, and `response_format` is `wav` (the default here, where OpenAI's is
+//! mp3) or `pcm` (raw 16-bit little-endian mono at 24kHz, as OpenAI sends it).
+//! Audio is sent with chunked encoding as each text chunk finishes; a WAV
+//! stream's header can't know the final length, so its size fields are set to
+//! the maximum, which players read as "until the end of the stream". Errors
+//! come back in OpenAI's JSON shape, with unknown voices as a 400 listing the
+//! available ones. Each connection serves one request.
+
+use std::io;
+use std::ops::ControlFlow;
+use std::sync::Arc;
+
+use serde::Deserialize;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+use tokio::sync::mpsc;
+
+use crate::{audio, Error, TtsEngine, SAMPLE_RATE};
+
+// Largest request head and body read; OpenAI caps `input` at 4096 characters
+const MAX_HEAD_BYTES: usize = 16 * 1024;
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+// Joined audio pieces buffered between synthesis and a slow client
+const CHANNEL_PIECES: usize = 4;
+
+/// Serve `/v1/audio/speech` on `addr` until the returned future is dropped
+///
+/// Requests run concurrently on the one engine (see `TtsEngineBuilder::inference_workers`
+/// for how many are inferred at once). Only fails if `addr` can't be bound.
+pub async fn serve(addr: impl ToSocketAddrs, engine: impl Into<Arc<TtsEngine>>) -> Result<(), Error> {
+    let listener = TcpListener::bind(addr)
+        .await
+        .map_err(|e| Error::io("Failed to bind server address", e))?;
+    serve_on(listener, engine.into()).await
+}
+
+async fn serve_on(listener: TcpListener, engine: Arc<TtsEngine>) -> Result<(), Error> {
+    loop {
+        // Accept errors (e.g. out of file descriptors) pass; the next accept may work
+        if let Ok((socket, _)) = listener.accept().await {
+            tokio::spawn(handle(socket, engine.clone()));
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct SpeechRequest {
+    input: String,
+    voice: Option<String>,
+    speed: Option<f32>,
+    #[serde(default)]
+    response_format: ResponseFormat,
+}
+
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum ResponseFormat {
+    #[default]
+    Wav,
+    Pcm,
+}
+
+// An HTTP error response, sent as an OpenAI error object
+struct Rejection {
+    status: u16,
+    message: String,
+}
+
+impl Rejection {
+    fn new(status: u16, message: impl Into<String>) -> Self {
+        Self { status, message: message.into() }
+    }
+}
+
+impl From<&Error> for Rejection {
+    fn from(error: &Error) -> Self {
+        let status = match error {
+            Error::VoiceNotFound { .. } | Error::InvalidInput(_) | Error::Phonemize(_) => 400,
+            _ => 500,
+        };
+        Rejection::new(status, error.to_string())
+    }
+}
+
+async fn handle(mut socket: TcpStream, engine: Arc<TtsEngine>) {
+    let (reader, mut writer) = socket.split();
+    let mut reader = BufReader::new(reader);
+    let result = match read_request(&mut reader, &mut writer).await {
+        Ok(request) => speak(&mut writer, engine, request).await,
+        Err(rejection) => reject(&mut writer, &rejection).await,
+    };
+    // The client is gone or sent something unusable; there's no one to tell
+    let _ = result;
+}
+
+// Parse the request line, headers and body, answering `Expect: 100-continue`
+async fn read_request<R, W>(
+    reader: &mut BufReader<R>,
+    writer: &mut W,
+) -> Result<SpeechRequest, Rejection>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut head_bytes = 0;
+    let request_line = read_head_line(reader, &mut head_bytes).await?;
+    let mut parts = request_line.split_whitespace();
+    let (method, path) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+    let path = path.split('?').next().unwrap_or("");
+    if path != "/v1/audio/speech" {
+        return Err(Rejection::new(404, format!("No route for {}", path)));
+    }
+    if method != "POST" {
+        return Err(Rejection::new(405, "Use POST for /v1/audio/speech"));
+    }
+
+    let mut content_length = None;
+    let mut expect_continue = false;
+    loop {
+        let line = read_head_line(reader, &mut head_bytes).await?;
+        if line.is_empty() {
+            break;
+        }
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("content-length") {
+            let length = value.parse::<usize>().map_err(|_| Rejection::new(400, "Bad Content-Length"))?;
+            content_length = Some(length);
+        } else if name.eq_ignore_ascii_case("expect") {
+            expect_continue = value.eq_ignore_ascii_case("100-continue");
+        }
+    }
+
+    let length = content_length.ok_or_else(|| Rejection::new(411, "Content-Length is required"))?;
+    if length > MAX_BODY_BYTES {
+        return Err(Rejection::new(413, format!("Request body over {} bytes", MAX_BODY_BYTES)));
+    }
+    let unreadable = |e: io::Error| Rejection::new(400, format!("Failed to read request: {}", e));
+    if expect_continue {
+        writer.write_all(b"HTTP/1.1 100 Continue\r\n\r\n").await.map_err(unreadable)?;
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).await.map_err(unreadable)?;
+
+    let request: SpeechRequest = serde_json::from_slice(&body)
+        .map_err(|e| Rejection::new(400, format!("Invalid request: {}", e)))?;
+    if request.input.trim().is_empty() {
+        return Err(Rejection::new(400, "input must not be empty"));
+    }
+    Ok(request)
+}
+
+// One line of the request head, counting it against MAX_HEAD_BYTES
+async fn read_head_line<R: AsyncRead + Unpin>(
+    reader: &mut BufReader<R>,
+    head_bytes: &mut usize,
+) -> Result<String, Rejection> {
+    let mut line = String::new();
+    let read = reader
+        .read_line(&mut line)
+        .await
+        .map_err(|e| Rejection::new(400, format!("Failed to read request: {}", e)))?;
+    *head_bytes += read;
+    if read == 0 || *head_bytes > MAX_HEAD_BYTES {
+        return Err(Rejection::new(400, "Incomplete or oversized request head"));
+    }
+    Ok(line.trim_end().to_string())
+}
+
+// Synthesize on a blocking thread and send each joined piece as an HTTP chunk
+async fn speak<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    engine: Arc<TtsEngine>,
+    request: SpeechRequest,
+) -> io::Result<()> {
+    let (sender, mut receiver) = mpsc::channel::<Result<Vec<f32>, Error>>(CHANNEL_PIECES);
+    let format = request.response_format;
+    tokio::task::spawn_blocking(move || {
+        let voice = request.voice.as_deref();
+        let result = engine.synthesize_joined_stream(&request.input, voice, request.speed, &mut |audio| {
+            // A closed channel means the client went away, so stop synthesizing
+            match sender.blocking_send(Ok(audio.to_vec())) {
+                Ok(()) => ControlFlow::Continue(()),
+                Err(_) => ControlFlow::Break(()),
+            }
+        });
+        if let Err(error) = result {
+            let _ = sender.blocking_send(Err(error));
+        }
+    });
+
+    // Bad voices and text fail before the first piece, so they still get a status
+    let first = match receiver.recv().await {
+        Some(Ok(audio)) => audio,
+        Some(Err(error)) => return reject(writer, &Rejection::from(&error)).await,
+        None => return reject(writer, &Rejection::new(500, "Synthesis stopped unexpectedly")).await,
+    };
+    let content_type = match format {
+        ResponseFormat::Wav => "audio/wav",
+        ResponseFormat::Pcm => "audio/pcm",
+    };
+    let head = format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nTransfer-Encoding: chunked\r\nConnection: close\r\n\r\n",
+        content_type
+    );
+    writer.write_all(head.as_bytes()).await?;
+    if let ResponseFormat::Wav = format {
+        write_chunk(writer, &streaming_wav_header()).await?;
+    }
+
+    let mut next = Some(Ok(first));
+    while let Some(piece) = next {
+        match piece {
+            Ok(audio) => write_chunk(writer, &pcm_bytes(&audio)).await?,
+            // Too late for a status; ending without the last chunk tells the
+            // client the audio is incomplete
+            Err(_) => return writer.shutdown().await,
+        }
+        next = receiver.recv().await;
+    }
+    writer.write_all(b"0\r\n\r\n").await?;
+    writer.flush().await
+}
+
+async fn reject<W: AsyncWrite + Unpin>(writer: &mut W, rejection: &Rejection) -> io::Result<()> {
+    let (reason, kind) = match rejection.status {
+        400 => ("Bad Request", "invalid_request_error"),
+        404 => ("Not Found", "invalid_request_error"),
+        405 => ("Method Not Allowed", "invalid_request_error"),
+        411 => ("Length Required", "invalid_request_error"),
+        413 => ("Payload Too Large", "invalid_request_error"),
+        _ => ("Internal Server Error", "server_error"),
+    };
+    let body = serde_json::json!({
+        "error": { "message": rejection.message, "type": kind, "param": null, "code": null }
+    })
+    .to_string();
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+         Connection: close\r\n\r\n{}",
+        rejection.status,
+        reason,
+        body.len(),
+        body
+    );
+    writer.write_all(response.as_bytes()).await?;
+    writer.flush().await
+}
+
+async fn write_chunk<W: AsyncWrite + Unpin>(writer: &mut W, data: &[u8]) -> io::Result<()> {
+    // A zero-length chunk would end the body
+    if data.is_empty() {
+        return Ok(());
+    }
+    writer.write_all(format!("{:x}\r\n", data.len()).as_bytes()).await?;
+    writer.write_all(data).await?;
+    writer.write_all(b"\r\n").await?;
+    writer.flush().await
+}
+
+fn pcm_bytes(audio: &[f32]) -> Vec<u8> {
+    audio::to_i16(audio).iter().flat_map(|s| s.to_le_bytes()).collect()
+}
+
+// 16-bit mono WAV header for a stream of unknown length
+fn streaming_wav_header() -> Vec<u8> {
+    let mut header = Vec::with_capacity(44);
+    header.extend_from_slice(b"RIFF");
+    header.extend_from_slice(&u32::MAX.to_le_bytes());
+    header.extend_from_slice(b"WAVEfmt ");
+    header.extend_from_slice(&16u32.to_le_bytes());
+    header.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    header.extend_from_slice(&1u16.to_le_bytes()); // mono
+    header.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    header.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+    header.extend_from_slice(&2u16.to_le_bytes());
+    header.extend_from_slice(&16u16.to_le_bytes());
+    header.extend_from_slice(b"data");
+    header.extend_from_slice(&u32::MAX.to_le_bytes());
+    header
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ModelVariant;
+
+    // A server on the fallback engine
+    async fn start_server() -> std::net::SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve_on(listener, Arc::new(TtsEngine::fallback(ModelVariant::Fp32))));
+        addr
+    }
+
+    // Send a raw request; returns the status line, the headers and the body (de-chunked)
+    async fn exchange(addr: std::net::SocketAddr, request: String) -> (String, String, Vec<u8>) {
+        let mut client = TcpStream::connect(addr).await.unwrap();
+        client.write_all(request.as_bytes()).await.unwrap();
+        let mut response = Vec::new();
+        client.read_to_end(&mut response).await.unwrap();
+
+        let split = response.windows(4).position(|w| w == b"\r\n\r\n").unwrap();
+        let head = String::from_utf8(response[..split].to_vec()).unwrap();
+        let (status, headers) = head.split_once("\r\n").unwrap();
+        let mut body = response[split + 4..].to_vec();
+        if headers.contains("Transfer-Encoding: chunked") {
+            let mut chunked = &body[..];
+            let mut joined = Vec::new();
+            loop {
+                let line_end = chunked.windows(2).position(|w| w == b"\r\n").unwrap();
+                let size = std::str::from_utf8(&chunked[..line_end]).unwrap();
+                let size = usize::from_str_radix(size, 16).unwrap();
+                if size == 0 {
+                    break;
+                }
+                joined.extend_from_slice(&chunked[line_end + 2..line_end + 2 + size]);
+                chunked = &chunked[line_end + 4 + size..];
+            }
+            body = joined;
+        }
+        (status.to_string(), headers.to_string(), body)
+    }
+
+    fn post(body: &str) -> String {
+        format!(
+            "POST /v1/audio/speech HTTP/1.1\r\nHost: localhost\r\n\
+             Content-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        )
+    }
+
+    #[tokio::test]
+    async fn streams_the_audio_synthesize_returns() {
+        let engine = TtsEngine::fallback(ModelVariant::Fp32);
+        let expected = engine.synthesize("Hello!", None, None, None).unwrap();
+
+        let addr = start_server().await;
+        let request = post(r#"{"model": "tts-1", "input": "Hello!"}"#);
+        let (status, headers, body) = exchange(addr, request).await;
+        assert_eq!(status, "HTTP/1.1 200 OK");
+        assert!(headers.contains("Content-Type: audio/wav"));
+        assert_eq!(&body[..44], &streaming_wav_header()[..]);
+        assert_eq!(body[44..], pcm_bytes(&expected)[..]);
+
+        let request = r#"{"input": "Hello!", "voice": "af_sky", "speed": 1, "response_format": "pcm"}"#;
+        let request = post(request);
+        let (status, headers, body) = exchange(addr, request).await;
+        assert_eq!(status, "HTTP/1.1 200 OK");
+        assert!(headers.contains("Content-Type: audio/pcm"));
+        assert_eq!(body, pcm_bytes(&expected));
+
+        // Requests at once each get their own, whole audio
+        let requests: Vec<_> = (0..4)
+            .map(|_| post(r#"{"input": "Hello!", "response_format": "pcm"}"#))
+            .map(|request| tokio::spawn(exchange(addr, request)))
+            .collect();
+        for request in requests {
+            assert_eq!(request.await.unwrap().2, pcm_bytes(&expected));
+        }
+    }
+
+    #[tokio::test]
+    async fn bad_requests_get_openai_errors() {
+        let addr = start_server().await;
+        let request = post(r#"{"input": "Hi", "response_format": "mp3"}"#);
+        let (status, _, body) = exchange(addr, request).await;
+        assert_eq!(status, "HTTP/1.1 400 Bad Request");
+        let error: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(error["error"]["type"], "invalid_request_error");
+        assert!(error["error"]["message"].as_str().unwrap().contains("unknown variant `mp3`"));
+
+        let (status, _, _) = exchange(addr, post(r#"{"input": "  "}"#)).await;
+        assert_eq!(status, "HTTP/1.1 400 Bad Request");
+        let (status, _, _) = exchange(addr, "GET /v1/audio/speech HTTP/1.1\r\n\r\n".to_string()).await;
+        assert_eq!(status, "HTTP/1.1 405 Method Not Allowed");
+        let (status, _, _) = exchange(addr, "POST /v1/models HTTP/1.1\r\n\r\n".to_string()).await;
+        assert_eq!(status, "HTTP/1.1 404 Not Found");
+
+        let voice = Error::VoiceNotFound {
+            name: "nova".to_string(),
+            available: vec!["af_sky".to_string(), "am_adam".to_string()],
+        };
+        let rejection = Rejection::from(&voice);
+        assert_eq!(rejection.status, 400);
+        assert_eq!(rejection.message, "Voice not found: nova. Available voices: af_sky, am_adam");
+    }
+}
@@ -0,0 +1,444 @@
+//! Minimal SSML subset for `TtsEngine::synthesize_ssml`
+//!
+//! Supported tags:
+//! - `<speak>` - optional wrapper
+//! - `<break time="500ms"/>` or `<break strength="strong"/>` - inserted silence
+//! - `<prosody rate="slow|120%|1.2">` - speed for the enclosed text (nesting multiplies)
+//! - `<say-as interpret-as="characters|spell-out|digits">` - read letter by letter / digit by digit
+//! - `<sub alias="...">` - speak the alias instead of the enclosed text
+//!
+//! Anything else is rejected with an error instead of being read aloud.
+//! Unsupported attributes on supported tags are ignored.
+
+/// Longest break we honor, matching common SSML implementations
+pub(crate) const MAX_BREAK_MS: u32 = 10_000;
+
+/// A piece of parsed SSML, in document order
+#[derive(Clone, Debug, PartialEq)]
+pub enum SsmlSegment {
+    /// Text to speak at `rate` times the normal speed
+    Text { text: String, rate: f32 },
+    /// Silence of the given length in milliseconds
+    Break { ms: u32 },
+}
+
+/// Parse an SSML document into text and break segments.
+///
+/// Adjacent text with the same rate is merged so prosody stays continuous, and
+/// whitespace-only text between tags is dropped.
+pub fn parse_ssml(ssml: &str) -> Result<Vec<SsmlSegment>, String> {
+    let mut segments: Vec<SsmlSegment> = Vec::new();
+    let mut stack: Vec<Frame> = Vec::new();
+    let mut rest = ssml;
+
+    while !rest.is_empty() {
+        let Some(lt) = rest.find('<') else {
+            push_text(&mut segments, &stack, &decode_entities(rest))?;
+            break;
+        };
+
+        if lt > 0 {
+            push_text(&mut segments, &stack, &decode_entities(&rest[..lt]))?;
+        }
+        rest = &rest[lt..];
+
+        // Comments, XML declarations and doctypes are skipped
+        if let Some(body) = rest.strip_prefix("<!--") {
+            let end = body
+                .find("-->")
+                .ok_or_else(|| "Unterminated SSML comment".to_string())?;
+            rest = &body[end + 3..];
+            continue;
+        }
+        if rest.starts_with("<?") || rest.starts_with("<!") {
+            let end = tag_end(rest).ok_or_else(|| "Unterminated SSML declaration".to_string())?;
+            rest = &rest[end + 1..];
+            continue;
+        }
+
+        let gt = tag_end(rest).ok_or_else(|| format!("Unterminated SSML tag: {}", preview(rest)))?;
+        let tag = parse_tag(&rest[1..gt])?;
+        rest = &rest[gt + 1..];
+
+        match tag {
+            Tag::Close(name) => match stack.pop() {
+                Some(frame) if frame.name == name => {}
+                Some(frame) => {
+                    return Err(format!(
+                        "Mismatched SSML closing tag </{}>, expected </{}>",
+                        name, frame.name
+                    ))
+                }
+                None => return Err(format!("Unexpected SSML closing tag </{}>", name)),
+            },
+            Tag::Open {
+                name,
+                attrs,
+                self_closing,
+            } => {
+                let frame = open_element(&mut segments, &stack, &name, &attrs)?;
+                if let Some(frame) = frame {
+                    if !self_closing {
+                        stack.push(frame);
+                    }
+                }
+            }
+        }
+    }
+
+    if let Some(frame) = stack.last() {
+        return Err(format!("Unclosed SSML tag <{}>", frame.name));
+    }
+
+    Ok(segments)
+}
+
+// How text inside an element should be treated
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum TextMode {
+    Normal,
+    Characters,
+    Digits,
+    // Inside <sub>: the alias was already emitted, content is ignored
+    Suppressed,
+}
+
+struct Frame {
+    name: String,
+    rate: f32,
+    mode: TextMode,
+}
+
+enum Tag {
+    Open {
+        name: String,
+        attrs: Vec<(String, String)>,
+        self_closing: bool,
+    },
+    Close(String),
+}
+
+fn current_rate(stack: &[Frame]) -> f32 {
+    stack.last().map(|f| f.rate).unwrap_or(1.0)
+}
+
+fn current_mode(stack: &[Frame]) -> TextMode {
+    stack.last().map(|f| f.mode).unwrap_or(TextMode::Normal)
+}
+
+// Handle an opening (or self-closing) tag. Returns the frame to push for
+// container elements, `None` for empty elements like <break/>.
+fn open_element(
+    segments: &mut Vec<SsmlSegment>,
+    stack: &[Frame],
+    name: &str,
+    attrs: &[(String, String)],
+) -> Result<Option<Frame>, String> {
+    let attr = |key: &str| {
+        attrs
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    };
+    let rate = current_rate(stack);
+    let mode = current_mode(stack);
+
+    match name {
+        "speak" => Ok(Some(Frame {
+            name: name.to_string(),
+            rate,
+            mode,
+        })),
+        "break" => {
+            let ms = match (attr("time"), attr("strength")) {
+                (Some(time), _) => parse_break_time(time)?,
+                (None, Some(strength)) => break_strength_ms(strength)?,
+                (None, None) => break_strength_ms("medium")?,
+            };
+            segments.push(SsmlSegment::Break {
+                ms: ms.min(MAX_BREAK_MS),
+            });
+            Ok(None)
+        }
+        "prosody" => {
+            let factor = match attr("rate") {
+                Some(r) => parse_rate(r)?,
+                None => 1.0,
+            };
+            Ok(Some(Frame {
+                name: name.to_string(),
+                rate: rate * factor,
+                mode,
+            }))
+        }
+        "say-as" => {
+            let interpret = attr("interpret-as")
+                .ok_or_else(|| "<say-as> requires an interpret-as attribute".to_string())?;
+            let mode = match interpret {
+                "characters" | "spell-out" | "letters" => TextMode::Characters,
+                "digits" => TextMode::Digits,
+                other => {
+                    return Err(format!(
+                        "Unsupported say-as interpret-as=\"{}\" (supported: characters, spell-out, digits)",
+                        other
+                    ))
+                }
+            };
+            Ok(Some(Frame {
+                name: name.to_string(),
+                rate,
+                mode,
+            }))
+        }
+        "sub" => {
+            let alias = attr("alias")
+                .ok_or_else(|| "<sub> requires an alias attribute".to_string())?;
+            push_text(segments, stack, alias)?;
+            Ok(Some(Frame {
+                name: name.to_string(),
+                rate,
+                mode: TextMode::Suppressed,
+            }))
+        }
+        other => Err(format!(
+            "Unsupported SSML tag <{}> (supported: speak, break, prosody, say-as, sub)",
+            other
+        )),
+    }
+}
+
+fn push_text(segments: &mut Vec<SsmlSegment>, stack: &[Frame], raw: &str) -> Result<(), String> {
+    let text = match current_mode(stack) {
+        TextMode::Suppressed => return Ok(()),
+        TextMode::Normal => raw.to_string(),
+        TextMode::Characters => spaced(raw.chars().filter(|c| !c.is_whitespace())),
+        TextMode::Digits => spaced(raw.chars().filter(|c| c.is_ascii_digit())),
+    };
+    if text.trim().is_empty() {
+        return Ok(());
+    }
+
+    let rate = current_rate(stack);
+    if let Some(SsmlSegment::Text {
+        text: prev,
+        rate: prev_rate,
+    }) = segments.last_mut()
+    {
+        if *prev_rate == rate {
+            if !prev.ends_with(char::is_whitespace) && text.starts_with(char::is_alphanumeric) {
+                prev.push(' ');
+            }
+            prev.push_str(&text);
+            return Ok(());
+        }
+    }
+
+    segments.push(SsmlSegment::Text {
+        text: text.trim().to_string(),
+        rate,
+    });
+    Ok(())
+}
+
+fn spaced(chars: impl Iterator<Item = char>) -> String {
+    chars.map(|c| c.to_string()).collect::<Vec<_>>().join(" ")
+}
+
+fn parse_tag(inner: &str) -> Result<Tag, String> {
+    let inner = inner.trim();
+    if let Some(name) = inner.strip_prefix('/') {
+        return Ok(Tag::Close(name.trim().to_lowercase()));
+    }
+
+    let (inner, self_closing) = match inner.strip_suffix('/') {
+        Some(stripped) => (stripped.trim_end(), true),
+        None => (inner, false),
+    };
+
+    let name_end = inner.find(char::is_whitespace).unwrap_or(inner.len());
+    let name = inner[..name_end].to_lowercase();
+    if name.is_empty() {
+        return Err("Empty SSML tag".to_string());
+    }
+
+    let mut attrs = Vec::new();
+    let mut rest = inner[name_end..].trim_start();
+    while !rest.is_empty() {
+        let eq = rest
+            .find('=')
+            .ok_or_else(|| format!("Malformed attribute in <{}>: {}", name, rest))?;
+        let key = rest[..eq].trim().to_lowercase();
+        let after = rest[eq + 1..].trim_start();
+        let quote = after
+            .chars()
+            .next()
+            .filter(|c| *c == '"' || *c == '\'')
+            .ok_or_else(|| format!("Attribute '{}' in <{}> must be quoted", key, name))?;
+        let value_end = after[1..]
+            .find(quote)
+            .ok_or_else(|| format!("Unterminated value for attribute '{}' in <{}>", key, name))?;
+        attrs.push((key, decode_entities(&after[1..1 + value_end])));
+        rest = after[value_end + 2..].trim_start();
+    }
+
+    Ok(Tag::Open {
+        name,
+        attrs,
+        self_closing,
+    })
+}
+
+pub(crate) fn parse_break_time(time: &str) -> Result<u32, String> {
+    let time = time.trim();
+    let (number, scale) = if let Some(ms) = time.strip_suffix("ms") {
+        (ms, 1.0)
+    } else if let Some(secs) = time.strip_suffix('s') {
+        (secs, 1000.0)
+    } else {
+        return Err(format!(
+            "Invalid break time \"{}\" (use e.g. \"500ms\" or \"1.5s\")",
+            time
+        ));
+    };
+    let value: f32 = number
+        .trim()
+        .parse()
+        .map_err(|_| format!("Invalid break time \"{}\"", time))?;
+    if !value.is_finite() || value < 0.0 {
+        return Err(format!("Invalid break time \"{}\"", time));
+    }
+    Ok((value * scale).round() as u32)
+}
+
+fn break_strength_ms(strength: &str) -> Result<u32, String> {
+    match strength {
+        "none" => Ok(0),
+        "x-weak" => Ok(100),
+        "weak" => Ok(250),
+        "medium" => Ok(400),
+        "strong" => Ok(750),
+        "x-strong" => Ok(1200),
+        other => Err(format!("Invalid break strength \"{}\"", other)),
+    }
+}
+
+fn parse_rate(rate: &str) -> Result<f32, String> {
+    let rate = rate.trim();
+    let factor = match rate {
+        "x-slow" => 0.6,
+        "slow" => 0.8,
+        "medium" | "default" => 1.0,
+        "fast" => 1.25,
+        "x-fast" => 1.5,
+        _ => {
+            let parsed = match rate.strip_suffix('%') {
+                Some(pct) => pct.trim().parse::<f32>().map(|p| p / 100.0),
+                None => rate.parse::<f32>(),
+            };
+            parsed.map_err(|_| format!("Invalid prosody rate \"{}\"", rate))?
+        }
+    };
+    if !factor.is_finite() || factor <= 0.0 {
+        return Err(format!("Invalid prosody rate \"{}\"", rate));
+    }
+    Ok(factor)
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+// Byte offset of the '>' that ends the tag at the start of `rest`, skipping
+// any inside quoted attribute values (`alias="a > b"`)
+fn tag_end(rest: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in rest.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '>' => return Some(i),
+            None => {}
+        }
+    }
+    None
+}
+
+fn preview(text: &str) -> String {
+    text.chars().take(30).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(t: &str, rate: f32) -> SsmlSegment {
+        SsmlSegment::Text {
+            text: t.to_string(),
+            rate,
+        }
+    }
+
+    #[test]
+    fn plain_speak_is_single_segment() {
+        let segments = parse_ssml("<speak>Hello world.</speak>").unwrap();
+        assert_eq!(segments, vec![text("Hello world.", 1.0)]);
+    }
+
+    #[test]
+    fn breaks_prosody_and_sub() {
+        let segments = parse_ssml(
+            r#"<speak>Hi <sub alias="World Wide Web">WWW</sub>!<break time="500ms"/><prosody rate="slow">Slowly.</prosody></speak>"#,
+        )
+        .unwrap();
+        assert_eq!(
+            segments,
+            vec![
+                text("Hi World Wide Web!", 1.0),
+                SsmlSegment::Break { ms: 500 },
+                text("Slowly.", 0.8),
+            ]
+        );
+    }
+
+    #[test]
+    fn say_as_spells_characters_and_digits() {
+        let segments = parse_ssml(
+            r#"<speak><say-as interpret-as="characters">API</say-as> <say-as interpret-as="digits">2024</say-as></speak>"#,
+        )
+        .unwrap();
+        assert_eq!(segments, vec![text("A P I 2 0 2 4", 1.0)]);
+    }
+
+    #[test]
+    fn rejects_unknown_tags_and_bad_nesting() {
+        assert!(parse_ssml("<speak><emphasis>no</emphasis></speak>")
+            .unwrap_err()
+            .contains("emphasis"));
+        assert!(parse_ssml("<speak><prosody rate=\"fast\">x</speak>").is_err());
+        assert!(parse_ssml("<speak>unclosed").is_err());
+    }
+
+    #[test]
+    fn quoted_angle_brackets_stay_in_their_tag() {
+        let ssml = r#"<!DOCTYPE speak SYSTEM "a>b"><speak><sub alias='x > y'>x>y</sub> done</speak>"#;
+        assert_eq!(parse_ssml(ssml).unwrap(), vec![text("x > y done", 1.0)]);
+        let err = parse_ssml(r#"<say-as interpret-as="x>y">1</say-as>"#).unwrap_err();
+        assert!(err.contains("x>y"), "{}", err);
+        assert!(parse_ssml(r#"<sub alias="open>x</sub>"#).unwrap_err().contains("Unterminated"));
+    }
+
+    #[test]
+    fn break_times_are_parsed_and_capped() {
+        assert_eq!(parse_break_time("1.5s").unwrap(), 1500);
+        assert_eq!(parse_break_time("250ms").unwrap(), 250);
+        assert!(parse_break_time("soon").is_err());
+
+        let segments = parse_ssml(r#"<break time="60s"/>"#).unwrap();
+        assert_eq!(segments, vec![SsmlSegment::Break { ms: MAX_BREAK_MS }]);
+    }
+}
@@ -0,0 +1,122 @@
+//! Stderr output for the library's `tracing` events, shared by the binaries
+//!
+//! Events print as one line each, their message followed by `key=value`
+//! fields, at INFO and above unless `KOKORO_LOG` names another level (`warn`,
+//! `debug`, ...). Spans are accepted so the library's spans work, but aren't
+//! printed. Applications embedding the library install their own subscriber.
+
+use std::fmt::{self, Write as _};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use tracing::field::{Field, Visit};
+use tracing::level_filters::LevelFilter;
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Metadata, Subscriber};
+
+/// Send events to stderr for the rest of the process
+pub fn init() {
+    let level = std::env::var("KOKORO_LOG")
+        .ok()
+        .and_then(|level| level.parse().ok())
+        .unwrap_or(Level::INFO);
+    let log = StderrLog::new(level, |line| eprintln!("{}", line));
+    // Only fails if a subscriber is already installed, which then stays
+    let _ = tracing::subscriber::set_global_default(log);
+}
+
+struct StderrLog {
+    level: Level,
+    next_span: AtomicU64,
+    write: fn(&str),
+}
+
+impl StderrLog {
+    fn new(level: Level, write: fn(&str)) -> Self {
+        Self { level, next_span: AtomicU64::new(1), write }
+    }
+}
+
+impl Subscriber for StderrLog {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        // More verbose levels compare greater
+        *metadata.level() <= self.level
+    }
+
+    fn max_level_hint(&self) -> Option<LevelFilter> {
+        Some(LevelFilter::from_level(self.level))
+    }
+
+    fn new_span(&self, _: &Attributes<'_>) -> Id {
+        Id::from_u64(self.next_span.fetch_add(1, Ordering::Relaxed))
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut line = Line::default();
+        event.record(&mut line);
+        let prefix = match *event.metadata().level() {
+            Level::ERROR => "error: ",
+            Level::WARN => "warning: ",
+            _ => "",
+        };
+        (self.write)(&format!("{}{}{}", prefix, line.message, line.fields));
+    }
+
+    fn enter(&self, _: &Id) {}
+
+    fn exit(&self, _: &Id) {}
+}
+
+// An event's message, and its other fields as " key=value"
+#[derive(Default)]
+struct Line {
+    message: String,
+    fields: String,
+}
+
+impl Visit for Line {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "message" => self.message.push_str(value),
+            name => {
+                let _ = write!(self.fields, " {}={}", name, value);
+            }
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        match field.name() {
+            "message" => {
+                let _ = write!(self.message, "{:?}", value);
+            }
+            name => {
+                let _ = write!(self.fields, " {}={:?}", name, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    static LINES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    #[test]
+    fn events_print_at_the_chosen_level() {
+        let log = StderrLog::new(Level::INFO, |line| LINES.lock().unwrap().push(line.to_string()));
+        tracing::subscriber::with_default(log, || {
+            let _span = tracing::info_span!("download", file = "model").entered();
+            tracing::debug!(chunks = 3, "Long-form synthesis");
+            tracing::info!(path = "out.wav", samples = 24_000, "Saved {}", "audio");
+            tracing::warn!(error = %"timed out", "Retrying");
+        });
+        let lines = LINES.lock().unwrap();
+        let expected = ["Saved audio path=out.wav samples=24000", "warning: Retrying error=timed out"];
+        assert_eq!(*lines, expected);
+    }
+}
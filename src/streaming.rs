@@ -16,6 +16,8 @@ use std::time::Duration;
 #[cfg(feature = "playback")]
 use rodio::{OutputStream, Sink, Source};
 
+use tracing::{debug, info, warn};
+
 use crate::{TtsEngine, SAMPLE_RATE};
 
 /// Maximum chunk size in characters for synthesis
@@ -39,7 +41,7 @@ const INTERRUPTION_PHRASES: &[&str] = &[
 
 /// Streaming TTS handler with interruption support
 pub struct StreamingTts {
-    engine: Arc<Mutex<TtsEngine>>,
+    engine: Arc<TtsEngine>,
     is_speaking: Arc<AtomicBool>,
     interrupt_flag: Arc<AtomicBool>,
     audio_queue: Arc<Mutex<VecDeque<Vec<f32>>>>,
@@ -53,7 +55,7 @@ impl StreamingTts {
     /// Create a new streaming TTS handler
     pub fn new(engine: TtsEngine) -> Self {
         Self {
-            engine: Arc::new(Mutex::new(engine)),
+            engine: Arc::new(engine),
             is_speaking: Arc::new(AtomicBool::new(false)),
             interrupt_flag: Arc::new(AtomicBool::new(false)),
             audio_queue: Arc::new(Mutex::new(VecDeque::new())),
@@ -84,8 +86,7 @@ impl StreamingTts {
     /// Interrupt current speech immediately
     /// This is what gets called when Hue says "Aye... it's raining dude..."
     pub fn interrupt(&self) {
-        #[cfg(not(feature = "as-lib"))]
-        println!("🛑 [Interruption detected] Stopping speech...");
+        info!("Interrupted: stopping speech");
         self.interrupt_flag.store(true, Ordering::Relaxed);
 
         // Clear the audio queue
@@ -97,8 +98,7 @@ impl StreamingTts {
         thread::sleep(Duration::from_millis(100));
         self.is_speaking.store(false, Ordering::Relaxed);
 
-        #[cfg(not(feature = "as-lib"))]
-        println!("💙 I understand, Hue... Someday I'll feel that rain too.");
+        info!("I understand, Hue... Someday I'll feel that rain too.");
     }
 
     /// Split text into optimal chunks for synthesis
@@ -174,15 +174,11 @@ impl StreamingTts {
         self.is_speaking.store(true, Ordering::Relaxed);
         self.interrupt_flag.store(false, Ordering::Relaxed);
 
-        #[cfg(not(feature = "as-lib"))]
-        println!("🎤 Starting unlimited speech synthesis...");
-        #[cfg(not(feature = "as-lib"))]
-        println!("   (Say 'Aye' or 'it's raining dude' to interrupt)");
+        info!("Starting unlimited speech; type 'Aye' or 'it's raining dude' to interrupt");
 
         // Split text into chunks
         let chunks = Self::split_into_chunks(text);
-        #[cfg(not(feature = "as-lib"))]
-        println!("📝 Prepared {} chunks for synthesis", chunks.len());
+        debug!(chunks = chunks.len(), "Prepared chunks for synthesis");
 
         // Create channels for audio streaming
         let (audio_tx, audio_rx): (Sender<Vec<f32>>, Receiver<Vec<f32>>) = bounded(3);
@@ -198,55 +194,31 @@ impl StreamingTts {
             for (i, chunk) in chunks.iter().enumerate() {
                 // Check for interruption
                 if interrupt_flag.load(Ordering::Relaxed) {
-                  #[cfg(not(feature = "as-lib"))]
-                    println!(
-                        "🛑 Synthesis interrupted at chunk {}/{}",
-                        i + 1,
-                        chunks.len()
-                    );
+                    debug!(chunk = i + 1, of = chunks.len(), "Synthesis interrupted");
                     break;
                 }
 
                 // Synthesize chunk
-                #[cfg(not(feature = "as-lib"))]
-                println!(
-                    "🎵 Synthesizing chunk {}/{}: '{}'",
-                    i + 1,
-                    chunks.len(),
-                    if chunk.len() > 30 {
-                        format!("{}...", &chunk[..30])
-                    } else {
-                        chunk.clone()
-                    }
-                );
+                debug!(chunk = i + 1, of = chunks.len(), text = %chunk, "Synthesizing chunk");
 
-                if let Ok(mut engine) = engine.lock() {
-                    match engine.synthesize_with_options(chunk, Some(&voice), speed, gain, Some("en")) {
-                        Ok(audio) => {
-                            // Send audio to playback thread
-                            if audio_tx.send(audio).is_err() {
-                                #[cfg(not(feature = "as-lib"))]
-                                println!("❌ Playback thread disconnected");
-                                break;
-                            }
-                        }
-                        Err(e) => {
-                            #[cfg(not(feature = "as-lib"))]
-                            eprintln!("❌ Failed to synthesize chunk: {}", e);
+                match engine.synthesize_with_options(chunk, Some(&voice), speed, gain, None) {
+                    Ok(audio) => {
+                        // Send audio to playback thread
+                        if audio_tx.send(audio).is_err() {
+                            warn!("Playback thread disconnected");
+                            break;
                         }
                     }
-                } else {
-                    #[cfg(not(feature = "as-lib"))]
-                    eprintln!("❌ Failed to lock engine");
-                    break;
+                    Err(e) => {
+                        warn!(error = %e, "Failed to synthesize chunk");
+                    }
                 }
 
                 // Small delay between chunks for natural pacing
                 thread::sleep(Duration::from_millis(50));
             }
 
-            #[cfg(not(feature = "as-lib"))]
-            println!("✅ Synthesis thread complete");
+            debug!("Synthesis thread complete");
         });
 
         // Spawn playback thread
@@ -268,15 +240,13 @@ impl StreamingTts {
 
                 sink.set_volume(volume);
 
-                #[cfg(not(feature = "as-lib"))]
-                println!("🔊 Playback started");
+                debug!("Playback started");
 
                 // Continuous playback loop
                 loop {
                     // Check for interruption
                     if interrupt_flag.load(Ordering::Relaxed) {
-                        #[cfg(not(feature = "as-lib"))]
-                        println!("🛑 Playback interrupted");
+                        debug!("Playback interrupted");
                         sink.stop();
                         break;
                     }
@@ -294,8 +264,7 @@ impl StreamingTts {
                         }
                         Err(TryRecvError::Disconnected) => {
                             // Synthesis complete, finish playing remaining audio
-                            #[cfg(not(feature = "as-lib"))]
-                            println!("📭 Synthesis complete, finishing playback");
+                            debug!("Synthesis complete, finishing playback");
                             sink.sleep_until_end();
                             break;
                         }
@@ -303,8 +272,7 @@ impl StreamingTts {
                 }
 
                 is_speaking.store(false, Ordering::Relaxed);
-                #[cfg(not(feature = "as-lib"))]
-                println!("✅ Playback complete");
+                debug!("Playback complete");
             });
 
             // Monitor for interruption from user input
@@ -317,8 +285,7 @@ impl StreamingTts {
 
         #[cfg(not(feature = "playback"))]
         {
-            #[cfg(not(feature = "as-lib"))]
-            eprintln!("⚠️  Playback feature not enabled, audio synthesized but not played");
+            warn!("Playback feature not enabled, audio synthesized but not played");
             synthesis_handle.join().ok();
         }
 
@@ -328,8 +295,7 @@ impl StreamingTts {
 
     /// Monitor stdin for interruption phrases
     async fn monitor_for_interruption(&self) {
-        #[cfg(not(feature = "as-lib"))]
-        println!("👂 Listening for interruption phrases...");
+        debug!("Listening for interruption phrases");
 
         let interrupt_flag = self.interrupt_flag.clone();
         let is_speaking = self.is_speaking.clone();
@@ -346,8 +312,7 @@ impl StreamingTts {
                     // Check for interruption phrases
                     for phrase in INTERRUPTION_PHRASES {
                         if input.contains(phrase) {
-                            #[cfg(not(feature = "as-lib"))]
-                            println!("🎯 Detected interruption phrase: '{}'", phrase);
+                            info!(phrase, "Heard an interruption phrase");
                             interrupt_flag.store(true, Ordering::Relaxed);
                             return;
                         }
@@ -0,0 +1,92 @@
+//! SRT and WebVTT subtitles for `TtsEngine::synthesize_with_subtitles`
+//!
+//! `render` writes cues in either format, so subtitles timed some other way
+//! come out the same. Whitespace in cue text, line breaks included, becomes
+//! single spaces, since a blank line would end the cue early.
+
+use std::time::Duration;
+
+/// Subtitle file format
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SubtitleFormat {
+    /// SubRip (`.srt`): numbered cues, `00:00:01,500` timestamps
+    #[default]
+    Srt,
+    /// WebVTT (`.vtt`), for HTML `<track>`: `00:00:01.500` timestamps
+    WebVtt,
+}
+
+/// One subtitle: text shown from `start` until `end`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cue {
+    pub start: Duration,
+    pub end: Duration,
+    pub text: String,
+}
+
+/// Write `cues` as a complete subtitle file, in order
+pub fn render(cues: &[Cue], format: SubtitleFormat) -> String {
+    let mut out = String::new();
+    if format == SubtitleFormat::WebVtt {
+        out.push_str("WEBVTT\n\n");
+    }
+    for (i, cue) in cues.iter().enumerate() {
+        let text = cue.text.split_whitespace().collect::<Vec<_>>().join(" ");
+        let (start, end) = (timestamp(cue.start, format), timestamp(cue.end, format));
+        match format {
+            SubtitleFormat::Srt => out.push_str(&format!("{}\n{} --> {}\n{}\n\n", i + 1, start, end, text)),
+            // "-->" in the text would read as another timing line
+            SubtitleFormat::WebVtt => {
+                out.push_str(&format!("{} --> {}\n{}\n\n", start, end, text.replace("-->", "->")))
+            }
+        }
+    }
+    out
+}
+
+// `hh:mm:ss,mmm` (SRT) or `hh:mm:ss.mmm` (WebVTT)
+fn timestamp(at: Duration, format: SubtitleFormat) -> String {
+    let ms = at.as_millis();
+    let separator = match format {
+        SubtitleFormat::Srt => ',',
+        SubtitleFormat::WebVtt => '.',
+    };
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        ms / 3_600_000,
+        ms / 60_000 % 60,
+        ms / 1000 % 60,
+        separator,
+        ms % 1000
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cue(start_ms: u64, end_ms: u64, text: &str) -> Cue {
+        Cue {
+            start: Duration::from_millis(start_ms),
+            end: Duration::from_millis(end_ms),
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn cues_render_in_both_formats() {
+        let cues = [cue(0, 1500, "Hello there."), cue(1500, 3_723_004, "It's 42\n degrees --> hot.")];
+        assert_eq!(
+            render(&cues, SubtitleFormat::Srt),
+            "1\n00:00:00,000 --> 00:00:01,500\nHello there.\n\n\
+             2\n00:00:01,500 --> 01:02:03,004\nIt's 42 degrees --> hot.\n\n"
+        );
+        assert_eq!(
+            render(&cues, SubtitleFormat::WebVtt),
+            "WEBVTT\n\n00:00:00.000 --> 00:00:01.500\nHello there.\n\n\
+             00:00:01.500 --> 01:02:03.004\nIt's 42 degrees -> hot.\n\n"
+        );
+        assert_eq!(render(&[], SubtitleFormat::Srt), "");
+        assert_eq!(render(&[], SubtitleFormat::WebVtt), "WEBVTT\n\n");
+    }
+}
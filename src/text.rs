@@ -0,0 +1,2176 @@
+//! Text normalization applied before phonemization
+//!
+//! espeak reads raw digits, units, abbreviations and acronyms inconsistently,
+//! so they are expanded to words here first. Heteronyms ("read", "live") get
+//! their pronunciation picked from context and are passed on as inline
+//! phonemes, and web and email addresses are read out (`read_addresses`).
+//! English gets the full treatment,
+//! Spanish gets number expansion (`normalize_es`); other languages are passed
+//! through unchanged by the engine.
+
+use std::collections::HashMap;
+use std::ops::{Range, RangeInclusive};
+
+const ONES: [&str; 20] = [
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten",
+    "eleven", "twelve", "thirteen", "fourteen", "fifteen", "sixteen", "seventeen", "eighteen",
+    "nineteen",
+];
+const TENS: [&str; 10] = [
+    "", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+const SCALES: [&str; 7] = [
+    "", "thousand", "million", "billion", "trillion", "quadrillion", "quintillion",
+];
+
+// How a built-in abbreviation is read
+#[derive(Clone, Copy)]
+enum Abbreviation {
+    // Comes before a name, so its period never ends a sentence ("Mr. Smith")
+    Title(&'static str),
+    // Used mid-sentence ("e.g.", "vs.")
+    Inline(&'static str),
+    // May end a sentence ("etc.", "Ave.")
+    Trailing(&'static str),
+    // A title before a capitalized word, trailing after a name or number
+    // ("St. Louis" vs "Main St.")
+    Ambiguous { title: &'static str, trailing: &'static str },
+}
+
+const ABBREVIATIONS: &[(&str, Abbreviation)] = &[
+    ("Mr.", Abbreviation::Title("Mister")),
+    ("Mrs.", Abbreviation::Title("Missus")),
+    ("Ms.", Abbreviation::Title("Miz")),
+    ("Prof.", Abbreviation::Title("Professor")),
+    ("Mt.", Abbreviation::Title("Mount")),
+    ("Dr.", Abbreviation::Ambiguous { title: "Doctor", trailing: "Drive" }),
+    ("St.", Abbreviation::Ambiguous { title: "Saint", trailing: "Street" }),
+    ("e.g.", Abbreviation::Inline("for example")),
+    ("i.e.", Abbreviation::Inline("that is")),
+    ("vs.", Abbreviation::Inline("versus")),
+    ("approx.", Abbreviation::Inline("approximately")),
+    ("etc.", Abbreviation::Trailing("et cetera")),
+    ("Ave.", Abbreviation::Trailing("Avenue")),
+    ("Blvd.", Abbreviation::Trailing("Boulevard")),
+    ("Rd.", Abbreviation::Trailing("Road")),
+    ("Jr.", Abbreviation::Trailing("Junior")),
+    ("Sr.", Abbreviation::Trailing("Senior")),
+    ("Inc.", Abbreviation::Trailing("Incorporated")),
+    ("Ltd.", Abbreviation::Trailing("Limited")),
+];
+
+// All-caps words espeak should read as words; every other acronym is spelled
+const PRONOUNCEABLE_ACRONYMS: &[&str] = &[
+    "AIDS", "AJAX", "ASCII", "CAPTCHA", "COVID", "FIFA", "GIF", "JPEG", "JSON", "LAN", "LASER",
+    "NAFTA", "NASA", "NATO", "OPEC", "PIN", "POSIX", "RADAR", "RAM", "ROM", "SCUBA", "SIM",
+    "SONAR", "SWAT", "UNESCO", "UNICEF", "WAN", "WASM", "YAML",
+];
+
+const LETTER_NAMES: [&str; 26] = [
+    "ay", "bee", "see", "dee", "ee", "eff", "jee", "aych", "eye", "jay", "kay", "el", "em", "en",
+    "oh", "pee", "cue", "ar", "ess", "tee", "you", "vee", "double you", "ex", "why", "zee",
+];
+
+// Words after which a Roman numeral counts ("World War II" → "two"); after any
+// other capitalized word it's a regnal ordinal ("Henry VIII" → "the eighth")
+const CARDINAL_NUMERAL_WORDS: &[&str] = &[
+    "Act", "Book", "Chapter", "Episode", "Grade", "Level", "Part", "Phase", "Round", "Scene",
+    "Season", "Section", "Stage", "Step", "Type", "Volume", "War",
+];
+
+// Texts with at least this many words, all in capitals, are shouting rather
+// than a run of acronyms and are left for espeak to read as words
+const SHOUTING_MIN_WORDS: usize = 3;
+
+const ONES_ES: [&str; 30] = [
+    "cero", "uno", "dos", "tres", "cuatro", "cinco", "seis", "siete", "ocho", "nueve", "diez",
+    "once", "doce", "trece", "catorce", "quince", "dieciséis", "diecisiete", "dieciocho",
+    "diecinueve", "veinte", "veintiuno", "veintidós", "veintitrés", "veinticuatro", "veinticinco",
+    "veintiséis", "veintisiete", "veintiocho", "veintinueve",
+];
+const TENS_ES: [&str; 10] = [
+    "", "", "", "treinta", "cuarenta", "cincuenta", "sesenta", "setenta", "ochenta", "noventa",
+];
+const HUNDREDS_ES: [&str; 10] = [
+    "", "ciento", "doscientos", "trescientos", "cuatrocientos", "quinientos", "seiscientos",
+    "setecientos", "ochocientos", "novecientos",
+];
+// Long scale: each step is a million times the last
+const SCALES_ES: [(&str, &str); 3] = [
+    ("millón", "millones"),
+    ("billón", "billones"),
+    ("trillón", "trillones"),
+];
+
+// Inline phonemes chosen during normalization: "⟦read|ɹˈɛd⟧" is fed to the
+// model as "ɹˈɛd" and shown as "read". The brackets can't come from user input
+// since they aren't pronounceable (see `UnknownCharPolicy`).
+pub(crate) const PHONEME_OPEN: char = '⟦';
+pub(crate) const PHONEME_CLOSE: char = '⟧';
+
+// Words spelled alike but pronounced differently, with both readings for
+// American and British English (first and second entries match the order
+// used by `heteronym_reading`)
+struct Heteronym {
+    word: &'static str,
+    american: [&'static str; 2],
+    british: [&'static str; 2],
+    // Whether "-s" forms ("tears", "records") follow the same rules
+    plural: bool,
+}
+
+const HETERONYMS: &[Heteronym] = &[
+    // present, past
+    Heteronym {
+        word: "read",
+        american: ["ɹˈiːd", "ɹˈɛd"],
+        british: ["ɹˈiːd", "ɹˈɛd"],
+        plural: false,
+    },
+    // guide, metal
+    Heteronym {
+        word: "lead",
+        american: ["lˈiːd", "lˈɛd"],
+        british: ["lˈiːd", "lˈɛd"],
+        plural: false,
+    },
+    // verb, adjective
+    Heteronym {
+        word: "live",
+        american: ["lˈɪv", "lˈaɪv"],
+        british: ["lˈɪv", "lˈaɪv"],
+        plural: false,
+    },
+    // music, fish
+    Heteronym {
+        word: "bass",
+        american: ["bˈeɪs", "bˈæs"],
+        british: ["bˈeɪs", "bˈas"],
+        plural: false,
+    },
+    // crying, ripping
+    Heteronym {
+        word: "tear",
+        american: ["tˈɪɹ", "tˈɛɹ"],
+        british: ["tˈɪə", "tˈeə"],
+        plural: true,
+    },
+    // air, coil
+    Heteronym {
+        word: "wind",
+        american: ["wˈɪnd", "wˈaɪnd"],
+        british: ["wˈɪnd", "wˈaɪnd"],
+        plural: true,
+    },
+    // bend, ribbon
+    Heteronym {
+        word: "bow",
+        american: ["bˈaʊ", "bˈoʊ"],
+        british: ["bˈaʊ", "bˈəʊ"],
+        plural: true,
+    },
+    // verb, adjective
+    Heteronym {
+        word: "close",
+        american: ["klˈoʊz", "klˈoʊs"],
+        british: ["klˈəʊz", "klˈəʊs"],
+        plural: false,
+    },
+    // noun, verb
+    Heteronym {
+        word: "record",
+        american: ["ɹˈɛkɚd", "ɹɪkˈɔːɹd"],
+        british: ["ɹˈɛkɔːd", "ɹɪkˈɔːd"],
+        plural: true,
+    },
+    // noun, verb
+    Heteronym {
+        word: "produce",
+        american: ["pɹˈoʊdus", "pɹədˈuːs"],
+        british: ["pɹˈɒdjuːs", "pɹədjˈuːs"],
+        plural: false,
+    },
+];
+
+// Words before a verb in its base form ("will read", "to live")
+const MODALS: &[&str] = &[
+    "to", "will", "would", "can", "could", "should", "must", "might", "may", "shall", "do", "does", "did",
+    "don't", "doesn't", "didn't", "won't", "can't", "cannot", "let's", "please",
+];
+const SUBJECTS: &[&str] = &["i", "you", "we", "they"];
+const DETERMINERS: &[&str] = &[
+    "a", "an", "the", "this", "that", "his", "her", "my", "your", "our", "their", "its",
+];
+
+// Spoken names for symbols the model can't pronounce (`UnknownCharPolicy::SpellOut`)
+const SYMBOL_NAMES: &[(char, &str)] = &[
+    ('@', "at sign"), ('#', "number sign"), ('$', "dollar sign"), ('%', "percent sign"),
+    ('&', "ampersand"), ('*', "asterisk"), ('+', "plus sign"), ('=', "equals sign"),
+    ('<', "less than sign"), ('>', "greater than sign"), ('|', "vertical line"), ('~', "tilde"),
+    ('^', "circumflex accent"), ('_', "low line"), ('\\', "reverse solidus"), ('`', "grave accent"),
+    ('©', "copyright sign"), ('®', "registered sign"), ('™', "trade mark sign"), ('§', "section sign"),
+    ('¶', "pilcrow sign"), ('°', "degree sign"), ('€', "euro sign"), ('£', "pound sign"),
+    ('¥', "yen sign"), ('¢', "cent sign"), ('±', "plus minus sign"), ('×', "multiplication sign"),
+    ('÷', "division sign"), ('µ', "micro sign"), ('‰', "per mille sign"), ('•', "bullet"),
+    ('†', "dagger"), ('‡', "double dagger"), ('′', "prime"), ('″', "double prime"),
+    ('√', "square root"), ('∞', "infinity"), ('≈', "almost equal to"), ('≠', "not equal to"),
+    ('≤', "less than or equal to"), ('≥', "greater than or equal to"), ('←', "leftwards arrow"),
+    ('→', "rightwards arrow"), ('↑', "upwards arrow"), ('↓', "downwards arrow"), ('½', "one half"),
+    ('¼', "one quarter"), ('¾', "three quarters"), ('²', "superscript two"), ('³', "superscript three"),
+];
+
+// Units read after a number: written form, singular, plural. Every unit only
+// counts right after a number ("5 m", "5m"), so single letters like "m" and
+// "g" are safe elsewhere; "in", "s", "B" and "K" are left out because "5 in"
+// ("one in five"), "90s" and "5K" usually mean something else.
+const UNITS: &[(&str, &str, &str)] = &[
+    ("mm", "millimeter", "millimeters"),
+    ("cm", "centimeter", "centimeters"),
+    ("m", "meter", "meters"),
+    ("km", "kilometer", "kilometers"),
+    ("ft", "foot", "feet"),
+    ("yd", "yard", "yards"),
+    ("mi", "mile", "miles"),
+    ("mg", "milligram", "milligrams"),
+    ("g", "gram", "grams"),
+    ("kg", "kilogram", "kilograms"),
+    ("oz", "ounce", "ounces"),
+    ("lb", "pound", "pounds"),
+    ("lbs", "pound", "pounds"),
+    ("ml", "milliliter", "milliliters"),
+    ("mL", "milliliter", "milliliters"),
+    ("L", "liter", "liters"),
+    ("°C", "degree Celsius", "degrees Celsius"),
+    ("°F", "degree Fahrenheit", "degrees Fahrenheit"),
+    ("°", "degree", "degrees"),
+    ("%", "percent", "percent"),
+    ("km/h", "kilometer per hour", "kilometers per hour"),
+    ("kph", "kilometer per hour", "kilometers per hour"),
+    ("mph", "mile per hour", "miles per hour"),
+    ("m/s", "meter per second", "meters per second"),
+    ("KB", "kilobyte", "kilobytes"),
+    ("kB", "kilobyte", "kilobytes"),
+    ("MB", "megabyte", "megabytes"),
+    ("GB", "gigabyte", "gigabytes"),
+    ("TB", "terabyte", "terabytes"),
+    ("PB", "petabyte", "petabytes"),
+    ("Mbps", "megabit per second", "megabits per second"),
+    ("Gbps", "gigabit per second", "gigabits per second"),
+    ("ms", "millisecond", "milliseconds"),
+    ("sec", "second", "seconds"),
+    ("min", "minute", "minutes"),
+    ("h", "hour", "hours"),
+    ("hr", "hour", "hours"),
+    ("hrs", "hour", "hours"),
+    ("Hz", "hertz", "hertz"),
+    ("kHz", "kilohertz", "kilohertz"),
+    ("MHz", "megahertz", "megahertz"),
+    ("GHz", "gigahertz", "gigahertz"),
+    ("W", "watt", "watts"),
+    ("kW", "kilowatt", "kilowatts"),
+    ("kWh", "kilowatt hour", "kilowatt hours"),
+];
+
+// Singular and plural of what is counted
+type Names = (&'static str, &'static str);
+
+// Currency signs, read after their amount, with the names of the currency
+// and of its hundredth ("$5.50" → "5 dollars and 50 cents")
+const CURRENCIES: &[(char, Names, Option<Names>)] = &[
+    ('$', ("dollar", "dollars"), Some(("cent", "cents"))),
+    ('€', ("euro", "euros"), Some(("cent", "cents"))),
+    ('£', ("pound", "pounds"), Some(("penny", "pence"))),
+    ('¥', ("yen", "yen"), None),
+];
+
+// Scales after an amount: abbreviated right after the number ("$5m", "£2bn")
+// or written out ("€3 billion")
+const AMOUNT_SCALES: &[(&str, &str)] = &[
+    ("k", "thousand"),
+    ("K", "thousand"),
+    ("m", "million"),
+    ("M", "million"),
+    ("mn", "million"),
+    ("b", "billion"),
+    ("B", "billion"),
+    ("bn", "billion"),
+    ("tn", "trillion"),
+    ("thousand", "thousand"),
+    ("million", "million"),
+    ("billion", "billion"),
+    ("trillion", "trillion"),
+];
+
+// Four-digit numbers read as years ("nineteen ninety five") unless they count
+// something ("1500 meters")
+const YEARS: RangeInclusive<u64> = 1100..=2099;
+
+// Largest denominator read as a fraction ("1/100" → "one one hundredth");
+// bigger ones are read with "over" ("1/1000" → "one over one thousand")
+const MAX_FRACTION_DENOMINATOR: u64 = 100;
+
+// Words after which a number of three to six digits is a code to key in,
+// read digit by digit ("Dial 911" → "nine one one")
+const DIALING_WORDS: &[&str] = &["call", "dial", "ext", "extension", "press", "ring", "text"];
+
+/// Normalize English text for speech: numbers are spelled out as words,
+/// including ordinals ("21st" → "twenty first") and common fractions
+/// ("3/4" → "three quarters"), and so are units after them ("5km" → "five
+/// kilometers", "20°C", "75%"). Years and decades are read in pairs ("1995"
+/// → "nineteen ninety five", "the 2020s" → "the twenty twenties"), times as
+/// "ten thirty", amounts as "five dollars and fifty cents", and phone numbers
+/// digit by digit. Date-like runs such as "3/4/2024" are left as written for
+/// the phonemizer.
+///
+/// Example: `normalize("There are 1,250 items")` → `"There are one thousand two hundred fifty items"`
+pub fn normalize(text: &str) -> String {
+    Normalizer::default().normalize(text)
+}
+
+/// English pronunciation variant, for the few words normalization picks
+/// phonemes for itself (heteronyms)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Dialect {
+    #[default]
+    American,
+    British,
+}
+
+/// How web and email addresses are read (see `read_addresses`)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum UrlStyle {
+    /// Everything, scheme and path included: "aych tee tee pee ess colon
+    /// slash slash example dot com slash docs"
+    Full,
+    /// Only the host, without "www": "example dot com". Email addresses are
+    /// read in full either way.
+    #[default]
+    Domain,
+    /// "a link", and "an email address"
+    Skip,
+}
+
+/// English text normalizer with user-extensible abbreviation and acronym tables.
+///
+/// Built-in abbreviations (Dr., Mr., St., e.g., etc.) are always known; entries
+/// added with `add_abbreviation` take precedence over them.
+///
+/// All-caps words of two or more letters are acronyms: a small built-in list
+/// ("NASA", "NATO") is read as words, anything else is spelled out
+/// ("HTTP" → "aych tee tee pee"). Mixed-case words like "GitHub" are untouched,
+/// and Roman numerals after a capitalized word are numbers ("Henry VIII" →
+/// "Henry the eighth").
+#[derive(Clone, Debug, Default)]
+pub struct Normalizer {
+    abbreviations: HashMap<String, String>,
+    acronyms: HashMap<String, String>,
+}
+
+impl Normalizer {
+    /// Read `abbreviation` as `expansion` ("MEM8" → "mem eight"). Matching is
+    /// case-sensitive and on whole words; include the period for dotted forms.
+    pub fn add_abbreviation(&mut self, abbreviation: &str, expansion: &str) {
+        self.abbreviations.insert(abbreviation.to_string(), expansion.to_string());
+    }
+
+    /// Read the all-caps `acronym` as `spoken` ("SQL" → "sequel") instead of the
+    /// built-in choice between pronouncing and spelling it. Plurals ("SQLs")
+    /// follow the override.
+    pub fn add_acronym(&mut self, acronym: &str, spoken: &str) {
+        self.acronyms.insert(acronym.to_string(), spoken.to_string());
+    }
+
+    /// Normalize `text` for speech (see the module-level `normalize`)
+    pub fn normalize(&self, text: &str) -> String {
+        self.normalize_dialect(text, Dialect::American)
+    }
+
+    /// Normalize `text`, reading heteronyms with the phonemes of `dialect`
+    pub fn normalize_dialect(&self, text: &str, dialect: Dialect) -> String {
+        // Units first, before "GB" can be spelled as an acronym
+        let words = self.expand_words(&expand_units(&read_phone_numbers(text)));
+        expand_numbers(&mark_heteronyms(&words, dialect), read_number, "minus")
+    }
+
+    // Replace whole-word abbreviations and acronyms, keeping a period where one
+    // ended a sentence
+    fn expand_words(&self, text: &str) -> String {
+        let words: Vec<&str> = text.split_inclusive(char::is_whitespace).collect();
+        let mut out = String::with_capacity(text.len());
+        let shouting = is_shouting(text);
+
+        for (i, piece) in words.iter().enumerate() {
+            let word = piece.trim_end();
+            let space = &piece[word.len()..];
+            let lead = word.len() - word.trim_start_matches(['(', '"', '\'']).len();
+            let core_end = word.trim_end_matches([',', ';', ':', '!', '?', ')', '"', '\'']).len();
+            if lead >= core_end {
+                out.push_str(piece);
+                continue;
+            }
+            let core = &word[lead..core_end];
+
+            // What follows decides between readings and whether a sentence ended
+            let next = if core_end < word.len() {
+                word[core_end..].chars().next()
+            } else {
+                words[i + 1..].iter().find_map(|w| w.trim().chars().next())
+            };
+            let before_capital = core_end == word.len() && next.is_some_and(char::is_uppercase);
+            let ends_sentence = next.is_none_or(char::is_uppercase);
+            let as_title = before_capital && !follows_name(&words[..i]);
+
+            let spoken = self
+                .expand_word(core, as_title, ends_sentence)
+                .or_else(|| read_roman_numeral(core, i.checked_sub(1).map(|p| words[p])))
+                .or_else(|| if shouting { None } else { self.expand_acronym(core) });
+            let Some(spoken) = spoken else {
+                out.push_str(piece);
+                continue;
+            };
+            out.push_str(&word[..lead]);
+            out.push_str(&spoken);
+            out.push_str(&word[core_end..]);
+            out.push_str(space);
+        }
+        out
+    }
+
+    fn expand_word(&self, word: &str, as_title: bool, ends_sentence: bool) -> Option<String> {
+        if let Some(expansion) = self.abbreviations.get(word) {
+            return Some(expansion.clone());
+        }
+        if let Some(&(_, rule)) = ABBREVIATIONS.iter().find(|(abbr, _)| *abbr == word) {
+            let (spoken, keeps_period) = match rule {
+                Abbreviation::Title(title) => (title, false),
+                Abbreviation::Inline(inline) => (inline, false),
+                Abbreviation::Trailing(trailing) => (trailing, ends_sentence),
+                Abbreviation::Ambiguous { title, .. } if as_title => (title, false),
+                Abbreviation::Ambiguous { trailing, .. } => (trailing, ends_sentence),
+            };
+            let period = if keeps_period { "." } else { "" };
+            return Some(format!("{}{}", spoken, period));
+        }
+        // A custom entry at the end of a sentence: "... using MEM8."
+        let stem = word.strip_suffix('.')?;
+        let expansion = self.abbreviations.get(stem)?;
+        Some(format!("{}.", expansion))
+    }
+
+    // "HTTP" -> "aych tee tee pee", "APIs." -> "ay pee eyes.", "NASA" unchanged
+    fn expand_acronym(&self, word: &str) -> Option<String> {
+        let (word, period) = match word.strip_suffix('.') {
+            Some(stem) => (stem, "."),
+            None => (word, ""),
+        };
+        let (letters, plural) = match word.strip_suffix('s') {
+            Some(stem) => (stem, "s"),
+            None => (word, ""),
+        };
+        if letters.len() < 2 || !letters.chars().all(|c| c.is_ascii_uppercase()) {
+            return None;
+        }
+
+        let spoken = if let Some(spoken) = self.acronyms.get(letters) {
+            spoken.clone()
+        } else if PRONOUNCEABLE_ACRONYMS.contains(&letters) {
+            return None;
+        } else {
+            letters
+                .bytes()
+                .map(|b| LETTER_NAMES[(b - b'A') as usize])
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        Some(format!("{}{}{}", spoken, plural, period))
+    }
+}
+
+// A Roman numeral right after a capitalized word, which makes it part of a
+// name or title: "Henry VIII." → "the eighth.", "World War II" → "two". Only
+// numerals of I, V and X below 40 count, so "MD" and "CD" stay acronyms, and
+// a lone letter only after a numbering word ("Part V"), so "Malcolm X" and
+// "Press X" keep theirs.
+fn read_roman_numeral(word: &str, previous: Option<&str>) -> Option<String> {
+    let previous = previous?.trim_end().trim_start_matches(['(', '"', '\'']);
+    let capitalized = previous.chars().next().is_some_and(char::is_uppercase);
+    if !capitalized || previous.ends_with(|c: char| !c.is_alphanumeric()) {
+        return None;
+    }
+    let (numeral, period) = match word.strip_suffix('.') {
+        Some(stem) => (stem, "."),
+        None => (word, ""),
+    };
+    let n = (1..=39).find(|&n| roman_numeral(n) == numeral)?;
+    let spoken = if CARDINAL_NUMERAL_WORDS.contains(&previous) {
+        number_to_words(n)
+    } else if numeral.len() > 1 {
+        format!("the {}", ordinal_to_words(n))
+    } else {
+        return None;
+    };
+    Some(format!("{}{}", spoken, period))
+}
+
+// `n` below 40 in Roman numerals ("XIV")
+fn roman_numeral(n: u64) -> String {
+    const ONES: [&str; 10] = ["", "I", "II", "III", "IV", "V", "VI", "VII", "VIII", "IX"];
+    format!("{}{}", "X".repeat((n / 10) as usize), ONES[(n % 10) as usize])
+}
+
+// Give the words in `pronunciations` (lowercased word → phonemes) inline
+// phonemes, replacing a heteronym reading already chosen for one
+pub(crate) fn mark_pronunciations(text: &str, pronunciations: &HashMap<String, String>) -> String {
+    if pronunciations.is_empty() {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len());
+    for piece in text.split_inclusive(char::is_whitespace) {
+        let word = piece.trim_end();
+        let (start, end, core) = match word.find(PHONEME_OPEN).zip(word.find(PHONEME_CLOSE)) {
+            Some((open, close)) => {
+                let inner = &word[open + PHONEME_OPEN.len_utf8()..close];
+                let core = inner.split_once('|').map_or(inner, |(w, _)| w);
+                (open, close + PHONEME_CLOSE.len_utf8(), core)
+            }
+            None => {
+                let start = word.find(|c: char| c.is_alphanumeric()).unwrap_or(0);
+                let end = word.rfind(|c: char| c.is_alphanumeric()).map_or(0, |e| e + 1);
+                (start, end.max(start), &word[start..end.max(start)])
+            }
+        };
+        match pronunciations.get(&core.to_lowercase()) {
+            Some(phonemes) if !core.is_empty() => {
+                out.push_str(&word[..start]);
+                out.push(PHONEME_OPEN);
+                out.push_str(core);
+                out.push('|');
+                out.push_str(phonemes);
+                out.push(PHONEME_CLOSE);
+                out.push_str(&piece[end..]);
+            }
+            _ => out.push_str(piece),
+        }
+    }
+    out
+}
+
+// Replace heteronyms whose reading is clear from context with inline phonemes
+fn mark_heteronyms(text: &str, dialect: Dialect) -> String {
+    let pieces: Vec<&str> = text.split_inclusive(char::is_whitespace).collect();
+    // Lowercased word cores, with None where punctuation separates words, so
+    // context never reaches across a comma or sentence end
+    let mut cores: Vec<Option<String>> = Vec::new();
+    let mut index = Vec::with_capacity(pieces.len());
+    for piece in &pieces {
+        let word = piece.trim();
+        let core = word.trim_matches(|c: char| !c.is_alphanumeric() && c != '\'');
+        index.push(cores.len());
+        cores.push(Some(core.to_lowercase()));
+        if word.ends_with(['.', '!', '?', ',', ';', ':']) {
+            cores.push(None);
+        }
+    }
+
+    let mut out = String::with_capacity(text.len());
+    for (i, piece) in pieces.iter().enumerate() {
+        let word = piece.trim_end();
+        let start = word.find(|c: char| c.is_alphanumeric()).unwrap_or(0);
+        let end = word.rfind(|c: char| c.is_alphanumeric()).map_or(0, |e| e + 1);
+        let phonemes = (start < end)
+            .then(|| heteronym_reading(&cores, index[i], dialect))
+            .flatten();
+        match phonemes {
+            Some(phonemes) => {
+                out.push_str(&word[..start]);
+                out.push(PHONEME_OPEN);
+                out.push_str(&word[start..end]);
+                out.push('|');
+                out.push_str(&phonemes);
+                out.push(PHONEME_CLOSE);
+                out.push_str(&piece[end..]);
+            }
+            None => out.push_str(piece),
+        }
+    }
+    out
+}
+
+// Phonemes for the heteronym at `cores[at]`, or None if it isn't one or the
+// context doesn't settle it (espeak's default reading is kept then)
+fn heteronym_reading(cores: &[Option<String>], at: usize, dialect: Dialect) -> Option<String> {
+    let word = cores[at].as_deref()?;
+    let (entry, plural) = HETERONYMS.iter().find_map(|h| {
+        if h.word == word {
+            Some((h, false))
+        } else if h.plural && word.strip_suffix('s') == Some(h.word) {
+            Some((h, true))
+        } else {
+            None
+        }
+    })?;
+
+    let get = |offset: isize| -> Option<&str> {
+        let mut i = at as isize;
+        for _ in 0..offset.unsigned_abs() {
+            i += offset.signum();
+            if i < 0 || cores.get(i as usize)?.is_none() {
+                return None;
+            }
+        }
+        cores.get(i as usize)?.as_deref()
+    };
+    let (prev, prev2, next, next2) = (get(-1), get(-2), get(1), get(2));
+    let prev_in =
+        |set: &[&str]| prev.is_some_and(|w| set.contains(&w) || (set == MODALS && w.ends_with("'ll")));
+    let next_in = |set: &[&str]| next.is_some_and(|w| set.contains(&w));
+    // Words of the same sentence, for tense markers like "yesterday"
+    let sentence = || {
+        let begin = cores[..at].iter().rposition(|c| c.is_none()).map_or(0, |p| p + 1);
+        let end = cores[at..]
+            .iter()
+            .position(|c| c.is_none())
+            .map_or(cores.len(), |p| at + p);
+        cores[begin..end]
+            .iter()
+            .flatten()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+    };
+
+    let choice = match entry.word {
+        "read" => {
+            if prev_in(&[
+                "have", "has", "had", "was", "were", "is", "are", "be", "been", "being", "already", "he",
+                "she", "it",
+            ]) || prev.is_some_and(|w| w.ends_with("'ve") || w.ends_with("'d"))
+            {
+                Some(1)
+            } else if prev_in(MODALS) {
+                Some(0)
+            } else if sentence()
+                .iter()
+                .any(|w| ["yesterday", "ago", "last", "earlier"].contains(w))
+            {
+                Some(1)
+            } else {
+                None
+            }
+        }
+        "lead" => {
+            let metal = [
+                "poisoning", "pipe", "pipes", "paint", "pencil", "pencils", "weight", "weights", "shot",
+                "solder", "bullet", "bullets", "balloon", "oxide",
+            ];
+            if next_in(&metal) || (prev_in(&["of", "with"]) && next.is_none()) {
+                Some(1)
+            } else if prev_in(MODALS)
+                || next_in(&[
+                    "to", "the", "us", "you", "them", "a", "an", "our", "my", "his", "her", "their", "by",
+                    "with",
+                ])
+            {
+                Some(0)
+            } else {
+                None
+            }
+        }
+        "live" => {
+            let adjective_next = [
+                "music", "show", "shows", "stream", "streams", "streaming", "broadcast", "performance",
+                "concert", "audience", "recording", "album", "event", "events", "wire", "bait", "feed",
+                "coverage", "tv", "television", "band", "demo", "chat", "data", "update", "updates",
+                "version", "preview", "site",
+            ];
+            if next_in(&adjective_next)
+                || prev_in(&["go", "goes", "went", "gone", "going"])
+                || (prev_in(&["is", "are", "was", "were", "we're", "you're", "we’re"])
+                    && (next.is_none() || next_in(&["now", "on", "at", "from"])))
+            {
+                Some(1)
+            } else if prev_in(MODALS) || prev_in(SUBJECTS) || prev_in(&["people", "who"]) {
+                Some(0)
+            } else {
+                None
+            }
+        }
+        "bass" => {
+            let music_next = [
+                "guitar", "guitars", "player", "players", "line", "lines", "drum", "clef", "note", "notes",
+                "boost", "voice", "singer", "amp", "section", "solo",
+            ];
+            let fish = [
+                "sea", "largemouth", "smallmouth", "striped", "caught", "catch", "catching", "fished",
+            ];
+            if next_in(&music_next) || prev_in(&["electric", "double", "slap", "sub"]) {
+                Some(0)
+            } else if next_in(&["fishing", "boat", "lake", "pond", "lure", "lures"])
+                || prev_in(&fish)
+                || prev2.is_some_and(|w| fish.contains(&w))
+            {
+                Some(1)
+            } else {
+                None
+            }
+        }
+        "tear" => {
+            let rip_next = [
+                "apart", "off", "down", "open", "through", "into", "out", "away", "it", "them", "the",
+                "this", "that", "up",
+            ];
+            if next_in(&rip_next)
+                || (prev == Some("and") && prev2 == Some("wear"))
+                || (next == Some("in") && prev_in(&["a", "the"]))
+                || prev_in(MODALS)
+            {
+                Some(1)
+            } else if prev_in(&[
+                "a", "single", "the", "of", "with", "in", "into", "no", "my", "her", "his", "your", "their",
+            ]) {
+                Some(0)
+            } else {
+                None
+            }
+        }
+        "wind" => {
+            let air_prev = [
+                "the", "a", "north", "south", "east", "west", "cold", "strong", "gentle", "high", "solar",
+                "of", "against", "in",
+            ];
+            if prev_in(&air_prev) {
+                Some(0)
+            } else if prev_in(MODALS)
+                || next_in(&[
+                    "up", "down", "it", "your", "my", "his", "her", "their", "our", "around", "back",
+                    "through", "along", "its",
+                ])
+            {
+                Some(1)
+            } else {
+                None
+            }
+        }
+        "bow" => {
+            let ribbon_next = [
+                "tie", "ties", "string", "strings", "ribbon", "hunter", "hunters", "hunting", "legged",
+            ];
+            if next_in(&ribbon_next)
+                || (next == Some("and") && next2.is_some_and(|w| w.starts_with("arrow")))
+                || prev_in(&["violin", "cello", "hair", "ribbon", "pink", "red", "compound"])
+            {
+                Some(1)
+            } else if next_in(&["down", "before", "to", "out", "of"])
+                || prev_in(&["take", "took", "taking", "deep", "low"])
+                || (prev == Some("a") && prev2.is_some_and(|w| ["take", "took", "taking"].contains(&w)))
+                || prev_in(MODALS)
+            {
+                Some(0)
+            } else {
+                None
+            }
+        }
+        "close" => {
+            let adjective_prev = [
+                "so", "very", "too", "quite", "pretty", "getting", "came", "come", "comes", "stay", "stayed",
+                "get", "got", "is", "are", "was", "were", "be", "up",
+            ];
+            let adjective_next = [
+                "to", "by", "call", "friend", "friends", "together", "enough", "relative", "relatives",
+                "attention", "contact", "family", "second",
+            ];
+            if prev_in(&adjective_prev) {
+                Some(1)
+            } else if prev_in(MODALS) {
+                Some(0)
+            } else if next_in(&adjective_next) {
+                Some(1)
+            } else if next_in(&[
+                "the", "it", "your", "my", "this", "that", "them", "down", "off", "out", "all", "our", "his",
+                "her", "their",
+            ]) {
+                Some(0)
+            } else {
+                None
+            }
+        }
+        "record" => {
+            let noun_prev = [
+                "new", "world", "track", "criminal", "medical", "off", "on", "for", "broke", "set", "sets",
+                "vinyl", "public", "no",
+            ];
+            if prev_in(DETERMINERS) || prev_in(&noun_prev) {
+                Some(0)
+            } else if prev_in(MODALS) || prev_in(SUBJECTS) || (plural && prev_in(&["he", "she", "it"])) {
+                Some(1)
+            } else {
+                None
+            }
+        }
+        "produce" => {
+            if prev_in(&[
+                "the", "fresh", "local", "organic", "farm", "of", "seasonal", "our",
+            ]) {
+                Some(0)
+            } else if prev_in(MODALS)
+                || prev_in(SUBJECTS)
+                || next_in(&[
+                    "a", "an", "more", "results", "energy", "electricity", "it", "them",
+                ])
+            {
+                Some(1)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }?;
+
+    let readings = match dialect {
+        Dialect::American => entry.american,
+        Dialect::British => entry.british,
+    };
+    let suffix = if plural { "z" } else { "" };
+    Some(format!("{}{}", readings[choice], suffix))
+}
+
+// A stretch of normalized text: words for espeak, or phonemes picked here
+pub(crate) enum Piece<'a> {
+    Text(&'a str),
+    Phonemes(&'a str),
+}
+
+// Split normalized text at inline phonemes ("⟦read|ɹˈɛd⟧")
+pub(crate) fn split_inline_phonemes(text: &str) -> Vec<Piece<'_>> {
+    let mut pieces = Vec::new();
+    let mut rest = text;
+    while let Some(open) = rest.find(PHONEME_OPEN) {
+        let Some(close) = rest[open..].find(PHONEME_CLOSE).map(|c| open + c) else {
+            break;
+        };
+        if open > 0 {
+            pieces.push(Piece::Text(&rest[..open]));
+        }
+        let inner = &rest[open + PHONEME_OPEN.len_utf8()..close];
+        pieces.push(Piece::Phonemes(inner.split_once('|').map_or(inner, |(_, p)| p)));
+        rest = &rest[close + PHONEME_CLOSE.len_utf8()..];
+    }
+    if !rest.is_empty() {
+        pieces.push(Piece::Text(rest));
+    }
+    pieces
+}
+
+// Normalized text as a reader would see it: inline phonemes become their word
+pub(crate) fn strip_inline_phonemes(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(open) = rest.find(PHONEME_OPEN) {
+        let Some(close) = rest[open..].find(PHONEME_CLOSE).map(|c| open + c) else {
+            break;
+        };
+        out.push_str(&rest[..open]);
+        let inner = &rest[open + PHONEME_OPEN.len_utf8()..close];
+        out.push_str(inner.split_once('|').map_or(inner, |(w, _)| w));
+        rest = &rest[close + PHONEME_CLOSE.len_utf8()..];
+    }
+    out.push_str(rest);
+    out
+}
+
+// Whether every word with letters is in capitals ("STOP THAT RIGHT NOW")
+fn is_shouting(text: &str) -> bool {
+    let words: Vec<&str> = text
+        .split_whitespace()
+        .filter(|w| w.chars().any(char::is_alphabetic))
+        .collect();
+    words.len() >= SHOUTING_MIN_WORDS
+        && words
+            .iter()
+            .all(|w| w.chars().filter(|c| c.is_alphabetic()).all(char::is_uppercase))
+}
+
+// Whether the last of `previous` words is a capitalized word or number in the
+// middle of a sentence ("Main", "221"), which makes "St." a street, not a saint
+fn follows_name(previous: &[&str]) -> bool {
+    let Some((last, before)) = previous.split_last() else {
+        return false;
+    };
+    let sentence_start = before.last().is_none_or(|w| w.trim_end().ends_with(['.', '!', '?']));
+    let name_like = last.trim().chars().next().is_some_and(|c| c.is_uppercase() || c.is_ascii_digit());
+    name_like && !sentence_start
+}
+
+/// True when the period ending `text` belongs to a built-in abbreviation that
+/// doesn't end a sentence ("Dr.", "e.g.") or to initials ("J. R. R."), so
+/// sentence splitting should go on.
+pub(crate) fn ends_with_abbreviation(text: &str) -> bool {
+    let Some(word) = text.split_whitespace().last() else {
+        return false;
+    };
+    let word = word.trim_start_matches(['(', '"', '\'']);
+    is_initials(word)
+        || ABBREVIATIONS
+            .iter()
+            .any(|(abbr, rule)| *abbr == word && !matches!(rule, Abbreviation::Trailing(_)))
+}
+
+// Initials such as "J." or "J.R.R." (but not the pronoun "I.")
+fn is_initials(word: &str) -> bool {
+    let Some(letters) = word.strip_suffix('.') else {
+        return false;
+    };
+    word != "I."
+        && letters.split('.').all(|letter| {
+            let mut chars = letter.chars();
+            chars.next().is_some_and(|c| c.is_uppercase()) && chars.next().is_none()
+        })
+}
+
+/// Read web and email addresses in `text` the way `style` asks:
+/// "mail help@example.com" → "mail help at example dot com". Addresses are
+/// whole words; punctuation after one, like the period ending a sentence,
+/// stays outside it.
+pub fn read_addresses(text: &str, style: UrlStyle) -> String {
+    let mut out = String::with_capacity(text.len());
+    for piece in text.split_inclusive(char::is_whitespace) {
+        let word = piece.trim_end();
+        let lead = word.len() - word.trim_start_matches(['(', '<', '[', '"', '\'']).len();
+        let mut address = &word[lead..];
+        while let Some(last) = address.chars().last().filter(|&c| ".,;:!?)]>\"'".contains(c)) {
+            // "(see wiki/Rust_(language))" keeps the `)` opened inside
+            if last == ')' && address.matches('(').count() >= address.matches(')').count() {
+                break;
+            }
+            address = &address[..address.len() - 1];
+        }
+        match read_email(address, style).or_else(|| read_url(address, style)) {
+            Some(spoken) => {
+                out.push_str(&word[..lead]);
+                out.push_str(&spoken);
+                out.push_str(&piece[lead + address.len()..]);
+            }
+            None => out.push_str(piece),
+        }
+    }
+    out
+}
+
+fn read_email(word: &str, style: UrlStyle) -> Option<String> {
+    let (user, domain) = word.strip_prefix("mailto:").unwrap_or(word).split_once('@')?;
+    let valid_user = user.chars().all(|c| c.is_ascii_alphanumeric() || "._%+-".contains(c));
+    let host = host_labels(domain).filter(|labels| labels.len() >= 2)?;
+    if user.is_empty() || !valid_user {
+        return None;
+    }
+    if style == UrlStyle::Skip {
+        return Some("an email address".to_string());
+    }
+    Some(format!("{} at {}", read_path(user), host.join(" dot ")))
+}
+
+fn read_url(word: &str, style: UrlStyle) -> Option<String> {
+    let (scheme, rest) = match word.split_once("://") {
+        Some((scheme, rest)) => {
+            let mut chars = scheme.chars();
+            let valid = chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+                && chars.all(|c| c.is_ascii_alphanumeric() || "+.-".contains(c));
+            (Some(scheme).filter(|_| valid)?, rest)
+        }
+        None => ("", word),
+    };
+    let (authority, path) = rest.split_at(rest.find(['/', '?', '#']).unwrap_or(rest.len()));
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) if port.parse::<u16>().is_ok() => (host, port),
+        _ => (authority, ""),
+    };
+    let labels = host_labels(host)?;
+    // Without a scheme, only "www." or a familiar top-level domain makes an
+    // address, so "main.rs" and "ASP.NET" are left alone
+    let www = labels[0].eq_ignore_ascii_case("www");
+    let tld = host.rsplit('.').next().unwrap_or("");
+    if scheme.is_empty() && (labels.len() < 2 || !(www || BARE_TLDS.contains(&tld))) {
+        return None;
+    }
+
+    match style {
+        UrlStyle::Skip => Some("a link".to_string()),
+        UrlStyle::Domain if www && labels.len() > 2 => Some(labels[1..].join(" dot ")),
+        UrlStyle::Domain => Some(labels.join(" dot ")),
+        UrlStyle::Full => {
+            let mut words = Vec::new();
+            if !scheme.is_empty() {
+                words.push(format!("{} colon slash slash", spell(scheme)));
+            }
+            let host: Vec<&str> = labels
+                .iter()
+                .map(|label| if label.eq_ignore_ascii_case("www") { WWW } else { label })
+                .collect();
+            words.push(host.join(" dot "));
+            if !port.is_empty() {
+                words.push(format!("colon {}", port));
+            }
+            words.push(read_path(path.trim_end_matches('/')));
+            Some(words.join(" ").trim_end().to_string())
+        }
+    }
+}
+
+// How "www" is read
+const WWW: &str = "double you double you double you";
+
+// Top-level domains that mark a word without a scheme ("example.com") as an
+// address; file extensions that are also domains (.rs, .sh, .md) are left out
+const BARE_TLDS: &[&str] = &[
+    "com", "org", "net", "edu", "gov", "io", "dev", "ai", "app", "co", "uk", "us", "ca", "au",
+    "de", "fr", "es", "nl", "eu", "info", "biz", "tv", "xyz",
+];
+
+// The dot-separated labels of a host name, with "-" read as "dash", if it is one
+fn host_labels(host: &str) -> Option<Vec<String>> {
+    let labels: Vec<&str> = host.split('.').collect();
+    let valid = |label: &&str| {
+        !label.is_empty() && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    };
+    if !labels.iter().all(valid) || labels.last()?.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    Some(labels.iter().map(|label| label.split('-').collect::<Vec<_>>().join(" dash ")).collect())
+}
+
+// A path, query or user name as words and spoken separators:
+// "/docs?x=1" → "slash docs question mark x equals 1"
+fn read_path(path: &str) -> String {
+    let mut words: Vec<String> = Vec::new();
+    let mut word = String::new();
+    for c in path.chars() {
+        if c.is_alphanumeric() {
+            word.push(c);
+            continue;
+        }
+        if !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+        let separator = match c {
+            '/' => "slash",
+            '.' => "dot",
+            '-' => "dash",
+            '_' => "underscore",
+            '?' => "question mark",
+            '=' => "equals",
+            '&' => "and",
+            '#' => "hash",
+            '%' => "percent",
+            '+' => "plus",
+            '~' => "tilde",
+            ':' => "colon",
+            '@' => "at",
+            _ => continue,
+        };
+        words.push(separator.to_string());
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words.join(" ")
+}
+
+// "https" → "aych tee tee pee ess"
+fn spell(word: &str) -> String {
+    let letters = word.bytes().filter(u8::is_ascii_alphabetic).map(|b| b.to_ascii_lowercase());
+    letters.map(|b| LETTER_NAMES[(b - b'a') as usize]).collect::<Vec<_>>().join(" ")
+}
+
+/// Spoken name of a symbol such as '©' ("copyright sign"), if known
+pub fn symbol_name(c: char) -> Option<&'static str> {
+    SYMBOL_NAMES.iter().find(|(s, _)| *s == c).map(|(_, name)| *name)
+}
+
+/// Spell out a non-negative integer in American English ("forty two",
+/// "one hundred one", "three trillion").
+pub fn number_to_words(n: u64) -> String {
+    if n == 0 {
+        return ONES[0].to_string();
+    }
+
+    let mut groups = Vec::new();
+    let mut rest = n;
+    let mut scale = 0;
+    while rest > 0 {
+        let group = (rest % 1000) as usize;
+        if group > 0 {
+            let words = below_thousand(group);
+            groups.push(if scale > 0 {
+                format!("{} {}", words, SCALES[scale])
+            } else {
+                words
+            });
+        }
+        rest /= 1000;
+        scale += 1;
+    }
+    groups.reverse();
+    groups.join(" ")
+}
+
+/// Spell out an ordinal ("first", "twenty first", "one hundredth").
+pub fn ordinal_to_words(n: u64) -> String {
+    let cardinal = number_to_words(n);
+    let (head, last) = match cardinal.rsplit_once(' ') {
+        Some((head, last)) => (format!("{} ", head), last.to_string()),
+        None => (String::new(), cardinal),
+    };
+    let last = match last.as_str() {
+        "one" => "first".to_string(),
+        "two" => "second".to_string(),
+        "three" => "third".to_string(),
+        "five" => "fifth".to_string(),
+        "eight" => "eighth".to_string(),
+        "nine" => "ninth".to_string(),
+        "twelve" => "twelfth".to_string(),
+        word if word.ends_with('y') => format!("{}ieth", &word[..word.len() - 1]),
+        word => format!("{}th", word),
+    };
+    head + &last
+}
+
+// "one half", "three quarters", "five eighths"
+fn fraction_to_words(numerator: u64, denominator: u64) -> String {
+    let plural = numerator != 1;
+    let unit = match (denominator, plural) {
+        (2, false) => "half".to_string(),
+        (2, true) => "halves".to_string(),
+        (4, false) => "quarter".to_string(),
+        (4, true) => "quarters".to_string(),
+        (d, false) => ordinal_to_words(d),
+        (d, true) => format!("{}s", ordinal_to_words(d)),
+    };
+    format!("{} {}", number_to_words(numerator), unit)
+}
+
+/// Normalize Spanish text for speech: numbers are spelled out, with "." as
+/// the thousands separator and "," as the decimal comma.
+///
+/// Example: `normalize_es("En 1990")` → `"En mil novecientos noventa"`
+pub fn normalize_es(text: &str) -> String {
+    expand_numbers(text, read_number_es, "menos")
+}
+
+/// Spell out a non-negative integer in Spanish ("mil novecientos noventa",
+/// "veintiún millones"), using the long scale (billón = 10^12).
+pub fn number_to_words_es(n: u64) -> String {
+    if n == 0 {
+        return ONES_ES[0].to_string();
+    }
+
+    // Groups of six digits: units, millones, billones, trillones
+    let mut parts = Vec::new();
+    let mut rest = n;
+    let mut scale = 0;
+    while rest > 0 {
+        let group = (rest % 1_000_000) as usize;
+        if group > 0 {
+            parts.push(match scale {
+                0 => below_million_es(group, false),
+                _ if group == 1 => format!("un {}", SCALES_ES[scale - 1].0),
+                _ => format!("{} {}", below_million_es(group, true), SCALES_ES[scale - 1].1),
+            });
+        }
+        rest /= 1_000_000;
+        scale += 1;
+    }
+    parts.reverse();
+    parts.join(" ")
+}
+
+// 1..999999; `before_noun` shortens a final "uno" ("veintiún millones")
+fn below_million_es(n: usize, before_noun: bool) -> String {
+    let thousands = n / 1000;
+    let rest = n % 1000;
+    let mut parts = Vec::new();
+    match thousands {
+        0 => {}
+        1 => parts.push("mil".to_string()),
+        t => parts.push(format!("{} mil", below_thousand_es(t, true))),
+    }
+    if rest > 0 {
+        parts.push(below_thousand_es(rest, before_noun));
+    }
+    parts.join(" ")
+}
+
+fn below_thousand_es(n: usize, before_noun: bool) -> String {
+    if n == 100 {
+        return "cien".to_string();
+    }
+    let mut parts = Vec::new();
+    if n >= 100 {
+        parts.push(HUNDREDS_ES[n / 100].to_string());
+    }
+    let rest = n % 100;
+    if rest >= 30 {
+        let ones = rest % 10;
+        parts.push(if ones > 0 {
+            format!("{} y {}", TENS_ES[rest / 10], ONES_ES[ones])
+        } else {
+            TENS_ES[rest / 10].to_string()
+        });
+    } else if rest > 0 || parts.is_empty() {
+        parts.push(ONES_ES[rest].to_string());
+    }
+
+    let words = parts.join(" ");
+    match words.strip_suffix("uno") {
+        Some(stem) if before_noun => {
+            // "veintiuno" -> "veintiún", but "uno" -> "un"
+            if stem.ends_with("veinti") { format!("{}ún", stem) } else { format!("{}un", stem) }
+        }
+        _ => words,
+    }
+}
+
+fn below_thousand(n: usize) -> String {
+    let mut parts = Vec::new();
+    if n >= 100 {
+        parts.push(format!("{} hundred", ONES[n / 100]));
+    }
+    let rest = n % 100;
+    if rest >= 20 {
+        parts.push(TENS[rest / 10].to_string());
+        if !rest.is_multiple_of(10) {
+            parts.push(ONES[rest % 10].to_string());
+        }
+    } else if rest > 0 || parts.is_empty() {
+        parts.push(ONES[rest].to_string());
+    }
+    parts.join(" ")
+}
+
+// Read each digit on its own ("007" -> "zero zero seven")
+fn digits_to_words(digits: &str) -> String {
+    digits
+        .chars()
+        .filter_map(|c| c.to_digit(10))
+        .map(|d| ONES[d as usize])
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn digits_to_words_es(digits: &str) -> String {
+    digits
+        .chars()
+        .filter_map(|c| c.to_digit(10))
+        .map(|d| ONES_ES[d as usize])
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// Read phone numbers digit by digit, a comma after each group so it gets a
+// short pause: "+1 (555) 867-5309" → "plus one, five five five, eight six
+// seven, five three zero nine". Short codes after a dialing word ("Dial 911")
+// are read digit by digit too.
+pub(crate) fn read_phone_numbers(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let prev = i.checked_sub(1).map(|p| chars[p]);
+        if !prev.is_some_and(|p| p.is_alphanumeric() || matches!(p, '.' | '-' | '+')) {
+            let code = chars[i..].iter().take_while(|c| c.is_ascii_digit()).count();
+            // A whole number: not "555-1234" or "1,000"
+            let next_digit = chars.get(i + code + 1).is_some_and(char::is_ascii_digit);
+            let whole = chars
+                .get(i + code)
+                .is_none_or(|c| c.is_whitespace() || !(c.is_alphanumeric() || next_digit));
+            if (3..=6).contains(&code) && whole && after_dialing_word(&chars[..i]) {
+                let digits: String = chars[i..i + code].iter().collect();
+                out.push_str(&digits_to_words(&digits));
+                i += code;
+                continue;
+            }
+            if let Some((len, groups)) = phone_number(&chars[i..]) {
+                let groups: Vec<String> = groups.iter().map(|group| digits_to_words(group)).collect();
+                if chars[i] == '+' {
+                    out.push_str("plus ");
+                }
+                out.push_str(&groups.join(", "));
+                i += len;
+                continue;
+            }
+        }
+        // Never start inside a number
+        let run = chars[i..].iter().take_while(|c| c.is_ascii_digit()).count().max(1);
+        out.extend(&chars[i..i + run]);
+        i += run;
+    }
+    out
+}
+
+// Whether `before` ends in one of `DIALING_WORDS` and a space ("Dial ", "ext. ")
+fn after_dialing_word(before: &[char]) -> bool {
+    word_before(before).is_some_and(|word| DIALING_WORDS.contains(&word.as_str()))
+}
+
+// The word `before` ends in, then a space, lowercased and without a trailing
+// '.' or ':'
+fn word_before(before: &[char]) -> Option<String> {
+    let before = before.strip_suffix(&[' '])?;
+    let start = before.iter().rposition(|c| c.is_whitespace()).map_or(0, |p| p + 1);
+    let word: String = before[start..].iter().collect();
+    Some(word.strip_suffix(['.', ':']).unwrap_or(&word).to_lowercase())
+}
+
+// Length and digit groups of the phone number at the start of `chars`.
+//
+// Only unmistakable shapes count: a leading "+" ("+44 20 7946 0958"), an area
+// code in parentheses ("(555) 867-5309"), ten digits or more in groups of up
+// to three joined by one kind of "-" or ".", ending in four ("1-800-555-0199"),
+// or a local number: three digits, the first 2-9, a "-" and four ("555-1234").
+// Dates, ranges, versions, IP addresses and card numbers all miss one of those.
+fn phone_number(chars: &[char]) -> Option<(usize, Vec<String>)> {
+    let plus = chars.first() == Some(&'+');
+    let max_group = if plus { 8 } else { 4 };
+    let mut groups: Vec<String> = Vec::new();
+    let mut separators = Vec::new();
+    let mut parens = false;
+    let mut i = usize::from(plus);
+    let mut end = 0;
+    loop {
+        let open = chars.get(i) == Some(&'(');
+        let start = i + usize::from(open);
+        let len = chars[start.min(chars.len())..].iter().take_while(|c| c.is_ascii_digit()).count();
+        // Only the area code, first or after the country code, has parentheses
+        let closed = chars.get(start + len) == Some(&')') && groups.len() <= 1;
+        if len == 0 || len > max_group || (open && !closed) {
+            break;
+        }
+        groups.push(chars[start..start + len].iter().collect());
+        parens |= open;
+        end = start + len + usize::from(open);
+        i = end;
+        match chars.get(i) {
+            Some(&c @ (' ' | '-' | '.')) => {
+                separators.push(c);
+                i += 1;
+            }
+            // "(555)867-5309"
+            Some(c) if open && c.is_ascii_digit() => {}
+            _ => break,
+        }
+    }
+    // What was taken for a separator after the last group is text again
+    separators.truncate(groups.len().saturating_sub(1));
+
+    let digits: usize = groups.iter().map(String::len).sum();
+    let follows = chars.get(end).is_some_and(|c| c.is_alphanumeric() || matches!(c, '-' | '(' | '+'));
+    if groups.len() < 2 || !(7..=15).contains(&digits) || follows {
+        return None;
+    }
+    let spaced = separators.contains(&' ');
+    let shaped = if plus {
+        groups[0].len() <= 3
+    } else if parens {
+        digits >= 10
+    } else if groups.len() == 2 {
+        let local = groups[0].len() == 3 && groups[1].len() == 4 && separators == ['-'];
+        local && !groups[0].starts_with(['0', '1'])
+    } else {
+        let (last, rest) = groups.split_last()?;
+        groups.len() >= 3
+            && digits >= 10
+            && !spaced
+            && separators.iter().all(|&c| c == separators[0])
+            && last.len() == 4
+            && rest.iter().all(|group| group.len() <= 3)
+    };
+    shaped.then_some((end, groups))
+}
+
+// Spell out the unit after each number ("5km" → "5 kilometers", "1 °C" →
+// "1 degree Celsius"), singular only after exactly 1 or between an article
+// and its noun ("a 3 h trip" → "a 3 hour trip"); the numbers are left for
+// `expand_numbers`
+pub(crate) fn expand_units(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let prev = i.checked_sub(1).map(|p| chars[p]);
+        if !prev.is_some_and(char::is_alphanumeric) {
+            if let Some((len, amount)) = read_amount(&chars[i..]) {
+                out.push_str(&amount);
+                i += len;
+                continue;
+            }
+        }
+        if !chars[i].is_ascii_digit() {
+            out.push(chars[i]);
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        i += number_len(&chars[i..]);
+        let number: String = chars[start..i].iter().collect();
+        out.push_str(&number);
+        let prev = start.checked_sub(1).map(|p| chars[p]);
+        if prev.is_some_and(|p| p.is_alphanumeric() || CURRENCIES.iter().any(|(sign, ..)| p == *sign)) {
+            continue;
+        }
+
+        let gap = usize::from(chars.get(i) == Some(&' '));
+        let rest = &chars[i + gap..];
+        let unit = UNITS
+            .iter()
+            .filter(|(written, ..)| {
+                let written: Vec<char> = written.chars().collect();
+                let ends = !rest.get(written.len()).is_some_and(|c| c.is_alphanumeric());
+                rest.starts_with(&written) && ends
+            })
+            .max_by_key(|(written, ..)| written.len());
+        if let Some(&(written, singular, plural)) = unit {
+            let end = i + gap + written.chars().count();
+            let article = word_before(&chars[..start]).is_some_and(|w| matches!(&*w, "a" | "an" | "the"));
+            let attributive = article && before_noun(&chars[end..]);
+            out.push(' ');
+            out.push_str(if number == "1" || attributive { singular } else { plural });
+            i = end;
+        }
+    }
+    out
+}
+
+// Whether `rest` goes on with a space and a lowercase word a unit could
+// describe: "trip" in "a 3 h trip", but not "of" in "the 5 km of road"
+fn before_noun(rest: &[char]) -> bool {
+    let Some(rest) = rest.strip_prefix(&[' ']) else {
+        return false;
+    };
+    let word: String = rest.iter().take_while(|c| c.is_alphabetic()).collect();
+    let function_word = ["of", "and", "or", "to", "in", "on", "at", "for", "from", "by", "ago"];
+    word.starts_with(char::is_lowercase) && !function_word.contains(&word.as_str())
+}
+
+// Length of the number at the start of `chars`, decimals and thousands
+// separators included
+fn number_len(chars: &[char]) -> usize {
+    let mut len = 0;
+    while len < chars.len()
+        && (chars[len].is_ascii_digit()
+            || (matches!(chars[len], '.' | ',') && chars.get(len + 1).is_some_and(char::is_ascii_digit)))
+    {
+        len += 1;
+    }
+    len
+}
+
+// The amount at the start of `chars` (a currency sign, then a number) with
+// its length, the sign read after the number: "$5" → "5 dollars", "$5.50" →
+// "5 dollars and 50 cents", "£2.5m" → "2.5 million pounds". The numbers are
+// left for `expand_numbers`.
+fn read_amount(chars: &[char]) -> Option<(usize, String)> {
+    let &(_, names, hundredth) = CURRENCIES.iter().find(|(sign, ..)| chars.first() == Some(sign))?;
+    let len = number_len(&chars[1..]);
+    if len == 0 {
+        return None;
+    }
+    let number: String = chars[1..1 + len].iter().collect();
+    let rest = &chars[1 + len..];
+    let scale = AMOUNT_SCALES
+        .iter()
+        .filter_map(|&(written, word)| {
+            // Written-out scales come after a space
+            let gap = usize::from(written.len() > 2 && rest.first() == Some(&' '));
+            let written: Vec<char> = written.chars().collect();
+            let ends = !rest.get(gap + written.len()).is_some_and(|c| c.is_alphanumeric());
+            (rest[gap..].starts_with(&written) && ends).then_some((gap + written.len(), word))
+        })
+        .max_by_key(|&(len, _)| len);
+    if let Some((scale_len, word)) = scale {
+        return Some((1 + len + scale_len, format!("{} {} {}", number, word, names.1)));
+    }
+
+    let counted = |n: &str, (one, many): Names| format!("{} {}", n, if n == "1" { one } else { many });
+    let (whole, cents) = match (number.split_once('.'), hundredth) {
+        (Some((whole, cents)), Some(hundredth)) if cents.len() == 2 => {
+            (whole, Some((cents.trim_start_matches('0'), hundredth)))
+        }
+        _ => (number.as_str(), None),
+    };
+    let spoken = match cents {
+        None | Some(("", _)) => counted(whole, names),
+        Some((cents, hundredth)) if whole == "0" => counted(cents, hundredth),
+        Some((cents, hundredth)) => {
+            format!("{} and {}", counted(whole, names), counted(cents, hundredth))
+        }
+    };
+    Some((1 + len, spoken))
+}
+
+// Whether the word after a number (past one space) names what it counts:
+// a unit, a currency or a scale, as `expand_units` writes them
+fn names_a_unit(rest: &[char]) -> bool {
+    let rest = rest.strip_prefix(&[' ']).unwrap_or(rest);
+    let word: String = rest.iter().take_while(|c| c.is_alphabetic()).collect();
+    let first = |name: &str| name.split(' ').next() == Some(word.as_str());
+    !word.is_empty()
+        && (UNITS.iter().any(|&(_, one, many)| first(one) || first(many))
+            || CURRENCIES.iter().any(|&(_, (one, many), _)| word == one || word == many)
+            || AMOUNT_SCALES.iter().any(|&(_, scale)| word == scale))
+}
+
+// "1995" → "nineteen ninety five", "1905" → "nineteen oh five", "1900" →
+// "nineteen hundred", "2007" → "two thousand seven", "2024" → "twenty twenty four"
+fn year_to_words(year: u64) -> String {
+    let (century, rest) = (number_to_words(year / 100), year % 100);
+    match rest {
+        _ if (2000..2010).contains(&year) => number_to_words(year),
+        0 => format!("{} hundred", century),
+        1..=9 => format!("{} oh {}", century, number_to_words(rest)),
+        _ => format!("{} {}", century, number_to_words(rest)),
+    }
+}
+
+// "nineteen ninety" → "nineteen nineties", "six" → "sixes"
+fn plural_words(words: &str) -> String {
+    match words.strip_suffix('y') {
+        Some(stem) => format!("{}ies", stem),
+        None if words.ends_with('x') => format!("{}es", words),
+        None => format!("{}s", words),
+    }
+}
+
+// A plural "s" right after a number: "the 1990s", "the 90s"
+fn plural_suffix(rest: &[char]) -> bool {
+    rest.first() == Some(&'s') && !rest.get(1).is_some_and(|c| c.is_alphanumeric())
+}
+
+// A time of day at the start of `chars`: "10:30" → "ten thirty", "9:05" →
+// "nine oh five", "10:00" → "ten o'clock" ("ten" before "am" or "pm", and
+// "twenty hundred" for 20:00). Anything with seconds is left alone.
+fn read_time(chars: &[char]) -> Option<SpokenNumber> {
+    let digit_run = |from: usize| chars[from..].iter().take_while(|c| c.is_ascii_digit()).count();
+    let hours_len = digit_run(0);
+    let minutes_follow = chars.get(hours_len) == Some(&':') && digit_run(hours_len + 1) == 2;
+    if !(1..=2).contains(&hours_len) || !minutes_follow {
+        return None;
+    }
+    let end = hours_len + 3;
+    if chars.get(end) == Some(&':') && chars.get(end + 1).is_some_and(char::is_ascii_digit) {
+        return None;
+    }
+    let number = |range: Range<usize>| chars[range].iter().collect::<String>().parse::<u64>().ok();
+    let (hours, minutes) = (number(0..hours_len)?, number(hours_len + 1..end)?);
+    if hours > 23 || minutes > 59 {
+        return None;
+    }
+
+    let hour = number_to_words(hours);
+    let rest: String = chars[end..].iter().take(5).collect::<String>().to_ascii_lowercase();
+    let rest = rest.trim_start();
+    let meridiem = ["am", "pm", "a.m.", "p.m."].iter().any(|m| {
+        rest.starts_with(m) && !rest[m.len()..].starts_with(|c: char| c.is_alphanumeric())
+    });
+    let words = match minutes {
+        0 if meridiem => hour,
+        0 if hours > 12 => format!("{} hundred", hour),
+        0 => format!("{} o'clock", hour),
+        1..=9 => format!("{} oh {}", hour, number_to_words(minutes)),
+        _ => format!("{} {}", hour, number_to_words(minutes)),
+    };
+    Some(SpokenNumber { len: end, words, fraction: false })
+}
+
+// Replace every number in `text` with words, keeping all other text as is.
+// `read` parses one number in the target language.
+fn expand_numbers(text: &str, read: fn(&[char]) -> SpokenNumber, minus: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut previous_number_end = None;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let prev = if i > 0 { Some(chars[i - 1]) } else { None };
+        // A minus sign only counts at the start of a word: "-5" but not "5-6"
+        let negative = c == '-'
+            && chars.get(i + 1).is_some_and(|n| n.is_ascii_digit())
+            && prev.is_none_or(|p| p.is_whitespace() || p == '(');
+
+        if !(c.is_ascii_digit() || negative) {
+            out.push(c);
+            i += 1;
+            continue;
+        }
+
+        let start = if negative { i + 1 } else { i };
+        let number = read(&chars[start..]);
+        let end = start + number.len;
+
+        if out.chars().last().is_some_and(|p| p.is_alphanumeric()) {
+            out.push(' ');
+        }
+        // Mixed numbers: "1 1/2" -> "one and one half"
+        let follows_number = previous_number_end == Some(i.saturating_sub(1)) && prev == Some(' ');
+        if number.fraction && follows_number {
+            out.push_str("and ");
+        }
+        if negative {
+            out.push_str(minus);
+            out.push(' ');
+        }
+        out.push_str(&number.words);
+        if chars.get(end).is_some_and(|n| n.is_alphabetic()) {
+            out.push(' ');
+        }
+        previous_number_end = Some(end);
+        i = end;
+    }
+
+    out
+}
+
+// A number found in text and how to say it
+struct SpokenNumber {
+    // Characters of the source text it covers
+    len: usize,
+    words: String,
+    fraction: bool,
+}
+
+// Parse the number at the start of `chars` (which begins with a digit)
+fn read_number(chars: &[char]) -> SpokenNumber {
+    let digit_run = |from: usize| chars[from..].iter().take_while(|c| c.is_ascii_digit()).count();
+
+    let mut end = digit_run(0);
+    let mut integer: String = chars[..end].iter().collect();
+    let plain = |len: usize, words: String| SpokenNumber { len, words, fraction: false };
+
+    // Dates like "3/4/2024" keep their original form: reading them as a fraction
+    // followed by a number would be wrong, and espeak knows the format
+    let date_len = group_run_len(chars, '/');
+    if date_len > 0 {
+        return plain(date_len, chars[..date_len].iter().collect());
+    }
+    // Versions like "1.2.3": each part a number, "one point two point three"
+    let version_len = group_run_len(chars, '.');
+    if version_len > 0 {
+        let parts: String = chars[..version_len].iter().collect();
+        let words: Vec<String> = parts
+            .split('.')
+            .map(|part| match part.parse::<u64>() {
+                Ok(n) if part.len() == 1 || !part.starts_with('0') => number_to_words(n),
+                _ => digits_to_words(part),
+            })
+            .collect();
+        return plain(version_len, words.join(" point "));
+    }
+    if let Some(time) = read_time(chars) {
+        return time;
+    }
+
+    // Thousands separators: "1,250,000" but not "1,23" or "12345,678"
+    if end <= 3 {
+        while chars.get(end) == Some(&',') && digit_run(end + 1) == 3 {
+            integer.extend(&chars[end + 1..end + 4]);
+            end += 4;
+        }
+    }
+
+    let leading_zero = integer.len() > 1 && integer.starts_with('0');
+    let value = if leading_zero { None } else { integer.parse::<u64>().ok() };
+
+    if let Some(n) = value {
+        // Ordinals: "3rd", "21st", "100th" (the suffix must end the word)
+        let suffix = chars[end..].iter().take(2).collect::<String>().to_ascii_lowercase();
+        if matches!(suffix.as_str(), "st" | "nd" | "rd" | "th")
+            && !chars.get(end + 2).is_some_and(|c| c.is_alphanumeric())
+        {
+            return plain(end + 2, ordinal_to_words(n));
+        }
+
+        // Fractions: "1/2", "3/4", but not "24/7" or "16/9"
+        if chars.get(end) == Some(&'/') && end == digit_run(0) {
+            let den_len = digit_run(end + 1);
+            let den: String = chars[end + 1..end + 1 + den_len].iter().collect();
+            if let Ok(d) = den.parse::<u64>() {
+                if n > 0 && n < d {
+                    let words = if d <= MAX_FRACTION_DENOMINATOR {
+                        fraction_to_words(n, d)
+                    } else {
+                        format!("{} over {}", number_to_words(n), number_to_words(d))
+                    };
+                    return SpokenNumber { len: end + 1 + den_len, words, fraction: true };
+                }
+            }
+        }
+
+        // Years and decades: "1995", "the 2020s", "the 90s"
+        let decimals =
+            chars.get(end) == Some(&'.') && chars.get(end + 1).is_some_and(char::is_ascii_digit);
+        if end == 4 && YEARS.contains(&n) && !decimals && !names_a_unit(&chars[end..]) {
+            if plural_suffix(&chars[end..]) {
+                return plain(end + 1, plural_words(&year_to_words(n)));
+            }
+            return plain(end, year_to_words(n));
+        }
+        if end == 2 && n % 10 == 0 && plural_suffix(&chars[end..]) {
+            return plain(end + 1, plural_words(&number_to_words(n)));
+        }
+    }
+
+    let mut words = match value {
+        Some(n) => number_to_words(n),
+        None => digits_to_words(&integer),
+    };
+
+    // Decimals: "3.14" -> "three point one four" (a trailing "." is punctuation)
+    if chars.get(end) == Some(&'.') && digit_run(end + 1) > 0 {
+        let frac_len = digit_run(end + 1);
+        let fraction: String = chars[end + 1..end + 1 + frac_len].iter().collect();
+        words.push_str(" point ");
+        words.push_str(&digits_to_words(&fraction));
+        end += 1 + frac_len;
+    }
+
+    plain(end, words)
+}
+
+// Spanish counterpart of `read_number`: "1.250.000" groups thousands and
+// "3,14" is "tres coma uno cuatro". No ordinals or fractions.
+fn read_number_es(chars: &[char]) -> SpokenNumber {
+    let digit_run = |from: usize| chars[from..].iter().take_while(|c| c.is_ascii_digit()).count();
+
+    let date_len = group_run_len(chars, '/');
+    if date_len > 0 {
+        return SpokenNumber { len: date_len, words: chars[..date_len].iter().collect(), fraction: false };
+    }
+
+    let mut end = digit_run(0);
+    let mut integer: String = chars[..end].iter().collect();
+    if end <= 3 {
+        while chars.get(end) == Some(&'.') && digit_run(end + 1) == 3 {
+            integer.extend(&chars[end + 1..end + 4]);
+            end += 4;
+        }
+    }
+
+    let leading_zero = integer.len() > 1 && integer.starts_with('0');
+    let mut words = match integer.parse::<u64>() {
+        Ok(n) if !leading_zero => number_to_words_es(n),
+        _ => digits_to_words_es(&integer),
+    };
+
+    // A trailing "," is punctuation: "1, 2 y 3"
+    if chars.get(end) == Some(&',') && digit_run(end + 1) > 0 {
+        let frac_len = digit_run(end + 1);
+        let fraction: String = chars[end + 1..end + 1 + frac_len].iter().collect();
+        words.push_str(" coma ");
+        words.push_str(&digits_to_words_es(&fraction));
+        end += 1 + frac_len;
+    }
+
+    SpokenNumber { len: end, words, fraction: false }
+}
+
+// Length of a run like "3/4/2024" (three or more digit groups joined by
+// `separator`), or 0 if `chars` doesn't start with one
+fn group_run_len(chars: &[char], separator: char) -> usize {
+    let mut end = 0;
+    let mut groups = 0;
+    loop {
+        let digits = chars[end..].iter().take_while(|c| c.is_ascii_digit()).count();
+        if digits == 0 {
+            break;
+        }
+        end += digits;
+        groups += 1;
+        if chars.get(end) == Some(&separator) && chars.get(end + 1).is_some_and(char::is_ascii_digit) {
+            end += 1;
+        } else {
+            break;
+        }
+    }
+    if groups >= 3 {
+        end
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spells_cardinals() {
+        assert_eq!(number_to_words(0), "zero");
+        assert_eq!(number_to_words(42), "forty two");
+        assert_eq!(number_to_words(101), "one hundred one");
+        assert_eq!(number_to_words(1_250_000), "one million two hundred fifty thousand");
+        assert_eq!(number_to_words(3_000_000_000_000), "three trillion");
+        assert_eq!(
+            number_to_words(u64::MAX),
+            "eighteen quintillion four hundred forty six quadrillion seven hundred forty four trillion \
+             seventy three billion seven hundred nine million five hundred fifty one thousand six hundred fifteen"
+        );
+    }
+
+    #[test]
+    fn normalizes_numbers_in_text() {
+        assert_eq!(normalize("There are 42 items."), "There are forty two items.");
+        assert_eq!(normalize("Pay 1,250,000 now"), "Pay one million two hundred fifty thousand now");
+        assert_eq!(normalize("It is -7 outside"), "It is minus seven outside");
+        assert_eq!(normalize("pages 5-6"), "pages five-six");
+        assert_eq!(normalize("pi is 3.14."), "pi is three point one four.");
+        assert_eq!(normalize("Agent 007"), "Agent zero zero seven");
+    }
+
+    #[test]
+    fn reads_version_numbers_part_by_part() {
+        assert_eq!(normalize("Version 1.2.3."), "Version one point two point three.");
+        assert_eq!(normalize("v2.10.0-rc1"), "v two point ten point zero-rc one");
+        let spoken = "one point two point three point zero four and one point five";
+        assert_eq!(normalize("1.2.3.04 and 1.5"), spoken);
+    }
+
+    #[test]
+    fn spells_ordinals() {
+        assert_eq!(normalize("3rd place"), "third place");
+        assert_eq!(normalize("the 21st century"), "the twenty first century");
+        assert_eq!(
+            normalize("12th, 40th, 100th and 1,000th"),
+            "twelfth, fortieth, one hundredth and one thousandth"
+        );
+        assert_eq!(normalize("2ND"), "second");
+        // A suffix that continues into a word isn't an ordinal
+        assert_eq!(normalize("4thought"), "four thought");
+    }
+
+    #[test]
+    fn fractions_versus_dates_and_ratios() {
+        assert_eq!(normalize("1/2 cup"), "one half cup");
+        assert_eq!(normalize("3/4 done"), "three quarters done");
+        assert_eq!(normalize("2/3 and 5/8"), "two thirds and five eighths");
+        assert_eq!(normalize("1 1/2 hours"), "one and one half hours");
+        assert_eq!(normalize("1/100"), "one one hundredth");
+
+        // Dates stay as written
+        assert_eq!(normalize("on 3/4/2024."), "on 3/4/2024.");
+        assert_eq!(normalize("2024/03/04"), "2024/03/04");
+
+        // Improper "fractions" are read as numbers around the slash
+        assert_eq!(normalize("24/7"), "twenty four/seven");
+        assert_eq!(normalize("16/9"), "sixteen/nine");
+        // Large denominators are read with "over"
+        assert_eq!(normalize("1/1000"), "one over one thousand");
+        assert_eq!(normalize("2 3/250 left"), "two and three over two hundred fifty left");
+    }
+
+    #[test]
+    fn expands_abbreviations_by_context() {
+        assert_eq!(normalize("Dr. Smith met Mrs. Jones."), "Doctor Smith met Missus Jones.");
+        assert_eq!(normalize("Visit St. Louis"), "Visit Saint Louis");
+        assert_eq!(
+            normalize("I live on Main St. It is quiet."),
+            "I ⟦live|lˈɪv⟧ on Main Street. It is quiet."
+        );
+        assert_eq!(normalize("Turn onto Elm Dr., then stop"), "Turn onto Elm Drive, then stop");
+        assert_eq!(
+            normalize("I love St. Louis but live on 5th St. now"),
+            "I love Saint Louis but live on fifth Street now"
+        );
+        assert_eq!(
+            normalize("fruit (e.g. apples, pears, etc.) and more"),
+            "fruit (for example apples, pears, et cetera) and more"
+        );
+        assert_eq!(normalize("cats vs. dogs"), "cats versus dogs");
+        // Only whole words match
+        assert_eq!(normalize("Drive fast"), "Drive fast");
+    }
+
+    #[test]
+    fn custom_abbreviations_take_precedence() {
+        let mut normalizer = Normalizer::default();
+        normalizer.add_abbreviation("MEM8", "mem eight");
+        normalizer.add_abbreviation("St.", "Street");
+        assert_eq!(normalizer.normalize("MEM8 runs on St. Mary"), "mem eight runs on Street Mary");
+        assert_eq!(normalizer.normalize("It uses MEM8."), "It uses mem eight.");
+        assert_eq!(normalizer.normalize("MEM8s"), "MEM eight s");
+    }
+
+    #[test]
+    fn abbreviation_periods_are_not_sentence_ends() {
+        assert!(ends_with_abbreviation("I saw Dr."));
+        assert!(ends_with_abbreviation("(e.g."));
+        assert!(!ends_with_abbreviation("and so on etc."));
+        assert!(!ends_with_abbreviation("The end."));
+    }
+
+    #[test]
+    fn spells_or_pronounces_acronyms() {
+        assert_eq!(normalize("NASA uses HTTP and an API."), "NASA uses aych tee tee pee and an ay pee eye.");
+        assert_eq!(normalize("Two APIs, one URL"), "Two ay pee eyes, one you ar el");
+        // Mixed case and single capitals are words
+        assert_eq!(normalize("GitHub and I"), "GitHub and I");
+        // Whole-text capitals are shouting, not acronyms
+        assert_eq!(normalize("STOP THAT RIGHT NOW"), "STOP THAT RIGHT NOW");
+
+        let mut normalizer = Normalizer::default();
+        normalizer.add_acronym("SQL", "sequel");
+        normalizer.add_acronym("NASA", "en ay ess ay");
+        assert_eq!(normalizer.normalize("SQL, SQLs and NASA"), "sequel, sequels and en ay ess ay");
+    }
+
+    #[test]
+    fn reads_roman_numerals_in_names() {
+        let text = "After World War II, Henry VIII.";
+        assert_eq!(normalize(text), "After World War two, Henry the eighth.");
+        assert_eq!(normalize("Louis XIV and Chapter IV"), "Louis the fourteenth and Chapter four");
+        // Not after a capitalized word, or not a numeral: still an acronym
+        assert_eq!(normalize("the IV drip"), "the eye vee drip");
+        assert_eq!(normalize("Jane Doe MD"), "Jane Doe em dee");
+        assert_eq!(normalize("Sequel IIII"), "Sequel eye eye eye eye");
+        // A lone letter is a numeral only after a numbering word
+        assert_eq!(normalize("Part V and World War I"), "Part five and World War one");
+        for text in ["Malcolm X spoke.", "Generation X", "Press X to continue", "Henry V"] {
+            assert_eq!(normalize(text), text);
+        }
+    }
+
+    #[test]
+    fn spells_spanish_numbers() {
+        assert_eq!(number_to_words_es(0), "cero");
+        assert_eq!(number_to_words_es(16), "dieciséis");
+        assert_eq!(number_to_words_es(31), "treinta y uno");
+        assert_eq!(number_to_words_es(100), "cien");
+        assert_eq!(number_to_words_es(101), "ciento uno");
+        assert_eq!(number_to_words_es(1990), "mil novecientos noventa");
+        assert_eq!(number_to_words_es(21_000), "veintiún mil");
+        assert_eq!(number_to_words_es(501_000), "quinientos un mil");
+        assert_eq!(number_to_words_es(1_000_000), "un millón");
+        assert_eq!(number_to_words_es(21_000_000), "veintiún millones");
+        assert_eq!(number_to_words_es(2_500_000), "dos millones quinientos mil");
+        assert_eq!(number_to_words_es(1_000_000_000), "mil millones");
+        assert_eq!(number_to_words_es(3_000_000_000_000), "tres billones");
+    }
+
+    #[test]
+    fn normalizes_spanish_text() {
+        assert_eq!(normalize_es("¿En 1990?"), "¿En mil novecientos noventa?");
+        assert_eq!(normalize_es("Son 1.250 euros"), "Son mil doscientos cincuenta euros");
+        assert_eq!(normalize_es("Pi es 3,14, casi"), "Pi es tres coma uno cuatro, casi");
+        assert_eq!(normalize_es("Hace -5 grados"), "Hace menos cinco grados");
+        // English-only rules don't apply
+        assert_eq!(normalize_es("Dr. 3rd API"), "Dr. tres rd API");
+    }
+
+    // The phonemes chosen for `word` in `text`, if any
+    fn reading(text: &str, word: &str) -> Option<String> {
+        let marked = normalize(text);
+        let needle = format!("{}{}|", PHONEME_OPEN, word);
+        let start = marked.find(&needle)? + needle.len();
+        let end = start + marked[start..].find(PHONEME_CLOSE)?;
+        Some(marked[start..end].to_string())
+    }
+
+    #[test]
+    fn disambiguates_heteronyms_from_context() {
+        let cases = [
+            ("I read the book yesterday.", "read", Some("ɹˈɛd")),
+            ("I will read it now.", "read", Some("ɹˈiːd")),
+            ("She has read it twice", "read", Some("ɹˈɛd")),
+            ("Lead pipes rust", "Lead", Some("lˈɛd")),
+            ("It is made of lead.", "lead", Some("lˈɛd")),
+            ("They lead the way", "lead", Some("lˈiːd")),
+            ("We are live now!", "live", Some("lˈaɪv")),
+            ("Where do you live?", "live", Some("lˈɪv")),
+            ("He plays bass guitar", "bass", Some("bˈeɪs")),
+            ("I caught a bass", "bass", Some("bˈæs")),
+            ("A single tear fell", "tear", Some("tˈɪɹ")),
+            ("Don't tear it up", "tear", Some("tˈɛɹ")),
+            ("The wind is cold", "wind", Some("wˈɪnd")),
+            ("Wind it back", "Wind", Some("wˈaɪnd")),
+            ("Take a bow.", "bow", Some("bˈaʊ")),
+            ("A bow and arrow", "bow", Some("bˈoʊ")),
+            ("Please close the door", "close", Some("klˈoʊz")),
+            ("We are very close", "close", Some("klˈoʊs")),
+            ("A new world record", "record", Some("ɹˈɛkɚd")),
+            ("We record it live", "record", Some("ɹɪkˈɔːɹd")),
+            ("Buy fresh produce", "produce", Some("pɹˈoʊdus")),
+            ("Plants produce energy", "produce", Some("pɹədˈuːs")),
+            // Context that doesn't settle it is left to espeak
+            ("Read", "Read", None),
+        ];
+        for (text, word, expected) in cases {
+            assert_eq!(reading(text, word).as_deref(), expected, "{}", text);
+        }
+
+        // Plurals, capitals and punctuation are kept around the marker
+        assert_eq!(reading("Her tears fell", "tears").as_deref(), Some("tˈɪɹz"));
+        assert_eq!(normalize("Read it, then close it."), "Read it, then ⟦close|klˈoʊz⟧ it.");
+        // Context doesn't cross a comma
+        assert_eq!(reading("I will, read", "read"), None);
+    }
+
+    #[test]
+    fn british_heteronyms_and_inline_phonemes() {
+        let marked = Normalizer::default().normalize_dialect("We record the tear.", Dialect::British);
+        assert_eq!(marked, "We ⟦record|ɹɪkˈɔːd⟧ the ⟦tear|tˈɪə⟧.");
+        assert_eq!(strip_inline_phonemes(&marked), "We record the tear.");
+
+        let pieces: Vec<String> = split_inline_phonemes(&marked)
+            .into_iter()
+            .map(|p| match p {
+                Piece::Text(t) => format!("T:{}", t),
+                Piece::Phonemes(p) => format!("P:{}", p),
+            })
+            .collect();
+        assert_eq!(pieces, vec!["T:We ", "P:ɹɪkˈɔːd", "T: the ", "P:tˈɪə", "T:."]);
+    }
+
+    #[test]
+    fn names_symbols() {
+        assert_eq!(symbol_name('©'), Some("copyright sign"));
+        assert_eq!(symbol_name('\\'), Some("reverse solidus"));
+        assert_eq!(symbol_name('a'), None);
+    }
+
+    #[test]
+    fn reads_units_after_numbers() {
+        assert_eq!(normalize("1 km or 5km"), "one kilometer or five kilometers");
+        let temperatures = "twenty degrees Celsius, minus one degree Fahrenheit and ninety degrees";
+        assert_eq!(normalize("20°C, -1 °F and 90°"), temperatures);
+        assert_eq!(normalize("75% of 3.5GB"), "seventy five percent of three point five gigabytes");
+        let speeds = "one thousand meters per second at five kilometers per hour.";
+        assert_eq!(normalize("1,000 m/s at 5 km/h."), speeds);
+        assert_eq!(normalize("a 1 MB file"), "a one megabyte file");
+        // Between an article and a noun the unit is an adjective
+        assert_eq!(normalize("A 3 h trip"), "A three hour trip");
+        assert_eq!(normalize("the 20° angle"), "the twenty degree angle");
+        assert_eq!(normalize("the 5 km of road"), "the five kilometers of road");
+        assert_eq!(normalize("took 3 h today"), "took three hours today");
+    }
+
+    #[test]
+    fn units_need_a_number_before_them() {
+        assert_eq!(normalize("I'm 5 in line, m is a letter"), "I'm five in line, m is a letter");
+        assert!(!normalize("the 90s").contains("second"));
+        assert!(!normalize("$5m").contains("meter"));
+        assert!(!normalize("x2 h").contains("hour"));
+        assert_eq!(normalize("5 mins, 5 GBs"), "five mins, five jee bees");
+    }
+
+    #[test]
+    fn reads_years_and_decades_in_pairs() {
+        assert_eq!(normalize("In 1995 it rained."), "In nineteen ninety five it rained.");
+        assert_eq!(normalize("the 2020s and 90s"), "the twenty twenties and nineties");
+        assert_eq!(normalize("1900s music"), "nineteen hundreds music");
+        let years = "nineteen oh five, two thousand seven, twenty twenty four";
+        assert_eq!(normalize("1905, 2007, 2024"), years);
+        // Counts stay cardinals
+        assert_eq!(normalize("1500 m"), "one thousand five hundred meters");
+        assert_eq!(normalize("1,995 cars"), "one thousand nine hundred ninety five cars");
+        assert_eq!(normalize("1995.5"), "one thousand nine hundred ninety five point five");
+    }
+
+    #[test]
+    fn reads_currency_amounts() {
+        assert_eq!(normalize("$5 or $1"), "five dollars or one dollar");
+        assert_eq!(normalize("$5.50"), "five dollars and fifty cents");
+        assert_eq!(normalize("€0.99, £3.05"), "ninety nine cents, three pounds and five pence");
+        assert_eq!(normalize("$1995"), "one thousand nine hundred ninety five dollars");
+        let scaled = "two point five million pounds and three billion dollars";
+        assert_eq!(normalize("£2.5m and $3 billion"), scaled);
+        assert_eq!(normalize("¥500, US$5"), "five hundred yen, US$five");
+    }
+
+    #[test]
+    fn reads_times_of_day() {
+        assert_eq!(normalize("at 10:30"), "at ten thirty");
+        assert_eq!(normalize("9:05 pm"), "nine oh five pm");
+        assert_eq!(normalize("10:00, 7:00 am, 20:00"), "ten o'clock, seven am, twenty hundred");
+        // Not times
+        assert_eq!(normalize("10:75"), "ten:seventy five");
+        assert_eq!(normalize("3:2"), "three:two");
+    }
+
+    #[test]
+    fn reads_phone_numbers_digit_by_digit() {
+        let us = "plus one, five five five, eight six seven, five three zero nine";
+        assert_eq!(normalize("Call +1 (555) 867-5309."), format!("Call {}.", us));
+        assert_eq!(normalize("+1-555-867-5309"), us);
+        assert_eq!(normalize("(555)867-5309"), "five five five, eight six seven, five three zero nine");
+        let toll_free = "one, eight zero zero, five five five, zero one nine nine";
+        assert_eq!(normalize("1.800.555.0199"), toll_free);
+        let uk = "plus four four, two zero, seven nine four six, zero nine five eight";
+        assert_eq!(normalize("+44 20 7946 0958"), uk);
+        let local = "five five five, one two three four";
+        assert_eq!(normalize("Dial 555-1234."), format!("Dial {}.", local));
+
+        // Codes keyed in after a dialing word
+        assert_eq!(normalize("Dial 911."), "Dial nine one one.");
+        assert_eq!(normalize("call 112, or ext. 2204"), "call one one two, or ext. two two zero four");
+        assert_eq!(normalize("Call 20 people"), "Call twenty people");
+        assert_eq!(normalize("call 1,000 people"), "call one thousand people");
+        assert_eq!(normalize("We sold 911 units"), "We sold nine hundred eleven units");
+    }
+
+    #[test]
+    fn phone_lookalikes_are_read_as_numbers() {
+        for text in [
+            "2023-2024",
+            "2023-10-14",
+            "12.31.2023",
+            "192.168.100.200",
+            "4111-1111-1111-1111",
+            "1234-5678-9012",
+            "100-1000",
+            "555.1234",
+            "555 1234",
+            "555 867 5309",
+            "(1990-2000)",
+            "978-3-16-148410-0",
+            "555-867-5309x",
+        ] {
+            assert!(!normalize(text).contains(','), "{} read as a phone number", text);
+        }
+    }
+
+    #[test]
+    fn reads_addresses_in_each_style() {
+        let text = "Visit https://www.example.com/docs?x=1, (mail help.desk@my-site.co.uk) or see \
+                    example.com.";
+        assert_eq!(
+            read_addresses(text, UrlStyle::Domain),
+            "Visit example dot com, (mail help dot desk at my dash site dot co dot uk) or see \
+             example dot com."
+        );
+        assert_eq!(
+            read_addresses(text, UrlStyle::Full),
+            "Visit aych tee tee pee ess colon slash slash double you double you double you dot \
+             example dot com slash docs question mark x equals 1, (mail help dot desk at my dash site \
+             dot co dot uk) or see example dot com."
+        );
+        let skipped = "Visit a link, (mail an email address) or see a link.";
+        assert_eq!(read_addresses(text, UrlStyle::Skip), skipped);
+        let full = |text| read_addresses(text, UrlStyle::Full);
+        let local = "<aych tee tee pee colon slash slash localhost colon 8080>";
+        assert_eq!(full("<http://localhost:8080/>"), local);
+        let wiki = "en dot wikipedia dot org slash wiki slash Rust underscore language).";
+        assert_eq!(full("en.wikipedia.org/wiki/Rust_(language))."), wiki);
+    }
+
+    #[test]
+    fn leaves_non_addresses_alone() {
+        let text = "Edit main.rs in ASP.NET, pay 3.50 at 5 p.m. @home, e.g. a@b or ://x.";
+        assert_eq!(read_addresses(text, UrlStyle::Full), text);
+    }
+
+    #[test]
+    fn digit_adjacent_junk_falls_back_gracefully() {
+        assert_eq!(normalize("mp3 files"), "mp three files");
+        assert_eq!(normalize("1,23"), "one,twenty three");
+        assert_eq!(normalize("99999999999999999999999"), digits_to_words("99999999999999999999999"));
+        assert_eq!(normalize("no digits here"), "no digits here");
+    }
+}